@@ -0,0 +1,45 @@
+use std::sync::Mutex;
+use std::thread;
+
+use code_spells::common_room;
+use code_spells::common_room::CommonRoom;
+use criterion::{criterion_group, criterion_main, Criterion};
+
+const STUDENTS: usize = 4;
+const VISITS: usize = 1_000;
+
+/// Lets every student increment a counter through `visit`, each on their own thread.
+fn crowd(visit: &(impl Fn() + Sync)) {
+    thread::scope(|s| {
+        for _ in 0..STUDENTS {
+            s.spawn(|| {
+                for _ in 0..VISITS {
+                    visit();
+                }
+            });
+        }
+    });
+}
+
+fn bench_counters(c: &mut Criterion) {
+    let mut group = c.benchmark_group("counters");
+    group.sample_size(10);
+    group.bench_function("single_mutex", |b| {
+        b.iter(|| {
+            let points = Mutex::new(0_usize);
+            crowd(&|| *points.lock().unwrap() += 1);
+            points.into_inner().unwrap()
+        })
+    });
+    group.bench_function("common_room", |b| {
+        b.iter(|| {
+            let points: CommonRoom<usize> = common_room!();
+            crowd(&|| *points.enter() += 1);
+            points.gather(0, |total, points| total + points)
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_counters);
+criterion_main!(benches);
@@ -0,0 +1,47 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread;
+
+use code_spells::galleon::GalleonCounter;
+use code_spells::galleon_counter;
+use criterion::{criterion_group, criterion_main, Criterion};
+
+const GOBLINS: usize = 4;
+const DEPOSITS: usize = 10_000;
+
+/// Lets every goblin make its deposits through `deposit`, each on their own thread.
+fn count(deposit: &(impl Fn() + Sync)) {
+    thread::scope(|s| {
+        for _ in 0..GOBLINS {
+            s.spawn(|| {
+                for _ in 0..DEPOSITS {
+                    deposit();
+                }
+            });
+        }
+    });
+}
+
+fn bench_counters(c: &mut Criterion) {
+    let mut group = c.benchmark_group("counters");
+    group.sample_size(10);
+    group.bench_function("single_atomic", |b| {
+        b.iter(|| {
+            let gold = AtomicU64::new(0);
+            count(&|| {
+                gold.fetch_add(1, Ordering::Relaxed);
+            });
+            gold.load(Ordering::Relaxed)
+        })
+    });
+    group.bench_function("galleon_counter", |b| {
+        b.iter(|| {
+            let gold: GalleonCounter = galleon_counter!();
+            count(&|| gold.add(1));
+            gold.total()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_counters);
+criterion_main!(benches);
@@ -0,0 +1,45 @@
+use std::hint::black_box;
+use std::thread;
+
+use code_spells::harvest;
+use criterion::{criterion_group, criterion_main, Criterion};
+
+const WORKERS: usize = 4;
+const ITEMS: u64 = 256;
+
+/// Work whose cost grows with the item, so that the last items are by far the most expensive.
+fn grow(n: u64) -> u64 {
+    (0..n * n * 10).fold(0, |acc, x| black_box(acc ^ x))
+}
+
+/// Splits the items into one contiguous chunk per worker without any stealing.
+fn chunked(items: &[u64], work: impl Fn(u64) -> u64 + Sync) -> Vec<u64> {
+    let chunk = (items.len() + WORKERS - 1) / WORKERS;
+    thread::scope(|s| {
+        let workers: Vec<_> = items
+            .chunks(chunk)
+            .map(|chunk| {
+                let work = &work;
+                s.spawn(move || chunk.iter().map(|&n| work(n)).collect::<Vec<_>>())
+            })
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().unwrap())
+            .collect()
+    })
+}
+
+fn bench_uneven_work(c: &mut Criterion) {
+    let items: Vec<u64> = (0..ITEMS).collect();
+    let mut group = c.benchmark_group("uneven_work");
+    group.sample_size(10);
+    group.bench_function("naive_chunks", |b| b.iter(|| chunked(&items, grow)));
+    group.bench_function("harvest", |b| {
+        b.iter(|| harvest!(items.iter().copied(), workers: WORKERS, grow))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_uneven_work);
+criterion_main!(benches);
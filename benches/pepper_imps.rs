@@ -0,0 +1,57 @@
+use std::hint::spin_loop;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+use code_spells::backoff_spin;
+use code_spells::pepper_imps::Backoff;
+use criterion::{criterion_group, criterion_main, Criterion};
+
+const ROUNDS: usize = 100;
+
+/// Passes a token back and forth between two threads, waiting for it with `wait`.
+fn ping_pong(wait: fn(&AtomicBool, bool)) {
+    let token = Arc::new(AtomicBool::new(false));
+    let other = {
+        let token = Arc::clone(&token);
+        thread::spawn(move || {
+            for _ in 0..ROUNDS {
+                wait(&token, true);
+                token.store(false, Ordering::Release);
+            }
+        })
+    };
+    for _ in 0..ROUNDS {
+        token.store(true, Ordering::Release);
+        wait(&token, false);
+    }
+    other.join().unwrap();
+}
+
+fn pure_spin(token: &AtomicBool, value: bool) {
+    while token.load(Ordering::Acquire) != value {
+        spin_loop();
+    }
+}
+
+fn pure_yield(token: &AtomicBool, value: bool) {
+    while token.load(Ordering::Acquire) != value {
+        thread::yield_now();
+    }
+}
+
+fn backoff(token: &AtomicBool, value: bool) {
+    backoff_spin!(Backoff::new(), || token.load(Ordering::Acquire) == value);
+}
+
+fn bench_ping_pong(c: &mut Criterion) {
+    let mut group = c.benchmark_group("ping_pong");
+    group.sample_size(10);
+    group.bench_function("spin_loop", |b| b.iter(|| ping_pong(pure_spin)));
+    group.bench_function("yield_now", |b| b.iter(|| ping_pong(pure_yield)));
+    group.bench_function("backoff_spin", |b| b.iter(|| ping_pong(backoff)));
+    group.finish();
+}
+
+criterion_group!(benches, bench_ping_pong);
+criterion_main!(benches);
@@ -0,0 +1,73 @@
+use std::sync::mpsc;
+use std::thread;
+
+use code_spells::pigwidgeon;
+use code_spells::pigwidgeon::{Receiver, Sender};
+use criterion::{criterion_group, criterion_main, Criterion};
+
+const LETTERS: u64 = 10_000;
+const CAPACITY: usize = 64;
+
+/// Sends every letter from a second thread through `send`, and receives them all through `receive`.
+fn deliver(send: impl FnOnce() + Send, receive: impl FnOnce() -> u64) -> u64 {
+    thread::scope(|s| {
+        s.spawn(send);
+        receive()
+    })
+}
+
+fn bench_queues(c: &mut Criterion) {
+    let mut group = c.benchmark_group("spsc");
+    group.sample_size(10);
+    group.bench_function("mpsc_channel", |b| {
+        b.iter(|| {
+            let (tx, rx) = mpsc::channel();
+            deliver(
+                move || (0..LETTERS).for_each(|letter| tx.send(letter).unwrap()),
+                || rx.iter().sum(),
+            )
+        })
+    });
+    group.bench_function("mpsc_sync_channel", |b| {
+        b.iter(|| {
+            let (tx, rx) = mpsc::sync_channel(CAPACITY);
+            deliver(
+                move || (0..LETTERS).for_each(|letter| tx.send(letter).unwrap()),
+                || rx.iter().sum(),
+            )
+        })
+    });
+    group.bench_function("pigwidgeon", |b| {
+        b.iter(|| {
+            let (mut tx, mut rx): (Sender<u64, CAPACITY>, Receiver<u64, CAPACITY>) = pigwidgeon!();
+            deliver(
+                move || {
+                    for mut letter in 0..LETTERS {
+                        while let Err(returned) = tx.try_send(letter) {
+                            letter = returned;
+                            thread::yield_now();
+                        }
+                    }
+                },
+                || {
+                    let mut total = 0;
+                    let mut received = 0;
+                    while received < LETTERS {
+                        match rx.try_recv() {
+                            Some(letter) => {
+                                total += letter;
+                                received += 1;
+                            }
+                            None => thread::yield_now(),
+                        }
+                    }
+                    total
+                },
+            )
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_queues);
+criterion_main!(benches);
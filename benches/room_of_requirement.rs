@@ -0,0 +1,37 @@
+use std::hint::black_box;
+
+use code_spells::room_of_requirement;
+use criterion::{criterion_group, criterion_main, Criterion};
+
+const BUFFER: usize = 4096;
+const MESSAGES: usize = 1_000;
+
+/// Fills a buffer the way a hot loop would, e.g. when serializing a message.
+fn fill(buffer: &mut Vec<u8>, message: usize) {
+    buffer.extend((0..BUFFER).map(|i| (i ^ message) as u8));
+    black_box(&buffer[..]);
+}
+
+fn bench_buffers(c: &mut Criterion) {
+    let mut group = c.benchmark_group("buffers");
+    group.bench_function("fresh_allocation", |b| {
+        b.iter(|| {
+            for message in 0..MESSAGES {
+                let mut buffer = Vec::with_capacity(BUFFER);
+                fill(&mut buffer, message);
+            }
+        })
+    });
+    group.bench_function("room_of_requirement", |b| {
+        let room = room_of_requirement!(|| Vec::with_capacity(BUFFER));
+        b.iter(|| {
+            for message in 0..MESSAGES {
+                fill(&mut room.require(), message);
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_buffers);
+criterion_main!(benches);
@@ -0,0 +1,1595 @@
+//! Procedural spells for the [`code-spells`](https://crates.io/crates/code-spells) crate.
+//!
+//! Enable the `macros` feature of `code-spells` to cast these from there.
+
+use std::convert::TryFrom;
+use std::iter::FromIterator;
+use std::sync::{Mutex, PoisonError};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use proc_macro::{Delimiter, Group, Ident, Punct, Spacing, Span, TokenStream, TokenTree};
+
+/// Silences the given lints on the item it is cast on by expanding to the corresponding `#[allow(...)]` attribute.
+/// The compiler can no longer hear what you are doing.
+/// # Example
+/// ```
+/// # use code_spells_macros::muffliato;
+/// #[muffliato(clippy::too_many_arguments, dead_code)]
+/// fn secret_meeting(a: u8, b: u8, c: u8, d: u8, e: u8, f: u8, g: u8, h: u8) {}
+/// ```
+/// Casting it without naming any lints does not compile.
+/// ```compile_fail
+/// # use code_spells_macros::muffliato;
+/// #[muffliato]
+/// fn loud() {}
+/// ```
+#[proc_macro_attribute]
+pub fn muffliato(lints: TokenStream, item: TokenStream) -> TokenStream {
+    if lints.is_empty() {
+        return spell_error(
+            Span::call_site(),
+            "muffliato needs to know which lints to silence, e.g. `#[muffliato(dead_code)]`",
+        );
+    }
+    let allow = TokenStream::from_iter([
+        TokenTree::from(Ident::new("allow", Span::call_site())),
+        TokenTree::from(Group::new(Delimiter::Parenthesis, lints)),
+    ]);
+    let mut out = TokenStream::from_iter([
+        TokenTree::from(Punct::new('#', Spacing::Alone)),
+        TokenTree::from(Group::new(Delimiter::Bracket, allow)),
+    ]);
+    out.extend(item);
+    out
+}
+
+/// Registers the implementation of a trait it is cast on in the registry of `code_spells::summons`,
+/// so that it can be summoned as a `Box<dyn Trait>` with `accio_trait!`.
+///
+/// The attribute is cast on an `impl Trait for Type` block. `Type` must implement [`Default`],
+/// which is used to construct it when summoned, and the impl can not be generic.
+/// The implementation is registered under the name of the type,
+/// unless another is given with `#[answers_summons(Trait, name = "...")]`.
+/// If `code-spells` is known by another name in the crate, pass its path as
+/// `#[answers_summons(Trait, crate = ::spells)]`.
+///
+/// The registration runs before `main` using the platform's static initializers.
+/// Only Linux, Android, the BSDs, macOS, iOS and Windows are supported,
+/// and casting the attribute for any other target does not compile.
+/// Like all such tricks it only works if the linker keeps the object file the impl is in,
+/// which it always does for impls in the binary crate itself.
+#[proc_macro_attribute]
+pub fn answers_summons(attr: TokenStream, item: TokenStream) -> TokenStream {
+    match answer_summons(attr, &item) {
+        Ok(registration) => {
+            let mut out = item;
+            out.extend(registration);
+            out
+        }
+        Err((span, message)) => {
+            let mut out = spell_error(span, &message);
+            out.extend(item);
+            out
+        }
+    }
+}
+
+fn answer_summons(attr: TokenStream, item: &TokenStream) -> Result<TokenStream, (Span, String)> {
+    let tokens: Vec<TokenTree> = item.clone().into_iter().collect();
+    let impl_position = tokens
+        .iter()
+        .position(|token| is_ident(token, "impl"))
+        .ok_or_else(|| {
+            (
+                Span::call_site(),
+                String::from("answers_summons can only be cast on an `impl Trait for Type` block"),
+            )
+        })?;
+    if let Some(TokenTree::Punct(p)) = tokens.get(impl_position + 1) {
+        if p.as_char() == '<' {
+            return Err((
+                p.span(),
+                String::from(
+                    "generic impls can not answer summons, as there is no single type to summon",
+                ),
+            ));
+        }
+    }
+    let header = &tokens[impl_position + 1..tokens.len() - 1];
+    let for_position = top_level_position(header, |token, next| {
+        is_ident(token, "for") && !matches!(next, Some(next) if is_punct(next, '<'))
+    })
+    .ok_or_else(|| {
+        (
+            tokens[impl_position].span(),
+            String::from("answers_summons needs a trait impl, e.g. `impl Greeter for Hagrid`"),
+        )
+    })?;
+    let self_type = &header[for_position + 1..];
+    if let Some(where_position) = top_level_position(self_type, |token, _| is_ident(token, "where"))
+    {
+        return Err((
+            self_type[where_position].span(),
+            String::from("impls with where clauses can not answer summons, as they are generic"),
+        ));
+    }
+    let impl_trait = join(&header[..for_position]);
+    let self_type = join(self_type);
+
+    let mut summoned_trait = impl_trait;
+    let mut name = format!("{:?}", self_type.replace(' ', ""));
+    let mut code_spells = String::from("::code_spells");
+    let attr: Vec<TokenTree> = attr.into_iter().collect();
+    for argument in attr.split(|token| is_punct(token, ',')) {
+        match argument {
+            [] => {}
+            [key, eq, value] if is_ident(key, "name") && is_punct(eq, '=') => {
+                name = value.to_string();
+            }
+            [key, eq, path @ ..] if is_ident(key, "crate") && is_punct(eq, '=') => {
+                if path.is_empty() {
+                    return Err((
+                        key.span(),
+                        String::from(
+                            "answers_summons needs the path of code-spells after `crate =`",
+                        ),
+                    ));
+                }
+                code_spells = join(path);
+            }
+            _ => summoned_trait = join(argument),
+        }
+    }
+
+    let registration: TokenStream = format!(
+        r#"
+        const _: () = {{
+            #[cfg(not(any(
+                target_os = "linux",
+                target_os = "android",
+                target_os = "freebsd",
+                target_os = "netbsd",
+                target_os = "openbsd",
+                target_os = "dragonfly",
+                target_os = "macos",
+                target_os = "ios",
+                windows
+            )))]
+            ::core::compile_error!(
+                "answers_summons needs static initializers, which are only supported on Linux, Android, the BSDs, macOS, iOS and Windows"
+            );
+            extern "C" fn answer_summons() {{
+                {code_spells}::summons::register::<dyn {summoned_trait}>(
+                    {name},
+                    || ::std::boxed::Box::new(<{self_type} as ::core::default::Default>::default()),
+                );
+            }}
+            #[used]
+            {link_section}
+            static ANSWER_SUMMONS: extern "C" fn() = answer_summons;
+        }};
+        "#,
+        code_spells = code_spells,
+        summoned_trait = summoned_trait,
+        name = name,
+        self_type = self_type,
+        link_section = "@",
+    )
+    .parse()
+    .map_err(|e| (Span::call_site(), format!("answers_summons failed: {}", e)))?;
+    // The sections are named with the span of this crate, so that they are read with its edition,
+    // where `link_section` is not yet an unsafe attribute that must be written as `#[unsafe(link_section = ...)]`.
+    let link_section = respan(
+        r#"
+        #[cfg_attr(
+            any(
+                target_os = "linux",
+                target_os = "android",
+                target_os = "freebsd",
+                target_os = "netbsd",
+                target_os = "openbsd",
+                target_os = "dragonfly"
+            ),
+            link_section = ".init_array"
+        )]
+        #[cfg_attr(any(target_os = "macos", target_os = "ios"), link_section = "__DATA,__mod_init_func")]
+        #[cfg_attr(windows, link_section = ".CRT$XCU")]
+        "#
+        .parse()
+        .expect("the link sections are valid Rust"),
+        Span::mixed_site(),
+    );
+    Ok(replace_placeholder(registration, &link_section))
+}
+
+/// Returns the position of the first token that is not nested inside angle brackets and matches the predicate,
+/// which is also given the token after it.
+fn top_level_position(
+    tokens: &[TokenTree],
+    predicate: impl Fn(&TokenTree, Option<&TokenTree>) -> bool,
+) -> Option<usize> {
+    let mut depth = 0_usize;
+    let mut after_dash = false;
+    for (position, token) in tokens.iter().enumerate() {
+        match token {
+            TokenTree::Punct(p) if p.as_char() == '<' => depth += 1,
+            // The `>` of an `->` does not close an angle bracket.
+            TokenTree::Punct(p) if p.as_char() == '>' && !after_dash => {
+                depth = depth.saturating_sub(1)
+            }
+            _ if depth == 0 && predicate(token, tokens.get(position + 1)) => return Some(position),
+            _ => {}
+        }
+        after_dash = matches!(token, TokenTree::Punct(p) if p.as_char() == '-' && p.spacing() == Spacing::Joint);
+    }
+    None
+}
+
+/// Replaces every `@` in the stream, also inside groups, with the given tokens.
+fn replace_placeholder(stream: TokenStream, replacement: &TokenStream) -> TokenStream {
+    stream
+        .into_iter()
+        .flat_map(|token| match token {
+            TokenTree::Punct(p) if p.as_char() == '@' => replacement.clone(),
+            TokenTree::Group(group) => {
+                let mut replaced = Group::new(
+                    group.delimiter(),
+                    replace_placeholder(group.stream(), replacement),
+                );
+                replaced.set_span(group.span());
+                TokenStream::from_iter([TokenTree::from(replaced)])
+            }
+            token => TokenStream::from_iter([token]),
+        })
+        .collect()
+}
+
+/// Restricts the methods of an impl block that are marked with `#[restricted]`,
+/// so that they can only be called by someone who shows a librarian's pass.
+///
+/// The attribute is cast on an inherent impl block. Every method marked with `#[restricted]` gets an extra
+/// parameter of type `code_spells::restricted_section::LibrarianPass<Self>`, placed right after the receiver.
+/// The type also gets a `librarian_pass()` constructor that issues such passes,
+/// but it only exists in tests, or also when the given feature of the crate is enabled
+/// if the attribute is cast as `#[restricted_section(feature = "...")]`.
+/// The section implements `code_spells::restricted_section::RestrictedSection` for the type with a librarian
+/// that can only be found where the section is declared, so no one else can issue passes.
+/// Each type can only have one restricted section, since it would have two librarians otherwise.
+#[proc_macro_attribute]
+pub fn restricted_section(attr: TokenStream, item: TokenStream) -> TokenStream {
+    match restrict_section(attr, &item) {
+        Ok(section) => section,
+        Err((span, message)) => {
+            let mut out = spell_error(span, &message);
+            out.extend(item);
+            out
+        }
+    }
+}
+
+fn restrict_section(attr: TokenStream, item: &TokenStream) -> Result<TokenStream, (Span, String)> {
+    let attr: Vec<TokenTree> = attr.into_iter().collect();
+    let gate = match attr.as_slice() {
+        [] => String::from("test"),
+        [key, TokenTree::Punct(eq), TokenTree::Literal(feature)]
+            if is_ident(key, "feature") && eq.as_char() == '=' =>
+        {
+            format!("any(test, feature = {})", feature)
+        }
+        [first, ..] => {
+            return Err((
+                first.span(),
+                String::from("the restricted section can only be opened by a feature, e.g. `#[restricted_section(feature = \"librarian\")]`"),
+            ))
+        }
+    };
+
+    let mut tokens: Vec<TokenTree> = item.clone().into_iter().collect();
+    let not_an_impl = || {
+        (
+            Span::call_site(),
+            String::from("restricted_section can only be cast on an inherent impl block"),
+        )
+    };
+    let impl_position = tokens
+        .iter()
+        .position(|token| is_ident(token, "impl"))
+        .ok_or_else(not_an_impl)?;
+    let body = match tokens.pop() {
+        Some(TokenTree::Group(body)) if body.delimiter() == Delimiter::Brace => body,
+        _ => return Err(not_an_impl()),
+    };
+    let header = &tokens[impl_position..];
+    for (i, token) in header.iter().enumerate() {
+        let higher_ranked =
+            matches!(header.get(i + 1), Some(TokenTree::Punct(p)) if p.as_char() == '<');
+        if is_ident(token, "for") && !higher_ranked {
+            return Err((
+                token.span(),
+                String::from("trait methods can not demand a librarian's pass, so restricted_section can only be cast on an inherent impl block"),
+            ));
+        }
+    }
+
+    let shelves = restrict_methods(body.stream())?;
+    // The generics of the impl end at the `>` that closes the `<` right after `impl`.
+    let mut generics_end = 1;
+    if matches!(header.get(1), Some(token) if is_punct(token, '<')) {
+        let mut depth = 0_usize;
+        for (i, token) in header.iter().enumerate().skip(1) {
+            let arrow = matches!(&header[i - 1], TokenTree::Punct(p) if p.as_char() == '-' && p.spacing() == Spacing::Joint);
+            if is_punct(token, '<') {
+                depth += 1;
+            } else if is_punct(token, '>') && !arrow {
+                depth -= 1;
+                if depth == 0 {
+                    generics_end = i + 1;
+                    break;
+                }
+            }
+        }
+    }
+    // The librarian is declared in an unnamed constant, so that only the section itself can find one
+    // to issue passes, and the pass can only be issued from the module the section is declared in.
+    let librarian: TokenStream = format!(
+        r#"
+        const _: () = {{
+            pub struct Librarian(());
+
+            {impl_generics} ::code_spells::restricted_section::RestrictedSection for {self_type} {{
+                type Librarian = Librarian;
+            }}
+
+            {header} {{
+                /// Issues a pass to the restricted section of this type.
+                #[cfg({gate})]
+                #[allow(dead_code)]
+                pub fn librarian_pass() -> ::code_spells::restricted_section::LibrarianPass<Self> {{
+                    ::code_spells::restricted_section::LibrarianPass::issue(Librarian(()))
+                }}
+            }}
+        }};
+        "#,
+        impl_generics = join(&header[..generics_end]),
+        self_type = join(&header[generics_end..]),
+        header = join(header),
+        gate = gate,
+    )
+    .parse()
+    .map_err(|e| {
+        (
+            Span::call_site(),
+            format!("restricted_section failed: {}", e),
+        )
+    })?;
+
+    let mut section = Group::new(Delimiter::Brace, shelves);
+    section.set_span(body.span());
+    tokens.push(TokenTree::from(section));
+    let mut section = TokenStream::from_iter(tokens);
+    section.extend(librarian);
+    Ok(section)
+}
+
+/// Removes every `#[restricted]` attribute from the body of an impl block,
+/// and adds a pass parameter to the method it was cast on.
+fn restrict_methods(body: TokenStream) -> Result<TokenStream, (Span, String)> {
+    let mut tokens = body.into_iter().peekable();
+    let mut out = Vec::new();
+    let mut restricted: Option<Span> = None;
+    while let Some(token) = tokens.next() {
+        if let TokenTree::Punct(hash) = &token {
+            if hash.as_char() == '#' {
+                if let Some(TokenTree::Group(attribute)) = tokens.peek() {
+                    if attribute.stream().to_string() == "restricted" {
+                        restricted = Some(attribute.span());
+                        tokens.next();
+                        continue;
+                    }
+                }
+            }
+        }
+        match (&token, restricted) {
+            (TokenTree::Ident(ident), Some(_)) if ident.to_string() == "fn" => {
+                restricted = None;
+                out.push(token);
+                let mut depth = 0_usize;
+                let mut previous: Option<TokenTree> = None;
+                for token in tokens.by_ref() {
+                    match &token {
+                        TokenTree::Group(parameters)
+                            if depth == 0 && parameters.delimiter() == Delimiter::Parenthesis =>
+                        {
+                            out.push(TokenTree::from(demand_pass(parameters)));
+                            break;
+                        }
+                        TokenTree::Punct(p) => depth = angle_depth(depth, p, previous.as_ref()),
+                        _ => {}
+                    }
+                    previous = Some(token.clone());
+                    out.push(token);
+                }
+            }
+            (TokenTree::Punct(p), Some(span)) if p.as_char() == ';' => {
+                return Err((
+                    span,
+                    String::from("#[restricted] can only be cast on methods"),
+                ))
+            }
+            (TokenTree::Group(g), Some(span)) if g.delimiter() == Delimiter::Brace => {
+                return Err((
+                    span,
+                    String::from("#[restricted] can only be cast on methods"),
+                ))
+            }
+            _ => out.push(token),
+        }
+    }
+    match restricted {
+        Some(span) => Err((
+            span,
+            String::from("#[restricted] can only be cast on methods"),
+        )),
+        None => Ok(TokenStream::from_iter(out)),
+    }
+}
+
+/// Adds a pass parameter after the receiver of a method, or first if it has none.
+fn demand_pass(parameters: &Group) -> Group {
+    let tokens: Vec<TokenTree> = parameters.stream().into_iter().collect();
+    let pass: TokenStream = "_: ::code_spells::restricted_section::LibrarianPass<Self>"
+        .parse()
+        .expect("the type of a librarian's pass is valid Rust");
+    let comma = || TokenTree::from(Punct::new(',', Spacing::Alone));
+
+    let mut depth = 0_usize;
+    let first_comma = tokens
+        .iter()
+        .enumerate()
+        .position(|(i, token)| match token {
+            TokenTree::Punct(p) if p.as_char() == ',' => depth == 0,
+            TokenTree::Punct(p) => {
+                depth = angle_depth(depth, p, i.checked_sub(1).map(|i| &tokens[i]));
+                false
+            }
+            _ => false,
+        })
+        .unwrap_or(tokens.len());
+    let (first, rest) = tokens.split_at(first_comma);
+
+    let mut out = TokenStream::new();
+    if first.iter().any(|token| is_ident(token, "self")) {
+        out.extend(first.iter().cloned());
+        out.extend([comma()]);
+        out.extend(pass);
+        out.extend(rest.iter().cloned());
+    } else {
+        out.extend(pass);
+        if !tokens.is_empty() {
+            out.extend([comma()]);
+            out.extend(tokens);
+        }
+    }
+    let mut group = Group::new(Delimiter::Parenthesis, out);
+    group.set_span(parameters.span());
+    group
+}
+
+/// Tracks how deep inside angle brackets a token is, ignoring the `>` of `->`.
+fn angle_depth(depth: usize, punct: &Punct, previous: Option<&TokenTree>) -> usize {
+    let arrow = matches!(
+        previous,
+        Some(TokenTree::Punct(p)) if p.as_char() == '-' && p.spacing() == Spacing::Joint
+    );
+    match punct.as_char() {
+        '<' => depth + 1,
+        '>' if !arrow => depth.saturating_sub(1),
+        _ => depth,
+    }
+}
+
+/// Turns the panics of the function it is cast on into errors, by running its body inside
+/// `code_spells::boggart::riddikulus`.
+///
+/// The return type `T` of the function becomes `Result<T, code_spells::boggart::Boggart>`, and a panic
+/// in its body is returned as the `Err` instead of unwinding out of the function.
+/// `return` and `?` in the body still return from the function as before, but wrapped in `Ok`.
+/// The attribute can not be cast on `async` or `const` functions.
+#[proc_macro_attribute]
+pub fn riddikulus(attr: TokenStream, item: TokenStream) -> TokenStream {
+    match laugh_at(attr, &item) {
+        Ok(harmless) => harmless,
+        Err((span, message)) => {
+            let mut out = spell_error(span, &message);
+            out.extend(item);
+            out
+        }
+    }
+}
+
+fn laugh_at(attr: TokenStream, item: &TokenStream) -> Result<TokenStream, (Span, String)> {
+    if let Some(token) = attr.into_iter().next() {
+        return Err((
+            token.span(),
+            String::from("riddikulus takes no arguments, it is cast as `#[riddikulus]`"),
+        ));
+    }
+    let mut tokens: Vec<TokenTree> = item.clone().into_iter().collect();
+    let not_a_function = || {
+        (
+            Span::call_site(),
+            String::from("riddikulus can only be cast on a function with a body"),
+        )
+    };
+    let body = match tokens.pop() {
+        Some(TokenTree::Group(body)) if body.delimiter() == Delimiter::Brace => body,
+        _ => return Err(not_a_function()),
+    };
+    let fn_position = tokens
+        .iter()
+        .position(|token| is_ident(token, "fn"))
+        .ok_or_else(not_a_function)?;
+    if let Some(qualifier) = tokens[..fn_position]
+        .iter()
+        .find(|token| is_ident(token, "async") || is_ident(token, "const"))
+    {
+        return Err((
+            qualifier.span(),
+            format!(
+                "panics can not be caught in a `{}` function, so riddikulus can not be cast on it",
+                qualifier
+            ),
+        ));
+    }
+
+    let mut depth = 0_usize;
+    let mut parameters = None;
+    for i in fn_position + 1..tokens.len() {
+        match &tokens[i] {
+            TokenTree::Group(group)
+                if depth == 0 && group.delimiter() == Delimiter::Parenthesis =>
+            {
+                parameters = Some(i);
+                break;
+            }
+            TokenTree::Punct(p) => depth = angle_depth(depth, p, Some(&tokens[i - 1])),
+            _ => {}
+        }
+    }
+    let parameters = parameters.ok_or_else(not_a_function)?;
+    let where_position = tokens[parameters..]
+        .iter()
+        .position(|token| is_ident(token, "where"))
+        .map_or(tokens.len(), |position| parameters + position);
+    let return_type: Vec<TokenTree> = match &tokens[parameters + 1..where_position] {
+        [minus, greater, return_type @ ..] if is_punct(minus, '-') && is_punct(greater, '>') => {
+            return_type.to_vec()
+        }
+        [] => "()".parse::<TokenStream>().unwrap().into_iter().collect(),
+        [token, ..] => {
+            return Err((
+                token.span(),
+                String::from("riddikulus could not read the return type of the function"),
+            ))
+        }
+    };
+
+    let mut out = TokenStream::from_iter(tokens[..=parameters].iter().cloned());
+    out.extend("-> ::core::result::Result<".parse::<TokenStream>().unwrap());
+    out.extend(return_type.iter().cloned());
+    out.extend(
+        ", ::code_spells::boggart::Boggart>"
+            .parse::<TokenStream>()
+            .unwrap(),
+    );
+    out.extend(tokens[where_position..].iter().cloned());
+
+    // Closures can not return `impl Trait`, so such return types are left for the compiler to infer.
+    let mut closure: TokenStream = "::code_spells::boggart::riddikulus".parse().unwrap();
+    let mut call: TokenStream = "move ||".parse().unwrap();
+    if !mentions(&return_type, "impl") {
+        call.extend("->".parse::<TokenStream>().unwrap());
+        call.extend(return_type);
+    }
+    call.extend([TokenTree::from(body.clone())]);
+    closure.extend([TokenTree::from(Group::new(Delimiter::Parenthesis, call))]);
+    let mut harmless = Group::new(Delimiter::Brace, closure);
+    harmless.set_span(body.span());
+    out.extend([TokenTree::from(harmless)]);
+    Ok(out)
+}
+
+/// Returns whether the tokens contain the identifier `name`, also inside groups.
+fn mentions(tokens: &[TokenTree], name: &str) -> bool {
+    tokens.iter().any(|token| match token {
+        TokenTree::Group(group) => {
+            let inner: Vec<TokenTree> = group.stream().into_iter().collect();
+            mentions(&inner, name)
+        }
+        token => is_ident(token, name),
+    })
+}
+
+/// Hisses literals into a byte string, producing a `&'static [u8]`.
+///
+/// The spell takes a comma separated list of string, byte string, character, byte and integer literals,
+/// and concatenates their bytes. Strings are encoded as UTF-8 and integers must fit in a byte,
+/// so text and arbitrary bytes can be mixed in a way that neither string nor byte string literals allow.
+/// Prefixing the list with `c` produces a `&'static CStr` instead, and fails to compile if it contains a NUL byte.
+/// # Examples
+/// ```
+/// # use code_spells_macros::parseltongue;
+/// const HISS: &[u8] = parseltongue!("ssss");
+/// assert_eq!(HISS, b"ssss");
+/// assert_eq!(parseltongue!("Nagini 🐍", 0xFF, b"\x00", '!'), b"Nagini \xF0\x9F\x90\x8D\xFF\x00!");
+///
+/// let name = parseltongue!(c "Salazar", b' ', "Slytherin");
+/// assert_eq!(name.to_str(), Ok("Salazar Slytherin"));
+/// ```
+/// Bytes must be bytes.
+/// ```compile_fail
+/// # use code_spells_macros::parseltongue;
+/// parseltongue!("ssss", 256);
+/// ```
+/// C strings can not contain NUL bytes.
+/// ```compile_fail
+/// # use code_spells_macros::parseltongue;
+/// parseltongue!(c "Tom", 0, "Riddle");
+/// ```
+#[proc_macro]
+pub fn parseltongue(input: TokenStream) -> TokenStream {
+    match hiss(input) {
+        Ok(hissed) => hissed,
+        Err((span, message)) => spell_error_expression(span, &message),
+    }
+}
+
+fn hiss(input: TokenStream) -> Result<TokenStream, (Span, String)> {
+    let mut tokens: Vec<TokenTree> = input.into_iter().collect();
+    let c_string = matches!(tokens.first(), Some(token) if is_ident(token, "c"));
+    if c_string {
+        tokens.remove(0);
+    }
+    if tokens.is_empty() {
+        return Err((
+            Span::call_site(),
+            String::from("parseltongue needs something to hiss, e.g. `parseltongue!(\"ssss\")`"),
+        ));
+    }
+
+    let mut bytes = Vec::new();
+    let mut c_string = c_string;
+    for words in tokens.split(|token| matches!(token, TokenTree::Punct(p) if p.as_char() == ',')) {
+        match words {
+            [] => {}
+            [TokenTree::Literal(literal)] => {
+                let start = bytes.len();
+                c_string |= hiss_literal(literal, &mut bytes)?;
+                if let Some(nul) = bytes[start..].iter().position(|&b| b == 0) {
+                    if c_string {
+                        return Err((
+                            literal.span(),
+                            format!("a CStr can not contain a NUL byte, but there is one at byte {}", start + nul),
+                        ));
+                    }
+                }
+            }
+            [token, ..] => {
+                return Err((
+                    token.span(),
+                    String::from("parseltongue only understands string, byte string, character, byte and integer literals"),
+                ))
+            }
+        }
+    }
+
+    if c_string {
+        if let Some(nul) = bytes.iter().position(|&b| b == 0) {
+            return Err((
+                Span::call_site(),
+                format!("a CStr can not contain a NUL byte, but there is one at byte {}", nul),
+            ));
+        }
+        bytes.push(0);
+        format!(
+            "{{
+                const PARSELTONGUE: &::core::ffi::CStr = match ::core::ffi::CStr::from_bytes_with_nul({}) {{
+                    ::core::result::Result::Ok(hissed) => hissed,
+                    ::core::result::Result::Err(_) => ::core::panic!(\"parseltongue hissed an invalid CStr\"),
+                }};
+                PARSELTONGUE
+            }}",
+            proc_macro::Literal::byte_string(&bytes)
+        )
+    } else {
+        format!(
+            "({} as &'static [u8])",
+            proc_macro::Literal::byte_string(&bytes)
+        )
+    }
+    .parse()
+    .map_err(|e| (Span::call_site(), format!("parseltongue failed: {}", e)))
+}
+
+/// Appends the bytes of a literal, and returns whether it was a C string literal.
+fn hiss_literal(
+    literal: &proc_macro::Literal,
+    bytes: &mut Vec<u8>,
+) -> Result<bool, (Span, String)> {
+    let span = literal.span();
+    let text = literal.to_string();
+    let (prefix, rest) = text.split_at(text.find(['"', '\'', '#']).unwrap_or(0));
+    match prefix {
+        "" | "b" | "c" if rest.starts_with('"') => {
+            unescape(&rest[1..rest.len() - 1], bytes, span)?;
+        }
+        "" | "b" if rest.starts_with('\'') => {
+            unescape(&rest[1..rest.len() - 1], bytes, span)?;
+        }
+        "r" | "br" | "cr" => {
+            let hashes = rest.len() - rest.trim_start_matches('#').len();
+            bytes.extend_from_slice(&rest.as_bytes()[hashes + 1..rest.len() - hashes - 1]);
+        }
+        _ => {
+            let byte = parse_byte(&text).ok_or_else(|| {
+                (
+                    span,
+                    format!("`{}` is not a byte, so parseltongue can not hiss it", text),
+                )
+            })?;
+            bytes.push(byte);
+        }
+    }
+    Ok(prefix.starts_with('c'))
+}
+
+/// Parses an unsuffixed or `u8` suffixed integer literal that fits in a byte.
+fn parse_byte(text: &str) -> Option<u8> {
+    let digits = text.strip_suffix("u8").unwrap_or(text).replace('_', "");
+    let (digits, radix) = match digits.get(..2) {
+        Some("0x") => (&digits[2..], 16),
+        Some("0o") => (&digits[2..], 8),
+        Some("0b") => (&digits[2..], 2),
+        _ => (&digits[..], 10),
+    };
+    u8::from_str_radix(digits, radix).ok()
+}
+
+/// Appends the bytes of the contents of a quoted literal, resolving its escapes.
+fn unescape(quoted: &str, bytes: &mut Vec<u8>, span: Span) -> Result<(), (Span, String)> {
+    let invalid = |escape: &str| {
+        (
+            span,
+            format!("parseltongue does not understand the escape `{}`", escape),
+        )
+    };
+    let mut chars = quoted.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            let mut buffer = [0; 4];
+            bytes.extend_from_slice(c.encode_utf8(&mut buffer).as_bytes());
+            continue;
+        }
+        match chars.next() {
+            Some('n') => bytes.push(b'\n'),
+            Some('r') => bytes.push(b'\r'),
+            Some('t') => bytes.push(b'\t'),
+            Some('0') => bytes.push(0),
+            Some(c @ ('\\' | '\'' | '"')) => bytes.push(c as u8),
+            Some('x') => {
+                let hex: String = chars.by_ref().take(2).collect();
+                let byte =
+                    u8::from_str_radix(&hex, 16).map_err(|_| invalid(&format!("\\x{}", hex)))?;
+                bytes.push(byte);
+            }
+            Some('u') => {
+                let code: String = chars.by_ref().take_while(|&c| c != '}').collect();
+                let c = code
+                    .strip_prefix('{')
+                    .and_then(|hex| u32::from_str_radix(&hex.replace('_', ""), 16).ok())
+                    .and_then(char::from_u32)
+                    .ok_or_else(|| invalid(&format!("\\u{}}}", code)))?;
+                let mut buffer = [0; 4];
+                bytes.extend_from_slice(c.encode_utf8(&mut buffer).as_bytes());
+            }
+            Some('\n') => {
+                while chars.peek().is_some_and(|c| c.is_whitespace()) {
+                    chars.next();
+                }
+            }
+            Some(c) => return Err(invalid(&format!("\\{}", c))),
+            None => return Err(invalid("\\")),
+        }
+    }
+    Ok(())
+}
+
+/// Every note that has been remembered, with its expiry date and the name of the crate it was remembered in.
+static REMEMBERED: Mutex<Vec<(String, String, String)>> = Mutex::new(Vec::new());
+
+/// Remembers a note until its expiry date, written as `YYYY-MM-DD`, after which the remembrall glows red
+/// and the crate stops compiling until the note is dealt with.
+/// The date is compared to the date of the build, which is taken from `SOURCE_DATE_EPOCH` if it is set,
+/// and to the current date in UTC otherwise. The note expires at the end of its expiry date.
+///
+/// The date is only checked when the compiler expands the spell. A build that does not compile the crate again,
+/// like an incremental build after the crate has not changed, keeps succeeding after the note has expired.
+///
+/// The spell is cast as an item or a statement and expands to nothing.
+/// The notes that have not expired can be listed with [`remembrall_report!`].
+/// # Examples
+/// ```
+/// # use code_spells_macros::remembrall;
+/// remembrall!("return the Remembrall to Neville", expires: "2999-09-01");
+/// ```
+/// ```compile_fail
+/// # use code_spells_macros::remembrall;
+/// remembrall!("find the Philosopher's Stone", expires: "1992-06-04"); // error: the Remembrall glows red ...
+/// ```
+/// The date must exist.
+/// ```compile_fail
+/// # use code_spells_macros::remembrall;
+/// remembrall!("take the train", expires: "2999-09-31");
+/// ```
+#[proc_macro]
+pub fn remembrall(input: TokenStream) -> TokenStream {
+    match remember(input) {
+        Ok(()) => TokenStream::new(),
+        Err((span, message)) => spell_error(span, &message),
+    }
+}
+
+fn remember(input: TokenStream) -> Result<(), (Span, String)> {
+    let tokens: Vec<TokenTree> = input.into_iter().collect();
+    let tokens = unwrap_invisible(&tokens);
+    let (note, expires) = match tokens.as_slice() {
+        [TokenTree::Literal(note), comma, key, colon, TokenTree::Literal(expires)]
+        | [TokenTree::Literal(note), comma, key, colon, TokenTree::Literal(expires), _]
+            if is_punct(comma, ',') && is_ident(key, "expires") && is_punct(colon, ':') =>
+        {
+            (note, expires)
+        }
+        _ => {
+            return Err((
+                Span::call_site(),
+                String::from("remembrall needs a note and when it expires, e.g. `remembrall!(\"feed Trevor\", expires: \"2025-12-01\")`"),
+            ))
+        }
+    };
+    let not_a_string = |literal: &proc_macro::Literal| {
+        (
+            literal.span(),
+            String::from("remembrall can only remember string literals"),
+        )
+    };
+    let text = string_literal(note).ok_or_else(|| not_a_string(note))?;
+    let date = string_literal(expires).ok_or_else(|| not_a_string(expires))?;
+    let expiry = days_since_epoch(&date).ok_or_else(|| {
+        (
+            expires.span(),
+            format!(
+                "`{}` is not a date that the remembrall understands, write it as `YYYY-MM-DD`",
+                date
+            ),
+        )
+    })?;
+    let today = build_day().map_err(|message| (Span::call_site(), message))?;
+    if today > expiry {
+        return Err((
+            note.span(),
+            format!(
+                "the Remembrall glows red: you have forgotten to {:?}, which expired on {} ({} days ago)",
+                text,
+                date,
+                today - expiry
+            ),
+        ));
+    }
+    let note = (crate_name(), text, date);
+    let mut remembered = REMEMBERED.lock().unwrap_or_else(PoisonError::into_inner);
+    if !remembered.contains(&note) {
+        remembered.push(note);
+    }
+    Ok(())
+}
+
+/// Lists the notes that have been remembered with [`remembrall!`] so far while compiling the current crate,
+/// as a `&'static [(&'static str, &'static str)]` of every note and its expiry date.
+///
+/// Notes are collected as the compiler expands the spells, so the report only lists the notes it came across
+/// before the report itself, which are usually the ones above it in the crate.
+/// Every note is listed once, and notes from other crates, told apart by their `CARGO_CRATE_NAME`, are never listed.
+/// A long-lived process like the proc-macro server of rust-analyzer keeps every note it has seen until it restarts,
+/// including notes that have since been removed from the code.
+/// # Example
+/// ```
+/// # use code_spells_macros::{remembrall, remembrall_report};
+/// remembrall!("practice the Wingardium Leviosa", expires: "2999-10-31");
+/// let notes = remembrall_report!();
+/// assert!(notes.contains(&("practice the Wingardium Leviosa", "2999-10-31")));
+/// ```
+#[proc_macro]
+pub fn remembrall_report(input: TokenStream) -> TokenStream {
+    if let Some(token) = input.into_iter().next() {
+        return spell_error_expression(
+            token.span(),
+            "remembrall_report! reports every note by itself and takes no arguments",
+        );
+    }
+    let remembered = REMEMBERED.lock().unwrap_or_else(PoisonError::into_inner);
+    let name = crate_name();
+    let notes: Vec<String> = remembered
+        .iter()
+        .filter(|(crate_name, _, _)| *crate_name == name)
+        .map(|(_, note, date)| {
+            format!(
+                "({}, {})",
+                proc_macro::Literal::string(note),
+                proc_macro::Literal::string(date)
+            )
+        })
+        .collect();
+    format!(
+        "(&[{}] as &'static [(&'static str, &'static str)])",
+        notes.join(", ")
+    )
+    .parse()
+    .expect("the report is valid Rust")
+}
+
+/// Every reason that has been given to the Wizengamot for casting an unforgivable curse,
+/// along with the name of the crate it was given in.
+static TESTIMONIES: Mutex<Vec<(String, String)>> = Mutex::new(Vec::new());
+
+/// Records the reason for casting an unforgivable curse with the Wizengamot.
+///
+/// The spell is cast as an item or a statement and expands to nothing.
+/// It is cast by `unforgivable!("reason" => { code })` in `code-spells` with the `wizengamot` feature,
+/// and the recorded reasons can be listed with [`wizengamot_report!`].
+/// # Example
+/// ```
+/// # use code_spells_macros::wizengamot;
+/// wizengamot!("the pointer is non-null by construction");
+/// ```
+/// Only string literals are heard.
+/// ```compile_fail
+/// # use code_spells_macros::wizengamot;
+/// wizengamot!(42);
+/// ```
+#[proc_macro]
+pub fn wizengamot(input: TokenStream) -> TokenStream {
+    let tokens: Vec<TokenTree> = input.into_iter().collect();
+    let tokens = unwrap_invisible(&tokens);
+    let reason = match tokens.as_slice() {
+        [TokenTree::Literal(reason)] => reason,
+        _ => return spell_error(
+            Span::call_site(),
+            "the Wizengamot hears a single reason, e.g. `wizengamot!(\"the pointer is non-null\")`",
+        ),
+    };
+    match string_literal(reason) {
+        Some(testimony) => {
+            let testimony = (crate_name(), testimony);
+            let mut testimonies = TESTIMONIES.lock().unwrap_or_else(PoisonError::into_inner);
+            if !testimonies.contains(&testimony) {
+                testimonies.push(testimony);
+            }
+            TokenStream::new()
+        }
+        None => spell_error(
+            reason.span(),
+            "the Wizengamot only hears reasons written as string literals",
+        ),
+    }
+}
+
+/// Lists the reasons that have been recorded with [`wizengamot!`] so far while compiling the current crate,
+/// as a `&'static [&'static str]`.
+///
+/// Like [`remembrall_report!`] the report only lists the reasons the compiler came across before the report itself.
+/// Every reason is listed once, even if it was given, or expanded, more than once.
+///
+/// The reasons are kept in the memory of the process that expands the spells, and are told apart by the
+/// `CARGO_CRATE_NAME` of the crate they were given in, so the report depends on the order of expansion
+/// and on the tool that expands it.
+/// A long-lived process like the proc-macro server of rust-analyzer keeps every reason it has heard until it
+/// restarts, including reasons that have since been removed from the code, and incremental builds may not
+/// expand every spell again, and list fewer reasons.
+/// # Example
+/// ```
+/// # use code_spells_macros::{wizengamot, wizengamot_report};
+/// wizengamot!("two is not zero");
+/// let testimonies = wizengamot_report!();
+/// assert!(testimonies.contains(&"two is not zero"));
+/// ```
+#[proc_macro]
+pub fn wizengamot_report(input: TokenStream) -> TokenStream {
+    if let Some(token) = input.into_iter().next() {
+        return spell_error_expression(
+            token.span(),
+            "wizengamot_report! reports every reason by itself and takes no arguments",
+        );
+    }
+    let testimonies = TESTIMONIES.lock().unwrap_or_else(PoisonError::into_inner);
+    let name = crate_name();
+    let reasons: Vec<String> = testimonies
+        .iter()
+        .filter(|(crate_name, _)| *crate_name == name)
+        .map(|(_, reason)| proc_macro::Literal::string(reason).to_string())
+        .collect();
+    format!("(&[{}] as &'static [&'static str])", reasons.join(", "))
+        .parse()
+        .expect("the report is valid Rust")
+}
+
+/// Returns the name of the crate that is being compiled, as set by Cargo, or an empty string outside of Cargo.
+fn crate_name() -> String {
+    std::env::var("CARGO_CRATE_NAME").unwrap_or_default()
+}
+
+/// Returns the contents of a, possibly raw, string literal.
+fn string_literal(literal: &proc_macro::Literal) -> Option<String> {
+    let text = literal.to_string();
+    if !(text.starts_with('"') || text.starts_with("r\"") || text.starts_with("r#")) {
+        return None;
+    }
+    let mut bytes = Vec::new();
+    hiss_literal(literal, &mut bytes).ok()?;
+    String::from_utf8(bytes).ok()
+}
+
+/// Returns the number of days from 1970-01-01 to a date written as `YYYY-MM-DD`,
+/// or `None` if the date does not exist.
+fn days_since_epoch(date: &str) -> Option<i64> {
+    let mut parts = date.splitn(3, '-');
+    let mut part = |digits: usize| {
+        parts
+            .next()
+            .filter(|part| part.len() == digits && part.bytes().all(|b| b.is_ascii_digit()))
+            .and_then(|part| part.parse::<i64>().ok())
+    };
+    let (year, month, day) = (part(4)?, part(2)?, part(2)?);
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let days_in_month = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if leap => 29,
+        2 => 28,
+        _ => return None,
+    };
+    if !(1..=days_in_month).contains(&day) {
+        return None;
+    }
+    // Counts from the 1st of March so that the leap day is at the end of the year.
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    Some(era * 146_097 + day_of_era - 719_468)
+}
+
+/// Returns the number of days from 1970-01-01 to the date of the build.
+fn build_day() -> Result<i64, String> {
+    let seconds = match std::env::var("SOURCE_DATE_EPOCH") {
+        Ok(epoch) => epoch.trim().parse::<i64>().map_err(|_| {
+            format!(
+                "the remembrall can not read the SOURCE_DATE_EPOCH `{}`",
+                epoch
+            )
+        })?,
+        Err(_) => match SystemTime::now().duration_since(UNIX_EPOCH) {
+            Ok(elapsed) => i64::try_from(elapsed.as_secs()).unwrap_or(i64::MAX),
+            Err(before) => -i64::try_from(before.duration().as_secs()).unwrap_or(i64::MAX),
+        },
+    };
+    Ok(seconds.div_euclid(86_400))
+}
+
+/// Derives `code_spells::st_mungos::Triage` for an enum or a struct, sending it to the ward of St Mungo's
+/// named by its `#[ward(...)]` attributes.
+///
+/// Every variant of an enum is sent to the ward named by its own attribute, or by the attribute on the enum
+/// if it has none. A struct is sent to the ward named by its attribute.
+/// The wards are `Transient`, `Permanent`, `SpellDamage` and `ArtifactAccident`,
+/// and `#[ward(inherit)]` sends a variant or struct to the ward of its first field, which must implement `Triage`.
+/// Generic types are not supported.
+#[proc_macro_derive(Triage, attributes(ward))]
+pub fn triage(item: TokenStream) -> TokenStream {
+    match admit(item) {
+        Ok(triage) => triage,
+        Err((span, message)) => spell_error(span, &message),
+    }
+}
+
+/// The wards that `#[ward(...)]` can name, other than `inherit`.
+const WARDS: [&str; 4] = ["Transient", "Permanent", "SpellDamage", "ArtifactAccident"];
+
+fn admit(item: TokenStream) -> Result<TokenStream, (Span, String)> {
+    let tokens: Vec<TokenTree> = item.into_iter().collect();
+    let keyword = tokens
+        .iter()
+        .position(|token| is_ident(token, "enum") || is_ident(token, "struct"))
+        .ok_or_else(|| {
+            (
+                Span::call_site(),
+                String::from("only enums and structs can be triaged"),
+            )
+        })?;
+    let default = ward_attribute(&tokens[..keyword])?;
+    let name = match tokens.get(keyword + 1) {
+        Some(TokenTree::Ident(name)) => name.clone(),
+        _ => {
+            return Err((
+                tokens[keyword].span(),
+                String::from("the patient has no name"),
+            ))
+        }
+    };
+    if let Some(angle) = tokens.get(keyword + 2).filter(|token| is_punct(token, '<')) {
+        return Err((
+            angle.span(),
+            String::from(
+                "generic types can not be triaged, as St Mungo's only admits one patient at a time",
+            ),
+        ));
+    }
+    let body = tokens[keyword + 2..].iter().find_map(|token| match token {
+        TokenTree::Group(group) if group.delimiter() != Delimiter::Bracket => Some(group.clone()),
+        _ => None,
+    });
+
+    let arms = if is_ident(&tokens[keyword], "struct") {
+        let (ward, span) = default.ok_or_else(|| {
+            (
+                name.span(),
+                String::from("a struct needs a ward to be sent to, e.g. `#[ward(Permanent)]`"),
+            )
+        })?;
+        vec![triage_arm("Self", body.as_ref(), &ward, span)?]
+    } else {
+        let body = body.ok_or_else(|| (name.span(), String::from("the enum has no variants")))?;
+        let variants: Vec<TokenTree> = body.stream().into_iter().collect();
+        let mut arms = Vec::new();
+        for variant in variants.split(|token| is_punct(token, ',')) {
+            let position = match variant
+                .iter()
+                .position(|token| matches!(token, TokenTree::Ident(_)))
+            {
+                Some(position) => position,
+                None => continue,
+            };
+            let variant_name = &variant[position];
+            let (ward, span) = match ward_attribute(&variant[..position])? {
+                Some(ward) => ward,
+                None => default.clone().ok_or_else(|| {
+                    (
+                        variant_name.span(),
+                        format!(
+                            "the variant `{}` needs a ward to be sent to, e.g. `#[ward(Permanent)]` on it or on the enum",
+                            variant_name
+                        ),
+                    )
+                })?,
+            };
+            let fields = match variant.get(position + 1) {
+                Some(TokenTree::Group(group)) if group.delimiter() != Delimiter::Bracket => {
+                    Some(group.clone())
+                }
+                _ => None,
+            };
+            arms.push(triage_arm(
+                &format!("Self::{}", variant_name),
+                fields.as_ref(),
+                &ward,
+                span,
+            )?);
+        }
+        arms
+    };
+
+    format!(
+        "impl ::code_spells::st_mungos::Triage for {} {{
+            fn ward(&self) -> ::code_spells::st_mungos::Ward {{
+                match *self {{
+                    {}
+                }}
+            }}
+        }}",
+        name,
+        arms.join("\n")
+    )
+    .parse()
+    .map_err(|e| (Span::call_site(), format!("triage failed: {}", e)))
+}
+
+/// Returns the ward named by the `#[ward(...)]` attribute among the given tokens, if there is one,
+/// and the span of the name of the ward.
+fn ward_attribute(tokens: &[TokenTree]) -> Result<Option<(String, Span)>, (Span, String)> {
+    for attribute in tokens.iter().filter_map(|token| match token {
+        TokenTree::Group(group) if group.delimiter() == Delimiter::Bracket => Some(group),
+        _ => None,
+    }) {
+        let attribute: Vec<TokenTree> = attribute.stream().into_iter().collect();
+        match attribute.as_slice() {
+            [ward, TokenTree::Group(name)] if is_ident(ward, "ward") => {
+                let name: Vec<TokenTree> = name.stream().into_iter().collect();
+                return match name.as_slice() {
+                    [TokenTree::Ident(name)]
+                        if name.to_string() == "inherit"
+                            || WARDS.contains(&name.to_string().as_str()) =>
+                    {
+                        Ok(Some((name.to_string(), name.span())))
+                    }
+                    _ => Err((
+                        attribute[1].span(),
+                        format!(
+                            "St Mungo's has no such ward, send the patient to one of {} or `inherit`",
+                            WARDS.join(", ")
+                        ),
+                    )),
+                };
+            }
+            _ => {}
+        }
+    }
+    Ok(None)
+}
+
+/// Returns the match arm that sends the variant or struct at `path`, with the given fields, to `ward`.
+fn triage_arm(
+    path: &str,
+    fields: Option<&Group>,
+    ward: &str,
+    span: Span,
+) -> Result<String, (Span, String)> {
+    if ward != "inherit" {
+        return Ok(format!(
+            "{} {{ .. }} => ::code_spells::st_mungos::Ward::{},",
+            path, ward
+        ));
+    }
+    let first = fields.and_then(|fields| match fields.delimiter() {
+        Delimiter::Parenthesis if !fields.stream().is_empty() => Some(String::from("0")),
+        Delimiter::Brace => {
+            let fields: Vec<TokenTree> = fields.stream().into_iter().collect();
+            fields.windows(2).find_map(|pair| match pair {
+                [TokenTree::Ident(field), TokenTree::Punct(colon)]
+                    if colon.as_char() == ':' && colon.spacing() == Spacing::Alone =>
+                {
+                    Some(field.to_string())
+                }
+                _ => None,
+            })
+        }
+        _ => None,
+    });
+    let first = first.ok_or_else(|| {
+        (
+            span,
+            String::from("only a patient with a field can inherit the ward of its first field"),
+        )
+    })?;
+    Ok(format!(
+        "{} {{ {}: ref inner, .. }} => ::code_spells::st_mungos::Triage::ward(inner),",
+        path, first
+    ))
+}
+
+/// Reads the margin notes of a talented previous owner: warns about casts of a spell that have a better alternative,
+/// such as cloning a literal or unwrapping a constant `Some`.
+/// Stable Rust has no way for a macro to emit a warning of its own, so every note is reported as
+/// the use of a deprecated constant at the tokens it is about.
+///
+/// The spells of `code-spells` read their notes themselves when its `princes-notes` feature is enabled,
+/// so this is not meant to be cast directly. It takes the name of a spell followed by its arguments in parentheses,
+/// and knows the notes of `geminio`, `expecto_patronum` and `reparo`.
+/// # Examples
+/// ```compile_fail
+/// #![deny(deprecated)]
+/// # use code_spells_macros::half_blood_prince;
+/// half_blood_prince! { geminio (&5) } // error: this clones a literal ...
+/// ```
+/// ```
+/// #![deny(deprecated)]
+/// # use code_spells_macros::half_blood_prince;
+/// half_blood_prince! { expecto_patronum ("5".parse::<u8>(), "not a number") }
+/// ```
+#[proc_macro]
+pub fn half_blood_prince(input: TokenStream) -> TokenStream {
+    let mut tokens = input.into_iter();
+    let (spell, arguments) = match (tokens.next(), tokens.next(), tokens.next()) {
+        (Some(TokenTree::Ident(spell)), Some(TokenTree::Group(arguments)), None)
+            if arguments.delimiter() == Delimiter::Parenthesis =>
+        {
+            (spell, arguments)
+        }
+        _ => {
+            return spell_error(
+                Span::call_site(),
+                "half_blood_prince! reads a spell name followed by its arguments, e.g. `half_blood_prince! { geminio (&wand) }`",
+            )
+        }
+    };
+    let tokens: Vec<TokenTree> = arguments.stream().into_iter().collect();
+    let arguments: Vec<Vec<TokenTree>> = tokens
+        .split(|token| is_punct(token, ','))
+        .map(unwrap_invisible)
+        .collect();
+
+    let notes = match (spell.to_string().as_str(), arguments.as_slice()) {
+        ("geminio", [object]) => geminio_notes(object),
+        ("expecto_patronum", [danger]) => expecto_patronum_notes(danger, None),
+        ("expecto_patronum", [danger, message]) => expecto_patronum_notes(danger, Some(message)),
+        ("reparo", [result]) => reparo_notes(result, None),
+        ("reparo", [result, fallback]) => reparo_notes(result, Some(fallback)),
+        (name, _) => {
+            return spell_error(
+                spell.span(),
+                &format!(
+                    "the prince left no notes on how to cast `{}!` like this",
+                    name
+                ),
+            )
+        }
+    };
+    TokenStream::from_iter(
+        notes
+            .into_iter()
+            .map(|(span, note)| margin_note(span, note)),
+    )
+}
+
+fn geminio_notes(object: &[TokenTree]) -> Vec<(Span, &'static str)> {
+    let mut notes = Vec::new();
+    let value = match object {
+        [ampersand, value @ ..] if is_punct(ampersand, '&') => value,
+        _ => return notes,
+    };
+    match value {
+        [ampersand, ..] if is_punct(ampersand, '&') => notes.push((
+            ampersand.span(),
+            "this clones the reference, not the value behind it: remove one `&`",
+        )),
+        [TokenTree::Literal(literal)] => notes.push((
+            literal.span(),
+            "this clones a literal, which is `Copy`: use the literal directly",
+        )),
+        [boolean] if is_ident(boolean, "true") || is_ident(boolean, "false") => notes.push((
+            boolean.span(),
+            "this clones a literal, which is `Copy`: use the literal directly",
+        )),
+        _ => {}
+    }
+    if let Some(span) = trailing_call(value, &["clone"]) {
+        notes.push((
+            span,
+            "this clones twice: the value returned by `.clone()` is already a copy",
+        ));
+    }
+    if let Some(span) = allocation(value) {
+        notes.push((
+            span,
+            "this clones a value that was just created: use it directly instead",
+        ));
+    }
+    notes
+}
+
+fn expecto_patronum_notes(
+    danger: &[TokenTree],
+    message: Option<&Vec<TokenTree>>,
+) -> Vec<(Span, &'static str)> {
+    let mut notes = Vec::new();
+    if let Some(span) = constant_variant(danger, &["Some", "Ok"]) {
+        notes.push((
+            span,
+            "this unwraps a constant `Some` or `Ok`, which can never fail: use the value inside directly",
+        ));
+    }
+    if let Some(span) = constant_variant(danger, &["None", "Err"]) {
+        notes.push((
+            span,
+            "this unwraps a constant `None` or `Err`, which always panics: cast `avada_kedavra!` instead",
+        ));
+    }
+    if let Some(span) = trailing_call(danger, &["ok"]) {
+        notes.push((
+            span,
+            "`.ok()` throws the error away: unwrap the `Result` itself so that the error ends up in the panic message",
+        ));
+    }
+    if let Some([TokenTree::Literal(literal)]) = message.map(Vec::as_slice) {
+        if literal.to_string() == "\"\"" {
+            notes.push((
+                literal.span(),
+                "an empty message does not help anyone understand the panic: write one, or leave it out",
+            ));
+        }
+    }
+    notes
+}
+
+fn reparo_notes(
+    result: &[TokenTree],
+    fallback: Option<&Vec<TokenTree>>,
+) -> Vec<(Span, &'static str)> {
+    let mut notes = Vec::new();
+    if let Some(span) = constant_variant(result, &["Some", "Ok"]) {
+        notes.push((
+            span,
+            "this repairs a constant `Some` or `Ok`, which is never broken: use the value inside directly",
+        ));
+    }
+    let fallback = match fallback {
+        Some(fallback) => fallback,
+        None => return notes,
+    };
+    let is_default = match fallback.as_slice() {
+        [path @ .., call, TokenTree::Group(arguments)]
+            if arguments.delimiter() == Delimiter::Parenthesis && arguments.stream().is_empty() =>
+        {
+            is_path(path)
+                && (is_ident(call, "default")
+                    || (is_ident(call, "new")
+                        && matches!(path.first(), Some(ty) if is_ident(ty, "String") || is_ident(ty, "Vec"))))
+        }
+        _ => false,
+    };
+    if is_default {
+        notes.push((
+            fallback[0].span(),
+            "the fallback is the default value: cast `reparo!` without a fallback instead",
+        ));
+    } else if let Some(span) = allocation(fallback) {
+        notes.push((
+            span,
+            "the fallback is built even when it is not needed: pass a closure to build it lazily",
+        ));
+    }
+    notes
+}
+
+/// Returns the span of `variant` if the tokens are a, possibly qualified, use of one of the `variants`.
+fn constant_variant(tokens: &[TokenTree], variants: &[&str]) -> Option<Span> {
+    let (path, variant) = match tokens {
+        [path @ .., variant, TokenTree::Group(value)]
+            if value.delimiter() == Delimiter::Parenthesis =>
+        {
+            (path, variant)
+        }
+        [path @ .., variant] => (path, variant),
+        [] => return None,
+    };
+    let known = variants.iter().any(|name| is_ident(variant, name));
+    (known && is_path(path)).then(|| variant.span())
+}
+
+/// Returns the span of the method name if the tokens end with a call of one of the `methods` without arguments.
+fn trailing_call(tokens: &[TokenTree], methods: &[&str]) -> Option<Span> {
+    match tokens {
+        [.., dot, method, TokenTree::Group(arguments)]
+            if is_punct(dot, '.')
+                && methods.iter().any(|name| is_ident(method, name))
+                && arguments.delimiter() == Delimiter::Parenthesis
+                && arguments.stream().is_empty() =>
+        {
+            Some(method.span())
+        }
+        _ => None,
+    }
+}
+
+/// Returns a span if the tokens obviously allocate a new value.
+fn allocation(tokens: &[TokenTree]) -> Option<Span> {
+    match tokens {
+        [macro_name, bang, TokenTree::Group(_)]
+            if (is_ident(macro_name, "format") || is_ident(macro_name, "vec"))
+                && is_punct(bang, '!') =>
+        {
+            Some(macro_name.span())
+        }
+        [string, colon, colon2, from, TokenTree::Group(_)]
+            if is_ident(string, "String")
+                && is_punct(colon, ':')
+                && is_punct(colon2, ':')
+                && is_ident(from, "from") =>
+        {
+            Some(string.span())
+        }
+        _ => trailing_call(tokens, &["to_string", "to_owned", "to_vec"]),
+    }
+}
+
+/// Returns whether the tokens are empty or a path prefix like `core::option::Option::`.
+fn is_path(tokens: &[TokenTree]) -> bool {
+    tokens
+        .iter()
+        .all(|token| matches!(token, TokenTree::Ident(_)) || is_punct(token, ':'))
+}
+
+/// Unwraps the invisible groups that `macro_rules!` puts around the fragments it passes on.
+fn unwrap_invisible(tokens: &[TokenTree]) -> Vec<TokenTree> {
+    match tokens {
+        [TokenTree::Group(group)] if group.delimiter() == Delimiter::None => {
+            let inner: Vec<TokenTree> = group.stream().into_iter().collect();
+            unwrap_invisible(&inner)
+        }
+        _ => tokens.to_vec(),
+    }
+}
+
+/// Expands to a statement that warns with the given note at the given span,
+/// by using a deprecated constant.
+fn margin_note(span: Span, note: &str) -> TokenStream {
+    let note = proc_macro::Literal::string(note);
+    let statement: TokenStream = format!(
+        "{{
+            #[deprecated(note = {})]
+            #[allow(non_upper_case_globals)]
+            const half_blood_prince: () = ();
+            #[allow(clippy::let_unit_value)]
+            let _ = half_blood_prince;
+        }}",
+        note
+    )
+    .parse()
+    .expect("the margin note is valid Rust");
+    respan(statement, span)
+}
+
+/// Sets the span of every token in the stream.
+fn respan(stream: TokenStream, span: Span) -> TokenStream {
+    stream
+        .into_iter()
+        .map(|mut token| {
+            if let TokenTree::Group(group) = &token {
+                let mut respanned = Group::new(group.delimiter(), respan(group.stream(), span));
+                respanned.set_span(span);
+                token = TokenTree::from(respanned);
+            } else {
+                token.set_span(span);
+            }
+            token
+        })
+        .collect()
+}
+
+fn is_punct(token: &TokenTree, c: char) -> bool {
+    matches!(token, TokenTree::Punct(p) if p.as_char() == c)
+}
+
+fn is_ident(token: &TokenTree, name: &str) -> bool {
+    matches!(token, TokenTree::Ident(ident) if ident.to_string() == name)
+}
+
+fn join(tokens: &[TokenTree]) -> String {
+    TokenStream::from_iter(tokens.iter().cloned()).to_string()
+}
+
+/// Expands to a `compile_error!` statement with the given message at the given span.
+fn spell_error(span: Span, message: &str) -> TokenStream {
+    let mut semi = Punct::new(';', Spacing::Alone);
+    semi.set_span(span);
+    let mut error = spell_error_expression(span, message);
+    error.extend([TokenTree::from(semi)]);
+    error
+}
+
+/// Expands to a `compile_error!` expression with the given message at the given span.
+fn spell_error_expression(span: Span, message: &str) -> TokenStream {
+    let mut literal = proc_macro::Literal::string(message);
+    literal.set_span(span);
+    let mut bang = Punct::new('!', Spacing::Alone);
+    bang.set_span(span);
+    let mut message = Group::new(
+        Delimiter::Parenthesis,
+        TokenStream::from_iter([TokenTree::from(literal)]),
+    );
+    message.set_span(span);
+    TokenStream::from_iter([
+        TokenTree::from(Ident::new("compile_error", span)),
+        TokenTree::from(bang),
+        TokenTree::from(message),
+    ])
+}
@@ -0,0 +1,441 @@
+//! Command line arguments, cast with [`accio_argv!`](crate::accio_argv!).
+//!
+//! The spell lists the arguments that a program can be called with, and generates a struct with one field per argument
+//! that summons their values from the command line. There are three kinds of arguments:
+//! - a `flag` is a `bool` that is `true` if `--name` is given,
+//! - an `option` is given as `--name value` or `--name=value`, and is required unless it has a default,
+//! - a `positional` is given without a name, in the order that the positionals are listed in, and is also
+//!   required unless it has a default.
+//!
+//! Values are parsed with [`FromStr`], and defaults are written like values on the command line
+//! would be, since they are parsed the same way. Underscores in the names of flags and options are written as dashes,
+//! so `dry_run` is given as `--dry-run`. Everything after `--` is a positional.
+//!
+//! Casting `--help` or `-h` summons an [`Unsummonable::Help`] with a list of the arguments,
+//! described by the doc comments of the fields.
+//! ```
+//! # use code_spells::accio_argv;
+//! use std::path::PathBuf;
+//!
+//! accio_argv! {
+//!     /// The arguments of the Marauder's Map.
+//!     struct Args {
+//!         /// Shows every footstep in the castle.
+//!         flag verbose: bool,
+//!         /// The name of the wizard to find.
+//!         option name: String = "harry",
+//!         /// Where to write the map.
+//!         positional map: PathBuf,
+//!     }
+//! }
+//!
+//! let args = Args::accio_from(["marauders-map", "--verbose", "parchment.txt"])?;
+//! assert!(args.verbose);
+//! assert_eq!(args.name, "harry");
+//! assert_eq!(args.map, PathBuf::from("parchment.txt"));
+//! # Ok::<(), code_spells::accio_argv::Unsummonable>(())
+//! ```
+
+use core::fmt;
+use core::str::FromStr;
+use std::process;
+
+/// The error returned when the command line arguments could not be summoned.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Unsummonable {
+    /// `--help` or `-h` was given. Contains the list of arguments that can be summoned.
+    Help(String),
+    /// An argument that the program does not know about was given.
+    UnknownSpell(String),
+    /// An option was given as the last argument, without a value.
+    MissingValue(String),
+    /// A flag was given a value with `--flag=value`.
+    UnexpectedValue(String),
+    /// A required option or positional was not given.
+    Missing(String),
+    /// A value could not be parsed into the type of its argument.
+    Invalid {
+        /// The argument, like `--name` or `<path>`.
+        argument: String,
+        /// The value that was given for it.
+        value: String,
+        /// Why the value could not be parsed.
+        reason: String,
+    },
+    /// More positionals were given than the program has.
+    TooMany(String),
+}
+
+impl Unsummonable {
+    /// Prints the list of arguments to stdout and exits with code 0 if this is [`Help`](Unsummonable::Help),
+    /// and otherwise prints the error to stderr and exits with code 2.
+    pub fn exit(self) -> ! {
+        match self {
+            Self::Help(help) => {
+                print!("{}", help);
+                process::exit(0)
+            }
+            error => {
+                eprintln!("error: {}", error);
+                eprintln!("Cast --help to list the arguments that can be summoned.");
+                process::exit(2)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Unsummonable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Help(help) => f.write_str(help),
+            Self::UnknownSpell(argument) => {
+                write!(f, "`{}` is not an argument that can be summoned", argument)
+            }
+            Self::MissingValue(argument) => {
+                write!(f, "`{}` was summoned without a value", argument)
+            }
+            Self::UnexpectedValue(argument) => {
+                write!(f, "`{}` is a flag, and can not be given a value", argument)
+            }
+            Self::Missing(argument) => write!(f, "`{}` must be summoned", argument),
+            Self::Invalid {
+                argument,
+                value,
+                reason,
+            } => write!(
+                f,
+                "could not summon `{}` from `{}`: {}",
+                argument, value, reason
+            ),
+            Self::TooMany(value) => write!(
+                f,
+                "`{}` was summoned, but there is no argument left to summon it into",
+                value
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Unsummonable {}
+
+#[doc(hidden)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Flag,
+    Option,
+    Positional,
+}
+
+/// An argument as it was listed in [`accio_argv!`](crate::accio_argv!).
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct Spell {
+    pub kind: Kind,
+    pub name: &'static str,
+    pub ty: &'static str,
+    pub default: Option<String>,
+    pub doc: &'static [&'static str],
+}
+
+impl Spell {
+    /// The argument as it is written on the command line, like `--dry-run` or `<path>`.
+    fn incantation(&self) -> String {
+        match self.kind {
+            Kind::Positional => format!("<{}>", self.name),
+            Kind::Flag | Kind::Option => format!("--{}", self.name.replace('_', "-")),
+        }
+    }
+
+    /// The argument as it is listed in the help.
+    fn usage(&self) -> String {
+        match self.kind {
+            Kind::Option => format!("{} <{}>", self.incantation(), self.ty),
+            Kind::Flag | Kind::Positional => self.incantation(),
+        }
+    }
+}
+
+/// The values summoned for every [`Spell`], before they are parsed.
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct Summoned {
+    spells: Vec<Spell>,
+    values: Vec<Option<String>>,
+}
+
+impl Summoned {
+    /// Summons the values for `spells` from `args`, whose first item is the name of the program.
+    pub fn summon<I, S>(spells: Vec<Spell>, args: I) -> Result<Self, Unsummonable>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut args = args.into_iter().map(Into::into);
+        let program = args.next().unwrap_or_default();
+        let mut values: Vec<Option<String>> = vec![None; spells.len()];
+        let mut positionals = spells
+            .iter()
+            .enumerate()
+            .filter(|(_, spell)| spell.kind == Kind::Positional)
+            .map(|(index, _)| index);
+        let mut only_positionals = false;
+
+        while let Some(arg) = args.next() {
+            let named = !only_positionals && arg.starts_with('-') && arg != "-";
+            if !named {
+                let index = positionals
+                    .next()
+                    .ok_or_else(|| Unsummonable::TooMany(arg.clone()))?;
+                values[index] = Some(arg);
+            } else if arg == "--" {
+                only_positionals = true;
+            } else if arg == "--help" || arg == "-h" {
+                return Err(Unsummonable::Help(help(&program, &spells)));
+            } else {
+                let (incantation, value) = match arg.split_once('=') {
+                    Some((incantation, value)) => (incantation, Some(value.to_owned())),
+                    None => (arg.as_str(), None),
+                };
+                let index = spells
+                    .iter()
+                    .position(|spell| {
+                        spell.kind != Kind::Positional && spell.incantation() == incantation
+                    })
+                    .ok_or_else(|| Unsummonable::UnknownSpell(incantation.to_owned()))?;
+                values[index] = match (spells[index].kind, value) {
+                    (Kind::Flag, None) => Some(String::new()),
+                    (Kind::Flag, Some(_)) => {
+                        return Err(Unsummonable::UnexpectedValue(incantation.to_owned()))
+                    }
+                    (_, Some(value)) => Some(value),
+                    (_, None) => Some(
+                        args.next()
+                            .ok_or_else(|| Unsummonable::MissingValue(incantation.to_owned()))?,
+                    ),
+                };
+            }
+        }
+
+        for (value, spell) in values.iter_mut().zip(&spells) {
+            if value.is_none() {
+                value.clone_from(&spell.default);
+            }
+        }
+        Ok(Self { spells, values })
+    }
+
+    /// Returns whether the flag `name` was given.
+    pub fn flag(&self, name: &str) -> bool {
+        self.values[self.index(name)].is_some()
+    }
+
+    /// Parses the value of the option or positional `name`.
+    pub fn value<T>(&mut self, name: &str) -> Result<T, Unsummonable>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        let index = self.index(name);
+        let spell = &self.spells[index];
+        let value = self.values[index]
+            .take()
+            .ok_or_else(|| Unsummonable::Missing(spell.incantation()))?;
+        value
+            .parse()
+            .map_err(|reason: T::Err| Unsummonable::Invalid {
+                argument: spell.incantation(),
+                reason: reason.to_string(),
+                value,
+            })
+    }
+
+    fn index(&self, name: &str) -> usize {
+        self.spells
+            .iter()
+            .position(|spell| spell.name == name)
+            .expect("every field of the struct is listed as a spell")
+    }
+}
+
+/// Lists the arguments of the program.
+fn help(program: &str, spells: &[Spell]) -> String {
+    let mut usage = format!("Usage: {}", program);
+    if spells.iter().any(|spell| spell.kind != Kind::Positional) {
+        usage.push_str(" [OPTIONS]");
+    }
+    for spell in spells.iter().filter(|spell| spell.kind == Kind::Positional) {
+        if spell.default.is_some() {
+            usage.push_str(&format!(" [{}]", spell.incantation()));
+        } else {
+            usage.push_str(&format!(" {}", spell.incantation()));
+        }
+    }
+
+    let width = spells
+        .iter()
+        .map(|spell| spell.usage().len())
+        .chain(Some("-h, --help".len()))
+        .max()
+        .unwrap_or(0);
+    let line = |usage: &str, doc: &[&str], default: Option<&String>| {
+        let mut description = doc
+            .iter()
+            .map(|line| line.trim())
+            .collect::<Vec<_>>()
+            .join(" ");
+        if let Some(default) = default {
+            if !description.is_empty() {
+                description.push(' ');
+            }
+            description.push_str(&format!("[default: {}]", default));
+        }
+        format!("  {:width$}  {}", usage, description, width = width)
+            .trim_end()
+            .to_owned()
+    };
+
+    let mut help = vec![usage];
+    let positionals: Vec<_> = spells
+        .iter()
+        .filter(|spell| spell.kind == Kind::Positional)
+        .collect();
+    if !positionals.is_empty() {
+        help.push(String::new());
+        help.push("Arguments:".to_owned());
+        for spell in positionals {
+            help.push(line(&spell.usage(), spell.doc, spell.default.as_ref()));
+        }
+    }
+    help.push(String::new());
+    help.push("Options:".to_owned());
+    for spell in spells.iter().filter(|spell| spell.kind != Kind::Positional) {
+        help.push(line(&spell.usage(), spell.doc, spell.default.as_ref()));
+    }
+    help.push(line(
+        "-h, --help",
+        &["Lists the arguments that can be summoned."],
+        None,
+    ));
+    help.push(String::new());
+    help.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::accio_argv;
+    use std::path::PathBuf;
+
+    accio_argv! {
+        /// The arguments of the Goblet of Fire.
+        #[derive(Debug, PartialEq)]
+        struct Goblet {
+            /// Lets the goblet pick more than one champion per school.
+            flag confunded: bool,
+            /// The number of champions.
+            option champions: u8 = 3,
+            option dry_run_school: String,
+            /// The name written on the parchment.
+            positional name: String,
+            positional parchment: PathBuf = "parchment.txt",
+        }
+    }
+
+    #[test]
+    fn practice_accio_argv() {
+        let goblet = Goblet::accio_from([
+            "goblet",
+            "--dry-run-school=Hogwarts",
+            "--confunded",
+            "Harry Potter",
+        ])
+        .unwrap();
+        assert_eq!(
+            goblet,
+            Goblet {
+                confunded: true,
+                champions: 3,
+                dry_run_school: "Hogwarts".to_owned(),
+                name: "Harry Potter".to_owned(),
+                parchment: PathBuf::from("parchment.txt"),
+            }
+        );
+
+        let goblet = Goblet::accio_from(vec![
+            "goblet".to_owned(),
+            "--champions".to_owned(),
+            "4".to_owned(),
+            "--dry-run-school".to_owned(),
+            "Durmstrang".to_owned(),
+            "--".to_owned(),
+            "--Viktor Krum".to_owned(),
+            "burnt.txt".to_owned(),
+        ])
+        .unwrap();
+        assert!(!goblet.confunded);
+        assert_eq!(goblet.champions, 4);
+        assert_eq!(goblet.name, "--Viktor Krum");
+        assert_eq!(goblet.parchment, PathBuf::from("burnt.txt"));
+    }
+
+    #[test]
+    fn the_goblet_rejects_bad_parchments() {
+        let cast = |args: &[&str]| Goblet::accio_from(args.iter().copied()).unwrap_err();
+        assert_eq!(
+            cast(&["goblet", "Cedric"]),
+            Unsummonable::Missing("--dry-run-school".to_owned())
+        );
+        assert_eq!(
+            cast(&["goblet", "--dry-run-school", "Beauxbatons"]),
+            Unsummonable::Missing("<name>".to_owned())
+        );
+        assert_eq!(
+            cast(&["goblet", "--ageline", "Fred"]),
+            Unsummonable::UnknownSpell("--ageline".to_owned())
+        );
+        assert_eq!(
+            cast(&["goblet", "Fred", "--dry-run-school"]),
+            Unsummonable::MissingValue("--dry-run-school".to_owned())
+        );
+        assert_eq!(
+            cast(&["goblet", "--confunded=yes"]),
+            Unsummonable::UnexpectedValue("--confunded".to_owned())
+        );
+        assert_eq!(
+            cast(&["goblet", "Fleur", "a.txt", "b.txt"]),
+            Unsummonable::TooMany("b.txt".to_owned())
+        );
+        let invalid = cast(&["goblet", "--champions", "many", "Fleur"]);
+        assert_eq!(
+            invalid.to_string(),
+            "could not summon `--champions` from `many`: invalid digit found in string"
+        );
+    }
+
+    #[test]
+    fn the_goblet_lists_its_arguments() {
+        let help = Goblet::accio_from(["goblet", "Fred", "-h"]).unwrap_err();
+        assert_eq!(
+            help.to_string(),
+            "\
+Usage: goblet [OPTIONS] <name> [<parchment>]
+
+Arguments:
+  <name>                     The name written on the parchment.
+  <parchment>                [default: parchment.txt]
+
+Options:
+  --confunded                Lets the goblet pick more than one champion per school.
+  --champions <u8>           The number of champions. [default: 3]
+  --dry-run-school <String>
+  -h, --help                 Lists the arguments that can be summoned.
+"
+        );
+    }
+
+    #[test]
+    fn an_expression_summons_from_the_command_line() {
+        let summoned = accio_argv! { flag nonexistent_flag_for_the_test_harness: bool };
+        assert!(summoned.map_or(true, |args| !args.nonexistent_flag_for_the_test_harness));
+    }
+}
@@ -0,0 +1,126 @@
+//! Running work with a timeout, cast with [`arresto_momentum!`](crate::arresto_momentum!).
+//!
+//! [`arrest`] runs a closure on a helper thread and waits for it for at most a given duration.
+//! Rust can not stop a thread from the outside, so work that takes too long is not stopped but abandoned:
+//! the helper thread keeps running in the background until the closure returns, and whatever it returns is dropped.
+//! Work that might never finish should therefore check a flag or a deadline of its own,
+//! and the process can exit while abandoned work is still running.
+//! ```
+//! # use code_spells::arresto_momentum;
+//! use code_spells::arresto_momentum::Timeout;
+//! use std::thread;
+//! use std::time::Duration;
+//!
+//! let caught = arresto_momentum!(Duration::from_secs(10), || "Dumbledore caught Harry");
+//! assert_eq!(caught, Ok("Dumbledore caught Harry"));
+//!
+//! let fall = arresto_momentum!(Duration::from_millis(10), || {
+//!     thread::sleep(Duration::from_secs(1));
+//!     "splat"
+//! });
+//! assert_eq!(fall, Err(Timeout::after(Duration::from_millis(10))));
+//! ```
+
+use core::fmt;
+use core::time::Duration;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+
+/// Runs `work` on a helper thread and waits at most `timeout` for it to finish.
+/// # Errors
+/// Returns [`Timeout`] if the work did not finish in time. The work is then abandoned, not stopped.
+/// # Panics
+/// Resumes the panic of `work` if it panicked in time, and panics if the helper thread could not be spawned.
+pub fn arrest<T, F>(timeout: Duration, work: F) -> Result<T, Timeout>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    let (sender, receiver) = mpsc::sync_channel(1);
+    let helper = thread::Builder::new()
+        .name(String::from("arresto_momentum"))
+        .spawn(move || {
+            // The receiver is gone if the work was abandoned, then there is no one left to hand the result to.
+            let _ = sender.send(work());
+        })
+        .expect("arresto_momentum! failed to spawn a helper thread");
+    match receiver.recv_timeout(timeout) {
+        Ok(value) => Ok(value),
+        Err(RecvTimeoutError::Timeout) => Err(Timeout::after(timeout)),
+        Err(RecvTimeoutError::Disconnected) => match helper.join() {
+            Err(panic) => std::panic::resume_unwind(panic),
+            Ok(()) => unreachable!("the helper thread sends its result before it finishes"),
+        },
+    }
+}
+
+/// The error returned when work did not finish within its timeout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Timeout {
+    timeout: Duration,
+}
+
+impl Timeout {
+    /// Creates the error for work that did not finish within `timeout`.
+    pub const fn after(timeout: Duration) -> Self {
+        Self { timeout }
+    }
+
+    /// Returns the timeout that the work did not finish within.
+    pub const fn timeout(&self) -> Duration {
+        self.timeout
+    }
+}
+
+impl fmt::Display for Timeout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "arresto momentum: the work did not finish within {:?}",
+            self.timeout
+        )
+    }
+}
+
+impl std::error::Error for Timeout {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arresto_momentum;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    #[test]
+    fn practice_arresto_momentum() {
+        let quaffle = String::from("red");
+        assert_eq!(
+            arresto_momentum!(Duration::from_secs(60), move || quaffle.len()),
+            Ok(3)
+        );
+
+        let landed = Arc::new(AtomicBool::new(false));
+        let fall = {
+            let landed = Arc::clone(&landed);
+            arresto_momentum!(Duration::from_millis(1), move || {
+                thread::sleep(Duration::from_millis(50));
+                landed.store(true, Ordering::Release);
+            })
+        };
+        assert_eq!(fall, Err(Timeout::after(Duration::from_millis(1))));
+        assert_eq!(fall.unwrap_err().timeout(), Duration::from_millis(1));
+        while !landed.load(Ordering::Acquire) {
+            thread::yield_now();
+        }
+        assert_eq!(
+            Timeout::after(Duration::from_millis(1)).to_string(),
+            "arresto momentum: the work did not finish within 1ms"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Peeves")]
+    fn arrested_work_that_panics() {
+        let _ = arrest(Duration::from_secs(60), || panic!("Peeves"));
+    }
+}
@@ -0,0 +1,191 @@
+//! Panics that are turned into errors, with the `#[riddikulus]` attribute from the `macros` feature.
+//!
+//! A boggart takes the shape of whatever scares you most, and laughing at it makes it harmless.
+//! [`riddikulus`] runs a closure and catches the panic it unwinds with, if any, as a [`Boggart`] error value.
+//! The attribute does the same for a whole function: its body runs inside [`riddikulus`], and its return type `T`
+//! becomes `Result<T, Boggart>`. That keeps panics from unwinding out of code that must not unwind,
+//! like the boundary of a plugin host or a function that is called over FFI.
+//!
+//! The panic hook still runs when the panic happens, so the panic is reported as usual before it is caught.
+//! Panics with `panic = "abort"` can not be caught at all.
+#![cfg_attr(feature = "macros", doc = "```")]
+#![cfg_attr(not(feature = "macros"), doc = "```ignore")]
+//! use code_spells::riddikulus;
+//!
+//! #[riddikulus]
+//! fn open_the_wardrobe(fear: &str) -> usize {
+//!     if fear == "spiders" {
+//!         panic!("the boggart turned into a giant spider");
+//!     }
+//!     fear.len()
+//! }
+//!
+//! assert_eq!(open_the_wardrobe("clowns").unwrap(), 6);
+//! let boggart = open_the_wardrobe("spiders").unwrap_err();
+//! assert_eq!(boggart.message(), "the boggart turned into a giant spider");
+//! ```
+
+use core::any::Any;
+use core::fmt;
+use std::panic::{self, AssertUnwindSafe};
+
+use crate::obliviator_squad::panic_message;
+
+/// A panic that was caught and made harmless.
+pub struct Boggart {
+    message: String,
+    payload: Box<dyn Any + Send>,
+}
+
+impl Boggart {
+    /// Makes a boggart out of the payload of a caught panic.
+    pub fn new(payload: Box<dyn Any + Send>) -> Self {
+        Self {
+            message: panic_message(&*payload),
+            payload,
+        }
+    }
+
+    /// Returns the message of the panic, or a placeholder if its payload was not a message.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Returns the payload of the panic, to downcast it to what was panicked with.
+    pub fn payload(&self) -> &(dyn Any + Send) {
+        &*self.payload
+    }
+
+    /// Takes the payload of the panic out of the boggart.
+    pub fn into_payload(self) -> Box<dyn Any + Send> {
+        self.payload
+    }
+
+    /// Lets the boggart out of the wardrobe again, by resuming the panic that it was made from.
+    pub fn resume(self) -> ! {
+        panic::resume_unwind(self.payload)
+    }
+}
+
+impl fmt::Debug for Boggart {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Boggart")
+            .field("message", &self.message)
+            .finish_non_exhaustive()
+    }
+}
+
+impl fmt::Display for Boggart {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "a boggart was laughed away: {}", self.message)
+    }
+}
+
+impl std::error::Error for Boggart {}
+
+/// Runs `f`, and returns what it returns, or a [`Boggart`] made from the panic it unwinds with.
+///
+/// The closure is asserted to be unwind safe. Values that it mutated when it panicked
+/// may be left in a state that breaks their invariants.
+/// # Errors
+/// Returns a [`Boggart`] if `f` panics.
+pub fn riddikulus<T>(f: impl FnOnce() -> T) -> Result<T, Boggart> {
+    panic::catch_unwind(AssertUnwindSafe(f)).map_err(Boggart::new)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn practice_boggart() {
+        assert_eq!(riddikulus(|| 7).unwrap(), 7);
+        let name = String::from("Snape");
+        let boggart =
+            riddikulus(|| -> u8 { panic!("Professor {} in a vulture hat", name) }).unwrap_err();
+        assert_eq!(boggart.message(), "Professor Snape in a vulture hat");
+        assert_eq!(
+            boggart.to_string(),
+            "a boggart was laughed away: Professor Snape in a vulture hat"
+        );
+        assert_eq!(
+            format!("{:?}", boggart),
+            r#"Boggart { message: "Professor Snape in a vulture hat", .. }"#
+        );
+    }
+
+    #[test]
+    fn a_boggart_keeps_its_payload() {
+        let boggart = riddikulus(|| panic::panic_any(42_u32)).unwrap_err();
+        assert_eq!(boggart.message(), "a panic without a message");
+        assert_eq!(boggart.payload().downcast_ref::<u32>(), Some(&42));
+        let resumed = panic::catch_unwind(AssertUnwindSafe(|| boggart.resume())).unwrap_err();
+        assert_eq!(resumed.downcast_ref::<u32>(), Some(&42));
+    }
+
+    #[cfg(feature = "macros")]
+    mod attribute {
+        use super::*;
+        use crate::riddikulus;
+
+        struct Wardrobe {
+            opened: u32,
+        }
+
+        impl Wardrobe {
+            #[riddikulus]
+            fn open(&mut self, fear: &str) -> &'static str {
+                self.opened += 1;
+                match fear {
+                    "dementor" => panic!("the boggart turned into a dementor"),
+                    _ => "a boggart",
+                }
+            }
+        }
+
+        #[riddikulus]
+        fn parse_fear<T: core::str::FromStr>(fear: &str) -> Result<T, T::Err>
+        where
+            T::Err: fmt::Debug,
+        {
+            let number = fear.parse::<T>()?;
+            Ok(number)
+        }
+
+        #[riddikulus]
+        fn laugh(laughs: &mut Vec<&'static str>) {
+            laughs.push("riddikulus");
+            if laughs.len() > 1 {
+                return;
+            }
+            laughs.push("ha");
+        }
+
+        #[riddikulus]
+        fn shapes(count: usize) -> impl Iterator<Item = usize> {
+            assert!(count > 0, "an empty wardrobe");
+            0..count
+        }
+
+        #[test]
+        fn practice_riddikulus() {
+            let mut wardrobe = Wardrobe { opened: 0 };
+            assert_eq!(wardrobe.open("mummy").unwrap(), "a boggart");
+            let boggart = wardrobe.open("dementor").unwrap_err();
+            assert_eq!(boggart.message(), "the boggart turned into a dementor");
+            assert_eq!(wardrobe.opened, 2);
+
+            assert_eq!(parse_fear::<u8>("7").unwrap(), Ok(7));
+            assert!(parse_fear::<u8>("spider").unwrap().is_err());
+
+            let mut laughs = Vec::new();
+            laugh(&mut laughs).unwrap();
+            laugh(&mut laughs).unwrap();
+            assert_eq!(laughs, ["riddikulus", "ha", "riddikulus"]);
+
+            assert_eq!(shapes(3).unwrap().sum::<usize>(), 3);
+            let empty = shapes(0).err().unwrap();
+            assert_eq!(empty.message(), "an empty wardrobe");
+        }
+    }
+}
@@ -0,0 +1,214 @@
+//! Simulated latency injection for testing timeouts and retries,
+//! cast with [`curse_of_the_bogies!`](crate::curse_of_the_bogies).
+//!
+//! A [`Bogies`] curse makes the work it is cast on slow, like a cold that makes you sneeze
+//! before and after everything you do. The delays are drawn from a [`Latency`] distribution
+//! and the curse can be lifted and recast at runtime.
+//! The random distributions need the `rand` feature and can be seeded for reproducible tests.
+
+use core::sync::atomic::{AtomicBool, Ordering};
+use core::time::Duration;
+
+#[cfg(feature = "rand")]
+use rand::{rngs::StdRng, Rng, SeedableRng};
+#[cfg(feature = "rand")]
+use std::sync::Mutex;
+
+/// A distribution of delays.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub enum Latency {
+    /// Always the same delay.
+    Fixed(Duration),
+    /// A delay drawn uniformly from the inclusive range `min..=max`.
+    #[cfg(feature = "rand")]
+    Uniform {
+        /// The shortest possible delay.
+        min: Duration,
+        /// The longest possible delay.
+        max: Duration,
+    },
+    /// A heavy-tailed delay drawn from a Pareto distribution: usually close to `scale`,
+    /// but every now and then much longer. A smaller `shape` gives a heavier tail.
+    /// The delay is capped at `max`.
+    #[cfg(feature = "rand")]
+    Pareto {
+        /// The shortest possible delay.
+        scale: Duration,
+        /// How quickly long delays become unlikely. Must be positive.
+        shape: f64,
+        /// The longest possible delay.
+        max: Duration,
+    },
+}
+
+/// A curse that delays the work it is cast on.
+/// # Example
+/// ```
+/// # use code_spells::bogies::{Bogies, Latency};
+/// use std::time::{Duration, Instant};
+///
+/// let bogies = Bogies::new().before(Latency::Fixed(Duration::from_millis(20)));
+/// let start = Instant::now();
+/// assert_eq!(bogies.curse(|| 1 + 1), 2);
+/// assert!(start.elapsed() >= Duration::from_millis(20));
+///
+/// bogies.lift();
+/// let start = Instant::now();
+/// bogies.curse(|| ());
+/// assert!(start.elapsed() < Duration::from_millis(20));
+/// ```
+#[derive(Debug)]
+pub struct Bogies {
+    before: Option<Latency>,
+    after: Option<Latency>,
+    active: AtomicBool,
+    #[cfg(feature = "rand")]
+    rng: Mutex<StdRng>,
+}
+
+impl Bogies {
+    /// Creates a new, active curse without any delays.
+    pub fn new() -> Self {
+        Self {
+            before: None,
+            after: None,
+            active: AtomicBool::new(true),
+            #[cfg(feature = "rand")]
+            rng: Mutex::new(StdRng::from_os_rng()),
+        }
+    }
+
+    /// Delays the work by a duration drawn from `latency` before it starts.
+    pub fn before(mut self, latency: Latency) -> Self {
+        self.before = Some(latency);
+        self
+    }
+
+    /// Delays the result by a duration drawn from `latency` after the work is done.
+    pub fn after(mut self, latency: Latency) -> Self {
+        self.after = Some(latency);
+        self
+    }
+
+    /// Seeds the random number generator used by the random distributions,
+    /// so that the same sequence of delays is drawn every run.
+    #[cfg(feature = "rand")]
+    pub fn seed(self, seed: u64) -> Self {
+        Self {
+            rng: Mutex::new(StdRng::seed_from_u64(seed)),
+            ..self
+        }
+    }
+
+    /// Lifts the curse, so that the work is no longer delayed.
+    pub fn lift(&self) {
+        self.active.store(false, Ordering::Relaxed);
+    }
+
+    /// Casts the curse again after it has been lifted.
+    pub fn recast(&self) {
+        self.active.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns whether the curse is currently delaying work.
+    pub fn is_active(&self) -> bool {
+        self.active.load(Ordering::Relaxed)
+    }
+
+    /// Runs `work`, delayed as configured if the curse is active.
+    pub fn curse<R>(&self, work: impl FnOnce() -> R) -> R {
+        self.sneeze(self.before);
+        let result = work();
+        self.sneeze(self.after);
+        result
+    }
+
+    /// Draws a delay from `latency`.
+    pub fn draw(&self, latency: Latency) -> Duration {
+        match latency {
+            Latency::Fixed(delay) => delay,
+            #[cfg(feature = "rand")]
+            Latency::Uniform { min, max } => {
+                if min >= max {
+                    min
+                } else {
+                    self.rng().random_range(min..=max)
+                }
+            }
+            #[cfg(feature = "rand")]
+            Latency::Pareto { scale, shape, max } => {
+                // Inverse transform sampling, with u in (0, 1].
+                let u = 1.0 - self.rng().random::<f64>();
+                let delay = scale.as_secs_f64() / u.powf(shape.recip());
+                Duration::try_from_secs_f64(delay).map_or(max, |delay| delay.min(max))
+            }
+        }
+    }
+
+    #[cfg(feature = "rand")]
+    fn rng(&self) -> std::sync::MutexGuard<'_, StdRng> {
+        self.rng
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    fn sneeze(&self, latency: Option<Latency>) {
+        if let Some(latency) = latency {
+            if self.is_active() {
+                crate::time_room::sleep(self.draw(latency));
+            }
+        }
+    }
+}
+
+impl Default for Bogies {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curse_of_the_bogies;
+    use std::time::Instant;
+
+    #[test]
+    fn practice_curse_of_the_bogies() {
+        let bogies = Bogies::new()
+            .before(Latency::Fixed(Duration::from_millis(5)))
+            .after(Latency::Fixed(Duration::from_millis(5)));
+        let start = Instant::now();
+        assert_eq!(curse_of_the_bogies!(&bogies, || "achoo"), "achoo");
+        assert!(start.elapsed() >= Duration::from_millis(10));
+        bogies.lift();
+        assert!(!bogies.is_active());
+        bogies.recast();
+        assert!(bogies.is_active());
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn practice_random_latency() {
+        let uniform = Latency::Uniform {
+            min: Duration::from_millis(1),
+            max: Duration::from_millis(3),
+        };
+        let pareto = Latency::Pareto {
+            scale: Duration::from_millis(1),
+            shape: 1.5,
+            max: Duration::from_secs(1),
+        };
+        let first = Bogies::new().seed(934);
+        let second = Bogies::new().seed(934);
+        for _ in 0..100 {
+            let delay = first.draw(uniform);
+            assert!(delay >= Duration::from_millis(1) && delay <= Duration::from_millis(3));
+            assert_eq!(delay, second.draw(uniform));
+            let delay = first.draw(pareto);
+            assert!(delay >= Duration::from_millis(1) && delay <= Duration::from_secs(1));
+            assert_eq!(delay, second.draw(pareto));
+        }
+    }
+}
@@ -0,0 +1,130 @@
+//! Structured concurrency on scoped threads, cast with [`three_broomsticks!`](crate::three_broomsticks),
+//! [`broomstick_fleet!`](crate::broomstick_fleet) and [`avis!`](crate::avis).
+//!
+//! Every closure flies on its own [scoped thread](std::thread::scope), so it can borrow from the caller.
+//! All of them are always allowed to land: if one of them panics the others still run to completion,
+//! and the first panic (in argument order) is then propagated to the caller.
+
+use std::panic;
+use std::thread::{self, ScopedJoinHandle};
+
+/// Runs three closures on scoped threads and returns their results once all of them have finished.
+/// # Panics
+/// If any of the closures panic, the panic of the first one is propagated once all of them have finished.
+pub fn three<A, B, C, RA, RB, RC>(a: A, b: B, c: C) -> (RA, RB, RC)
+where
+    A: FnOnce() -> RA + Send,
+    B: FnOnce() -> RB + Send,
+    C: FnOnce() -> RC + Send,
+    RA: Send,
+    RB: Send,
+    RC: Send,
+{
+    thread::scope(|s| {
+        let a = s.spawn(a);
+        let b = s.spawn(b);
+        let c = s.spawn(c);
+        let (a, b, c) = (a.join(), b.join(), c.join());
+        match (a, b, c) {
+            (Ok(a), Ok(b), Ok(c)) => (a, b, c),
+            (Err(e), _, _) | (_, Err(e), _) | (_, _, Err(e)) => panic::resume_unwind(e),
+        }
+    })
+}
+
+/// Runs every closure on its own scoped thread and returns their results in order once all of them have finished.
+/// # Panics
+/// If any of the closures panic, the panic of the first one is propagated once all of them have finished.
+pub fn fleet<F, R>(closures: impl IntoIterator<Item = F>) -> Vec<R>
+where
+    F: FnOnce() -> R + Send,
+    R: Send,
+{
+    thread::scope(|s| land(closures.into_iter().map(|f| s.spawn(f)).collect()))
+}
+
+/// Joins every handle, and returns their results in order or propagates the first panic.
+#[doc(hidden)]
+pub fn land<R>(handles: Vec<ScopedJoinHandle<'_, R>>) -> Vec<R> {
+    let results: Vec<thread::Result<R>> = handles.into_iter().map(|h| h.join()).collect();
+    results
+        .into_iter()
+        .map(|result| result.unwrap_or_else(|e| panic::resume_unwind(e)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{avis, broomstick_fleet, three_broomsticks};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn practice_three_broomsticks() {
+        let drinks = ["butterbeer", "gillywater", "firewhisky"];
+        let (a, b, c) = three_broomsticks!(
+            || drinks[0].len(),
+            || drinks.iter().any(|d| d.contains("whisky")),
+            || drinks.join(", ")
+        );
+        assert_eq!(a, 10);
+        assert!(b);
+        assert_eq!(c, "butterbeer, gillywater, firewhisky");
+    }
+
+    #[test]
+    fn practice_broomstick_fleet() {
+        let seekers = ["Harry", "Cho", "Cedric", "Draco"];
+        let lengths = broomstick_fleet![|| seekers[0].len(), || seekers[1].len(), || 7];
+        assert_eq!(lengths, [5, 3, 7]);
+        let lengths = fleet(seekers.iter().map(|s| move || s.len()));
+        assert_eq!(lengths, [5, 3, 6, 5]);
+        assert!(fleet(Vec::<fn()>::new()).is_empty());
+    }
+
+    #[test]
+    fn practice_avis() {
+        let mut owls = vec!["Hedwig"];
+        avis!(|s| {
+            s.spawn(|| owls.push("Errol"));
+        });
+        assert_eq!(owls, ["Hedwig", "Errol"]);
+        let names = avis!(for owl in &owls => owl.to_uppercase());
+        assert_eq!(names, ["HEDWIG", "ERROL"]);
+        let sums = avis!(for (i, owl) in owls.iter().enumerate() => i + owl.len());
+        assert_eq!(sums, [6, 6]);
+        let none: Vec<usize> = avis!(for owl in Vec::<&str>::new() => owl.len());
+        assert!(none.is_empty());
+    }
+
+    #[test]
+    fn every_broomstick_lands_before_a_crash_is_reported() {
+        let landed = AtomicUsize::new(0);
+        let result = panic::catch_unwind(|| {
+            three_broomsticks!(
+                || landed.fetch_add(1, Ordering::SeqCst),
+                || -> usize { panic!("crashed into the Whomping Willow") },
+                || {
+                    thread::sleep(std::time::Duration::from_millis(20));
+                    landed.fetch_add(1, Ordering::SeqCst)
+                }
+            )
+        });
+        let payload = result.unwrap_err();
+        assert_eq!(
+            payload.downcast_ref::<&str>(),
+            Some(&"crashed into the Whomping Willow")
+        );
+        assert_eq!(landed.load(Ordering::SeqCst), 2);
+
+        let result = panic::catch_unwind(|| {
+            broomstick_fleet![
+                || -> usize { panic!("first") },
+                || -> usize { panic!("second") },
+                || landed.fetch_add(1, Ordering::SeqCst)
+            ]
+        });
+        assert_eq!(result.unwrap_err().downcast_ref::<&str>(), Some(&"first"));
+        assert_eq!(landed.load(Ordering::SeqCst), 3);
+    }
+}
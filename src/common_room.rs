@@ -0,0 +1,175 @@
+//! Sharded state that reduces lock contention, cast with [`common_room!`](crate::common_room!).
+//!
+//! Instead of crowding every student into a single room behind a single lock, a [`CommonRoom`]
+//! has one house per shard. Every thread is sorted into a house the first time it enters, and
+//! keeps entering the same house, so threads mostly wait on their own house mates.
+//! The state of every house can then be [gathered](CommonRoom::gather) into a summary.
+//! ```
+//! # use code_spells::common_room::CommonRoom;
+//! use std::thread;
+//!
+//! let points = CommonRoom::<u64>::with_houses(4);
+//! thread::scope(|s| {
+//!     for _ in 0..8 {
+//!         s.spawn(|| {
+//!             for _ in 0..100 {
+//!                 *points.enter() += 1;
+//!             }
+//!         });
+//!     }
+//! });
+//! assert_eq!(points.gather(0, |total, house| total + house), 800);
+//! ```
+
+use core::hash::{Hash, Hasher};
+use std::collections::hash_map::DefaultHasher;
+use std::sync::{Mutex, MutexGuard};
+use std::thread;
+
+thread_local! {
+    // The sorting hat only has to look at each thread once.
+    static SORTING_HAT: u64 = {
+        let mut hasher = DefaultHasher::new();
+        thread::current().id().hash(&mut hasher);
+        hasher.finish()
+    };
+}
+
+/// Returns a hash of the current thread's id, computed once per thread.
+pub(crate) fn sorting_hat() -> u64 {
+    SORTING_HAT.with(|hat| *hat)
+}
+
+/// A house, padded to its own cache lines so that neighbouring houses do not contend.
+#[derive(Debug, Default)]
+#[repr(align(128))]
+struct House<T>(Mutex<T>);
+
+/// State sharded into houses, where every thread enters the house it is sorted into.
+#[derive(Debug)]
+pub struct CommonRoom<T> {
+    houses: Box<[House<T>]>,
+}
+
+impl<T: Default> CommonRoom<T> {
+    /// Creates a common room with one house per unit of available parallelism.
+    pub fn new() -> Self {
+        Self::with_houses(thread::available_parallelism().map_or(4, core::num::NonZeroUsize::get))
+    }
+
+    /// Creates a common room with the given number of houses.
+    /// # Panics
+    /// Panics if `houses` is zero.
+    pub fn with_houses(houses: usize) -> Self {
+        Self::from_fn(houses, |_| T::default())
+    }
+}
+
+impl<T> CommonRoom<T> {
+    /// Creates a common room with the given number of houses,
+    /// where the state of every house is created by calling `f` with its index.
+    /// # Panics
+    /// Panics if `houses` is zero.
+    pub fn from_fn(houses: usize, f: impl FnMut(usize) -> T) -> Self {
+        assert!(houses > 0, "a common room needs at least one house");
+        Self {
+            houses: (0..houses).map(f).map(|t| House(Mutex::new(t))).collect(),
+        }
+    }
+
+    /// Returns the number of houses.
+    pub fn houses(&self) -> usize {
+        self.houses.len()
+    }
+
+    /// Enters the house that the current thread is sorted into, and locks it.
+    ///
+    /// A house whose previous occupant panicked is entered anyway.
+    pub fn enter(&self) -> MutexGuard<'_, T> {
+        let house = sorting_hat() % self.houses.len() as u64;
+        Self::lock(&self.houses[house as usize])
+    }
+
+    /// Folds the state of every house into a summary, visiting the houses in order.
+    ///
+    /// Every house is locked only while it is visited, so the summary is not a snapshot
+    /// if other threads keep entering while it is gathered.
+    pub fn gather<R>(&self, init: R, mut f: impl FnMut(R, &T) -> R) -> R {
+        self.houses
+            .iter()
+            .fold(init, |summary, house| f(summary, &Self::lock(house)))
+    }
+
+    /// Returns the state of every house, in order.
+    pub fn into_houses(self) -> Vec<T> {
+        self.houses
+            .into_vec()
+            .into_iter()
+            .map(|house| {
+                house
+                    .0
+                    .into_inner()
+                    .unwrap_or_else(std::sync::PoisonError::into_inner)
+            })
+            .collect()
+    }
+
+    fn lock(house: &House<T>) -> MutexGuard<'_, T> {
+        house
+            .0
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+impl<T: Default> Default for CommonRoom<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common_room;
+
+    #[test]
+    fn practice_common_room() {
+        let room: CommonRoom<Vec<usize>> = common_room!(3);
+        assert_eq!(room.houses(), 3);
+        thread::scope(|s| {
+            for student in 0..6 {
+                let room = &room;
+                s.spawn(move || room.enter().push(student));
+            }
+        });
+        let mut students = room.gather(Vec::new(), |mut all, house| {
+            all.extend_from_slice(house);
+            all
+        });
+        students.sort_unstable();
+        assert_eq!(students, [0, 1, 2, 3, 4, 5]);
+        assert_eq!(room.into_houses().iter().map(Vec::len).sum::<usize>(), 6);
+
+        let room: CommonRoom<u8> = common_room!();
+        assert!(room.houses() > 0);
+    }
+
+    #[test]
+    fn a_thread_stays_in_its_house() {
+        let room = CommonRoom::from_fn(8, |house| (house, 0));
+        let house = room.enter().0;
+        for _ in 0..10 {
+            let mut guard = room.enter();
+            assert_eq!(guard.0, house);
+            guard.1 += 1;
+        }
+        assert_eq!(room.gather(0, |total, (_, visits)| total + visits), 10);
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one house")]
+    fn a_common_room_without_houses() {
+        let _ = CommonRoom::<u8>::with_houses(0);
+    }
+}
@@ -0,0 +1,270 @@
+//! A shared resource that is repaired or replaced when it breaks, cast with
+//! [`dervish_and_banges!`](crate::dervish_and_banges!).
+//!
+//! Dervish and Banges in Hogsmeade repairs magical instruments, and replaces the ones that are beyond repair.
+//! A [`Workshop`] holds a resource, like a connection, that is built by a factory the first time it is
+//! [borrowed](Workshop::borrow) and then shared by every borrower. A borrower that finds the resource broken
+//! [reports it as faulty](Loan::report_faulty), after which the next borrower gets a newly built one.
+//! Callers just borrow, and borrow again after reporting a fault.
+//!
+//! Building the resource is retried with a [`TimeTurner`], so it can back off between attempts,
+//! and the workshop can be limited to a number of replacements before it gives up.
+//! ```
+//! # use code_spells::dervish_and_banges;
+//! use core::sync::atomic::{AtomicU32, Ordering};
+//!
+//! let built = AtomicU32::new(0);
+//! let workshop = dervish_and_banges!(|| Ok::<_, ()>(built.fetch_add(1, Ordering::Relaxed)), max_repairs: 1);
+//! let sneakoscope = workshop.borrow()?;
+//! assert_eq!(*sneakoscope, 0);
+//! sneakoscope.report_faulty();
+//! assert_eq!(*workshop.borrow()?, 1);
+//! # Ok::<(), code_spells::dervish_and_banges::Unrepairable<()>>(())
+//! ```
+
+use core::fmt;
+use core::ops::Deref;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use crate::time_turner::TimeTurner;
+
+/// The error returned when a [`Workshop`] can not provide its resource.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Unrepairable<E> {
+    /// Every attempt to build the resource failed, and this is the error of the last one.
+    Broken(E),
+    /// The resource has been replaced as many times as the workshop allows.
+    WornOut,
+}
+
+impl<E: fmt::Display> fmt::Display for Unrepairable<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Broken(error) => write!(f, "Dervish and Banges could not build it: {}", error),
+            Self::WornOut => write!(
+                f,
+                "Dervish and Banges has replaced it too many times and refuses to do it again"
+            ),
+        }
+    }
+}
+
+impl<E: fmt::Debug + fmt::Display> std::error::Error for Unrepairable<E> {}
+
+/// The state of the workbench: the current resource, if there is one.
+#[derive(Debug)]
+struct Bench<T> {
+    resource: Option<Arc<T>>,
+    /// Counts the resources that have been built, so that reports about replaced resources can be ignored.
+    generation: u64,
+}
+
+/// Holds a shared resource that is built by a factory, and rebuilt when a borrower reports it as faulty.
+pub struct Workshop<T, F> {
+    factory: F,
+    bench: Mutex<Bench<T>>,
+    time_turner: TimeTurner,
+    max_repairs: Option<u64>,
+}
+
+impl<T, E, F: Fn() -> Result<T, E>> Workshop<T, F> {
+    /// Creates a workshop that builds its resource with `factory` when it is first borrowed.
+    /// Building is attempted as many times as the [default](TimeTurner::new) time turner allows,
+    /// and the resource can be replaced any number of times.
+    pub fn new(factory: F) -> Self {
+        Self {
+            factory,
+            bench: Mutex::new(Bench {
+                resource: None,
+                generation: 0,
+            }),
+            time_turner: TimeTurner::new(),
+            max_repairs: None,
+        }
+    }
+
+    /// Retries building the resource with the given time turner, which decides the attempts and the backoff.
+    pub fn time_turner(mut self, time_turner: TimeTurner) -> Self {
+        self.time_turner = time_turner;
+        self
+    }
+
+    /// Allows the resource to be replaced at most `max_repairs` times after it was first built.
+    pub fn max_repairs(mut self, max_repairs: u64) -> Self {
+        self.max_repairs = Some(max_repairs);
+        self
+    }
+
+    /// Lends out the resource, building it first if there is none.
+    ///
+    /// Only one borrower builds the resource, the others wait for it.
+    /// # Errors
+    /// Returns [`Unrepairable::Broken`] with the last error of the factory if every attempt to build the resource failed,
+    /// and [`Unrepairable::WornOut`] if it would have to be replaced more times than allowed.
+    pub fn borrow(&self) -> Result<Loan<'_, T>, Unrepairable<E>> {
+        let mut bench = self.lock();
+        let resource = match &bench.resource {
+            Some(resource) => Arc::clone(resource),
+            None => {
+                let repairs = bench.generation.saturating_sub(1);
+                if bench.generation > 0 && self.max_repairs.is_some_and(|max| repairs >= max) {
+                    return Err(Unrepairable::WornOut);
+                }
+                let resource = Arc::new(
+                    self.time_turner
+                        .turn(&self.factory)
+                        .map_err(Unrepairable::Broken)?,
+                );
+                bench.generation += 1;
+                bench.resource = Some(Arc::clone(&resource));
+                resource
+            }
+        };
+        Ok(Loan {
+            resource,
+            generation: bench.generation,
+            bench: &self.bench,
+        })
+    }
+}
+
+impl<T, F> Workshop<T, F> {
+    /// Returns how many times the resource has been replaced after it was first built.
+    pub fn repairs(&self) -> u64 {
+        self.lock().generation.saturating_sub(1)
+    }
+
+    /// Returns whether there is a resource that can be lent out without building one.
+    pub fn is_ready(&self) -> bool {
+        self.lock().resource.is_some()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Bench<T>> {
+        self.bench.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<T, F> fmt::Debug for Workshop<T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bench = self.lock();
+        f.debug_struct("Workshop")
+            .field("ready", &bench.resource.is_some())
+            .field("generation", &bench.generation)
+            .field("max_repairs", &self.max_repairs)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A resource lent out by a [`Workshop`]. Cast with [`Workshop::borrow`].
+pub struct Loan<'w, T> {
+    resource: Arc<T>,
+    generation: u64,
+    bench: &'w Mutex<Bench<T>>,
+}
+
+impl<T> Loan<'_, T> {
+    /// Reports the resource as faulty, so that the workshop builds a new one for the next borrower.
+    ///
+    /// Borrowers that already have the resource keep it until they drop it.
+    /// A report about a resource that has already been replaced is ignored.
+    pub fn report_faulty(self) {
+        let mut bench = self.bench.lock().unwrap_or_else(PoisonError::into_inner);
+        if bench.generation == self.generation {
+            bench.resource = None;
+        }
+    }
+}
+
+impl<T> Deref for Loan<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.resource
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Loan<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Loan")
+            .field("resource", &self.resource)
+            .field("generation", &self.generation)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dervish_and_banges;
+    use core::sync::atomic::{AtomicU32, Ordering};
+    use core::time::Duration;
+    use std::thread;
+
+    #[test]
+    fn practice_dervish_and_banges() {
+        let built = AtomicU32::new(0);
+        let workshop = dervish_and_banges!(|| {
+            Ok::<_, &str>(format!(
+                "omnioculars #{}",
+                built.fetch_add(1, Ordering::SeqCst)
+            ))
+        });
+        assert!(!workshop.is_ready());
+        let first = workshop.borrow().unwrap();
+        let second = workshop.borrow().unwrap();
+        assert_eq!(*first, "omnioculars #0");
+        assert_eq!(*second, "omnioculars #0");
+        first.report_faulty();
+        assert_eq!(*second, "omnioculars #0");
+        second.report_faulty();
+        assert_eq!(*workshop.borrow().unwrap(), "omnioculars #1");
+        assert_eq!(workshop.repairs(), 1);
+        assert_eq!(built.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn the_workshop_retries_and_gives_up() {
+        let attempts = AtomicU32::new(0);
+        let workshop = dervish_and_banges!(
+            || {
+                let attempt = attempts.fetch_add(1, Ordering::SeqCst);
+                if attempt % 2 == 0 {
+                    Err(attempt)
+                } else {
+                    Ok(attempt)
+                }
+            },
+            time_turner: TimeTurner::new().attempts(2).delay(Duration::from_millis(1)),
+            max_repairs: 1,
+        );
+        assert_eq!(*workshop.borrow().unwrap(), 1);
+        workshop.borrow().unwrap().report_faulty();
+        assert_eq!(*workshop.borrow().unwrap(), 3);
+        workshop.borrow().unwrap().report_faulty();
+        assert_eq!(workshop.borrow().unwrap_err(), Unrepairable::WornOut);
+        assert_eq!(attempts.load(Ordering::SeqCst), 4);
+
+        let hopeless = Workshop::new(|| Err::<(), _>("the Vanishing Cabinet is broken"))
+            .time_turner(TimeTurner::new().attempts(3));
+        assert_eq!(
+            hopeless.borrow().unwrap_err().to_string(),
+            "Dervish and Banges could not build it: the Vanishing Cabinet is broken"
+        );
+    }
+
+    #[test]
+    fn only_one_borrower_builds() {
+        let built = AtomicU32::new(0);
+        let workshop = Workshop::new(|| {
+            thread::sleep(Duration::from_millis(10));
+            Ok::<_, ()>(built.fetch_add(1, Ordering::SeqCst))
+        });
+        thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| assert_eq!(*workshop.borrow().unwrap(), 0));
+            }
+        });
+        assert_eq!(built.load(Ordering::SeqCst), 1);
+        assert!(format!("{:?}", workshop).starts_with("Workshop { ready: true, generation: 1"));
+    }
+}
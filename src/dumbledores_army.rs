@@ -0,0 +1,219 @@
+//! Group synchronization, cast with [`dumbledores_army!`](crate::dumbledores_army!) and [`da_coin!`](crate::da_coin!).
+//!
+//! A [`DumbledoresArmy`] is a [`Barrier`]: every member waits in the Room of Requirement
+//! until the whole army has arrived, and exactly one of them is told that they lead the meeting.
+//! A [`DaCoin`] is a reusable rendezvous instead: any number of members wait on their coins
+//! until a coordinator [summons](DaCoin::summon) them all at a moment of their choosing.
+//! ```
+//! # use code_spells::{da_coin, dumbledores_army};
+//! use code_spells::dumbledores_army::{DaCoin, DumbledoresArmy};
+//! use std::thread;
+//!
+//! let army: DumbledoresArmy = dumbledores_army!(3);
+//! let coin = DaCoin::new();
+//! let leaders = thread::scope(|s| {
+//!     let members: Vec<_> = (0..3)
+//!         .map(|_| {
+//!             s.spawn(|| {
+//!                 da_coin!(coin);
+//!                 army.meet()
+//!             })
+//!         })
+//!         .collect();
+//!     while coin.waiting() < 3 {
+//!         thread::yield_now();
+//!     }
+//!     da_coin!(summon coin);
+//!     members
+//!         .into_iter()
+//!         .map(|member| member.join().unwrap())
+//!         .filter(|&led| led)
+//!         .count()
+//! });
+//! assert_eq!(leaders, 1);
+//! ```
+
+use core::fmt;
+use std::sync::{Barrier, Condvar, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+/// A [`Barrier`] for a fixed number of members.
+pub struct DumbledoresArmy {
+    room: Barrier,
+    members: usize,
+}
+
+impl DumbledoresArmy {
+    /// Creates an army of `members` members. The meeting starts once all of them have called [`meet`](DumbledoresArmy::meet).
+    ///
+    /// An army with zero members meets like an army with one: no one waits.
+    pub fn new(members: usize) -> Self {
+        Self {
+            room: Barrier::new(members),
+            members,
+        }
+    }
+
+    /// Returns the number of members in the army.
+    pub fn members(&self) -> usize {
+        self.members
+    }
+
+    /// Waits until every member has arrived. Returns `true` for exactly one of the members,
+    /// who leads the meeting.
+    ///
+    /// The army can meet again once the meeting has started.
+    pub fn meet(&self) -> bool {
+        self.room.wait().is_leader()
+    }
+}
+
+impl fmt::Debug for DumbledoresArmy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DumbledoresArmy")
+            .field("members", &self.members)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The state behind a [`DaCoin`].
+#[derive(Debug, Default)]
+struct Galleon {
+    // Incremented by every summons, so that waiters can tell whether they have been summoned.
+    summons: u64,
+    waiting: usize,
+}
+
+/// A reusable rendezvous where waiters are released together by a coordinator.
+///
+/// Only members who are already waiting when the coin is [summoned](DaCoin::summon) are released,
+/// members that start waiting afterwards wait for the next summons.
+#[derive(Debug, Default)]
+pub struct DaCoin {
+    galleon: Mutex<Galleon>,
+    burning: Condvar,
+}
+
+impl DaCoin {
+    /// Creates a coin that no one is waiting on.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Waits until the coin is summoned.
+    pub fn wait(&self) {
+        let mut galleon = self.lock();
+        let summons = galleon.summons;
+        galleon.waiting += 1;
+        while galleon.summons == summons {
+            galleon = self
+                .burning
+                .wait(galleon)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+    }
+
+    /// Waits until the coin is summoned or `timeout` has passed. Returns whether the coin was summoned.
+    pub fn wait_timeout(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        let mut galleon = self.lock();
+        let summons = galleon.summons;
+        galleon.waiting += 1;
+        while galleon.summons == summons {
+            let now = Instant::now();
+            if now >= deadline {
+                galleon.waiting -= 1;
+                return false;
+            }
+            galleon = self
+                .burning
+                .wait_timeout(galleon, deadline - now)
+                .unwrap_or_else(PoisonError::into_inner)
+                .0;
+        }
+        true
+    }
+
+    /// Releases every member waiting on the coin. Returns how many were released.
+    pub fn summon(&self) -> usize {
+        let mut galleon = self.lock();
+        galleon.summons = galleon.summons.wrapping_add(1);
+        let released = core::mem::take(&mut galleon.waiting);
+        self.burning.notify_all();
+        released
+    }
+
+    /// Returns the number of members currently waiting on the coin.
+    pub fn waiting(&self) -> usize {
+        self.lock().waiting
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Galleon> {
+        self.galleon.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{da_coin, dumbledores_army};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread;
+
+    #[test]
+    fn practice_dumbledores_army() {
+        let army = dumbledores_army!(4);
+        assert_eq!(army.members(), 4);
+        let arrived = AtomicUsize::new(0);
+        thread::scope(|s| {
+            let members: Vec<_> = (0..4)
+                .map(|_| {
+                    s.spawn(|| {
+                        let mut led = 0;
+                        for meeting in 1..=3 {
+                            arrived.fetch_add(1, Ordering::Relaxed);
+                            led += usize::from(army.meet());
+                            assert!(arrived.load(Ordering::Relaxed) >= 4 * meeting);
+                            army.meet();
+                        }
+                        led
+                    })
+                })
+                .collect();
+            let led: usize = members.into_iter().map(|m| m.join().unwrap()).sum();
+            assert_eq!(led, 3);
+        });
+        assert!(dumbledores_army!(0).meet());
+    }
+
+    #[test]
+    fn practice_da_coin() {
+        let coin = DaCoin::new();
+        assert!(!da_coin!(coin, Duration::from_millis(1)));
+        assert_eq!(coin.waiting(), 0);
+        assert_eq!(da_coin!(summon coin), 0);
+
+        thread::scope(|s| {
+            for round in 0..3 {
+                let waiters: Vec<_> = (0..2)
+                    .map(|member| {
+                        let coin = &coin;
+                        s.spawn(move || {
+                            if member == 0 {
+                                da_coin!(coin);
+                                true
+                            } else {
+                                da_coin!(coin, Duration::from_secs(60))
+                            }
+                        })
+                    })
+                    .collect();
+                while coin.waiting() < 2 {
+                    thread::yield_now();
+                }
+                assert_eq!(da_coin!(summon coin), 2, "round {}", round);
+                assert!(waiters.into_iter().all(|w| w.join().unwrap()));
+            }
+        });
+    }
+}
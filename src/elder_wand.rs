@@ -0,0 +1,229 @@
+//! Containers that allocate with a wand of your choosing, cast with [`elder_wand_vec!`](crate::elder_wand_vec!),
+//! [`elder_wand_box!`](crate::elder_wand_box!) and [`elder_wand!`](crate::elder_wand!).
+//!
+//! The Elder Wand answers to whoever won it, and these containers answer to whichever [`Allocator`] they are given:
+//! an arena, a pool, or one of the wands of this module that are made for tests.
+//! A [`CountingWand`] counts the allocations that pass through it, and a [`FailingWand`] refuses to allocate
+//! once it has run out of charges, which injects allocation failures into the code under test.
+//! Both wrap another allocator, the global one by default.
+//!
+//! The allocator API of std is unstable, so the containers and the [`Allocator`] trait come from
+//! [`allocator_api2`], which mirrors the API on stable and re-exports the std types when its `nightly` feature
+//! is enabled. Needs the `allocator-api` feature.
+//! ```
+//! # use code_spells::{elder_wand, elder_wand_box, elder_wand_vec};
+//! let wand = elder_wand!(counting);
+//! let mut duellists = elder_wand_vec!(in &wand; "Harry", "Voldemort");
+//! duellists.extend(["Dumbledore", "Grindelwald", "Dumbledore's Army"]);
+//! assert_eq!(wand.allocations(), 2);
+//!
+//! let broken = elder_wand!(failing after 0);
+//! assert!(elder_wand_box!(try in &broken; "Priori Incantatem").is_err());
+//! assert_eq!(broken.failures(), 1);
+//! ```
+
+use core::alloc::Layout;
+use core::ptr::NonNull;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+pub use allocator_api2::alloc::{AllocError, Allocator, Global};
+pub use allocator_api2::boxed::Box;
+pub use allocator_api2::collections::TryReserveError;
+pub use allocator_api2::vec::Vec;
+
+/// An allocator that counts the allocations that pass through it to another allocator.
+///
+/// A reallocation counts as one allocation and one deallocation.
+#[derive(Debug, Default)]
+pub struct CountingWand<A = Global> {
+    inner: A,
+    allocations: AtomicUsize,
+    deallocations: AtomicUsize,
+    bytes: AtomicUsize,
+    peak_bytes: AtomicUsize,
+}
+
+impl CountingWand {
+    /// Creates a wand that counts the allocations of the global allocator.
+    pub const fn new() -> Self {
+        Self::new_in(Global)
+    }
+}
+
+impl<A> CountingWand<A> {
+    /// Creates a wand that counts the allocations of `inner`.
+    pub const fn new_in(inner: A) -> Self {
+        Self {
+            inner,
+            allocations: AtomicUsize::new(0),
+            deallocations: AtomicUsize::new(0),
+            bytes: AtomicUsize::new(0),
+            peak_bytes: AtomicUsize::new(0),
+        }
+    }
+
+    /// Returns the number of allocations that have been made.
+    pub fn allocations(&self) -> usize {
+        self.allocations.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of allocations that have been freed.
+    pub fn deallocations(&self) -> usize {
+        self.deallocations.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of bytes that are allocated and not yet freed.
+    pub fn bytes(&self) -> usize {
+        self.bytes.load(Ordering::Relaxed)
+    }
+
+    /// Returns the largest number of bytes that have been allocated at the same time.
+    pub fn peak_bytes(&self) -> usize {
+        self.peak_bytes.load(Ordering::Relaxed)
+    }
+
+    /// Returns the allocator that the wand wraps.
+    pub const fn inner(&self) -> &A {
+        &self.inner
+    }
+}
+
+// SAFETY: every allocation is made and freed by the inner allocator, which upholds the contract.
+unsafe impl<A: Allocator> Allocator for CountingWand<A> {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        let memory = self.inner.allocate(layout)?;
+        self.allocations.fetch_add(1, Ordering::Relaxed);
+        let bytes = self.bytes.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+        self.peak_bytes.fetch_max(bytes, Ordering::Relaxed);
+        Ok(memory)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        self.deallocations.fetch_add(1, Ordering::Relaxed);
+        self.bytes.fetch_sub(layout.size(), Ordering::Relaxed);
+        // SAFETY: the caller guarantees that `ptr` was allocated by this wand, and so by the inner allocator, with `layout`.
+        unsafe { self.inner.deallocate(ptr, layout) }
+    }
+}
+
+/// An allocator that passes a number of allocations on to another allocator, and then fails every allocation after that.
+///
+/// A reallocation uses up a charge like an allocation does.
+#[derive(Debug, Default)]
+pub struct FailingWand<A = Global> {
+    inner: A,
+    charges: AtomicUsize,
+    failures: AtomicUsize,
+}
+
+impl FailingWand {
+    /// Creates a wand that lets `charges` allocations through to the global allocator before it starts failing.
+    pub const fn after(charges: usize) -> Self {
+        Self::after_in(charges, Global)
+    }
+}
+
+impl<A> FailingWand<A> {
+    /// Creates a wand that lets `charges` allocations through to `inner` before it starts failing.
+    pub const fn after_in(charges: usize, inner: A) -> Self {
+        Self {
+            inner,
+            charges: AtomicUsize::new(charges),
+            failures: AtomicUsize::new(0),
+        }
+    }
+
+    /// Returns the number of allocations that the wand will let through before it starts failing.
+    pub fn charges(&self) -> usize {
+        self.charges.load(Ordering::Relaxed)
+    }
+
+    /// Sets the number of allocations that the wand will let through before it starts failing again.
+    pub fn recharge(&self, charges: usize) {
+        self.charges.store(charges, Ordering::Relaxed);
+    }
+
+    /// Returns the number of allocations that the wand has failed.
+    pub fn failures(&self) -> usize {
+        self.failures.load(Ordering::Relaxed)
+    }
+
+    /// Returns the allocator that the wand wraps.
+    pub const fn inner(&self) -> &A {
+        &self.inner
+    }
+}
+
+// SAFETY: every allocation is made and freed by the inner allocator, which upholds the contract.
+unsafe impl<A: Allocator> Allocator for FailingWand<A> {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        let charged = self
+            .charges
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |charges| {
+                charges.checked_sub(1)
+            });
+        if charged.is_err() {
+            self.failures.fetch_add(1, Ordering::Relaxed);
+            return Err(AllocError);
+        }
+        self.inner.allocate(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        // SAFETY: the caller guarantees that `ptr` was allocated by this wand, and so by the inner allocator, with `layout`.
+        unsafe { self.inner.deallocate(ptr, layout) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{elder_wand, elder_wand_box, elder_wand_vec};
+    use core::mem::size_of;
+
+    #[test]
+    fn practice_elder_wand() {
+        let wand = elder_wand!(counting);
+        {
+            let mut wands: Vec<&str, _> = elder_wand_vec!(in &wand);
+            assert_eq!(wand.allocations(), 0);
+            wands.extend(["elder", "holly", "yew"]);
+            let cloak = elder_wand_box!(in &wand; [0_u8; 64]);
+            assert_eq!(cloak.len(), 64);
+            assert_eq!(wand.allocations(), 2);
+            assert_eq!(wand.bytes(), wands.capacity() * size_of::<&str>() + 64);
+        }
+        assert_eq!(wand.deallocations(), 2);
+        assert_eq!(wand.bytes(), 0);
+        assert!(wand.peak_bytes() >= 64 + 3 * size_of::<&str>());
+
+        let stones = elder_wand_vec!(in Global; "resurrection"; 3);
+        assert_eq!(stones, ["resurrection"; 3]);
+    }
+
+    #[test]
+    fn a_failing_wand_injects_allocation_failures() {
+        let wand = elder_wand!(failing after 1);
+        let first = elder_wand_vec!(try in &wand; 1, 2, 3).unwrap();
+        assert_eq!(first, [1, 2, 3]);
+        assert!(elder_wand_vec!(try in &wand; 0_u8; 16).is_err());
+        assert!(elder_wand_box!(try in &wand; 7).is_err());
+        assert_eq!((wand.charges(), wand.failures()), (0, 2));
+
+        wand.recharge(1);
+        let mut second = elder_wand_vec!(try in &wand; 'a'; 2).unwrap();
+        assert!(second.try_reserve(100).is_err());
+        assert_eq!(second, ['a', 'a']);
+        assert_eq!(wand.failures(), 3);
+    }
+
+    #[test]
+    fn wands_wrap_other_wands() {
+        let wand = FailingWand::after_in(2, CountingWand::new());
+        let mut growing = elder_wand_vec!(in &wand; 0_u64);
+        assert!(growing.try_reserve_exact(10).is_ok());
+        assert!(growing.try_reserve_exact(100).is_err());
+        assert_eq!(wand.inner().allocations(), 2);
+        assert_eq!(wand.inner().deallocations(), 1);
+    }
+}
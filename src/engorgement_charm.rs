@@ -0,0 +1,266 @@
+//! Memory-mapped files, cast with [`engorgement_charm!`](crate::engorgement_charm!).
+//!
+//! The Engorgement Charm makes a file as large as it really is in memory, without reading it:
+//! the operating system maps its pages into the address space of the process, and loads them only when they are touched.
+//! That makes it the way to work with files that are too large to read into a [`Vec`].
+//! [`Engorged`] is a read-only map that derefs to `&[u8]`, and [`EngorgedMut`] is a map that writes
+//! its changes back to the file. The maps are made with [`memmap2`](https://docs.rs/memmap2),
+//! which uses `mmap` on Unix and `MapViewOfFile` on Windows.
+//!
+//! # The dangers of engorgement
+//! A memory map is shared with the file itself, and with every other map of it, in this process or any other.
+//! Rust has no way of knowing when some other party changes the file, so a `&[u8]` into a map promises
+//! something that the operating system does not: that the bytes behind it do not change while it is alive.
+//! - If the file is modified while it is mapped, the bytes of the map change under the references into it,
+//!   which is undefined behaviour.
+//! - If the file is truncated while it is mapped, touching the pages past its new end kills the process
+//!   with `SIGBUS` on Unix, and raises an access violation on Windows.
+//!
+//! So both [`engorge`] and [`engorge_mut`] are `unsafe`, and the spell has to be cast inside
+//! [`unforgivable!`](crate::unforgivable!): make sure that nothing else writes to the file while it is engorged.
+//! A writable map can also alias another map of the same file in this process,
+//! so nothing else may even read the file while it is engorged with `mut`.
+//!
+//! Needs the `mmap` feature.
+//! ```
+//! # use code_spells::{engorgement_charm, unforgivable};
+//! # let path = std::env::temp_dir().join(format!("code_spells_engorgement_{}", std::process::id()));
+//! # std::fs::write(&path, b"Aunt Marge")?;
+//! let aunt = unforgivable!("nothing else writes to the file while it is engorged" => {
+//!     engorgement_charm!(&path)
+//! })?;
+//! assert_eq!(&aunt[..], b"Aunt Marge");
+//! # drop(aunt);
+//! # std::fs::remove_file(&path)?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use core::fmt;
+use core::ops::{Deref, DerefMut};
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::path::Path;
+
+use memmap2::{Mmap, MmapMut};
+
+/// The error returned when a file could not be engorged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Deflated {
+    /// There is no file at the path.
+    NotFound,
+    /// The file could not be opened with the needed permissions.
+    PermissionDenied,
+    /// Opening or mapping the file failed with an error of this kind.
+    Io(io::ErrorKind),
+}
+
+impl fmt::Display for Deflated {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFound => write!(f, "the Engorgement Charm found nothing to engorge"),
+            Self::PermissionDenied => {
+                write!(
+                    f,
+                    "the Engorgement Charm was blocked: the file is protected"
+                )
+            }
+            Self::Io(kind) => write!(f, "the Engorgement Charm backfired: {}", kind),
+        }
+    }
+}
+
+impl std::error::Error for Deflated {}
+
+impl From<io::Error> for Deflated {
+    fn from(error: io::Error) -> Self {
+        match error.kind() {
+            io::ErrorKind::NotFound => Self::NotFound,
+            io::ErrorKind::PermissionDenied => Self::PermissionDenied,
+            kind => Self::Io(kind),
+        }
+    }
+}
+
+/// Maps the file at `path` into memory, read-only.
+/// # Errors
+/// Returns an error if the file can not be opened for reading, or can not be mapped.
+/// # Safety
+/// Nothing may modify or truncate the file while it is mapped, neither a writable map of it in this process,
+/// nor any other process. See [the dangers of engorgement](self#the-dangers-of-engorgement).
+pub unsafe fn engorge(path: impl AsRef<Path>) -> Result<Engorged, Deflated> {
+    let file = File::open(path)?;
+    // SAFETY: the caller guarantees that nothing modifies the file while it is mapped.
+    let map = unsafe { Mmap::map(&file)? };
+    Ok(Engorged { map })
+}
+
+/// Maps the file at `path` into memory, so that changes to the map are written to the file.
+/// # Errors
+/// Returns an error if the file can not be opened for reading and writing, or can not be mapped.
+/// # Safety
+/// Nothing else may read or modify the file while it is mapped, neither another map of it in this process,
+/// nor any other process. See [the dangers of engorgement](self#the-dangers-of-engorgement).
+pub unsafe fn engorge_mut(path: impl AsRef<Path>) -> Result<EngorgedMut, Deflated> {
+    let file = OpenOptions::new().read(true).write(true).open(path)?;
+    // SAFETY: the caller guarantees that nothing else reads or modifies the file while it is mapped.
+    let map = unsafe { MmapMut::map_mut(&file)? };
+    Ok(EngorgedMut { map })
+}
+
+/// A file that is mapped into memory, read-only. Cast with [`engorge`].
+#[derive(Debug)]
+pub struct Engorged {
+    map: Mmap,
+}
+
+impl Engorged {
+    /// Returns the bytes of the file.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.map
+    }
+}
+
+impl Deref for Engorged {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.map
+    }
+}
+
+impl AsRef<[u8]> for Engorged {
+    fn as_ref(&self) -> &[u8] {
+        &self.map
+    }
+}
+
+/// A file that is mapped into memory, whose changes are written back to the file. Cast with [`engorge_mut`].
+#[derive(Debug)]
+pub struct EngorgedMut {
+    map: MmapMut,
+}
+
+impl EngorgedMut {
+    /// Returns the bytes of the file.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.map
+    }
+
+    /// Returns the bytes of the file, for writing.
+    pub fn as_bytes_mut(&mut self) -> &mut [u8] {
+        &mut self.map
+    }
+
+    /// Waits until every change to the map has been written to the file.
+    ///
+    /// The changes are written eventually even if this is never called,
+    /// but only this reports whether writing them failed.
+    /// # Errors
+    /// Returns an error if the changes could not be written.
+    pub fn flush(&self) -> Result<(), Deflated> {
+        Ok(self.map.flush()?)
+    }
+}
+
+impl Deref for EngorgedMut {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.map
+    }
+}
+
+impl DerefMut for EngorgedMut {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        &mut self.map
+    }
+}
+
+impl AsRef<[u8]> for EngorgedMut {
+    fn as_ref(&self) -> &[u8] {
+        &self.map
+    }
+}
+
+impl AsMut<[u8]> for EngorgedMut {
+    fn as_mut(&mut self) -> &mut [u8] {
+        &mut self.map
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::macro_metavars_in_unsafe)]
+mod tests {
+    use super::*;
+    use crate::{engorgement_charm, unforgivable};
+    use std::fs;
+    use std::path::PathBuf;
+
+    /// A file in the temporary directory that is removed when it is dropped.
+    struct Balloon(PathBuf);
+
+    impl Balloon {
+        fn new(name: &str, contents: &[u8]) -> Self {
+            let path = std::env::temp_dir().join(format!(
+                "code_spells_engorgement_{}_{}",
+                name,
+                std::process::id()
+            ));
+            fs::write(&path, contents).unwrap();
+            Self(path)
+        }
+    }
+
+    impl Drop for Balloon {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.0);
+        }
+    }
+
+    #[test]
+    fn practice_engorgement_charm() {
+        let balloon = Balloon::new("practice", b"Marjorie Dursley");
+        let aunt = unforgivable!("nothing else touches the balloon while it is engorged" => {
+            engorgement_charm!(&balloon.0)
+        })
+        .unwrap();
+        assert_eq!(aunt.len(), 16);
+        assert_eq!(aunt.as_bytes(), b"Marjorie Dursley");
+        assert!(aunt.starts_with(b"Marjorie"));
+        drop(aunt);
+
+        let mut inflated =
+            unforgivable!("nothing else touches the balloon while it is inflated" => {
+                engorgement_charm!(mut &balloon.0)
+            })
+            .unwrap();
+        inflated[..8].copy_from_slice(b"MARJORIE");
+        inflated.flush().unwrap();
+        drop(inflated);
+        assert_eq!(fs::read(&balloon.0).unwrap(), b"MARJORIE Dursley");
+    }
+
+    #[test]
+    fn an_empty_file_can_be_engorged() {
+        let balloon = Balloon::new("empty", b"");
+        let aunt = unforgivable!("nothing else touches the balloon while it is engorged" => {
+            engorge(&balloon.0)
+        });
+        assert!(aunt.unwrap().is_empty());
+    }
+
+    #[test]
+    fn nothing_to_engorge() {
+        let missing = std::env::temp_dir().join("code_spells_engorgement_of_nothing");
+        let nothing = unforgivable!("there is no file to touch" => { engorge(&missing) });
+        assert_eq!(nothing.unwrap_err(), Deflated::NotFound);
+        assert_eq!(
+            Deflated::NotFound.to_string(),
+            "the Engorgement Charm found nothing to engorge"
+        );
+        assert_eq!(
+            Deflated::from(io::Error::from(io::ErrorKind::OutOfMemory)),
+            Deflated::Io(io::ErrorKind::OutOfMemory)
+        );
+    }
+}
@@ -0,0 +1,125 @@
+//! Support for asserting on the output of spells in examples and doctests,
+//! used by [`ten_points_to!`](crate::ten_points_to).
+
+use core::fmt;
+
+/// A line-by-line difference between an expected and an actual text.
+///
+/// Displays as the lines of both texts, with lines only in the expected text
+/// prefixed by `-` and lines only in the actual text prefixed by `+`.
+/// # Example
+/// ```
+/// # use code_spells::examples::Diff;
+/// let diff = Diff::new("Gryffindor\nHufflepuff", "Gryffindor\nSlytherin");
+/// assert_eq!(diff.to_string(), " Gryffindor\n-Hufflepuff\n+Slytherin\n");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diff {
+    lines: Vec<(Change, String)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Change {
+    Same,
+    Removed,
+    Added,
+}
+
+impl Diff {
+    /// Computes the difference between `expected` and `actual`.
+    pub fn new(expected: &str, actual: &str) -> Self {
+        let old: Vec<&str> = expected.lines().collect();
+        let new: Vec<&str> = actual.lines().collect();
+
+        // lcs[i][j] is the length of the longest common subsequence of old[i..] and new[j..].
+        let mut lcs = vec![vec![0_usize; new.len() + 1]; old.len() + 1];
+        for i in (0..old.len()).rev() {
+            for j in (0..new.len()).rev() {
+                lcs[i][j] = if old[i] == new[j] {
+                    lcs[i + 1][j + 1] + 1
+                } else {
+                    lcs[i + 1][j].max(lcs[i][j + 1])
+                };
+            }
+        }
+
+        let mut lines = Vec::with_capacity(old.len().max(new.len()));
+        let (mut i, mut j) = (0, 0);
+        while i < old.len() && j < new.len() {
+            if old[i] == new[j] {
+                lines.push((Change::Same, old[i].to_owned()));
+                i += 1;
+                j += 1;
+            } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+                lines.push((Change::Removed, old[i].to_owned()));
+                i += 1;
+            } else {
+                lines.push((Change::Added, new[j].to_owned()));
+                j += 1;
+            }
+        }
+        lines.extend(old[i..].iter().map(|l| (Change::Removed, (*l).to_owned())));
+        lines.extend(new[j..].iter().map(|l| (Change::Added, (*l).to_owned())));
+        Self { lines }
+    }
+
+    /// Returns `true` if the two texts had the same lines.
+    pub fn is_empty(&self) -> bool {
+        self.lines.iter().all(|(change, _)| *change == Change::Same)
+    }
+}
+
+impl fmt::Display for Diff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (change, line) in &self.lines {
+            let marker = match change {
+                Change::Same => ' ',
+                Change::Removed => '-',
+                Change::Added => '+',
+            };
+            writeln!(f, "{}{}", marker, line)?;
+        }
+        Ok(())
+    }
+}
+
+#[doc(hidden)]
+#[track_caller]
+pub fn award(actual: &str, expected: &str) {
+    if actual != expected {
+        panic!(
+            "no points to anyone: the output did not match what was expected\n\
+            (- expected, + actual)\n{}",
+            Diff::new(expected, actual)
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ten_points_to;
+
+    #[test]
+    fn practice_diff() {
+        assert!(Diff::new("a\nb", "a\nb").is_empty());
+        let diff = Diff::new("a\nb\nc", "a\nc\nd");
+        assert!(!diff.is_empty());
+        assert_eq!(diff.to_string(), " a\n-b\n c\n+d\n");
+        assert_eq!(Diff::new("", "x").to_string(), "+x\n");
+    }
+
+    #[test]
+    fn practice_ten_points_to() {
+        let points = ten_points_to!(5 + 5, "10");
+        assert_eq!(points, 10);
+        ten_points_to!(Some("Gryffindor"), r#"Some("Gryffindor")"#);
+        ten_points_to!(pretty vec![1, 2], "[\n    1,\n    2,\n]");
+    }
+
+    #[test]
+    #[should_panic(expected = "-Some(\"Gryffindor\")\n+None")]
+    fn no_points_to_anyone() {
+        ten_points_to!(None::<&str>, r#"Some("Gryffindor")"#);
+    }
+}
@@ -0,0 +1,132 @@
+//! Lucky numbers, cast with [`felix_felicis!`](crate::felix_felicis!), and shuffling, cast with [`confundo!`](crate::confundo!).
+//!
+//! A sip of liquid luck makes everything go your way, or at least at random.
+//! The functions of this module draw from the thread-local generator of [`rand`], which is seeded from the
+//! operating system. Use a seeded generator from [`rand`] directly when the luck needs to be reproducible,
+//! except for [shuffling](shuffle_seeded), which has a seeded variant for deterministic tests.
+//!
+//! Needs the `rand` feature.
+//! ```
+//! use code_spells::felix_felicis;
+//!
+//! let roll = felix_felicis::lucky(1..=6);
+//! assert!((1..=6).contains(&roll));
+//! let heads = felix_felicis::coin_flip();
+//! let wand = felix_felicis::choose(&["holly", "yew", "elder"]);
+//! assert!(wand.is_some());
+//! ```
+
+use rand::distr::uniform::{SampleRange, SampleUniform};
+use rand::rngs::StdRng;
+use rand::seq::{IndexedRandom, SliceRandom};
+use rand::{Rng, SeedableRng};
+
+/// Returns a value drawn uniformly from `range`, which can be half-open or inclusive.
+/// # Panics
+/// Panics if `range` is empty.
+pub fn lucky<T, R>(range: R) -> T
+where
+    T: SampleUniform,
+    R: SampleRange<T>,
+{
+    assert!(
+        !range.is_empty(),
+        "felix_felicis! can not find luck in an empty range"
+    );
+    rand::rng().random_range(range)
+}
+
+/// Returns `true` or `false` with equal probability.
+pub fn coin_flip() -> bool {
+    rand::rng().random()
+}
+
+/// Returns a reference to a random element of `slice`, or `None` if it is empty.
+pub fn choose<T>(slice: &[T]) -> Option<&T> {
+    slice.choose(&mut rand::rng())
+}
+
+/// Randomly permutes the elements of `slice`.
+pub fn shuffle<T>(slice: &mut [T]) {
+    slice.shuffle(&mut rand::rng());
+}
+
+/// Randomly permutes the elements of `slice` in a way that only depends on `seed`.
+///
+/// The same seed gives the same permutation for as long as the generator of [`rand`] stays the same,
+/// which can change between versions of [`rand`].
+pub fn shuffle_seeded<T>(slice: &mut [T], seed: u64) {
+    slice.shuffle(&mut StdRng::seed_from_u64(seed));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{confundo, felix_felicis};
+
+    #[test]
+    fn practice_felix_felicis() {
+        for _ in 0..100 {
+            let n = felix_felicis!(1..=100);
+            assert!((1..=100).contains(&n));
+            let x: f64 = felix_felicis!(0.0..1.0);
+            assert!((0.0..1.0).contains(&x));
+        }
+
+        let houses = ["Gryffindor", "Hufflepuff", "Ravenclaw", "Slytherin"];
+        let hat: &[&str] = &houses;
+        let house = felix_felicis!(choose hat);
+        assert!(houses.contains(house.unwrap()));
+        let empty: &[u8] = &[];
+        assert_eq!(felix_felicis!(choose empty), None);
+    }
+
+    #[test]
+    fn a_fair_coin() {
+        // The chance that one side never comes up in 200 flips is 2^-199.
+        let heads = (0..200).filter(|_| felix_felicis!(bool)).count();
+        assert!(0 < heads && heads < 200);
+    }
+
+    #[test]
+    fn every_element_can_be_chosen() {
+        let wands = ["holly", "yew", "elder"];
+        let mut seen = [false; 3];
+        while seen.contains(&false) {
+            let wand = choose(&wands).unwrap();
+            seen[wands.iter().position(|w| w == wand).unwrap()] = true;
+        }
+        assert_eq!(lucky(7..=7), 7);
+    }
+
+    #[test]
+    #[should_panic(expected = "empty range")]
+    fn no_luck_in_an_empty_range() {
+        let _: u8 = lucky(5..5);
+    }
+
+    #[test]
+    fn practice_confundo() {
+        let mut students: Vec<u32> = (0..50).collect();
+        confundo!(&mut students);
+        assert_ne!(students, (0..50).collect::<Vec<_>>());
+        students.sort_unstable();
+        assert_eq!(students, (0..50).collect::<Vec<_>>());
+
+        let mut again = students.clone();
+        confundo!(&mut students, 1992);
+        confundo!(&mut again, 1992);
+        assert_eq!(students, again);
+        confundo!(&mut again[..], 1993);
+        assert_ne!(students, again);
+    }
+
+    #[test]
+    fn confunding_nothing() {
+        let mut nothing: [u8; 0] = [];
+        shuffle(&mut nothing);
+        let mut one = [7];
+        shuffle_seeded(&mut one, 0);
+        assert_eq!(one, [7]);
+    }
+}
@@ -0,0 +1,195 @@
+//! A secret keeper, cast with [`fidelius!`](crate::fidelius!).
+//!
+//! A secret that is hidden with the Fidelius Charm can only be learned from its secret keeper.
+//! A [`Fidelius`] holds a secret, like a password or a key, that should not end up in logs or core dumps.
+//! Its [`Debug`](fmt::Debug) and [`Display`](fmt::Display) implementations print `[the secret is kept]`,
+//! the secret is only [revealed](Fidelius::reveal_to) to a closure, and it is [wiped](Wipe) with zeroes
+//! when the keeper is dropped.
+//! ```
+//! # use code_spells::fidelius;
+//! let keeper = fidelius!(String::from("12 Grimmauld Place"));
+//! assert_eq!(format!("{:?}", keeper), "[the secret is kept]");
+//! assert!(keeper.reveal_to(|secret| secret.contains("Grimmauld")));
+//! ```
+
+use core::fmt;
+use core::mem::{ManuallyDrop, MaybeUninit};
+use core::ptr;
+use core::sync::atomic::{self, Ordering};
+
+/// Values that can be overwritten with zeroes before they are dropped.
+pub trait Wipe {
+    /// Overwrites the value with zeroes in a way that the compiler does not optimize away.
+    /// Containers are left empty, with their spare capacity wiped as well.
+    fn wipe(&mut self);
+}
+
+macro_rules! wipe_with {
+    ($($ty:ty => $zero:expr),* $(,)?) => {
+        $(
+            impl Wipe for $ty {
+                fn wipe(&mut self) {
+                    // SAFETY: `self` is a valid and aligned reference, and the zero is a valid value of the type.
+                    unsafe { ptr::write_volatile(self, $zero) }
+                }
+            }
+        )*
+    };
+}
+
+wipe_with! {
+    u8 => 0, u16 => 0, u32 => 0, u64 => 0, u128 => 0, usize => 0,
+    i8 => 0, i16 => 0, i32 => 0, i64 => 0, i128 => 0, isize => 0,
+    bool => false, char => '\0',
+}
+
+impl<T: Wipe> Wipe for [T] {
+    fn wipe(&mut self) {
+        self.iter_mut().for_each(Wipe::wipe);
+    }
+}
+
+impl<T: Wipe, const N: usize> Wipe for [T; N] {
+    fn wipe(&mut self) {
+        self[..].wipe();
+    }
+}
+
+impl<T: Wipe> Wipe for Box<[T]> {
+    fn wipe(&mut self) {
+        (**self).wipe();
+    }
+}
+
+impl<T: Wipe> Wipe for Vec<T> {
+    fn wipe(&mut self) {
+        self[..].wipe();
+        self.clear();
+        for slot in self.spare_capacity_mut() {
+            // SAFETY: the slot is a valid and aligned reference, and a `MaybeUninit` may hold any bytes.
+            unsafe { ptr::write_volatile(slot, MaybeUninit::zeroed()) }
+        }
+    }
+}
+
+impl Wipe for String {
+    fn wipe(&mut self) {
+        // SAFETY: the bytes are wiped and then cleared, so the string is empty, and valid UTF-8, afterwards.
+        unsafe { self.as_mut_vec() }.wipe();
+    }
+}
+
+/// A keeper of a secret that is never printed, and is wiped when the keeper is dropped.
+pub struct Fidelius<T: Wipe> {
+    secret: T,
+}
+
+impl<T: Wipe> Fidelius<T> {
+    /// Hides a secret in a new keeper.
+    pub const fn new(secret: T) -> Self {
+        Self { secret }
+    }
+
+    /// Reveals the secret to `confidant`, and returns what it returns.
+    pub fn reveal_to<R>(&self, confidant: impl FnOnce(&T) -> R) -> R {
+        confidant(&self.secret)
+    }
+
+    /// Reveals the secret to `confidant`, which may change it, and returns what it returns.
+    pub fn reveal_mut_to<R>(&mut self, confidant: impl FnOnce(&mut T) -> R) -> R {
+        confidant(&mut self.secret)
+    }
+
+    /// Gives the secret away without wiping it. It is up to the caller to keep it from here on.
+    pub fn betray(self) -> T {
+        let this = ManuallyDrop::new(self);
+        // SAFETY: the keeper is never used or dropped again, so the secret is read out exactly once.
+        unsafe { ptr::read(&this.secret) }
+    }
+}
+
+impl<T: Wipe> Drop for Fidelius<T> {
+    fn drop(&mut self) {
+        self.secret.wipe();
+        // Keeps the wipe from being reordered past the deallocation of the secret.
+        atomic::compiler_fence(Ordering::SeqCst);
+    }
+}
+
+impl<T: Wipe + Default> Default for Fidelius<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T: Wipe> From<T> for Fidelius<T> {
+    fn from(secret: T) -> Self {
+        Self::new(secret)
+    }
+}
+
+impl<T: Wipe> fmt::Debug for Fidelius<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("[the secret is kept]")
+    }
+}
+
+impl<T: Wipe> fmt::Display for Fidelius<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("[the secret is kept]")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fidelius;
+
+    #[test]
+    fn practice_fidelius() {
+        let mut keeper = fidelius!(vec![7_u8, 1, 3]);
+        assert_eq!(keeper.to_string(), "[the secret is kept]");
+        assert_eq!(format!("{:?}", Some(&keeper)), "Some([the secret is kept])");
+        keeper.reveal_mut_to(|secret| secret.push(9));
+        assert_eq!(keeper.reveal_to(|secret| secret.len()), 4);
+        assert_eq!(keeper.betray(), [7, 1, 3, 9]);
+
+        let empty: Fidelius<String> = Fidelius::default();
+        assert!(empty.reveal_to(String::is_empty));
+    }
+
+    #[test]
+    fn wiping_leaves_only_zeroes() {
+        let mut password = String::with_capacity(32);
+        password.push_str("Mimbulus mimbletonia");
+        password.truncate(8);
+        password.wipe();
+        assert!(password.is_empty());
+        let capacity = password.capacity();
+        assert!(capacity >= 32);
+        // SAFETY: the string is empty, so no bytes are written through the vector.
+        let spare = unsafe { password.as_mut_vec() }.spare_capacity_mut();
+        assert_eq!(spare.len(), capacity);
+        // SAFETY: every byte of the spare capacity was written by the wipe.
+        assert!(spare.iter().all(|byte| unsafe { byte.assume_init() } == 0));
+    }
+
+    #[test]
+    fn everything_byte_like_can_be_wiped() {
+        let mut key = [0xde_u8, 0xad, 0xbe, 0xef];
+        key.wipe();
+        assert_eq!(key, [0; 4]);
+
+        let mut pins: Box<[u32]> = Box::new([1234, 9876]);
+        pins.wipe();
+        assert_eq!(*pins, [0, 0]);
+
+        let mut nested = vec![vec![1_i64; 3], vec![2; 2]];
+        nested.wipe();
+        assert!(nested.is_empty());
+
+        let mut flags = [true, false, true];
+        flags.wipe();
+        assert_eq!(flags, [false; 3]);
+    }
+}
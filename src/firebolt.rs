@@ -0,0 +1,379 @@
+//! A fixed-capacity vector that lives on the stack, built with [`firebolt!`](crate::firebolt!).
+//!
+//! A [`Broom`] can carry at most `N` elements and never allocates, which makes it fast for small collections
+//! whose maximum size is known up front. It dereferences to a slice, so everything that works on slices works on it.
+//! ```
+//! # use code_spells::firebolt::Broom;
+//! let mut broom: Broom<&str, 3> = Broom::new();
+//! broom.push("Harry");
+//! broom.push("Hedwig");
+//! assert_eq!(broom.len(), 2);
+//! assert_eq!(broom.try_push("Ron"), Ok(()));
+//! assert_eq!(broom.try_push("Hermione"), Err("Hermione"));
+//! assert_eq!(broom.pop(), Some("Ron"));
+//! assert_eq!(broom.iter().map(|name| name.len()).sum::<usize>(), 11);
+//! ```
+
+use core::fmt;
+use core::mem::{self, MaybeUninit};
+use core::ops::{Deref, DerefMut};
+use core::{ptr, slice};
+use std::convert::TryFrom;
+use std::iter::FromIterator;
+
+/// A vector with a fixed capacity of `N` elements, stored inline.
+pub struct Broom<T, const N: usize> {
+    // The first `len` elements are initialized.
+    seats: [MaybeUninit<T>; N],
+    len: usize,
+}
+
+impl<T, const N: usize> Broom<T, N> {
+    /// Creates an empty broom.
+    pub const fn new() -> Self {
+        Self {
+            // SAFETY: an array of `MaybeUninit` does not need to be initialized.
+            seats: unsafe { MaybeUninit::<[MaybeUninit<T>; N]>::uninit().assume_init() },
+            len: 0,
+        }
+    }
+
+    /// Returns the number of elements on the broom.
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns whether the broom is empty.
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns whether the broom is full.
+    pub const fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    /// Returns the number of elements the broom can carry.
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Adds an element to the back of the broom.
+    /// # Panics
+    /// Panics if the broom is full.
+    pub fn push(&mut self, value: T) {
+        if self.try_push(value).is_err() {
+            panic!("the broom is full: it can only carry {} elements", N);
+        }
+    }
+
+    /// Adds an element to the back of the broom.
+    /// # Errors
+    /// Returns the element if the broom is full.
+    pub fn try_push(&mut self, value: T) -> Result<(), T> {
+        if self.is_full() {
+            return Err(value);
+        }
+        self.seats[self.len] = MaybeUninit::new(value);
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Removes the last element of the broom and returns it, or `None` if the broom is empty.
+    pub fn pop(&mut self) -> Option<T> {
+        if self.is_empty() {
+            return None;
+        }
+        self.len -= 1;
+        // SAFETY: the element was initialized, and is no longer counted so it will not be read again.
+        Some(unsafe { self.seats[self.len].assume_init_read() })
+    }
+
+    /// Moves the elements of a vector onto a broom.
+    /// # Errors
+    /// Returns the vector if it has more than `N` elements.
+    pub fn try_from_vec(vec: Vec<T>) -> Result<Self, Vec<T>> {
+        if vec.len() > N {
+            Err(vec)
+        } else {
+            Ok(vec.into_iter().collect())
+        }
+    }
+
+    /// Drops every element after the first `len`. Does nothing if the broom has at most `len` elements.
+    pub fn truncate(&mut self, len: usize) {
+        while self.len > len {
+            self.pop();
+        }
+    }
+
+    /// Drops every element on the broom.
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+
+    /// Returns the elements as a slice.
+    pub fn as_slice(&self) -> &[T] {
+        // SAFETY: the first `len` elements are initialized.
+        unsafe { slice::from_raw_parts(self.seats.as_ptr().cast::<T>(), self.len) }
+    }
+
+    /// Returns the elements as a mutable slice.
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        // SAFETY: the first `len` elements are initialized.
+        unsafe { slice::from_raw_parts_mut(self.seats.as_mut_ptr().cast::<T>(), self.len) }
+    }
+}
+
+impl<T, const N: usize> Drop for Broom<T, N> {
+    fn drop(&mut self) {
+        // SAFETY: the first `len` elements are initialized, and are never used again.
+        unsafe { ptr::drop_in_place(self.as_mut_slice()) }
+    }
+}
+
+impl<T, const N: usize> Deref for Broom<T, N> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T, const N: usize> DerefMut for Broom<T, N> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        self.as_mut_slice()
+    }
+}
+
+impl<T, const N: usize> Default for Broom<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone, const N: usize> Clone for Broom<T, N> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T: fmt::Debug, const N: usize> fmt::Debug for Broom<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: PartialEq, const N: usize, const M: usize> PartialEq<Broom<T, M>> for Broom<T, N> {
+    fn eq(&self, other: &Broom<T, M>) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl<T: PartialEq, const N: usize> PartialEq<[T]> for Broom<T, N> {
+    fn eq(&self, other: &[T]) -> bool {
+        self.as_slice() == other
+    }
+}
+
+impl<T: PartialEq, const N: usize, const M: usize> PartialEq<[T; M]> for Broom<T, N> {
+    fn eq(&self, other: &[T; M]) -> bool {
+        self.as_slice() == other
+    }
+}
+
+impl<T: Eq, const N: usize> Eq for Broom<T, N> {}
+
+impl<T, const N: usize> Extend<T> for Broom<T, N> {
+    /// # Panics
+    /// Panics if the broom runs out of room.
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+impl<T, const N: usize> FromIterator<T> for Broom<T, N> {
+    /// # Panics
+    /// Panics if the iterator has more than `N` elements.
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut broom = Self::new();
+        broom.extend(iter);
+        broom
+    }
+}
+
+impl<T, const N: usize> From<[T; N]> for Broom<T, N> {
+    fn from(array: [T; N]) -> Self {
+        IntoIterator::into_iter(array).collect()
+    }
+}
+
+impl<T, const N: usize> TryFrom<Vec<T>> for Broom<T, N> {
+    type Error = Vec<T>;
+
+    fn try_from(vec: Vec<T>) -> Result<Self, Vec<T>> {
+        Self::try_from_vec(vec)
+    }
+}
+
+impl<T, const N: usize> From<Broom<T, N>> for Vec<T> {
+    fn from(broom: Broom<T, N>) -> Self {
+        broom.into_iter().collect()
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a Broom<T, N> {
+    type Item = &'a T;
+    type IntoIter = slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a mut Broom<T, N> {
+    type Item = &'a mut T;
+    type IntoIter = slice::IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<T, const N: usize> IntoIterator for Broom<T, N> {
+    type Item = T;
+    type IntoIter = IntoIter<T, N>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let mut broom = mem::ManuallyDrop::new(self);
+        // SAFETY: the broom is never dropped, so its seats are moved out exactly once.
+        let seats = unsafe { ptr::read(&broom.seats) };
+        let end = mem::take(&mut broom.len);
+        IntoIter {
+            seats,
+            start: 0,
+            end,
+        }
+    }
+}
+
+/// An iterator that moves the elements out of a [`Broom`].
+pub struct IntoIter<T, const N: usize> {
+    // The elements in `start..end` are initialized.
+    seats: [MaybeUninit<T>; N],
+    start: usize,
+    end: usize,
+}
+
+impl<T, const N: usize> Iterator for IntoIter<T, N> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.start == self.end {
+            return None;
+        }
+        self.start += 1;
+        // SAFETY: the element was initialized, and is no longer in range so it will not be read again.
+        Some(unsafe { self.seats[self.start - 1].assume_init_read() })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.end - self.start;
+        (len, Some(len))
+    }
+}
+
+impl<T, const N: usize> DoubleEndedIterator for IntoIter<T, N> {
+    fn next_back(&mut self) -> Option<T> {
+        if self.start == self.end {
+            return None;
+        }
+        self.end -= 1;
+        // SAFETY: the element was initialized, and is no longer in range so it will not be read again.
+        Some(unsafe { self.seats[self.end].assume_init_read() })
+    }
+}
+
+impl<T, const N: usize> ExactSizeIterator for IntoIter<T, N> {}
+
+impl<T, const N: usize> Drop for IntoIter<T, N> {
+    fn drop(&mut self) {
+        for seat in &mut self.seats[self.start..self.end] {
+            // SAFETY: the elements in range are initialized, and are never used again.
+            unsafe { seat.assume_init_drop() }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::firebolt;
+    use std::rc::Rc;
+
+    #[test]
+    fn practice_firebolt() {
+        let broom: Broom<u8, 4> = firebolt![1, 2, 3];
+        assert_eq!(broom, [1, 2, 3]);
+        assert_eq!(broom.capacity(), 4);
+        let empty: Broom<u8, 4> = firebolt![];
+        assert!(empty.is_empty());
+        let stars: Broom<char, 3> = firebolt!['*'; 3];
+        assert!(stars.is_full());
+        assert_eq!(format!("{:?}", stars), "['*', '*', '*']");
+
+        let riders: Broom<String, 2> = firebolt!(mount vec![String::from("Harry")]).unwrap();
+        assert_eq!(riders, [String::from("Harry")]);
+        let crowd = firebolt!(mount vec![1, 2, 3]);
+        let crowd: Result<Broom<i32, 2>, _> = crowd;
+        assert_eq!(crowd.unwrap_err(), [1, 2, 3]);
+        let landed: Vec<String> = firebolt!(dismount riders);
+        assert_eq!(landed, ["Harry"]);
+    }
+
+    #[test]
+    fn a_broom_drops_what_it_carries() {
+        let owl = Rc::new("Hedwig");
+        let mut broom: Broom<Rc<&str>, 4> = Broom::new();
+        broom.extend([Rc::clone(&owl), Rc::clone(&owl), Rc::clone(&owl)]);
+        assert_eq!(Rc::strong_count(&owl), 4);
+        broom.truncate(2);
+        assert_eq!(Rc::strong_count(&owl), 3);
+        let mut riders = broom.clone().into_iter();
+        assert_eq!(Rc::strong_count(&owl), 5);
+        assert!(riders.next().is_some());
+        drop(riders);
+        assert_eq!(Rc::strong_count(&owl), 3);
+        drop(broom);
+        assert_eq!(Rc::strong_count(&owl), 1);
+    }
+
+    #[test]
+    fn a_broom_is_a_slice() {
+        let mut broom = Broom::from([3, 1, 2]);
+        broom.sort_unstable();
+        assert_eq!(broom[0], 1);
+        for seat in &mut broom {
+            *seat *= 10;
+        }
+        assert_eq!(
+            broom.iter().rev().copied().collect::<Vec<_>>(),
+            [30, 20, 10]
+        );
+        assert_eq!(
+            broom.clone().into_iter().rev().collect::<Vec<_>>(),
+            [30, 20, 10]
+        );
+        assert_eq!(broom.pop(), Some(30));
+        broom.clear();
+        assert_eq!(broom.pop(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "the broom is full")]
+    fn an_overloaded_broom() {
+        let _: Broom<u8, 1> = firebolt![1, 2];
+    }
+}
@@ -0,0 +1,264 @@
+//! In-process message routing by name, cast with [`floo!`](crate::floo!).
+//!
+//! A [`FlooNetwork`] is a registry of fireplaces. Every fireplace is [connected](FlooNetwork::connect) under a name
+//! and gets its own [`owl_post`](crate::owl_post) channel, and messages are routed to a fireplace by its name.
+//! The network only needs a shared reference to send and connect, so it can be shared between threads
+//! in an [`Arc`](std::sync::Arc).
+//! ```
+//! # use code_spells::floo;
+//! use code_spells::floo_network::FlooNetwork;
+//! use std::thread;
+//!
+//! let network = FlooNetwork::new();
+//! let burrow = network.connect("the Burrow");
+//! let ministry = network.connect("the Ministry");
+//! thread::scope(|s| {
+//!     s.spawn(|| floo!(network, "the Burrow", "Harry").unwrap());
+//!     s.spawn(|| floo!(network, "the Ministry", "Arthur").unwrap());
+//! });
+//! assert_eq!(burrow.recv(), Ok("Harry"));
+//! assert_eq!(ministry.recv(), Ok("Arthur"));
+//! assert!(floo!(network, "Diagon Alley", "Harry").is_err());
+//! ```
+//!
+//! A fireplace that is [connected with a regulation](FlooNetwork::connect_regulated) only has room for
+//! so many travellers, and its [`Backpressure`](crate::owl_post::Backpressure) decides what happens to the
+//! ones that arrive when it is full.
+//! ```
+//! # use code_spells::{floo, floo_regulation};
+//! use code_spells::floo_network::FlooNetwork;
+//!
+//! let network = FlooNetwork::new();
+//! let leaky_cauldron = network.connect_regulated("the Leaky Cauldron", floo_regulation!(1, error));
+//! floo!(network, "the Leaky Cauldron", "Hagrid").unwrap();
+//! assert!(floo!(network, "the Leaky Cauldron", "Harry").is_err());
+//! assert_eq!(leaky_cauldron.recv(), Ok("Hagrid"));
+//! ```
+
+use core::fmt;
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use crate::owl_post::{
+    owl_post, regulated_owl_post, Receiver, RegulatedReceiver, RegulatedSender, Regulation, Sender,
+    TrySendError,
+};
+
+/// The sending half of the channel of a fireplace.
+enum Fireplace<T> {
+    Open(Sender<T>),
+    Regulated(RegulatedSender<T>),
+}
+
+/// A registry of named fireplaces that messages can be routed between.
+pub struct FlooNetwork<T> {
+    fireplaces: Mutex<HashMap<String, Fireplace<T>>>,
+}
+
+impl<T> FlooNetwork<T> {
+    /// Creates a network without any fireplaces.
+    pub fn new() -> Self {
+        Self {
+            fireplaces: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Connects a fireplace to the network under `name`, and returns the receiver of the messages sent to it.
+    ///
+    /// If a fireplace was already connected under `name` it is replaced,
+    /// and its receiver is disconnected once the messages that were already sent to it have been received.
+    pub fn connect(&self, name: impl Into<String>) -> Receiver<T> {
+        let (sender, receiver) = owl_post();
+        self.lock().insert(name.into(), Fireplace::Open(sender));
+        receiver
+    }
+
+    /// Connects a fireplace to the network under `name` like [`connect`](FlooNetwork::connect),
+    /// but with a [`regulated_owl_post`] that has room for as many messages as `regulation` allows.
+    pub fn connect_regulated(
+        &self,
+        name: impl Into<String>,
+        regulation: Regulation,
+    ) -> RegulatedReceiver<T> {
+        let (sender, receiver) = regulated_owl_post(regulation);
+        self.lock()
+            .insert(name.into(), Fireplace::Regulated(sender));
+        receiver
+    }
+
+    /// Disconnects the fireplace under `name`. Returns whether a fireplace was connected under it.
+    pub fn disconnect(&self, name: &str) -> bool {
+        self.lock().remove(name).is_some()
+    }
+
+    /// Returns whether a fireplace is connected under `name`.
+    ///
+    /// A fireplace whose receiver has been dropped counts as connected until a message is sent to it.
+    pub fn is_connected(&self, name: &str) -> bool {
+        self.lock().contains_key(name)
+    }
+
+    /// Returns the names of the connected fireplaces in sorted order.
+    pub fn fireplaces(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.lock().keys().cloned().collect();
+        names.sort_unstable();
+        names
+    }
+
+    /// Sends `message` to the fireplace connected under `destination`.
+    ///
+    /// A regulated fireplace applies its backpressure when it is full.
+    /// The network is not locked while a sender waits for room in a fireplace with [`Backpressure::Block`](crate::owl_post::Backpressure::Block).
+    /// # Errors
+    /// Returns the message if no fireplace is connected under `destination`, if its receiver has been dropped,
+    /// or if it is regulated with [`Backpressure::Error`](crate::owl_post::Backpressure::Error) and full.
+    /// If the receiver has been dropped the fireplace is also disconnected.
+    pub fn send(&self, destination: &str, message: T) -> Result<(), Misrouted<T>> {
+        let mut fireplaces = self.lock();
+        let regulated = match fireplaces.get(destination) {
+            Some(Fireplace::Open(sender)) => {
+                return sender.send(message).map_err(|error| {
+                    fireplaces.remove(destination);
+                    Misrouted(error.0)
+                })
+            }
+            Some(Fireplace::Regulated(sender)) => sender.clone(),
+            None => return Err(Misrouted(message)),
+        };
+        drop(fireplaces);
+        match regulated.send(message) {
+            Ok(()) => Ok(()),
+            Err(TrySendError::Full(message)) => Err(Misrouted(message)),
+            Err(TrySendError::Disconnected(message)) => {
+                let mut fireplaces = self.lock();
+                // The fireplace may have been replaced while the lock was released.
+                if let Some(Fireplace::Regulated(sender)) = fireplaces.get(destination) {
+                    if sender.same_owlery(&regulated) {
+                        fireplaces.remove(destination);
+                    }
+                }
+                Err(Misrouted(message))
+            }
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, Fireplace<T>>> {
+        self.fireplaces
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<T> Default for FlooNetwork<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> fmt::Debug for FlooNetwork<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FlooNetwork")
+            .field("fireplaces", &self.fireplaces())
+            .finish()
+    }
+}
+
+/// The error returned when a message could not be delivered by a [`FlooNetwork`], because no fireplace is connected
+/// under its destination or because the fireplace refused it. Contains the message.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Misrouted<T>(pub T);
+
+impl<T> fmt::Debug for Misrouted<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Misrouted").finish_non_exhaustive()
+    }
+}
+
+impl<T> fmt::Display for Misrouted<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(
+            "misrouted by the floo network: no fireplace under that name could take the message",
+        )
+    }
+}
+
+impl<T> std::error::Error for Misrouted<T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{floo, floo_regulation};
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn practice_floo() {
+        let network = Arc::new(FlooNetwork::new());
+        let burrow = network.connect("burrow");
+        let grimmauld = network.connect(String::from("grimmauld place"));
+        assert_eq!(network.fireplaces(), ["burrow", "grimmauld place"]);
+
+        let travellers: Vec<_> = [("burrow", 1), ("grimmauld place", 2), ("burrow", 3)]
+            .iter()
+            .map(|&(destination, traveller)| {
+                let network = Arc::clone(&network);
+                thread::spawn(move || floo!(network, destination, traveller).unwrap())
+            })
+            .collect();
+        for traveller in travellers {
+            traveller.join().unwrap();
+        }
+        let mut arrivals: Vec<u32> = burrow.try_iter().collect();
+        arrivals.sort_unstable();
+        assert_eq!(arrivals, [1, 3]);
+        assert_eq!(grimmauld.try_recv(), Ok(2));
+
+        assert_eq!(floo!(network, "hogwarts", 4), Err(Misrouted(4)));
+        drop(grimmauld);
+        assert!(network.is_connected("grimmauld place"));
+        assert_eq!(floo!(network, "grimmauld place", 5), Err(Misrouted(5)));
+        assert!(!network.is_connected("grimmauld place"));
+        assert!(network.disconnect("burrow"));
+        assert!(!network.disconnect("burrow"));
+        assert_eq!(format!("{:?}", network), "FlooNetwork { fireplaces: [] }");
+    }
+
+    #[test]
+    fn regulated_fireplaces() {
+        let network = Arc::new(FlooNetwork::new());
+        let ministry = network.connect_regulated("ministry", floo_regulation!(2, drop_oldest));
+        let azkaban = network.connect_regulated("azkaban", floo_regulation!(1, block));
+        for traveller in 1..=3 {
+            floo!(network, "ministry", traveller).unwrap();
+        }
+        assert_eq!(ministry.try_iter().collect::<Vec<_>>(), [2, 3]);
+        assert_eq!(ministry.dropped(), 1);
+
+        floo!(network, "azkaban", 10).unwrap();
+        let blocked = {
+            let network = Arc::clone(&network);
+            thread::spawn(move || floo!(network, "azkaban", 11).unwrap())
+        };
+        // The network stays usable while a traveller waits for room.
+        floo!(network, "ministry", 4).unwrap();
+        assert_eq!(azkaban.recv(), Ok(10));
+        blocked.join().unwrap();
+        assert_eq!(azkaban.recv(), Ok(11));
+
+        drop(azkaban);
+        assert_eq!(floo!(network, "azkaban", 12), Err(Misrouted(12)));
+        assert!(!network.is_connected("azkaban"));
+    }
+
+    #[test]
+    fn reconnecting_a_fireplace_replaces_it() {
+        let network = FlooNetwork::new();
+        let old = network.connect("burrow");
+        floo!(network, "burrow", "before").unwrap();
+        let new = network.connect("burrow");
+        floo!(network, "burrow", "after").unwrap();
+        assert_eq!(old.recv(), Ok("before"));
+        assert!(old.recv().is_err());
+        assert_eq!(new.recv(), Ok("after"));
+    }
+}
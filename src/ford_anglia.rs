@@ -0,0 +1,448 @@
+//! Helper processes that are driven like a thread, cast with [`ford_anglia!`](crate::ford_anglia!).
+//!
+//! When the barrier to platform nine and three-quarters closes, the Ford Anglia flies on its own.
+//! [`ford_anglia`] starts the current executable again as a helper process, which runs the given entry
+//! instead of the rest of the program, so that risky work can crash without taking the program down with it.
+//! The two processes talk over a loopback connection: the program [sends](FordAnglia::send) messages
+//! to the helper and [receives](FordAnglia::recv) its replies, and the helper does the same through its [`Cabin`].
+//! Messages are written with [`Display`](fmt::Display) and read back with [`FromStr`], one per line.
+//!
+//! The helper runs the program from the start, and branches off into the entry when it reaches the same
+//! [`ford_anglia!`](crate::ford_anglia!), so the program should get there without doing anything
+//! that the helper should not do, and the captures of the entry are whatever the helper computed on its way.
+//! The helper is passed the arguments of the program, unless other arguments are given.
+//!
+//! If the helper crashes, the car restarts it once. The messages that were on board are lost.
+//! ```
+//! # use code_spells::ford_anglia;
+//! use code_spells::ford_anglia::Cabin;
+//!
+//! let mut car = ford_anglia!("the Flying Ford Anglia", |cabin: Cabin<u32, u32>| {
+//!     for galleons in cabin.iter() {
+//!         cabin.send(&(galleons * 17)).unwrap();
+//!     }
+//! })?;
+//! car.send(&2)?;
+//! assert_eq!(car.recv()?, 34);
+//! assert!(car.wait()?.success());
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use core::cell::RefCell;
+use core::fmt;
+use core::marker::PhantomData;
+use core::str::FromStr;
+use core::time::Duration;
+use std::collections::hash_map::RandomState;
+use std::env;
+use std::ffi::OsString;
+use std::hash::{BuildHasher, Hasher};
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{Ipv4Addr, Shutdown, TcpListener, TcpStream};
+use std::process::{self, Child, Command, ExitStatus, Stdio};
+use std::thread;
+
+use crate::gringotts_ledger::{escape, unescape};
+
+/// The environment variable that tells a helper process which entry to run.
+const NAME: &str = "CODE_SPELLS_FORD_ANGLIA";
+/// The environment variable that tells a helper process where to connect to.
+const ADDRESS: &str = "CODE_SPELLS_FORD_ANGLIA_ADDRESS";
+/// The environment variable with the key that a helper process identifies itself with.
+const KEY: &str = "CODE_SPELLS_FORD_ANGLIA_KEY";
+
+/// The error returned when a message could not make the trip between a program and its helper.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Stranded {
+    /// The other side closed the connection, and the helper, if it was the one, exited successfully.
+    Parked,
+    /// The helper crashed and was restarted. The messages that were on board were lost.
+    Restarted,
+    /// The helper crashed after it had already been restarted once.
+    Crashed,
+    /// A message could not be parsed.
+    Garbled,
+    /// The connection failed with an error of this kind.
+    Io(io::ErrorKind),
+}
+
+impl fmt::Display for Stranded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let trip = match self {
+            Self::Parked => "is parked: the other side closed the connection",
+            Self::Restarted => {
+                "crashed into the Whomping Willow and was restarted without its passengers"
+            }
+            Self::Crashed => "crashed again and ran off into the Forbidden Forest",
+            Self::Garbled => "delivered a message that could not be parsed",
+            Self::Io(kind) => return write!(f, "the Ford Anglia broke down: {}", kind),
+        };
+        write!(f, "the Ford Anglia {}", trip)
+    }
+}
+
+impl std::error::Error for Stranded {}
+
+impl From<io::Error> for Stranded {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error.kind())
+    }
+}
+
+/// Starts the current executable as a helper process that runs `entry` when it reaches this call,
+/// with the arguments of the program. Returns the handle that talks to the helper.
+///
+/// In the helper process this runs `entry` and then exits the process.
+/// # Errors
+/// Returns an error if the helper could not be started, or if it exited before it connected.
+pub fn ford_anglia<In, Out>(
+    name: &str,
+    entry: impl FnOnce(Cabin<In, Out>),
+) -> io::Result<FordAnglia<In, Out>>
+where
+    In: fmt::Display + FromStr,
+    Out: fmt::Display + FromStr,
+{
+    ford_anglia_with_args(name, env::args_os().skip(1), entry)
+}
+
+/// Starts the current executable as a helper process like [`ford_anglia`], but with the arguments `args`.
+/// # Errors
+/// Returns an error if the helper could not be started, or if it exited before it connected.
+pub fn ford_anglia_with_args<In, Out>(
+    name: &str,
+    args: impl IntoIterator<Item = impl Into<OsString>>,
+    entry: impl FnOnce(Cabin<In, Out>),
+) -> io::Result<FordAnglia<In, Out>>
+where
+    In: fmt::Display + FromStr,
+    Out: fmt::Display + FromStr,
+{
+    if env::var_os(NAME).is_some_and(|helper| helper == name) {
+        match Cabin::board() {
+            Ok(cabin) => entry(cabin),
+            Err(error) => {
+                eprintln!("the Ford Anglia could not find its way back: {}", error);
+                process::exit(1);
+            }
+        }
+        process::exit(0);
+    }
+    let args: Vec<OsString> = args.into_iter().map(Into::into).collect();
+    Ok(FordAnglia {
+        car: Car::start(name, &args)?,
+        name: name.to_owned(),
+        args,
+        restarts: 0,
+        types: PhantomData,
+    })
+}
+
+/// The helper process and the connection to it.
+struct Car {
+    child: Child,
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl Car {
+    fn start(name: &str, args: &[OsString]) -> io::Result<Self> {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+        let key = key();
+        let mut child = Command::new(env::current_exe()?)
+            .args(args)
+            .env(NAME, name)
+            .env(ADDRESS, listener.local_addr()?.to_string())
+            .env(KEY, &key)
+            .stdin(Stdio::null())
+            .spawn()?;
+        listener.set_nonblocking(true)?;
+        loop {
+            match listener.accept() {
+                Ok((stream, _)) => {
+                    if let Some(reader) = check_in(&stream, &key)? {
+                        return Ok(Self {
+                            child,
+                            reader,
+                            writer: stream,
+                        });
+                    }
+                }
+                Err(error) if error.kind() == io::ErrorKind::WouldBlock => {
+                    if let Some(status) = child.try_wait()? {
+                        return Err(io::Error::new(
+                            io::ErrorKind::ConnectionRefused,
+                            format!("the Ford Anglia stalled: the helper exited with {}", status),
+                        ));
+                    }
+                    thread::sleep(Duration::from_millis(1));
+                }
+                Err(error) => {
+                    // Nobody is told if the helper can not be stopped.
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    return Err(error);
+                }
+            }
+        }
+    }
+}
+
+/// Checks that a connection comes from the helper, which sends its key first,
+/// and returns the reader of the connection if it does.
+fn check_in(stream: &TcpStream, key: &str) -> io::Result<Option<BufReader<TcpStream>>> {
+    stream.set_nonblocking(false)?;
+    stream.set_nodelay(true)?;
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut line = String::new();
+    if reader.read_line(&mut line).is_err() || line.trim_end() != key {
+        return Ok(None);
+    }
+    stream.set_read_timeout(None)?;
+    Ok(Some(reader))
+}
+
+/// The handle of a helper process, cast with [`ford_anglia`]. Sends `In` to the helper and receives `Out` from it.
+///
+/// Dropping the handle closes the connection, and leaves the helper to finish on its own.
+pub struct FordAnglia<In, Out> {
+    car: Car,
+    name: String,
+    args: Vec<OsString>,
+    restarts: u32,
+    types: PhantomData<fn(In) -> Out>,
+}
+
+impl<In: fmt::Display, Out: FromStr> FordAnglia<In, Out> {
+    /// Sends `message` to the helper. If the helper has crashed it is restarted, and the message is sent to the new one.
+    /// # Errors
+    /// Returns [`Stranded::Parked`] if the helper has exited successfully, [`Stranded::Crashed`] if it crashed
+    /// after it had already been restarted, and [`Stranded::Io`] if the connection failed otherwise.
+    pub fn send(&mut self, message: &In) -> Result<(), Stranded> {
+        if write_line(&self.car.writer, message).is_ok() {
+            return Ok(());
+        }
+        self.recover()?;
+        Ok(write_line(&self.car.writer, message)?)
+    }
+
+    /// Waits for a message from the helper.
+    /// # Errors
+    /// Returns [`Stranded::Parked`] if the helper exited successfully, [`Stranded::Restarted`] if it crashed
+    /// and was restarted, [`Stranded::Crashed`] if it crashed after it had already been restarted,
+    /// and [`Stranded::Garbled`] if its message could not be parsed.
+    pub fn recv(&mut self) -> Result<Out, Stranded> {
+        match read_line(&mut self.car.reader) {
+            Err(Stranded::Parked) | Err(Stranded::Io(_)) => {
+                self.recover()?;
+                Err(Stranded::Restarted)
+            }
+            received => received,
+        }
+    }
+
+    /// Closes the connection, which tells the helper that no more messages are coming,
+    /// and waits for it to exit.
+    /// # Errors
+    /// Returns an error if the helper could not be waited for.
+    pub fn wait(mut self) -> io::Result<ExitStatus> {
+        // The helper may have closed the connection already.
+        let _ = self.car.writer.shutdown(Shutdown::Write);
+        self.car.child.wait()
+    }
+
+    /// Returns how many times the helper has been restarted after a crash.
+    pub fn restarts(&self) -> u32 {
+        self.restarts
+    }
+
+    /// Returns the process id of the helper.
+    pub fn id(&self) -> u32 {
+        self.car.child.id()
+    }
+
+    /// Waits for the helper, which has closed the connection, and restarts it if it crashed for the first time.
+    fn recover(&mut self) -> Result<(), Stranded> {
+        if self.car.child.wait()?.success() {
+            return Err(Stranded::Parked);
+        }
+        if self.restarts > 0 {
+            return Err(Stranded::Crashed);
+        }
+        self.car = Car::start(&self.name, &self.args)?;
+        self.restarts += 1;
+        Ok(())
+    }
+}
+
+impl<In, Out> fmt::Debug for FordAnglia<In, Out> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FordAnglia")
+            .field("name", &self.name)
+            .field("id", &self.car.child.id())
+            .field("restarts", &self.restarts)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The end of the connection that the entry of a helper process is given. Receives `In` and sends `Out`.
+pub struct Cabin<In, Out> {
+    reader: RefCell<BufReader<TcpStream>>,
+    writer: TcpStream,
+    types: PhantomData<fn(In) -> Out>,
+}
+
+impl<In, Out> Cabin<In, Out> {
+    /// Connects to the program that started this helper process.
+    fn board() -> io::Result<Self> {
+        let invalid = |error| io::Error::new(io::ErrorKind::InvalidInput, error);
+        let address = env::var(ADDRESS).map_err(invalid)?;
+        let key = env::var(KEY).map_err(invalid)?;
+        let stream = TcpStream::connect(address.as_str())?;
+        stream.set_nodelay(true)?;
+        writeln!(&stream, "{}", key)?;
+        Ok(Self {
+            reader: RefCell::new(BufReader::new(stream.try_clone()?)),
+            writer: stream,
+            types: PhantomData,
+        })
+    }
+}
+
+impl<In: FromStr, Out: fmt::Display> Cabin<In, Out> {
+    /// Waits for a message from the program.
+    /// # Errors
+    /// Returns [`Stranded::Parked`] if the program closed the connection,
+    /// and [`Stranded::Garbled`] if its message could not be parsed.
+    pub fn recv(&self) -> Result<In, Stranded> {
+        read_line(&mut self.reader.borrow_mut())
+    }
+
+    /// Returns an iterator over the messages from the program, which ends when the program closes the connection.
+    pub fn iter(&self) -> impl Iterator<Item = In> + '_ {
+        core::iter::from_fn(move || self.recv().ok())
+    }
+
+    /// Sends `message` to the program.
+    /// # Errors
+    /// Returns [`Stranded::Io`] if the connection failed.
+    pub fn send(&self, message: &Out) -> Result<(), Stranded> {
+        Ok(write_line(&self.writer, message)?)
+    }
+}
+
+impl<In, Out> fmt::Debug for Cabin<In, Out> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Cabin").finish_non_exhaustive()
+    }
+}
+
+/// Writes `message` on a line of its own.
+fn write_line(mut writer: &TcpStream, message: &impl fmt::Display) -> io::Result<()> {
+    let mut line = escape(&message.to_string());
+    line.push('\n');
+    writer.write_all(line.as_bytes())
+}
+
+/// Reads a message from a line of its own.
+fn read_line<T: FromStr>(reader: &mut BufReader<TcpStream>) -> Result<T, Stranded> {
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    // A line without its line break was cut off when the other side left.
+    if line.pop() != Some('\n') {
+        return Err(Stranded::Parked);
+    }
+    unescape(&line)
+        .and_then(|message| message.parse().ok())
+        .ok_or(Stranded::Garbled)
+}
+
+/// Returns a key that is hard to guess for a helper process to identify itself with.
+fn key() -> String {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u32(process::id());
+    format!("{:016x}", hasher.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ford_anglia;
+
+    /// Returns the arguments that make the test binary run only the test `name`, in which the helper branches off.
+    fn only(name: &str) -> Vec<String> {
+        vec![
+            format!("ford_anglia::tests::{}", name),
+            String::from("--exact"),
+            String::from("--quiet"),
+            String::from("--test-threads=1"),
+        ]
+    }
+
+    #[test]
+    fn practice_ford_anglia() {
+        let mut car = ford_anglia!(
+            "practice",
+            |cabin: Cabin<String, usize>| {
+                for passenger in cabin.iter() {
+                    if passenger == "Scabbers" {
+                        // The car does not carry rats.
+                        process::abort();
+                    }
+                    cabin.send(&passenger.len()).unwrap();
+                }
+            },
+            args: only("practice_ford_anglia"),
+        )
+        .unwrap();
+        car.send(&String::from("Harry")).unwrap();
+        car.send(&String::from("Ron\nWeasley")).unwrap();
+        assert_eq!(car.recv(), Ok(5));
+        assert_eq!(car.recv(), Ok(11));
+
+        let first = car.id();
+        car.send(&String::from("Scabbers")).unwrap();
+        assert_eq!(car.recv(), Err(Stranded::Restarted));
+        assert_eq!(car.restarts(), 1);
+        assert_ne!(car.id(), first);
+        car.send(&String::from("Hedwig")).unwrap();
+        assert_eq!(car.recv(), Ok(6));
+        assert!(format!("{:?}", car).starts_with("FordAnglia { name: \"practice\", id: "));
+
+        car.send(&String::from("Scabbers")).unwrap();
+        assert_eq!(car.recv(), Err(Stranded::Crashed));
+        assert_eq!(car.recv(), Err(Stranded::Crashed));
+    }
+
+    #[test]
+    fn a_parked_car() {
+        let mut car = ford_anglia!(
+            "parked",
+            |cabin: Cabin<u8, u8>| {
+                let galleons = cabin.recv().unwrap();
+                cabin.send(&(galleons + 1)).unwrap();
+            },
+            args: only("a_parked_car"),
+        )
+        .unwrap();
+        car.send(&41).unwrap();
+        assert_eq!(car.recv(), Ok(42));
+        assert_eq!(car.recv(), Err(Stranded::Parked));
+        assert_eq!(car.restarts(), 0);
+        assert!(car.wait().unwrap().success());
+    }
+
+    #[test]
+    fn a_stalled_car() {
+        let stalled = ford_anglia_with_args(
+            "stalled",
+            ["no such test", "--exact", "--quiet"],
+            |_: Cabin<u8, u8>| {},
+        );
+        assert_eq!(
+            stalled.unwrap_err().kind(),
+            io::ErrorKind::ConnectionRefused
+        );
+    }
+}
@@ -0,0 +1,148 @@
+//! Lock-free statistics counters that scale under contention, cast with [`galleon_counter!`](crate::galleon_counter!).
+//!
+//! A [`GalleonCounter`] spreads its count over a number of vaults, each on its own cache lines,
+//! and every thread deposits into the vault it is sorted into. Threads that count at the same time
+//! therefore rarely touch the same cache line, which a single shared atomic can not avoid.
+//! Reading the [`total`](GalleonCounter::total) has to visit every vault, so it is slower than a single atomic.
+//! ```
+//! # use code_spells::galleon::GalleonCounter;
+//! use std::thread;
+//!
+//! static GOLD: GalleonCounter = GalleonCounter::new();
+//!
+//! thread::scope(|s| {
+//!     for _ in 0..4 {
+//!         s.spawn(|| {
+//!             for _ in 0..1000 {
+//!                 GOLD.add(1);
+//!             }
+//!         });
+//!     }
+//! });
+//! assert_eq!(GOLD.total(), 4000);
+//! assert_eq!(GOLD.take(), 4000);
+//! assert_eq!(GOLD.total(), 0);
+//! ```
+
+use core::fmt;
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use crate::common_room::sorting_hat;
+
+/// A vault, padded to its own cache lines so that neighbouring vaults do not contend.
+#[repr(align(128))]
+struct Vault(AtomicU64);
+
+// Only used to fill the array of vaults, where every use is meant to be a fresh vault.
+#[allow(clippy::declare_interior_mutable_const)]
+const EMPTY_VAULT: Vault = Vault(AtomicU64::new(0));
+
+/// A counter sharded over `VAULTS` cache-line aligned atomics.
+///
+/// All operations use relaxed ordering, so the counter must not be used to synchronize other memory.
+/// The count wraps around on overflow.
+pub struct GalleonCounter<const VAULTS: usize = 16> {
+    vaults: [Vault; VAULTS],
+}
+
+impl<const VAULTS: usize> GalleonCounter<VAULTS> {
+    /// Creates a new counter at zero.
+    /// # Panics
+    /// Panics if `VAULTS` is zero. If the counter is created in a constant this fails the build instead.
+    pub const fn new() -> Self {
+        assert!(VAULTS > 0, "a galleon counter needs at least one vault");
+        Self {
+            vaults: [EMPTY_VAULT; VAULTS],
+        }
+    }
+
+    /// Deposits `galleons` into the vault of the current thread.
+    pub fn add(&self, galleons: u64) {
+        let vault = sorting_hat() % VAULTS as u64;
+        self.vaults[vault as usize]
+            .0
+            .fetch_add(galleons, Ordering::Relaxed);
+    }
+
+    /// Returns the sum of every vault.
+    ///
+    /// Deposits made while the vaults are being counted may or may not be included.
+    pub fn total(&self) -> u64 {
+        self.vaults.iter().fold(0, |total, vault| {
+            total.wrapping_add(vault.0.load(Ordering::Relaxed))
+        })
+    }
+
+    /// Empties every vault and returns what was in them.
+    ///
+    /// Every deposit is either included in the returned total or left in the counter,
+    /// so no deposits are lost when this races with [`add`](GalleonCounter::add).
+    pub fn take(&self) -> u64 {
+        self.vaults.iter().fold(0, |total, vault| {
+            total.wrapping_add(vault.0.swap(0, Ordering::Relaxed))
+        })
+    }
+
+    /// Empties every vault.
+    pub fn reset(&self) {
+        self.take();
+    }
+}
+
+impl<const VAULTS: usize> Default for GalleonCounter<VAULTS> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const VAULTS: usize> fmt::Debug for GalleonCounter<VAULTS> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GalleonCounter")
+            .field("total", &self.total())
+            .field("vaults", &VAULTS)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::galleon_counter;
+    use std::thread;
+
+    #[test]
+    fn practice_galleon_counter() {
+        let gold: GalleonCounter<4> = galleon_counter!(4);
+        thread::scope(|s| {
+            for thread in 1..=8 {
+                let gold = &gold;
+                s.spawn(move || {
+                    for _ in 0..100 {
+                        gold.add(thread);
+                    }
+                });
+            }
+        });
+        assert_eq!(gold.total(), 3600);
+        assert_eq!(
+            format!("{:?}", gold),
+            "GalleonCounter { total: 3600, vaults: 4 }"
+        );
+        assert_eq!(gold.take(), 3600);
+        assert_eq!(gold.take(), 0);
+        gold.add(7);
+        gold.reset();
+        assert_eq!(gold.total(), 0);
+
+        static SICKLES: GalleonCounter = galleon_counter!();
+        SICKLES.add(u64::MAX);
+        SICKLES.add(2);
+        assert_eq!(SICKLES.total(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one vault")]
+    fn a_galleon_counter_without_vaults() {
+        let _ = GalleonCounter::<0>::new();
+    }
+}
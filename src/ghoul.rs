@@ -0,0 +1,314 @@
+//! Background load for soak tests, cast with [`ghoul_in_the_attic!`](crate::ghoul_in_the_attic!).
+//!
+//! The Weasleys' ghoul bangs on the pipes whenever things get quiet. A [`Ghoul`] spawns background threads
+//! that interfere with the code under test: threads that keep a CPU busy, threads that churn through
+//! allocations, and threads that rattle a [`Mutex`] that the code under test also locks.
+//! [`haunt`](Ghoul::haunt) returns a [`Haunting`] guard, and the ghoul is banished when it is dropped,
+//! which stops and joins all of its threads.
+//! ```
+//! # use code_spells::ghoul_in_the_attic;
+//! use std::sync::{Arc, Mutex};
+//!
+//! let pipes = Arc::new(Mutex::new(Vec::new()));
+//! let haunting = ghoul_in_the_attic!(spin_threads: 1, rattle: &pipes);
+//! for bang in 0..100 {
+//!     pipes.lock().unwrap().push(bang);
+//! }
+//! let racket = haunting.banish();
+//! assert_eq!(pipes.lock().unwrap().len(), 100);
+//! println!("{}", racket);
+//! ```
+
+use core::fmt;
+use core::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use core::time::Duration;
+use std::hint;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread::{self, JoinHandle};
+use std::time::Instant;
+
+/// Locks a mutex that the ghoul rattles, and holds the lock for a while.
+type Rattle = Box<dyn Fn(Duration) + Send>;
+
+/// A configuration of background load.
+pub struct Ghoul {
+    spin_threads: usize,
+    churn_threads: usize,
+    churn_bytes: usize,
+    rattles: Vec<Rattle>,
+    hold: Duration,
+}
+
+impl Ghoul {
+    /// Creates a ghoul with one thread that spins and one that churns through allocations of 4 KiB.
+    pub fn new() -> Self {
+        Self {
+            spin_threads: 1,
+            churn_threads: 1,
+            churn_bytes: 4096,
+            rattles: Vec::new(),
+            hold: Duration::from_micros(10),
+        }
+    }
+
+    /// Sets the number of threads that keep a CPU busy.
+    #[must_use]
+    pub fn spin_threads(mut self, threads: usize) -> Self {
+        self.spin_threads = threads;
+        self
+    }
+
+    /// Sets the number of threads that allocate and free memory over and over.
+    #[must_use]
+    pub fn churn_threads(mut self, threads: usize) -> Self {
+        self.churn_threads = threads;
+        self
+    }
+
+    /// Sets the size of the allocations of the churning threads.
+    #[must_use]
+    pub fn churn_bytes(mut self, bytes: usize) -> Self {
+        self.churn_bytes = bytes;
+        self
+    }
+
+    /// Adds a thread that locks `mutex` over and over, holding it for the [hold time](Ghoul::hold) each time.
+    /// Rattling the same mutex more than once adds more contending threads.
+    #[must_use]
+    pub fn rattle<T: Send + 'static>(mut self, mutex: &Arc<Mutex<T>>) -> Self {
+        let mutex = Arc::clone(mutex);
+        self.rattles.push(Box::new(move |hold| {
+            let _guard = mutex.lock().unwrap_or_else(PoisonError::into_inner);
+            busy_wait(hold);
+        }));
+        self
+    }
+
+    /// Sets how long the rattling threads hold their mutex each time they lock it.
+    #[must_use]
+    pub const fn hold(mut self, hold: Duration) -> Self {
+        self.hold = hold;
+        self
+    }
+
+    /// Spawns the threads of the ghoul, which run until the returned guard is dropped.
+    /// # Panics
+    /// Panics if a thread could not be spawned.
+    pub fn haunt(self) -> Haunting {
+        let noise = Arc::new(Noise::default());
+        let mut threads = Vec::new();
+        let mut spawn = |work: Box<dyn Fn(&Noise) + Send>| {
+            let noise = Arc::clone(&noise);
+            let thread = thread::Builder::new()
+                .name(String::from("ghoul"))
+                .spawn(move || {
+                    while !noise.banished.load(Ordering::Relaxed) {
+                        work(&noise);
+                    }
+                })
+                .expect("ghoul_in_the_attic! failed to spawn a thread");
+            threads.push(thread);
+        };
+        for _ in 0..self.spin_threads {
+            spawn(Box::new(|noise| {
+                for i in 0..1024_u64 {
+                    hint::black_box(i);
+                    hint::spin_loop();
+                }
+                noise.spins.fetch_add(1, Ordering::Relaxed);
+            }));
+        }
+        for _ in 0..self.churn_threads {
+            let bytes = self.churn_bytes;
+            spawn(Box::new(move |noise| {
+                hint::black_box(vec![0xAA_u8; bytes]);
+                noise.churns.fetch_add(1, Ordering::Relaxed);
+            }));
+        }
+        for rattle in self.rattles {
+            let hold = self.hold;
+            spawn(Box::new(move |noise| {
+                rattle(hold);
+                noise.rattles.fetch_add(1, Ordering::Relaxed);
+                thread::yield_now();
+            }));
+        }
+        Haunting { noise, threads }
+    }
+}
+
+impl Default for Ghoul {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for Ghoul {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Ghoul")
+            .field("spin_threads", &self.spin_threads)
+            .field("churn_threads", &self.churn_threads)
+            .field("churn_bytes", &self.churn_bytes)
+            .field("rattles", &self.rattles.len())
+            .field("hold", &self.hold)
+            .finish()
+    }
+}
+
+/// Keeps the current thread busy without sleeping, since a sleeping thread would not contend for anything.
+fn busy_wait(duration: Duration) {
+    let start = Instant::now();
+    while start.elapsed() < duration {
+        hint::spin_loop();
+    }
+}
+
+/// The state shared between a [`Haunting`] and the threads of its ghoul.
+#[derive(Default)]
+struct Noise {
+    banished: AtomicBool,
+    spins: AtomicU64,
+    churns: AtomicU64,
+    rattles: AtomicU64,
+}
+
+/// A guard for a ghoul that is haunting in the background. Dropping it banishes the ghoul.
+pub struct Haunting {
+    noise: Arc<Noise>,
+    threads: Vec<JoinHandle<()>>,
+}
+
+impl Haunting {
+    /// Returns how much noise the ghoul has made so far.
+    pub fn racket(&self) -> Racket {
+        Racket {
+            spins: self.noise.spins.load(Ordering::Relaxed),
+            churns: self.noise.churns.load(Ordering::Relaxed),
+            rattles: self.noise.rattles.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Stops the threads of the ghoul, waits for them to finish, and returns how much noise it made.
+    pub fn banish(mut self) -> Racket {
+        self.stop();
+        self.racket()
+    }
+
+    fn stop(&mut self) {
+        self.noise.banished.store(true, Ordering::Relaxed);
+        for thread in self.threads.drain(..) {
+            // The threads only run code of this module, which does not panic.
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for Haunting {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+impl fmt::Debug for Haunting {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Haunting")
+            .field("threads", &self.threads.len())
+            .finish_non_exhaustive()
+    }
+}
+
+/// How much noise a ghoul made: the number of spin rounds, allocations and locks of its threads.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Racket {
+    spins: u64,
+    churns: u64,
+    rattles: u64,
+}
+
+impl Racket {
+    /// Returns the number of rounds of 1024 spins that the spinning threads made.
+    pub const fn spins(&self) -> u64 {
+        self.spins
+    }
+
+    /// Returns the number of allocations that the churning threads made.
+    pub const fn churns(&self) -> u64 {
+        self.churns
+    }
+
+    /// Returns the number of times that the rattling threads locked their mutex.
+    pub const fn rattles(&self) -> u64 {
+        self.rattles
+    }
+}
+
+impl fmt::Display for Racket {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "the ghoul spun {} times, churned {} allocations and rattled {} locks",
+            self.spins, self.churns, self.rattles
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ghoul_in_the_attic;
+
+    /// Waits until the ghoul has made some noise of every kind that `heard` checks.
+    fn listen(haunting: &Haunting, heard: impl Fn(Racket) -> bool) {
+        while !heard(haunting.racket()) {
+            thread::yield_now();
+        }
+    }
+
+    #[test]
+    fn practice_ghoul_in_the_attic() {
+        let attic = Arc::new(Mutex::new(0_u32));
+        let haunting = ghoul_in_the_attic!(
+            spin_threads: 2,
+            churn_threads: 1,
+            churn_bytes: 64,
+            rattle: &attic,
+            rattle: &attic
+        );
+        assert_eq!(format!("{:?}", haunting), "Haunting { threads: 5, .. }");
+        for _ in 0..1000 {
+            *attic.lock().unwrap() += 1;
+        }
+        listen(&haunting, |racket| {
+            racket.spins() > 0 && racket.churns() > 0 && racket.rattles() > 0
+        });
+        let racket = haunting.banish();
+        assert_eq!(*attic.lock().unwrap(), 1000);
+        assert!(racket.to_string().starts_with("the ghoul spun "));
+    }
+
+    #[test]
+    fn a_dropped_haunting_stops_the_ghoul() {
+        let pipes = Arc::new(Mutex::new(()));
+        let haunting = Ghoul::new()
+            .spin_threads(0)
+            .churn_threads(0)
+            .rattle(&pipes)
+            .hold(Duration::ZERO)
+            .haunt();
+        listen(&haunting, |racket| racket.rattles() > 0);
+        drop(haunting);
+        // The rattling thread has been joined, so it no longer holds on to the mutex.
+        assert_eq!(Arc::strong_count(&pipes), 1);
+        assert!(pipes.try_lock().is_ok());
+    }
+
+    #[test]
+    fn a_quiet_ghoul() {
+        let ghoul = Ghoul::new().spin_threads(0).churn_threads(0);
+        assert_eq!(
+            format!("{:?}", ghoul),
+            "Ghoul { spin_threads: 0, churn_threads: 0, churn_bytes: 4096, rattles: 0, hold: 10µs }"
+        );
+        assert_eq!(ghoul.haunt().banish(), Racket::default());
+    }
+}
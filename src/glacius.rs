@@ -0,0 +1,199 @@
+//! Values that are frozen the first time they are used, cast with [`glacius!`](crate::glacius!).
+//!
+//! A [`Glacier`] holds a function that computes its value, and calls it the first time the glacier is dereferenced,
+//! from whichever thread gets there first. The value stays frozen from then on. That makes it the way
+//! to have a `static` whose value can not be computed at compile time, like a lookup table.
+//! An [`Icicle`] is the same for a single thread, which lets it hold values that are not [`Sync`].
+//!
+//! They work like [`LazyLock`](https://doc.rust-lang.org/std/sync/struct.LazyLock.html) and
+//! [`LazyCell`](https://doc.rust-lang.org/std/cell/struct.LazyCell.html), which are newer than the MSRV of this crate.
+//! ```
+//! # use code_spells::glacius;
+//! use std::collections::HashMap;
+//!
+//! fn build_map() -> HashMap<u8, &'static str> {
+//!     HashMap::from([(1, "Gryffindor"), (2, "Hufflepuff")])
+//! }
+//!
+//! glacius! {
+//!     static LOOKUP: HashMap<u8, &'static str> = build_map();
+//! }
+//! assert_eq!(LOOKUP[&1], "Gryffindor");
+//! ```
+
+use core::cell::{Cell, OnceCell};
+use core::fmt;
+use core::ops::Deref;
+use std::sync::{Mutex, OnceLock, PoisonError};
+
+/// A value that is computed the first time it is used from any thread, and frozen from then on.
+pub struct Glacier<T, F = fn() -> T> {
+    ice: OnceLock<T>,
+    thaw: Mutex<Option<F>>,
+}
+
+impl<T, F: FnOnce() -> T> Glacier<T, F> {
+    /// Creates a glacier that computes its value with `thaw` when it is first used.
+    pub const fn new(thaw: F) -> Self {
+        Self {
+            ice: OnceLock::new(),
+            thaw: Mutex::new(Some(thaw)),
+        }
+    }
+
+    /// Computes the value if that has not been done yet, and returns it.
+    /// # Panics
+    /// Panics if the function that computes the value panics, and on every use after that.
+    pub fn force(this: &Self) -> &T {
+        this.ice.get_or_init(|| {
+            let thaw = this
+                .thaw
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .take()
+                .expect("the glacier cracked while it was being frozen");
+            thaw()
+        })
+    }
+
+    /// Returns whether the value has been computed.
+    pub fn is_frozen(this: &Self) -> bool {
+        this.ice.get().is_some()
+    }
+}
+
+impl<T, F: FnOnce() -> T> Deref for Glacier<T, F> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        Self::force(self)
+    }
+}
+
+impl<T: Default> Default for Glacier<T> {
+    fn default() -> Self {
+        Self::new(T::default)
+    }
+}
+
+impl<T: fmt::Debug, F> fmt::Debug for Glacier<T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Glacier")
+            .field("ice", &self.ice.get())
+            .finish_non_exhaustive()
+    }
+}
+
+/// A value that is computed the first time it is used, and frozen from then on. Can not be shared between threads.
+pub struct Icicle<T, F = fn() -> T> {
+    ice: OnceCell<T>,
+    thaw: Cell<Option<F>>,
+}
+
+impl<T, F: FnOnce() -> T> Icicle<T, F> {
+    /// Creates an icicle that computes its value with `thaw` when it is first used.
+    pub const fn new(thaw: F) -> Self {
+        Self {
+            ice: OnceCell::new(),
+            thaw: Cell::new(Some(thaw)),
+        }
+    }
+
+    /// Computes the value if that has not been done yet, and returns it.
+    /// # Panics
+    /// Panics if the function that computes the value panics, and on every use after that.
+    pub fn force(this: &Self) -> &T {
+        this.ice.get_or_init(|| {
+            let thaw = this
+                .thaw
+                .take()
+                .expect("the icicle cracked while it was being frozen");
+            thaw()
+        })
+    }
+
+    /// Returns whether the value has been computed.
+    pub fn is_frozen(this: &Self) -> bool {
+        this.ice.get().is_some()
+    }
+}
+
+impl<T, F: FnOnce() -> T> Deref for Icicle<T, F> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        Self::force(self)
+    }
+}
+
+impl<T: Default> Default for Icicle<T> {
+    fn default() -> Self {
+        Self::new(T::default)
+    }
+}
+
+impl<T: fmt::Debug, F> fmt::Debug for Icicle<T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Icicle")
+            .field("ice", &self.ice.get())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::glacius;
+    use core::sync::atomic::{AtomicUsize, Ordering};
+    use std::panic::{self, AssertUnwindSafe};
+    use std::thread;
+
+    static FROZEN: AtomicUsize = AtomicUsize::new(0);
+
+    glacius! {
+        /// The lake that the second task of the Triwizard Tournament is held in.
+        static BLACK_LAKE: Vec<&'static str> = {
+            FROZEN.fetch_add(1, Ordering::Relaxed);
+            vec!["Ron", "Hermione", "Cho", "Gabrielle"]
+        };
+        pub(crate) static DEPTH: u32 = 40;
+    }
+
+    #[test]
+    fn practice_glacius() {
+        let divers: Vec<_> = (0..4).map(|_| thread::spawn(|| BLACK_LAKE.len())).collect();
+        for diver in divers {
+            assert_eq!(diver.join().unwrap(), 4);
+        }
+        assert_eq!(FROZEN.load(Ordering::Relaxed), 1);
+        assert!(Glacier::is_frozen(&BLACK_LAKE));
+        assert_eq!(*DEPTH, 40);
+        assert_eq!(format!("{:?}", DEPTH), "Glacier { ice: Some(40), .. }");
+    }
+
+    #[test]
+    fn an_icicle_can_capture() {
+        let thawed = Cell::new(0);
+        let icicle = glacius!(cell {
+            thawed.set(thawed.get() + 1);
+            String::from("Nearly Headless Nick")
+        });
+        assert!(!Icicle::is_frozen(&icicle));
+        assert_eq!(format!("{:?}", icicle), "Icicle { ice: None, .. }");
+        assert_eq!(icicle.len(), 20);
+        assert!(icicle.starts_with("Nearly"));
+        assert_eq!(thawed.get(), 1);
+    }
+
+    #[test]
+    fn a_cracked_glacier_stays_cracked() {
+        let glacier: Glacier<u8, _> = Glacier::new(|| panic!("the ice broke"));
+        assert!(panic::catch_unwind(AssertUnwindSafe(|| *glacier)).is_err());
+        let cracked = panic::catch_unwind(AssertUnwindSafe(|| *glacier)).unwrap_err();
+        assert_eq!(
+            cracked.downcast_ref::<String>().map(String::as_str),
+            Some("the glacier cracked while it was being frozen")
+        );
+        assert_eq!(*Glacier::<Vec<u8>>::default(), []);
+    }
+}
@@ -0,0 +1,204 @@
+//! An arena of values, cast with [`gringotts!`](crate::gringotts!).
+//!
+//! What is deposited in Gringotts stays in its vault until the bank closes. A [`Gringotts`] arena
+//! takes values and hands out references to them that live as long as the arena, without a [`Box`]
+//! per value. The values are stored in vaults that are allocated in bulk, each twice the size of the last,
+//! and are all dropped at once together with the arena.
+//!
+//! This is handy for data structures that refer to each other, like the nodes of a graph,
+//! since every reference to a value in the arena has the same lifetime.
+//! ```
+//! # use code_spells::gringotts;
+//! use std::cell::Cell;
+//!
+//! struct Goblin<'a> {
+//!     name: &'static str,
+//!     boss: Cell<Option<&'a Goblin<'a>>>,
+//! }
+//!
+//! let bank = gringotts!();
+//! let ragnok = bank.deposit(Goblin { name: "Ragnok", boss: Cell::new(None) });
+//! let griphook = bank.deposit(Goblin { name: "Griphook", boss: Cell::new(None) });
+//! griphook.boss.set(Some(ragnok));
+//! assert_eq!(griphook.boss.get().map(|boss| boss.name), Some("Ragnok"));
+//! ```
+
+use core::cell::RefCell;
+use core::fmt;
+
+/// The vaults of an arena.
+struct Vaults<T> {
+    // Never grows beyond its capacity, so its values never move.
+    current: Vec<T>,
+    full: Vec<Vec<T>>,
+}
+
+impl<T> Vaults<T> {
+    /// Makes sure that the current vault has room for `additional` values, by opening a new one if it does not.
+    fn reserve(&mut self, additional: usize) {
+        if self.current.capacity() - self.current.len() < additional {
+            let capacity = additional.max(self.current.capacity() * 2).max(1);
+            let full = core::mem::replace(&mut self.current, Vec::with_capacity(capacity));
+            if !full.is_empty() {
+                self.full.push(full);
+            }
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.current.len() + self.full.iter().map(Vec::len).sum::<usize>()
+    }
+}
+
+/// An arena that hands out references to the values deposited in it, which live as long as the arena.
+pub struct Gringotts<T> {
+    vaults: RefCell<Vaults<T>>,
+}
+
+impl<T> Gringotts<T> {
+    /// Creates an empty arena. The first vault is opened by the first deposit.
+    pub const fn new() -> Self {
+        Self {
+            vaults: RefCell::new(Vaults {
+                current: Vec::new(),
+                full: Vec::new(),
+            }),
+        }
+    }
+
+    /// Creates an empty arena whose first vault has room for `capacity` values.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            vaults: RefCell::new(Vaults {
+                current: Vec::with_capacity(capacity),
+                full: Vec::new(),
+            }),
+        }
+    }
+
+    /// Deposits a value and returns a reference to it, which lives as long as the arena.
+    pub fn deposit(&self, value: T) -> &T {
+        let mut vaults = self.vaults.borrow_mut();
+        vaults.reserve(1);
+        let len = vaults.current.len();
+        vaults.current.push(value);
+        // SAFETY: the current vault had room for the value, so pushing it did not move any other values.
+        // A vault is never shrunk or cleared while the arena lives.
+        unsafe { &*vaults.current.as_ptr().add(len) }
+    }
+
+    /// Deposits every value of an iterator next to each other, and returns a slice of them, which lives as long as the arena.
+    pub fn deposit_iter(&self, values: impl IntoIterator<Item = T>) -> &[T] {
+        // The iterator is drained first, since it may deposit values itself.
+        let mut values: Vec<T> = values.into_iter().collect();
+        let mut vaults = self.vaults.borrow_mut();
+        vaults.reserve(values.len());
+        let start = vaults.current.len();
+        vaults.current.append(&mut values);
+        // SAFETY: as in `deposit`, the values were appended to a vault that had room for all of them.
+        unsafe {
+            core::slice::from_raw_parts(
+                vaults.current.as_ptr().add(start),
+                vaults.current.len() - start,
+            )
+        }
+    }
+
+    /// Returns the number of deposited values.
+    pub fn len(&self) -> usize {
+        self.vaults.borrow().len()
+    }
+
+    /// Returns whether no values have been deposited.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Closes the bank and returns every deposited value in the order they were deposited.
+    pub fn into_vec(self) -> Vec<T> {
+        let Vaults { current, full } = self.vaults.into_inner();
+        let mut values =
+            Vec::with_capacity(current.len() + full.iter().map(Vec::len).sum::<usize>());
+        for vault in full {
+            values.extend(vault);
+        }
+        values.extend(current);
+        values
+    }
+}
+
+impl<T> Default for Gringotts<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> fmt::Debug for Gringotts<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Gringotts")
+            .field("deposits", &self.len())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gringotts;
+    use std::rc::Rc;
+
+    #[test]
+    fn practice_gringotts() {
+        let bank = gringotts!(2);
+        let first = bank.deposit(String::from("the Philosopher's Stone"));
+        let second = bank.deposit(String::from("Hufflepuff's Cup"));
+        // The third deposit opens a new vault, which must not move the first two.
+        let third = bank.deposit(String::from("the Sword of Gryffindor"));
+        assert_eq!(first, "the Philosopher's Stone");
+        assert_eq!(second, "Hufflepuff's Cup");
+        assert_eq!(third, "the Sword of Gryffindor");
+        assert_eq!(bank.len(), 3);
+        assert_eq!(format!("{:?}", bank), "Gringotts { deposits: 3, .. }");
+
+        let empty: Gringotts<u8> = gringotts!();
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn deposits_stay_put() {
+        let bank = Gringotts::new();
+        let coins: Vec<&u32> = (0..1000).map(|n| bank.deposit(n)).collect();
+        let sickles = bank.deposit_iter(0..17_u32);
+        let nothing = bank.deposit_iter(core::iter::empty());
+        assert!(nothing.is_empty());
+        for (n, coin) in coins.into_iter().enumerate() {
+            assert_eq!(*coin, n as u32);
+        }
+        assert_eq!(sickles.len(), 17);
+        let values = bank.into_vec();
+        assert_eq!(values.len(), 1017);
+        assert_eq!(values[..3], [0, 1, 2]);
+        assert_eq!(values[1016], 16);
+    }
+
+    #[test]
+    fn deposits_are_dropped_with_the_bank() {
+        let gold = Rc::new(());
+        let bank = Gringotts::with_capacity(1);
+        for _ in 0..10 {
+            bank.deposit(Rc::clone(&gold));
+        }
+        bank.deposit_iter(vec![Rc::clone(&gold); 5]);
+        assert_eq!(Rc::strong_count(&gold), 16);
+        drop(bank);
+        assert_eq!(Rc::strong_count(&gold), 1);
+    }
+
+    #[test]
+    fn a_deposit_while_depositing() {
+        let bank = Gringotts::new();
+        let outer = bank.deposit_iter((0..3).map(|n| *bank.deposit(n * 10) + 1));
+        assert_eq!(outer, [1, 11, 21]);
+        assert_eq!(bank.into_vec(), [0, 10, 20, 1, 11, 21]);
+    }
+}
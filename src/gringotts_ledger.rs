@@ -0,0 +1,339 @@
+//! An append-only, hash-chained audit log, cast with [`gringotts_ledger!`](crate::gringotts_ledger!).
+//!
+//! Goblins keep track of every knut. Every [`Entry`] of a [`Ledger`] stores the hash of the entry before it,
+//! and its own hash covers that previous hash, its position and its event. Changing, removing or reordering
+//! any entry therefore breaks the chain from that entry on, which [`verify`](Ledger::verify) detects.
+//!
+//! The hash is the 64-bit FNV-1a hash, which catches accidents and careless forgers but is not cryptographic:
+//! someone who can rewrite the whole ledger can also recompute every hash.
+//! With the `persistence` feature a ledger can be saved to and loaded from a file.
+//! ```
+//! # use code_spells::gringotts_ledger;
+//! use code_spells::gringotts_ledger::{Entry, Ledger};
+//!
+//! let mut ledger: Ledger = gringotts_ledger!["vault 687 opened", "100 galleons withdrawn"];
+//! ledger.record("vault 687 closed");
+//! assert_eq!(ledger.verify(), Ok(()));
+//!
+//! let mut entries = ledger.into_entries();
+//! entries[1] = Entry::new("1000 galleons withdrawn", entries[1].previous(), entries[1].hash());
+//! let forged = Ledger::restore(entries);
+//! assert_eq!(forged.verify().unwrap_err().index(), 1);
+//! ```
+
+use core::fmt;
+
+use crate::taboo::{fnv1a, FNV_OFFSET_BASIS};
+
+/// Computes the hash of the entry at `index` that records `event` after an entry with the hash `previous`.
+fn seal(previous: u64, index: usize, event: &str) -> u64 {
+    let hash = fnv1a(FNV_OFFSET_BASIS, &previous.to_le_bytes());
+    let hash = fnv1a(hash, &(index as u64).to_le_bytes());
+    let hash = fnv1a(hash, &(event.len() as u64).to_le_bytes());
+    fnv1a(hash, event.as_bytes())
+}
+
+/// A recorded event, chained to the entry before it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Entry {
+    event: String,
+    previous: u64,
+    hash: u64,
+}
+
+impl Entry {
+    /// Creates an entry from its parts, e.g. when reading a ledger back from storage.
+    /// Whether the parts fit together is only checked by [`Ledger::verify`].
+    pub fn new(event: impl Into<String>, previous: u64, hash: u64) -> Self {
+        Self {
+            event: event.into(),
+            previous,
+            hash,
+        }
+    }
+
+    /// Returns the recorded event.
+    pub fn event(&self) -> &str {
+        &self.event
+    }
+
+    /// Returns the hash of the entry before this one, or zero for the first entry.
+    pub fn previous(&self) -> u64 {
+        self.previous
+    }
+
+    /// Returns the hash of this entry.
+    pub fn hash(&self) -> u64 {
+        self.hash
+    }
+}
+
+/// An append-only log of events, where every entry is chained to the one before it by its hash.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Ledger {
+    entries: Vec<Entry>,
+}
+
+impl Ledger {
+    /// Creates an empty ledger.
+    pub const fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+
+    /// Rebuilds a ledger from its entries, without checking them. Use [`verify`](Ledger::verify) to check them.
+    pub fn restore(entries: Vec<Entry>) -> Self {
+        Self { entries }
+    }
+
+    /// Appends an event to the ledger and returns its entry.
+    pub fn record(&mut self, event: impl Into<String>) -> &Entry {
+        let event = event.into();
+        let previous = self.head();
+        let hash = seal(previous, self.entries.len(), &event);
+        self.entries.push(Entry {
+            event,
+            previous,
+            hash,
+        });
+        &self.entries[self.entries.len() - 1]
+    }
+
+    /// Checks that every entry is chained to the one before it and that its hash matches its contents.
+    /// # Errors
+    /// Returns the index of the first entry that was tampered with.
+    pub fn verify(&self) -> Result<(), Tampered> {
+        let mut previous = 0;
+        for (index, entry) in self.entries.iter().enumerate() {
+            if entry.previous != previous || entry.hash != seal(previous, index, &entry.event) {
+                return Err(Tampered { index });
+            }
+            previous = entry.hash;
+        }
+        Ok(())
+    }
+
+    /// Returns the hash of the last entry, or zero if the ledger is empty.
+    /// Keeping a copy of the head elsewhere lets a whole rewritten ledger be detected too.
+    pub fn head(&self) -> u64 {
+        self.entries.last().map_or(0, Entry::hash)
+    }
+
+    /// Returns the entries in the order they were recorded.
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    /// Returns the number of entries.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns whether the ledger is empty.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the entries of the ledger.
+    pub fn into_entries(self) -> Vec<Entry> {
+        self.entries
+    }
+}
+
+#[cfg(feature = "persistence")]
+impl Ledger {
+    /// Writes the ledger to a file, one entry per line, replacing the file if it exists.
+    /// # Errors
+    /// Returns an error if the file can not be written.
+    pub fn save(&self, path: impl AsRef<std::path::Path>) -> std::io::Result<()> {
+        use std::io::Write;
+
+        let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        for entry in &self.entries {
+            writeln!(
+                file,
+                "{:016x} {:016x} {}",
+                entry.previous,
+                entry.hash,
+                escape(&entry.event)
+            )?;
+        }
+        file.flush()
+    }
+
+    /// Reads a ledger that was written by [`save`](Ledger::save) and verifies it.
+    /// # Errors
+    /// Returns an error if the file can not be read, and an error of kind
+    /// [`InvalidData`](std::io::ErrorKind::InvalidData) if it is not a ledger or if the ledger was tampered with.
+    pub fn load(path: impl AsRef<std::path::Path>) -> std::io::Result<Self> {
+        use std::io::{BufRead, Error, ErrorKind};
+
+        let file = std::io::BufReader::new(std::fs::File::open(path)?);
+        let mut entries = Vec::new();
+        for (number, line) in file.lines().enumerate() {
+            let line = line?;
+            let mut parts = line.splitn(3, ' ');
+            let parse_hash = |hash: &str| u64::from_str_radix(hash, 16).ok();
+            let previous = parts.next().and_then(parse_hash);
+            let hash = parts.next().and_then(parse_hash);
+            let event = parts.next().and_then(unescape);
+            let (previous, hash, event) = match (previous, hash, event) {
+                (Some(previous), Some(hash), Some(event)) => (previous, hash, event),
+                _ => {
+                    return Err(Error::new(
+                        ErrorKind::InvalidData,
+                        format!("line {} is not a ledger entry", number + 1),
+                    ))
+                }
+            };
+            entries.push(Entry::new(event, previous, hash));
+        }
+        let ledger = Self::restore(entries);
+        ledger
+            .verify()
+            .map_err(|tampered| Error::new(ErrorKind::InvalidData, tampered))?;
+        Ok(ledger)
+    }
+}
+
+/// Escapes backslashes and line breaks, so that a text fits on a single line.
+pub(crate) fn escape(event: &str) -> String {
+    event
+        .replace('\\', "\\\\")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+/// Reverts [`escape`], or returns `None` if the line contains an unknown escape.
+pub(crate) fn unescape(line: &str) -> Option<String> {
+    let mut event = String::with_capacity(line.len());
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            event.push(c);
+            continue;
+        }
+        event.push(match chars.next()? {
+            '\\' => '\\',
+            'n' => '\n',
+            'r' => '\r',
+            _ => return None,
+        });
+    }
+    Some(event)
+}
+
+/// The error returned when a [`Ledger`] fails verification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Tampered {
+    index: usize,
+}
+
+impl Tampered {
+    /// Returns the index of the first entry that was tampered with.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
+
+impl fmt::Display for Tampered {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "the goblins found that entry {} of the ledger was tampered with",
+            self.index
+        )
+    }
+}
+
+impl std::error::Error for Tampered {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gringotts_ledger;
+
+    #[test]
+    fn practice_gringotts_ledger() {
+        let mut ledger: Ledger = gringotts_ledger!();
+        assert!(ledger.is_empty());
+        assert_eq!(ledger.head(), 0);
+        assert_eq!(ledger.verify(), Ok(()));
+
+        let first = ledger.record("vault 713 emptied").hash();
+        let second = ledger.record(String::from("stone moved")).clone();
+        assert_eq!(second.previous(), first);
+        assert_eq!(ledger.head(), second.hash());
+        assert_eq!(ledger.len(), 2);
+        assert_eq!(ledger.verify(), Ok(()));
+        assert_eq!(
+            ledger,
+            gringotts_ledger!["vault 713 emptied", "stone moved"]
+        );
+        assert_ne!(first, second.hash());
+    }
+
+    #[test]
+    fn the_goblins_notice_tampering() {
+        let ledger: Ledger = gringotts_ledger!["opened", "withdrew 5", "closed"];
+        let entries = ledger.into_entries();
+
+        let mut forged = entries.clone();
+        forged[1] = Entry::new("withdrew 500", forged[1].previous(), forged[1].hash());
+        assert_eq!(Ledger::restore(forged).verify(), Err(Tampered { index: 1 }));
+
+        let mut removed = entries.clone();
+        removed.remove(1);
+        assert_eq!(
+            Ledger::restore(removed).verify(),
+            Err(Tampered { index: 1 })
+        );
+
+        let mut swapped = entries.clone();
+        swapped.swap(0, 2);
+        assert_eq!(
+            Ledger::restore(swapped).verify(),
+            Err(Tampered { index: 0 })
+        );
+
+        let mut truncated = entries.clone();
+        truncated.pop();
+        let truncated = Ledger::restore(truncated);
+        assert_eq!(truncated.verify(), Ok(()));
+        assert_ne!(truncated.head(), entries[2].hash());
+
+        assert_eq!(
+            Tampered { index: 1 }.to_string(),
+            "the goblins found that entry 1 of the ledger was tampered with"
+        );
+    }
+
+    #[cfg(feature = "persistence")]
+    #[test]
+    fn a_ledger_in_the_archives() {
+        let path = std::env::temp_dir().join(format!(
+            "code-spells-gringotts-ledger-{}.txt",
+            std::process::id()
+        ));
+        let ledger: Ledger = gringotts_ledger!["line\nbreak", "back\\slash", ""];
+        ledger.save(&path).unwrap();
+        assert_eq!(Ledger::load(&path).unwrap(), ledger);
+
+        let saved = std::fs::read_to_string(&path).unwrap();
+        std::fs::write(&path, saved.replacen("back", "bank", 1)).unwrap();
+        let error = Ledger::load(&path).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+        assert_eq!(
+            error.to_string(),
+            "the goblins found that entry 1 of the ledger was tampered with"
+        );
+
+        std::fs::write(&path, "not a ledger\n").unwrap();
+        assert_eq!(
+            Ledger::load(&path).unwrap_err().kind(),
+            std::io::ErrorKind::InvalidData
+        );
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,214 @@
+//! Small composable validators for the [`bat_bogey_hex!`](crate::bat_bogey_hex) spell.
+//!
+//! Every validator is a [`Hex`] that either lets a value pass or strikes it with a [`Violation`].
+//! Any closure of the form `Fn(&T) -> Result<(), Violation>` is also a [`Hex`], so custom
+//! validators can be cast alongside the built-in ones.
+//! ```
+//! # use code_spells::{bat_bogey_hex, hexes::{max_len, not_empty, Violation}};
+//! let no_spaces = |s: &str| {
+//!     if s.contains(' ') {
+//!         Err(Violation::new("no_spaces", "contains a space"))
+//!     } else {
+//!         Ok(())
+//!     }
+//! };
+//! let violations = bat_bogey_hex!("Ron Weasley", [not_empty(), max_len(3), no_spaces]).unwrap_err();
+//! assert_eq!(violations.len(), 2);
+//! ```
+
+use core::fmt;
+
+/// A validator that can be cast on a value of type `T`.
+pub trait Hex<T: ?Sized> {
+    /// Returns `Ok(())` if the value passes, and the [`Violation`] it commits otherwise.
+    fn cast(&self, value: &T) -> Result<(), Violation>;
+}
+
+impl<T: ?Sized, F> Hex<T> for F
+where
+    F: Fn(&T) -> Result<(), Violation>,
+{
+    fn cast(&self, value: &T) -> Result<(), Violation> {
+        self(value)
+    }
+}
+
+/// A rule broken by a value, as reported by a [`Hex`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    hex: &'static str,
+    message: String,
+}
+
+impl Violation {
+    /// Creates a new violation reported by the hex named `hex`.
+    pub fn new(hex: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            hex,
+            message: message.into(),
+        }
+    }
+
+    /// The name of the hex that reported the violation.
+    pub fn hex(&self) -> &'static str {
+        self.hex
+    }
+
+    /// A description of what was wrong with the value.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "struck by the `{}` hex: {}", self.hex, self.message)
+    }
+}
+
+impl std::error::Error for Violation {}
+
+/// A [`Hex`] that strikes empty strings. Created by [`not_empty`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NotEmpty;
+
+/// Creates a [`Hex`] that strikes empty strings.
+/// # Example
+/// ```
+/// # use code_spells::{bat_bogey_hex, hexes::not_empty};
+/// assert!(bat_bogey_hex!("Neville", [not_empty()]).is_ok());
+/// assert!(bat_bogey_hex!("", [not_empty()]).is_err());
+/// ```
+pub fn not_empty() -> NotEmpty {
+    NotEmpty
+}
+
+impl<T: AsRef<str> + ?Sized> Hex<T> for NotEmpty {
+    fn cast(&self, value: &T) -> Result<(), Violation> {
+        if value.as_ref().is_empty() {
+            Err(Violation::new("not_empty", "the value is empty"))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// A [`Hex`] that strikes strings with more than a given number of characters. Created by [`max_len`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MaxLen(usize);
+
+/// Creates a [`Hex`] that strikes strings with more than `max` characters.
+/// # Example
+/// ```
+/// # use code_spells::{bat_bogey_hex, hexes::max_len};
+/// assert!(bat_bogey_hex!("Ginny", [max_len(5)]).is_ok());
+/// assert!(bat_bogey_hex!("Ginevra", [max_len(5)]).is_err());
+/// ```
+pub fn max_len(max: usize) -> MaxLen {
+    MaxLen(max)
+}
+
+impl<T: AsRef<str> + ?Sized> Hex<T> for MaxLen {
+    fn cast(&self, value: &T) -> Result<(), Violation> {
+        let len = value.as_ref().chars().count();
+        if len > self.0 {
+            Err(Violation::new(
+                "max_len",
+                format!(
+                    "the value is {} characters long, but at most {} are allowed",
+                    len, self.0
+                ),
+            ))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// A [`Hex`] that strikes strings that do not match a regular expression. Created by [`matches()`].
+#[cfg(feature = "regex")]
+#[derive(Debug, Clone)]
+pub struct Matches(regex::Regex);
+
+/// Creates a [`Hex`] that strikes strings that do not match the regular expression `pattern`.
+/// # Errors
+/// Returns an error if `pattern` is not a valid regular expression.
+/// # Example
+/// ```
+/// # use code_spells::{bat_bogey_hex, hexes::matches};
+/// let owl = matches("^[A-Z][a-z]+$")?;
+/// assert!(bat_bogey_hex!("Hedwig", [owl.clone()]).is_ok());
+/// assert!(bat_bogey_hex!("errol", [owl]).is_err());
+/// # Ok::<(), regex::Error>(())
+/// ```
+#[cfg(feature = "regex")]
+pub fn matches(pattern: &str) -> Result<Matches, regex::Error> {
+    regex::Regex::new(pattern).map(Matches)
+}
+
+#[cfg(feature = "regex")]
+impl<T: AsRef<str> + ?Sized> Hex<T> for Matches {
+    fn cast(&self, value: &T) -> Result<(), Violation> {
+        if self.0.is_match(value.as_ref()) {
+            Ok(())
+        } else {
+            Err(Violation::new(
+                "matches",
+                format!("the value does not match the pattern `{}`", self.0.as_str()),
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bat_bogey_hex;
+
+    #[test]
+    fn practice_hexes() {
+        assert_eq!(Hex::<str>::cast(&not_empty(), "Hermione"), Ok(()));
+        assert_eq!(
+            Hex::<str>::cast(&not_empty(), "").unwrap_err().hex(),
+            "not_empty"
+        );
+        assert_eq!(
+            Hex::<String>::cast(&max_len(3), &String::from("Ron")),
+            Ok(())
+        );
+        assert_eq!(
+            Hex::<str>::cast(&max_len(3), "Ronald").unwrap_err().hex(),
+            "max_len"
+        );
+        // Characters are counted, not bytes.
+        assert_eq!(Hex::<str>::cast(&max_len(1), "ö"), Ok(()));
+    }
+
+    #[test]
+    fn practice_bat_bogey_hex() {
+        assert_eq!(bat_bogey_hex!("Harry", [not_empty(), max_len(10)]), Ok(()));
+        let violations = bat_bogey_hex!(&String::new(), [not_empty(), max_len(10)]).unwrap_err();
+        assert_eq!(
+            violations,
+            vec![Violation::new("not_empty", "the value is empty")]
+        );
+        let violations = bat_bogey_hex!(
+            "",
+            [max_len(0), not_empty(), |_: &str| {
+                Err(Violation::new("never", "always strikes"))
+            }]
+        )
+        .unwrap_err();
+        let hexes: Vec<_> = violations.iter().map(Violation::hex).collect();
+        assert_eq!(hexes, ["not_empty", "never"]);
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn practice_matches() {
+        let hex = matches(r"^\d+$").unwrap();
+        assert_eq!(Hex::<str>::cast(&hex, "934"), Ok(()));
+        assert_eq!(Hex::<str>::cast(&hex, "9¾").unwrap_err().hex(), "matches");
+        assert!(matches("(").is_err());
+    }
+}
@@ -0,0 +1,139 @@
+//! A two-phase "check before use" protocol, cast with [`hippogriff!`](crate::hippogriff!).
+//!
+//! Never approach a hippogriff before it has accepted your bow.
+//! A [`Hippogriff`] wraps a value together with a readiness check,
+//! and only hands out the value once the check has passed.
+
+use core::fmt;
+
+/// A value that can only be approached after bowing to it.
+/// # Example
+/// ```
+/// # use code_spells::hippogriff::{Clawed, Hippogriff};
+/// let mut buckbeak = Hippogriff::new(String::from("Buckbeak"), |name: &String| {
+///     if name.is_empty() { Err("no one to bow to") } else { Ok(()) }
+/// });
+/// assert_eq!(buckbeak.approach(), Err(Clawed));
+/// assert_eq!(buckbeak.offenses(), 1);
+///
+/// buckbeak.bow().unwrap();
+/// assert_eq!(buckbeak.approach().unwrap(), "Buckbeak");
+/// ```
+pub struct Hippogriff<T, F> {
+    creature: T,
+    check: F,
+    bowed: bool,
+    offenses: usize,
+}
+
+impl<T, F, E> Hippogriff<T, F>
+where
+    F: Fn(&T) -> Result<(), E>,
+{
+    /// Wraps `creature` so that it can only be approached after `check` has accepted a bow.
+    pub fn new(creature: T, check: F) -> Self {
+        Self {
+            creature,
+            check,
+            bowed: false,
+            offenses: 0,
+        }
+    }
+
+    /// Bows to the hippogriff by running the readiness check.
+    /// If the check passes the hippogriff can be approached until [`turn_away`](Hippogriff::turn_away) is called.
+    /// # Errors
+    /// Returns the error of the check if it fails.
+    pub fn bow(&mut self) -> Result<(), E> {
+        let result = (self.check)(&self.creature);
+        self.bowed = result.is_ok();
+        result
+    }
+
+    /// Returns whether a bow has been accepted.
+    pub fn is_bowed(&self) -> bool {
+        self.bowed
+    }
+
+    /// Turns away from the hippogriff, so that it has to be bowed to again before it can be approached.
+    pub fn turn_away(&mut self) {
+        self.bowed = false;
+    }
+
+    /// Approaches the hippogriff.
+    /// # Errors
+    /// Returns [`Clawed`] and records an offense if no bow has been accepted.
+    pub fn approach(&mut self) -> Result<&mut T, Clawed> {
+        if self.bowed {
+            Ok(&mut self.creature)
+        } else {
+            self.offenses += 1;
+            Err(Clawed)
+        }
+    }
+
+    /// Returns how many times the hippogriff has been approached without a bow.
+    pub fn offenses(&self) -> usize {
+        self.offenses
+    }
+
+    /// Sets the hippogriff free, returning the wrapped value.
+    /// # Errors
+    /// Returns [`Clawed`] and the hippogriff itself if no bow has been accepted.
+    pub fn into_inner(mut self) -> Result<T, (Clawed, Self)> {
+        if self.bowed {
+            Ok(self.creature)
+        } else {
+            self.offenses += 1;
+            Err((Clawed, self))
+        }
+    }
+}
+
+impl<T: fmt::Debug, F> fmt::Debug for Hippogriff<T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Hippogriff")
+            .field("creature", &self.creature)
+            .field("bowed", &self.bowed)
+            .field("offenses", &self.offenses)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The error returned when a [`Hippogriff`] is approached without bowing first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Clawed;
+
+impl fmt::Display for Clawed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("clawed by the hippogriff: it was approached without a bow")
+    }
+}
+
+impl std::error::Error for Clawed {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hippogriff;
+
+    #[test]
+    fn practice_hippogriff() {
+        let mut ready = hippogriff!(vec![1, 2, 3], |v: &Vec<i32>| if v.len() == 3 {
+            Ok(())
+        } else {
+            Err(v.len())
+        });
+        assert_eq!(ready.approach(), Err(Clawed));
+        assert!(!ready.is_bowed());
+        assert_eq!(ready.bow(), Ok(()));
+        ready.approach().unwrap().push(4);
+        ready.turn_away();
+        assert_eq!(ready.approach(), Err(Clawed));
+        // The check now fails, since the vector has grown.
+        assert_eq!(ready.bow(), Err(4));
+        let (clawed, ready) = ready.into_inner().unwrap_err();
+        assert_eq!(clawed, Clawed);
+        assert_eq!(ready.offenses(), 3);
+    }
+}
@@ -0,0 +1,245 @@
+//! Splitting data into shares, cast with [`horcrux!`](crate::horcrux!).
+//!
+//! A soul that is split into horcruxes can only be made whole again if every last one of them is found.
+//! [`split`] splits a byte buffer into a number of [`Horcrux`] shares with xor secret sharing:
+//! every share but the last is random, and the last is the data xor all the others.
+//! [`reassemble`] needs every share to restore the data, and any fewer than all of them
+//! are indistinguishable from random noise.
+//!
+//! The random shares are drawn from the thread-local generator of [`rand`], which is a CSPRNG seeded from the
+//! operating system. Needs the `rand` feature.
+//! ```
+//! # use code_spells::horcrux;
+//! use code_spells::horcrux::{reassemble, TornSoul};
+//!
+//! let mut horcruxes = horcrux!(b"Tom Marvolo Riddle", 7);
+//! assert_eq!(horcruxes.len(), 7);
+//! assert_eq!(reassemble(&horcruxes).unwrap(), b"Tom Marvolo Riddle");
+//!
+//! let diary = horcruxes.remove(0);
+//! assert_eq!(reassemble(&horcruxes), Err(TornSoul::Missing { found: 6, needed: 7 }));
+//! ```
+
+use core::fmt;
+use rand::Rng;
+
+/// One share of split data. Every share of a split is needed to [`reassemble`] it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Horcrux {
+    soul: u64,
+    index: usize,
+    count: usize,
+    fragment: Vec<u8>,
+}
+
+impl Horcrux {
+    /// Returns the position of the share in its split, starting from zero.
+    pub const fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the number of shares in the split, which are all needed to reassemble it.
+    pub const fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Returns the bytes of the share, which are as long as the data that was split.
+    pub fn fragment(&self) -> &[u8] {
+        &self.fragment
+    }
+}
+
+/// Splits `data` into `count` shares that are all needed to restore it.
+/// # Panics
+/// Panics if `count` is zero.
+pub fn split(data: impl AsRef<[u8]>, count: usize) -> Vec<Horcrux> {
+    assert!(count > 0, "a soul can not be split into zero horcruxes");
+    let mut rng = rand::rng();
+    let soul = rng.random();
+    let mut last = data.as_ref().to_vec();
+    let mut horcruxes = Vec::with_capacity(count);
+    for index in 0..count - 1 {
+        let mut fragment = vec![0; last.len()];
+        rng.fill(&mut fragment[..]);
+        for (byte, noise) in last.iter_mut().zip(&fragment) {
+            *byte ^= noise;
+        }
+        horcruxes.push(Horcrux {
+            soul,
+            index,
+            count,
+            fragment,
+        });
+    }
+    horcruxes.push(Horcrux {
+        soul,
+        index: count - 1,
+        count,
+        fragment: last,
+    });
+    horcruxes
+}
+
+/// Restores data from every share it was split into, in any order.
+/// # Errors
+/// Returns a [`TornSoul`] if a share is missing or found twice, or if the shares come from different splits.
+pub fn reassemble(horcruxes: &[Horcrux]) -> Result<Vec<u8>, TornSoul> {
+    let first = horcruxes.first().ok_or(TornSoul::Empty)?;
+    let mut found = vec![false; first.count];
+    for horcrux in horcruxes {
+        if (horcrux.soul, horcrux.count, horcrux.fragment.len())
+            != (first.soul, first.count, first.fragment.len())
+        {
+            return Err(TornSoul::Foreign);
+        }
+        if core::mem::replace(&mut found[horcrux.index], true) {
+            return Err(TornSoul::Duplicate {
+                index: horcrux.index,
+            });
+        }
+    }
+    if horcruxes.len() < first.count {
+        return Err(TornSoul::Missing {
+            found: horcruxes.len(),
+            needed: first.count,
+        });
+    }
+    let mut data = vec![0; first.fragment.len()];
+    for horcrux in horcruxes {
+        for (byte, piece) in data.iter_mut().zip(&horcrux.fragment) {
+            *byte ^= piece;
+        }
+    }
+    Ok(data)
+}
+
+/// The error returned when shares can not be [reassembled](reassemble).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum TornSoul {
+    /// There were no shares at all.
+    Empty,
+    /// Some of the shares are missing.
+    Missing {
+        /// The number of shares that were found.
+        found: usize,
+        /// The number of shares in the split.
+        needed: usize,
+    },
+    /// A share was found more than once.
+    Duplicate {
+        /// The position of the share in its split.
+        index: usize,
+    },
+    /// The shares come from different splits.
+    Foreign,
+}
+
+impl fmt::Display for TornSoul {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => f.write_str("there are no horcruxes to reassemble"),
+            Self::Missing { found, needed } => {
+                write!(f, "only {} of the {} horcruxes were found", found, needed)
+            }
+            Self::Duplicate { index } => write!(f, "horcrux {} was found twice", index),
+            Self::Foreign => f.write_str("the horcruxes hold pieces of different souls"),
+        }
+    }
+}
+
+impl std::error::Error for TornSoul {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::horcrux;
+
+    fn xor_all<'a>(horcruxes: impl IntoIterator<Item = &'a Horcrux>, len: usize) -> Vec<u8> {
+        let mut data = vec![0; len];
+        for horcrux in horcruxes {
+            for (byte, piece) in data.iter_mut().zip(horcrux.fragment()) {
+                *byte ^= piece;
+            }
+        }
+        data
+    }
+
+    #[test]
+    fn practice_horcrux() {
+        let horcruxes = horcrux!(String::from("Nagini"), 3);
+        assert_eq!(
+            horcruxes.iter().map(Horcrux::index).collect::<Vec<_>>(),
+            [0, 1, 2]
+        );
+        assert!(horcruxes.iter().all(|horcrux| horcrux.count() == 3));
+        let shuffled = [
+            horcruxes[2].clone(),
+            horcruxes[0].clone(),
+            horcruxes[1].clone(),
+        ];
+        assert_eq!(reassemble(&shuffled).unwrap(), b"Nagini");
+
+        let single = horcrux!([1_u8, 2, 3], 1);
+        assert_eq!(single[0].fragment(), [1, 2, 3]);
+        assert_eq!(reassemble(&horcrux!(Vec::new(), 4)).unwrap(), []);
+    }
+
+    #[test]
+    fn any_missing_horcrux_makes_reassembly_impossible() {
+        for len in [16, 33, 100] {
+            let data: Vec<u8> = (0..len).map(|_| rand::rng().random()).collect();
+            for count in 2..8 {
+                let horcruxes = split(&data, count);
+                assert_eq!(reassemble(&horcruxes).unwrap(), data);
+                for missing in 0..count {
+                    let rest: Vec<Horcrux> = horcruxes
+                        .iter()
+                        .filter(|horcrux| horcrux.index() != missing)
+                        .cloned()
+                        .collect();
+                    assert_eq!(
+                        reassemble(&rest),
+                        Err(TornSoul::Missing {
+                            found: count - 1,
+                            needed: count
+                        })
+                    );
+                    // Even combining what is left by hand only gives noise.
+                    assert_ne!(xor_all(&rest, len), data);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn mixed_up_horcruxes() {
+        let locket = split(b"Slytherin", 2);
+        let cup = split(b"Hufflepuff", 2);
+        assert_eq!(reassemble(&[]), Err(TornSoul::Empty));
+        assert_eq!(
+            reassemble(&[locket[0].clone(), cup[1].clone()]),
+            Err(TornSoul::Foreign)
+        );
+        let twice = [locket[1].clone(), locket[1].clone()];
+        assert_eq!(reassemble(&twice), Err(TornSoul::Duplicate { index: 1 }));
+        assert_eq!(
+            reassemble(&twice).unwrap_err().to_string(),
+            "horcrux 1 was found twice"
+        );
+        assert_eq!(
+            TornSoul::Missing {
+                found: 6,
+                needed: 7
+            }
+            .to_string(),
+            "only 6 of the 7 horcruxes were found"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "zero horcruxes")]
+    fn a_soul_split_zero_ways() {
+        let _ = split(b"Voldemort", 0);
+    }
+}
@@ -0,0 +1,449 @@
+//! Structured panic payloads, sent with the `letter` arm of [`avada_kedavra!`](crate::avada_kedavra!),
+//! and panic hooks that scream, installed with [`howler!`](crate::howler!).
+//!
+//! A one-line panic message rarely says everything that the person who reads it needs to know.
+//! A [`Letter`] is a panic payload with a summary of what went wrong, the values that were involved,
+//! and a hint at how to fix it. The panic hook of this module reads letters out in full, and leaves
+//! every other panic to the hook that was installed before it.
+//!
+//! The hook is [installed](install_hook) the first time a letter is [sent](Letter::send).
+//! A letter that is caught with [`catch_unwind`](std::panic::catch_unwind) can be downcast from the payload.
+//! ```
+//! use code_spells::howler_mail::Letter;
+//! use std::panic;
+//!
+//! let caught = panic::catch_unwind(|| {
+//!     Letter::new("the flying car was seen by Muggles")
+//!         .context("witnesses", 6)
+//!         .context("car", "Ford Anglia")
+//!         .remedy("send the Obliviators")
+//!         .send()
+//! });
+//! let payload = caught.unwrap_err();
+//! let letter = payload.downcast_ref::<Letter>().unwrap();
+//! assert_eq!(letter.summary(), "the flying car was seen by Muggles");
+//! assert_eq!(
+//!     letter.to_string(),
+//!     "the flying car was seen by Muggles\n\
+//!      \n\
+//!      context:\n    \
+//!          witnesses: 6\n    \
+//!          car: \"Ford Anglia\"\n\
+//!      \n\
+//!      remedy: send the Obliviators"
+//! );
+//! ```
+//!
+//! A [`Howler`] is a panic hook that screams every panic message, in red and optionally with a backtrace.
+//! [Installing](Howler::install) it returns a [`Howling`] guard, which puts the previous hook back when it is dropped.
+//! ```
+//! use code_spells::howler;
+//!
+//! let howling = howler!(format: "{thread} disgraced the family at {location}: {MESSAGE}", backtrace: false);
+//! let _ = std::panic::catch_unwind(|| panic!("you stole the car"));
+//! drop(howling);
+//! ```
+
+use core::fmt;
+use std::any::Any;
+use std::backtrace::{Backtrace, BacktraceStatus};
+use std::panic;
+use std::sync::{Mutex, MutexGuard, Once, PoisonError};
+use std::thread;
+
+use crate::obliviator_squad::panic_message;
+
+/// A panic payload with a summary, context fields and a remedy.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Letter {
+    summary: String,
+    context: Vec<(String, String)>,
+    remedy: Option<String>,
+}
+
+impl Letter {
+    /// Starts a letter with a summary of what went wrong.
+    pub fn new(summary: impl Into<String>) -> Self {
+        Self {
+            summary: summary.into(),
+            context: Vec::new(),
+            remedy: None,
+        }
+    }
+
+    /// Adds a value that was involved to the context of the letter. The value is written with its [`Debug`](fmt::Debug) implementation.
+    #[must_use]
+    pub fn context(mut self, name: impl Into<String>, value: impl fmt::Debug) -> Self {
+        self.context.push((name.into(), format!("{:?}", value)));
+        self
+    }
+
+    /// Adds a hint at how to fix the problem to the letter.
+    #[must_use]
+    pub fn remedy(mut self, remedy: impl Into<String>) -> Self {
+        self.remedy = Some(remedy.into());
+        self
+    }
+
+    /// Returns the summary of the letter.
+    pub fn summary(&self) -> &str {
+        &self.summary
+    }
+
+    /// Returns the names and values of the context fields, in the order they were added.
+    pub fn fields(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.context
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+    }
+
+    /// Returns the remedy of the letter, if it has one.
+    pub fn get_remedy(&self) -> Option<&str> {
+        self.remedy.as_deref()
+    }
+
+    /// Panics with the letter as the payload, after [installing](install_hook) the panic hook that reads it out.
+    #[track_caller]
+    pub fn send(self) -> ! {
+        install_hook();
+        panic::panic_any(self)
+    }
+}
+
+impl fmt::Display for Letter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.summary)?;
+        if !self.context.is_empty() {
+            f.write_str("\n\ncontext:")?;
+            for (name, value) in &self.context {
+                write!(f, "\n    {}: {}", name, value)?;
+            }
+        }
+        if let Some(remedy) = &self.remedy {
+            write!(f, "\n\nremedy: {}", remedy)?;
+        }
+        Ok(())
+    }
+}
+
+/// Writes out a letter the way the panic hook does, with the thread that sent it and where it was sent from.
+fn read_out(letter: &Letter, thread: Option<&str>, location: Option<String>) -> String {
+    let mut out = format!(
+        "thread '{}' was sent a letter",
+        thread.unwrap_or("<unnamed>")
+    );
+    if let Some(location) = location {
+        out.push_str(" from ");
+        out.push_str(&location);
+    }
+    out.push_str(":\n");
+    for line in letter.to_string().lines() {
+        if line.is_empty() {
+            out.push_str("  |\n");
+        } else {
+            out.push_str("  | ");
+            out.push_str(line);
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// Installs a panic hook that reads out panics with a [`Letter`] payload, and passes every other panic
+/// on to the hook that was installed before. Only installs the hook the first time it is called.
+pub fn install_hook() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            match info.payload().downcast_ref::<Letter>() {
+                Some(letter) => {
+                    let location = info.location().map(ToString::to_string);
+                    eprint!("{}", read_out(letter, thread::current().name(), location));
+                    let backtrace = Backtrace::capture();
+                    if backtrace.status() == BacktraceStatus::Captured {
+                        eprintln!("stack backtrace:\n{}", backtrace);
+                    }
+                }
+                None => previous(info),
+            }
+        }));
+    });
+}
+
+/// A panic hook, as returned by [`panic::take_hook`].
+// `PanicInfo` is a deprecated alias of `PanicHookInfo` since Rust 1.82, which is newer than the MSRV.
+#[allow(deprecated)]
+type Hook = Box<dyn Fn(&panic::PanicInfo<'_>) + Sync + Send + 'static>;
+
+/// The hooks that were replaced by howlers, with the most recently replaced one last.
+static REPLACED: Mutex<Vec<Hook>> = Mutex::new(Vec::new());
+
+fn replaced() -> MutexGuard<'static, Vec<Hook>> {
+    REPLACED.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// A panic hook that screams the message of every panic.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Howler {
+    format: String,
+    red: bool,
+    backtrace: bool,
+}
+
+impl Howler {
+    /// The default format of a howl.
+    pub const DEFAULT_FORMAT: &'static str = "thread '{thread}' HOWLS at {location}:\n{MESSAGE}";
+
+    /// Creates a howler with the [default format](Howler::DEFAULT_FORMAT) that screams in red, without a backtrace.
+    pub fn new() -> Self {
+        Self {
+            format: String::from(Self::DEFAULT_FORMAT),
+            red: true,
+            backtrace: false,
+        }
+    }
+
+    /// Sets the format of a howl. `{thread}`, `{location}` and `{message}` are replaced with the name of the thread
+    /// that panicked, where it panicked, and the panic message, and `{MESSAGE}` with the panic message in capitals.
+    /// A [`Letter`] is howled out in full.
+    #[must_use]
+    pub fn format(mut self, format: impl Into<String>) -> Self {
+        self.format = format.into();
+        self
+    }
+
+    /// Sets whether the howl is colored red with ANSI escape codes.
+    #[must_use]
+    pub const fn red(mut self, red: bool) -> Self {
+        self.red = red;
+        self
+    }
+
+    /// Sets whether a backtrace is captured and howled after the message, whatever `RUST_BACKTRACE` is set to.
+    #[must_use]
+    pub const fn backtrace(mut self, backtrace: bool) -> Self {
+        self.backtrace = backtrace;
+        self
+    }
+
+    /// Installs the howler as the panic hook, and returns a guard that puts the previous hook back when it is dropped.
+    pub fn install(self) -> Howling {
+        let mut replaced = replaced();
+        let depth = replaced.len();
+        replaced.push(panic::take_hook());
+        panic::set_hook(Box::new(move |info| {
+            let location = info.location().map(ToString::to_string);
+            eprintln!(
+                "{}",
+                self.howl(thread::current().name(), location, info.payload())
+            );
+        }));
+        Howling { depth }
+    }
+
+    /// Writes out the howl for a panic.
+    fn howl(
+        &self,
+        thread: Option<&str>,
+        location: Option<String>,
+        payload: &(dyn Any + Send),
+    ) -> String {
+        let message = match payload.downcast_ref::<Letter>() {
+            Some(letter) => letter.to_string(),
+            None => panic_message(payload),
+        };
+        let mut howl = self
+            .format
+            .replace("{thread}", thread.unwrap_or("<unnamed>"))
+            .replace("{location}", location.as_deref().unwrap_or("<unknown>"))
+            .replace("{MESSAGE}", &message.to_uppercase())
+            .replace("{message}", &message);
+        if self.backtrace {
+            howl.push_str(&format!(
+                "\nstack backtrace:\n{}",
+                Backtrace::force_capture()
+            ));
+        }
+        if self.red {
+            howl = format!("\x1b[1;31m{}\x1b[0m", howl);
+        }
+        howl
+    }
+}
+
+impl Default for Howler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A guard for an installed [`Howler`], which puts the panic hook that it replaced back when it is dropped.
+#[must_use = "the howler is silenced as soon as the guard is dropped, use `keep` to leave it installed"]
+#[derive(Debug)]
+pub struct Howling {
+    // The number of replaced hooks before this howler was installed.
+    depth: usize,
+}
+
+impl Howling {
+    /// Leaves the howler installed until it is [restored](restore) away.
+    pub fn keep(self) {
+        core::mem::forget(self);
+    }
+}
+
+impl Drop for Howling {
+    fn drop(&mut self) {
+        // The panic hook can not be changed while the thread is panicking, so the howler keeps howling.
+        if thread::panicking() {
+            return;
+        }
+        let mut replaced = replaced();
+        // Howlers that were installed after this one, and not removed yet, are removed with it.
+        if replaced.len() > self.depth {
+            let hook = replaced.swap_remove(self.depth);
+            replaced.truncate(self.depth);
+            panic::set_hook(hook);
+        }
+    }
+}
+
+/// Puts back the panic hook that the most recently installed howler replaced. Returns whether there was one.
+/// # Panics
+/// Panics if called from a panicking thread.
+pub fn restore() -> bool {
+    let hook = replaced().pop();
+    hook.map(panic::set_hook).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{avada_kedavra, howler};
+
+    /// Keeps the tests that install howlers from replacing each other's hooks.
+    static HOWLING: Mutex<()> = Mutex::new(());
+
+    fn caught(spell: impl FnOnce() + panic::UnwindSafe) -> Letter {
+        *panic::catch_unwind(spell)
+            .unwrap_err()
+            .downcast::<Letter>()
+            .unwrap()
+    }
+
+    #[test]
+    fn practice_avada_kedavra_letter() {
+        let vault = 713;
+        let letter = caught(|| {
+            avada_kedavra!(letter {
+                summary: format!("vault {} was opened", vault),
+                context: { vault: vault, goblin: "Griphook" },
+                remedy: "check the Stone",
+            })
+        });
+        assert_eq!(letter.summary(), "vault 713 was opened");
+        assert_eq!(
+            letter.fields().collect::<Vec<_>>(),
+            [("vault", "713"), ("goblin", "\"Griphook\"")]
+        );
+        assert_eq!(letter.get_remedy(), Some("check the Stone"));
+
+        let short = caught(|| {
+            avada_kedavra!(letter {
+                summary: "Expelliarmus"
+            })
+        });
+        assert_eq!(short, Letter::new("Expelliarmus"));
+        assert_eq!(short.to_string(), "Expelliarmus");
+    }
+
+    #[test]
+    fn a_letter_is_read_out() {
+        let letter = Letter::new("the Whomping Willow was hit")
+            .context("car", "Ford Anglia")
+            .remedy("walk next time");
+        assert_eq!(
+            read_out(&letter, Some("main"), Some(String::from("src/car.rs:3:5"))),
+            "thread 'main' was sent a letter from src/car.rs:3:5:\n  \
+               | the Whomping Willow was hit\n  \
+               |\n  \
+               | context:\n  \
+               |     car: \"Ford Anglia\"\n  \
+               |\n  \
+               | remedy: walk next time\n"
+        );
+        assert_eq!(
+            read_out(&Letter::new("Stupefy"), None, None),
+            "thread '<unnamed>' was sent a letter:\n  | Stupefy\n"
+        );
+    }
+
+    #[test]
+    fn practice_howler() {
+        let _quiet = HOWLING.lock().unwrap_or_else(PoisonError::into_inner);
+        let before = replaced().len();
+        let howling = howler!(red: false);
+        assert_eq!(replaced().len(), before + 1);
+        let caught = panic::catch_unwind(|| avada_kedavra!("{} crashed the car", "Ron"));
+        assert!(caught.is_err());
+        drop(howling);
+        assert_eq!(replaced().len(), before);
+
+        howler!().keep();
+        assert!(howler!(restore));
+        assert_eq!(replaced().len(), before);
+    }
+
+    #[test]
+    fn a_howl_is_loud() {
+        let howler = Howler::new();
+        let location = Some(String::from("src/burrow.rs:1:1"));
+        assert_eq!(
+            howler.howl(Some("main"), location.clone(), &"you stole the car"),
+            "\x1b[1;31mthread 'main' HOWLS at src/burrow.rs:1:1:\nYOU STOLE THE CAR\x1b[0m"
+        );
+        let plain = howler.red(false).format("{thread}@{location}: {message}");
+        assert_eq!(
+            plain.howl(None, None, &String::from("Expelliarmus")),
+            "<unnamed>@<unknown>: Expelliarmus"
+        );
+        assert_eq!(
+            plain.howl(None, None, &Letter::new("a letter").remedy("reply")),
+            "<unnamed>@<unknown>: a letter\n\nremedy: reply"
+        );
+        assert_eq!(
+            plain.howl(None, None, &7),
+            "<unnamed>@<unknown>: a panic without a message"
+        );
+        let traced = Howler::new().red(false).backtrace(true).format("!");
+        assert!(traced
+            .howl(None, None, &"")
+            .starts_with("!\nstack backtrace:\n"));
+    }
+
+    #[test]
+    fn guards_restore_their_own_hook() {
+        let _quiet = HOWLING.lock().unwrap_or_else(PoisonError::into_inner);
+        let before = replaced().len();
+        let outer = howler!(format: "outer");
+        let inner = howler!(format: "inner");
+        inner.keep();
+        howler!(format: "innermost").keep();
+        assert_eq!(replaced().len(), before + 3);
+        // Dropping the outer guard also silences the howlers that were installed after it.
+        drop(outer);
+        assert_eq!(replaced().len(), before);
+    }
+
+    #[test]
+    fn other_panics_keep_their_payload() {
+        install_hook();
+        let spell = String::from("Expelliarmus");
+        let message = panic::catch_unwind(|| avada_kedavra!("plain {}", spell)).unwrap_err();
+        assert_eq!(
+            message.downcast_ref::<String>().unwrap(),
+            "plain Expelliarmus"
+        );
+    }
+}
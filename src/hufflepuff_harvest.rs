@@ -0,0 +1,179 @@
+//! Fair parallel work on the items of an iterator, cast with [`harvest!`](crate::harvest!).
+//!
+//! Hufflepuffs share the work fairly. [`harvest`] deals the items out to a number of worker threads,
+//! every worker getting its own patch, and calls a closure on each of them. A worker that finishes its patch
+//! early steals half of the remaining items of another, so items that take very different amounts of time
+//! still keep every worker busy. The results are returned in the order of the items.
+//!
+//! The patches are plain deques behind a mutex each: workers take items from the front of their own patch,
+//! and thieves take from the back, so they rarely meet on the same lock.
+//! ```
+//! use code_spells::hufflepuff_harvest::harvest;
+//!
+//! let mandrakes = vec![3, 1, 4, 1, 5, 9, 2, 6];
+//! let repotted = harvest(mandrakes, 3, |mandrake| mandrake * 10);
+//! assert_eq!(repotted, [30, 10, 40, 10, 50, 90, 20, 60]);
+//! ```
+
+use std::collections::VecDeque;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::thread;
+
+/// The items that are left for a worker, together with their positions in the harvest.
+/// Padded to its own cache lines so that neighbouring patches do not contend.
+#[repr(align(128))]
+struct Patch<T>(Mutex<VecDeque<(usize, T)>>);
+
+impl<T> Patch<T> {
+    fn lock(&self) -> MutexGuard<'_, VecDeque<(usize, T)>> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Takes the back half of the items, rounded up.
+    fn steal(&self) -> VecDeque<(usize, T)> {
+        let mut items = self.lock();
+        let keep = items.len() / 2;
+        items.split_off(keep)
+    }
+}
+
+/// Calls `work` on every item of `items` on `workers` threads, and returns the results in the order of the items.
+///
+/// Uses fewer threads if there are fewer items than workers, and no extra threads at all for a single worker.
+/// # Panics
+/// Panics if `workers` is zero, and resumes the panic of `work` if it panicked on any item.
+pub fn harvest<I, F, R>(items: I, workers: usize, work: F) -> Vec<R>
+where
+    I: IntoIterator,
+    I::Item: Send,
+    F: Fn(I::Item) -> R + Sync,
+    R: Send,
+{
+    assert!(workers > 0, "a harvest needs at least one worker");
+    let items: Vec<I::Item> = items.into_iter().collect();
+    let len = items.len();
+    let workers = workers.min(len);
+    if workers <= 1 {
+        return items.into_iter().map(work).collect();
+    }
+
+    // Deal the items out in contiguous patches of nearly the same size.
+    let mut items = items.into_iter().enumerate();
+    let patches: Vec<Patch<I::Item>> = (0..workers)
+        .map(|worker| {
+            let size = len / workers + usize::from(worker < len % workers);
+            Patch(Mutex::new(items.by_ref().take(size).collect()))
+        })
+        .collect();
+
+    let mut slots: Vec<Option<R>> = (0..len).map(|_| None).collect();
+    thread::scope(|s| {
+        let helpers: Vec<_> = (1..workers)
+            .map(|worker| {
+                let (patches, work) = (&patches, &work);
+                s.spawn(move || tend(patches, worker, work))
+            })
+            .collect();
+        let harvested =
+            core::iter::once(tend(&patches, 0, &work)).chain(helpers.into_iter().map(|helper| {
+                helper
+                    .join()
+                    .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+            }));
+        for (index, result) in harvested.flatten() {
+            slots[index] = Some(result);
+        }
+    });
+    slots
+        .into_iter()
+        .map(|slot| slot.expect("every item is harvested by some worker"))
+        .collect()
+}
+
+/// Works through the patch of `worker`, stealing from the other patches once it is empty.
+fn tend<T, R>(patches: &[Patch<T>], worker: usize, work: &impl Fn(T) -> R) -> Vec<(usize, R)> {
+    let mut harvested = Vec::new();
+    loop {
+        loop {
+            // The patch is not locked while the work is done, so that thieves can steal in the meantime.
+            let next = patches[worker].lock().pop_front();
+            match next {
+                Some((index, item)) => harvested.push((index, work(item))),
+                None => break,
+            }
+        }
+        // Items never come back once taken, so when every other patch is empty the harvest is over for this worker.
+        let loot = (1..patches.len())
+            .map(|offset| &patches[(worker + offset) % patches.len()])
+            .map(Patch::steal)
+            .find(|loot| !loot.is_empty());
+        match loot {
+            Some(loot) => patches[worker].lock().extend(loot),
+            None => return harvested,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::harvest;
+    use core::time::Duration;
+
+    #[test]
+    fn practice_harvest() {
+        let squares = harvest!(0..100_u64, workers: 4, |n| n * n);
+        assert_eq!(squares, (0..100_u64).map(|n| n * n).collect::<Vec<_>>());
+
+        let names = ["Hannah", "Ernie", "Justin", "Susan"];
+        let lengths = harvest!(names.iter(), |name: &&str| name.len());
+        assert_eq!(lengths, [6, 5, 6, 5]);
+
+        let nothing: Vec<u8> = harvest!(Vec::<u8>::new(), workers: 8, |n| n);
+        assert!(nothing.is_empty());
+    }
+
+    #[test]
+    fn uneven_work_is_shared() {
+        // All of the slow items are dealt to the first worker, the others have to steal them.
+        let gardeners = Mutex::new(Vec::new());
+        let results = harvest(0..16_u64, 4, |n| {
+            if n < 4 {
+                thread::sleep(Duration::from_millis(20));
+                gardeners.lock().unwrap().push(thread::current().id());
+            }
+            n + 1
+        });
+        assert_eq!(results, (1..=16).collect::<Vec<_>>());
+        let mut gardeners = gardeners.into_inner().unwrap();
+        gardeners.dedup();
+        assert!(gardeners.len() > 1);
+    }
+
+    #[test]
+    fn a_single_worker_harvests_alone() {
+        let main = thread::current().id();
+        let results = harvest(["pumpkin"; 3], 1, |p| {
+            assert_eq!(thread::current().id(), main);
+            p.len()
+        });
+        assert_eq!(results, [7; 3]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Venomous Tentacula")]
+    fn a_panic_in_the_harvest() {
+        let _ = harvest(0..10, 3, |n| {
+            if n == 7 {
+                panic!("bitten by a Venomous Tentacula");
+            }
+            n
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one worker")]
+    fn a_harvest_without_workers() {
+        let _ = harvest(0..10, 0, |n: i32| n);
+    }
+}
@@ -0,0 +1,147 @@
+//! Rate limiting, cast with [`impedimenta!`](crate::impedimenta!).
+//!
+//! The impediment jinx slows down whatever it hits. An [`Impedimenta`] lets at most a given number of passes
+//! through per interval, and spreads them out evenly when it is used continuously.
+//! It behaves like a token bucket that holds one interval worth of passes and refills continuously,
+//! implemented as the generic cell rate algorithm so that it only has to keep track of a single instant.
+//! ```
+//! use code_spells::impedimenta::Impedimenta;
+//! use std::time::{Duration, Instant};
+//!
+//! let jinx = Impedimenta::new(10, Duration::from_millis(100));
+//! let passes = (0..15).filter(|_| jinx.try_pass()).count();
+//! assert_eq!(passes, 10);
+//!
+//! let start = Instant::now();
+//! jinx.block_until_allowed();
+//! assert!(start.elapsed() >= Duration::from_millis(5));
+//! ```
+
+use core::fmt;
+use core::time::Duration;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::Instant;
+
+use crate::time_room;
+
+/// A rate limiter that allows a number of passes per interval.
+pub struct Impedimenta {
+    // The time between two passes when the limiter is used continuously.
+    emission: Duration,
+    // How far ahead of the present the next pass may be booked while still letting a pass through.
+    tolerance: Duration,
+    // The instant the next pass is booked for.
+    next: Mutex<Instant>,
+}
+
+impl Impedimenta {
+    /// Creates a rate limiter that lets `passes` passes through per `interval`, all of them at once if they arrive together.
+    /// # Panics
+    /// Panics if `passes` is zero.
+    pub fn new(passes: u32, interval: Duration) -> Self {
+        assert!(
+            passes > 0,
+            "impedimenta needs to allow at least one pass per interval"
+        );
+        let emission = interval / passes;
+        Self {
+            emission,
+            tolerance: interval.saturating_sub(emission),
+            next: Mutex::new(time_room::now()),
+        }
+    }
+
+    /// Lets a pass through if the rate allows it right now. Returns whether it did.
+    pub fn try_pass(&self) -> bool {
+        let now = time_room::now();
+        let mut next = self.lock();
+        let booked = (*next).max(now);
+        if booked - now > self.tolerance {
+            return false;
+        }
+        *next = booked + self.emission;
+        true
+    }
+
+    /// Waits until the rate allows a pass, and lets it through.
+    ///
+    /// The pass is booked before waiting, so threads that are blocked at the same time are let through
+    /// in the order they arrived.
+    pub fn block_until_allowed(&self) {
+        let now = time_room::now();
+        let wait = {
+            let mut next = self.lock();
+            let booked = (*next).max(now);
+            *next = booked + self.emission;
+            (booked - now).saturating_sub(self.tolerance)
+        };
+        if !wait.is_zero() {
+            time_room::sleep(wait);
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Instant> {
+        self.next.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl fmt::Debug for Impedimenta {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Impedimenta")
+            .field("emission", &self.emission)
+            .field("tolerance", &self.tolerance)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::impedimenta;
+    use std::thread;
+
+    #[test]
+    fn practice_impedimenta() {
+        let jinx = Impedimenta::new(3, Duration::from_millis(300));
+        let cast = |jinx: &Impedimenta| impedimenta!(jinx, { "Harry" });
+        assert_eq!(cast(&jinx), Some("Harry"));
+        assert_eq!(cast(&jinx), Some("Harry"));
+        assert_eq!(cast(&jinx), Some("Harry"));
+        assert_eq!(cast(&jinx), None);
+        thread::sleep(Duration::from_millis(100));
+        assert_eq!(cast(&jinx), Some("Harry"));
+        assert_eq!(cast(&jinx), None);
+
+        let spells = (0..5)
+            .filter_map(|_| impedimenta!(2, Duration::from_secs(60), { "Stupefy" }))
+            .count();
+        assert_eq!(spells, 2);
+    }
+
+    #[test]
+    fn an_impeded_crowd_is_spread_out() {
+        let jinx = Impedimenta::new(5, Duration::from_millis(50));
+        let start = Instant::now();
+        thread::scope(|s| {
+            for _ in 0..3 {
+                s.spawn(|| {
+                    for _ in 0..5 {
+                        jinx.block_until_allowed();
+                    }
+                });
+            }
+        });
+        // The first 5 passes go through at once, the other 10 at one every 10 ms.
+        assert!(start.elapsed() >= Duration::from_millis(100));
+        assert_eq!(
+            format!("{:?}", jinx),
+            "Impedimenta { emission: 10ms, tolerance: 40ms, .. }"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one pass")]
+    fn an_impedimenta_that_allows_nothing() {
+        let _ = Impedimenta::new(0, Duration::from_secs(1));
+    }
+}
@@ -0,0 +1,144 @@
+//! Raw-pointer helpers that keep a record of every use, cast with [`knockturn_alley!`](crate::knockturn_alley!).
+//!
+//! Respectable wizards do not shop in Knockturn Alley, and the ones who do had better not mind being watched.
+//! This module collects the spells of this crate that turn raw pointers back into owned values, slices and
+//! [`NonNull`] pointers. Every one of them records the spell and the place it was cast from in a
+//! [`Ledger`] that can be read with [`ledger`], so that the unsafe surface of a program can be audited
+//! after the fact. [`aparecium!`](crate::aparecium!) also records its casts here when this module is enabled.
+//!
+//! The spells are as unsafe as the functions of std that they call, and the ledger does not make them any safer.
+//! Needs the `dark-arts` feature.
+//! ```
+//! # use code_spells::knockturn_alley;
+//! # code_spells::i_solemnly_swear!();
+//! use code_spells::knockturn_alley::ledger;
+//!
+//! let hand_of_glory = vec![1, 2, 3, 4];
+//! let fingers = unsafe { knockturn_alley!(borrowck_bypass hand_of_glory.as_ptr(), 2) };
+//! assert_eq!(fingers, [1, 2]);
+//!
+//! let cabinet = Box::into_raw(Box::new("a vanishing cabinet"));
+//! let cabinet = unsafe { knockturn_alley!(aparecium cabinet) };
+//! assert_eq!(*cabinet, "a vanishing cabinet");
+//!
+//! let ledger = ledger();
+//! assert_eq!(ledger.verify(), Ok(()));
+//! assert!(ledger.entries().iter().any(|entry| entry.event().starts_with("borrowck_bypass at ")));
+//! ```
+
+use core::panic::Location;
+use core::ptr::NonNull;
+use core::slice;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use crate::gringotts_ledger::Ledger;
+
+/// Every dark spell that has been cast in this process.
+static LEDGER: Mutex<Ledger> = Mutex::new(Ledger::new());
+
+/// Records that `spell` was cast from the location of the caller, as "`spell` at file:line:column".
+#[track_caller]
+pub fn record(spell: &str) {
+    let location = Location::caller();
+    lock().record(format!("{} at {}", spell, location));
+}
+
+/// Returns a copy of the ledger of every recorded spell.
+pub fn ledger() -> Ledger {
+    lock().clone()
+}
+
+/// Alias for [`Box::from_raw`] that records the cast as `aparecium`.
+/// # Safety
+/// See [`Box::from_raw`].
+#[track_caller]
+pub unsafe fn aparecium<T: ?Sized>(invisible: *mut T) -> Box<T> {
+    record("aparecium");
+    // SAFETY: the caller upholds the contract of `Box::from_raw`.
+    unsafe { Box::from_raw(invisible) }
+}
+
+/// Alias for [`slice::from_raw_parts`] that records the cast as `borrowck_bypass`.
+/// # Safety
+/// See [`slice::from_raw_parts`]. The lifetime of the slice is not tied to anything, so nothing stops it
+/// from outliving the data it points to.
+#[track_caller]
+pub unsafe fn borrowck_bypass<'a, T>(data: *const T, len: usize) -> &'a [T] {
+    record("borrowck_bypass");
+    // SAFETY: the caller upholds the contract of `slice::from_raw_parts`.
+    unsafe { slice::from_raw_parts(data, len) }
+}
+
+/// Alias for [`slice::from_raw_parts_mut`] that records the cast as `borrowck_bypass mut`.
+/// # Safety
+/// See [`slice::from_raw_parts_mut`]. The lifetime of the slice is not tied to anything, so nothing stops it
+/// from outliving the data it points to, or from aliasing another reference to the data.
+#[track_caller]
+pub unsafe fn borrowck_bypass_mut<'a, T>(data: *mut T, len: usize) -> &'a mut [T] {
+    record("borrowck_bypass mut");
+    // SAFETY: the caller upholds the contract of `slice::from_raw_parts_mut`.
+    unsafe { slice::from_raw_parts_mut(data, len) }
+}
+
+/// Alias for [`NonNull::new_unchecked`] that records the cast as `non_null`.
+/// # Safety
+/// `pointer` must not be null.
+#[track_caller]
+pub unsafe fn non_null<T: ?Sized>(pointer: *mut T) -> NonNull<T> {
+    record("non_null");
+    // SAFETY: the caller guarantees that `pointer` is not null.
+    unsafe { NonNull::new_unchecked(pointer) }
+}
+
+fn lock() -> MutexGuard<'static, Ledger> {
+    LEDGER.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{aparecium, knockturn_alley};
+
+    /// Returns whether the ledger has recorded `spell` at the line `line` of this file.
+    fn recorded(spell: &str, line: u32) -> bool {
+        let event = format!("{} at {}:{}:", spell, file!(), line);
+        ledger()
+            .entries()
+            .iter()
+            .any(|entry| entry.event().starts_with(&event))
+    }
+
+    #[test]
+    fn practice_knockturn_alley() {
+        let _oath = crate::marauders_oath::OATH_TAKERS
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        crate::i_solemnly_swear!();
+
+        let mut opals = [1_u8, 2, 3];
+        let cursed = unsafe { knockturn_alley!(borrowck_bypass mut opals.as_mut_ptr(), 3) };
+        let line = line!() - 1;
+        cursed[0] = 0;
+        assert_eq!(opals, [0, 2, 3]);
+        assert!(recorded("borrowck_bypass mut", line));
+
+        let necklace = unsafe { knockturn_alley!(non_null opals.as_mut_ptr()) };
+        let line = line!() - 1;
+        assert_eq!(necklace.as_ptr(), opals.as_mut_ptr());
+        assert!(recorded("non_null", line));
+
+        let skull = Box::into_raw(Box::new([7_u8; 2]) as Box<[u8]>);
+        assert_eq!(*unsafe { aparecium!(skull) }, [7, 7]);
+        assert!(recorded("aparecium", line!() - 1));
+        assert_eq!(ledger().verify(), Ok(()));
+    }
+
+    #[test]
+    fn the_functions_record_their_callers() {
+        let empty: &[u32] = unsafe { borrowck_bypass(NonNull::dangling().as_ptr(), 0) };
+        assert!(empty.is_empty());
+        assert!(recorded("borrowck_bypass", line!() - 2));
+        record("a hand of glory");
+        assert!(recorded("a hand of glory", line!() - 1));
+    }
+}
@@ -14,8 +14,128 @@
 //! ```
 //! Also aliases `unsafe` to the macro [`unforgivable!`](unforgivable),
 //! because what could be more unforgivable than undefined behaviour?  
+//!
+//! A miscast spell fails to compile, with the usual error of the compiler about the tokens that no rule expected.
+//! Enable the `kwikspell` feature to instead get a short lesson with the usage and an example of the spell in the error.
+//! ```compile_fail
+//! # use code_spells::obliviate;
+//! obliviate!(); // error: unexpected end of macro invocation
+//! ```
+//!
+//! Enable the `princes-notes` feature to have [`geminio!`](geminio), [`expecto_patronum!`](expecto_patronum)
+//! and [`reparo!`](reparo) read the margin notes of a talented previous owner of this book.
+//! The notes are warnings about casts that have a better alternative, like cloning a literal
+//! or unwrapping a constant `Some`.
+//!
+//! Enable the `marauders-oath` feature to make the dark spells [`imperio!`](imperio) and [`aparecium!`](aparecium)
+//! panic in debug builds unless [`i_solemnly_swear!`](i_solemnly_swear) has been cast first, see [`marauders_oath`].
+//!
+//! Enable the `unbreakable-vow` feature to make [`unforgivable!`](unforgivable) warn when it is cast without
+//! a reason, and the `wizengamot` feature to record the reasons that are given so that they can be listed with
+//! [`wizengamot_report!`](https://docs.rs/code-spells/latest/code_spells/macro.wizengamot_report.html).
+//!
+//! Enable the `dark-arts` feature to open [`knockturn_alley`](https://docs.rs/code-spells/latest/code_spells/knockturn_alley/),
+//! where the raw-pointer spells record every cast in a ledger.
+//!
+//! Enable the `mutation` feature to turn the mutation points of [`confunded_if_mutating!`](confunded_if_mutating)
+//! into hooks that a test can switch to their mutants, see
+//! [`owl_exam_results`](https://docs.rs/code-spells/latest/code_spells/owl_exam_results/).
+//!
+//! Enable the `mmap` feature to map files into memory with [`engorgement_charm!`](https://docs.rs/code-spells/latest/code_spells/macro.engorgement_charm.html),
+//! see [`engorgement_charm`](https://docs.rs/code-spells/latest/code_spells/engorgement_charm/).
+//!
+//! Enable the `rayon` feature to send a flock of birds at your data on the thread pool of
+//! [`rayon`](https://docs.rs/rayon) with [`oppugno!`](https://docs.rs/code-spells/latest/code_spells/macro.oppugno.html).
+#![cfg_attr(feature = "princes-notes", doc = "```compile_fail")]
+#![cfg_attr(not(feature = "princes-notes"), doc = "```ignore")]
+//! #![deny(deprecated)]
+//! # use code_spells::geminio;
+//! let seven = geminio!(&7); // error: this clones a literal, which is `Copy` ...
+//! ```
+
+pub mod accio_argv;
+pub mod arresto_momentum;
+pub mod boggart;
+pub mod bogies;
+pub mod broomsticks;
+pub mod common_room;
+pub mod dervish_and_banges;
+pub mod dumbledores_army;
+#[cfg(feature = "allocator-api")]
+pub mod elder_wand;
+#[cfg(feature = "mmap")]
+pub mod engorgement_charm;
+pub mod examples;
+#[cfg(feature = "rand")]
+pub mod felix_felicis;
+pub mod fidelius;
+#[cfg(feature = "firebolt")]
+pub mod firebolt;
+pub mod floo_network;
+pub mod ford_anglia;
+pub mod galleon;
+pub mod ghoul;
+pub mod glacius;
+pub mod gringotts;
+pub mod gringotts_ledger;
+pub mod hexes;
+pub mod hippogriff;
+#[cfg(feature = "rand")]
+pub mod horcrux;
+pub mod howler_mail;
+pub mod hufflepuff_harvest;
+pub mod impedimenta;
+#[cfg(feature = "dark-arts")]
+pub mod knockturn_alley;
+pub mod marauders_map;
+pub mod marauders_oath;
+pub mod obliviator_squad;
+#[cfg(feature = "mutation")]
+pub mod owl_exam_results;
+pub mod owl_post;
+pub mod patronus;
+pub mod pensieve;
+pub mod pepper_imps;
+pub mod piertotum_locomotor;
+pub mod pigwidgeon;
+pub mod portkey;
+pub mod portrait;
+pub mod prophecy_orb;
+pub mod quibbler;
+pub mod ravenclaw_riddle;
+pub mod restricted_section;
+pub mod room_of_hidden_things;
+pub mod room_of_requirement;
+pub mod shield_hat;
+pub mod slytherin_locket;
+pub mod st_mungos;
+pub mod summons;
+pub mod taboo;
+pub mod time_room;
+pub mod time_turner;
+pub mod veil_of_death;
+pub mod weasley_twins;
+
+#[cfg(feature = "princes-notes")]
+#[doc(hidden)]
+pub use code_spells_macros::half_blood_prince;
+#[cfg(feature = "wizengamot")]
+#[doc(hidden)]
+pub use code_spells_macros::wizengamot;
+#[cfg(feature = "wizengamot")]
+pub use code_spells_macros::wizengamot_report;
+#[cfg(feature = "macros")]
+pub use code_spells_macros::{
+    answers_summons, muffliato, parseltongue, remembrall, remembrall_report, restricted_section,
+    riddikulus,
+};
 
-/// Alias for [`std::thread::sleep`](std::thread::sleep).
+// Lets the attribute spells refer to this crate by name from inside its own tests.
+#[cfg(all(test, feature = "macros"))]
+extern crate self as code_spells;
+
+/// Alias for [`std::thread::sleep`](std::thread::sleep), which only moves the virtual clock
+/// inside a [`time_room!`](crate::time_room!).
 /// # Example
 /// ```
 /// # use code_spells::petrificus_totalus;
@@ -24,24 +144,304 @@
 #[macro_export]
 macro_rules! petrificus_totalus {
     ($duration:expr) => {
-        ::std::thread::sleep($duration)
+        $crate::time_room::sleep($duration)
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "petrificus_totalus",
+            "petrificus_totalus!(duration)",
+            "petrificus_totalus!(std::time::Duration::from_secs(1))",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Alias for [`std::thread::yield_now`](std::thread::yield_now).
+/// Clears the airway of the scheduler, so that other threads can breathe.
+/// If given a count it yields that many times.
+/// # Examples
+/// ```
+/// # use code_spells::anapneo;
+/// anapneo!();
+/// anapneo!(3);
+/// ```
+#[macro_export]
+macro_rules! anapneo {
+    () => {
+        ::std::thread::yield_now()
+    };
+    ($count:expr) => {
+        for _ in 0..$count {
+            ::std::thread::yield_now();
+        }
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! { "anapneo", "anapneo!() or anapneo!(count)", "anapneo!(3)", [$($miscast)*] }
+    };
+}
+
+/// Alias for [`std::thread::park`](std::thread::park), or [`std::thread::park_timeout`](std::thread::park_timeout)
+/// if given a duration. The current thread is stunned until someone casts [`rennervate!`](rennervate) on it.
+/// Like parking, the thread may also wake up spuriously, so the spell is usually cast in a loop.
+/// # Example
+/// ```
+/// # use code_spells::{rennervate, stupefy};
+/// use std::sync::atomic::{AtomicBool, Ordering};
+/// use std::sync::Arc;
+/// use std::time::Duration;
+///
+/// let revived = Arc::new(AtomicBool::new(false));
+/// let harry = std::thread::spawn({
+///     let revived = Arc::clone(&revived);
+///     move || {
+///         while !revived.load(Ordering::Acquire) {
+///             stupefy!();
+///         }
+///     }
+/// });
+/// stupefy!(Duration::from_millis(10));
+/// revived.store(true, Ordering::Release);
+/// rennervate!(harry.thread());
+/// harry.join().unwrap();
+/// ```
+#[macro_export]
+macro_rules! stupefy {
+    () => {
+        ::std::thread::park()
+    };
+    ($duration:expr) => {
+        ::std::thread::park_timeout($duration)
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "stupefy",
+            "stupefy!() or stupefy!(timeout)",
+            "stupefy!(std::time::Duration::from_millis(10))",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Alias for [`Thread::unpark`](std::thread::Thread::unpark).
+/// Revives a thread that was stunned with [`stupefy!`](stupefy).
+/// # Example
+/// ```
+/// # use code_spells::{rennervate, stupefy};
+/// let me = std::thread::current();
+/// rennervate!(me);
+/// // The thread was revived in advance, so this returns immediately.
+/// stupefy!();
+/// ```
+#[macro_export]
+macro_rules! rennervate {
+    ($thread:expr) => {
+        $thread.unpark()
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "rennervate",
+            "rennervate!(thread)",
+            "rennervate!(handle.thread())",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Alias for [`std::thread::spawn`](std::thread::spawn) with a `move` closure around the given block.
+/// Returns the [`JoinHandle`](std::thread::JoinHandle) of the new thread.
+/// If given a name before the block the thread is spawned with [`thread::Builder`](std::thread::Builder)
+/// and gets that name.
+/// # Examples
+/// ```
+/// # use code_spells::apparate;
+/// let destination = String::from("Diagon Alley");
+/// let handle = apparate!({ destination.len() });
+/// assert_eq!(handle.join().unwrap(), 12);
+///
+/// let seeker = apparate!("seeker", { std::thread::current().name().map(String::from) });
+/// assert_eq!(seeker.join().unwrap().as_deref(), Some("seeker"));
+/// ```
+/// # Panics
+/// Panics if the operating system fails to create the thread, just like [`std::thread::spawn`](std::thread::spawn).
+#[macro_export]
+macro_rules! apparate {
+    ($body:block) => {
+        ::std::thread::spawn(move || $body)
+    };
+    ($name:expr, $body:block) => {
+        ::std::thread::Builder::new()
+            .name(::core::convert::Into::into($name))
+            .spawn(move || $body)
+            .expect("apparate! failed to spawn a thread")
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "apparate",
+            "apparate!({ work }) or apparate!(name, { work })",
+            "apparate!(\"seeker\", { catch_the_snitch() })",
+            [$($miscast)*]
+        }
     };
 }
 
 /// Alias for [`panic!`].
+///
+/// The `letter` arm panics with a [`Letter`](howler_mail::Letter) instead of a one-line message:
+/// a summary, the values that were involved and a remedy, which the panic hook of
+/// [`howler_mail`] reads out in full. The `context` and `remedy` sections are optional.
 /// # Example
 /// ```no_run
 /// # use code_spells::avada_kedavra;
 /// avada_kedavra!("Lily Potter");
 /// let lily_potter = "continue"; // This code will never execute, as the program is dead!
 /// ```
+/// ```no_run
+/// # use code_spells::avada_kedavra;
+/// let horcruxes = 7;
+/// avada_kedavra!(letter {
+///     summary: "the killing curse rebounded",
+///     context: { target: "Harry Potter", horcruxes: horcruxes },
+///     remedy: "do not curse a child protected by love",
+/// });
+/// ```
 #[macro_export]
 macro_rules! avada_kedavra {
+    (letter {
+        summary: $summary:expr
+        $(, context: { $($name:ident: $value:expr),* $(,)? })?
+        $(, remedy: $remedy:expr)?
+        $(,)?
+    }) => {
+        $crate::howler_mail::Letter::new($summary)
+            $($(.context(::core::stringify!($name), $value))*)?
+            $(.remedy($remedy))?
+            .send()
+    };
     ($($arg:tt)*) => {
         panic!($($arg)*)
     };
 }
 
+/// Alias for [`std::process::abort`](std::process::abort).
+/// The blasting curse leaves nothing to unwind: no destructors run and no panic hook is called.
+/// # Example
+/// ```no_run
+/// # use code_spells::confringo;
+/// let horcrux = String::from("Hufflepuff's Cup");
+/// confringo!();
+/// drop(horcrux); // This code will never execute, and the cup is never dropped!
+/// ```
+#[macro_export]
+macro_rules! confringo {
+    () => {
+        ::std::process::abort()
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! { "confringo", "confringo!()", "confringo!()", [$($miscast)*] }
+    };
+}
+
+/// Alias for [`std::process::exit`](std::process::exit), which ends the process with the given exit code
+/// without unwinding the stack.
+/// # Example
+/// ```no_run
+/// # use code_spells::finite;
+/// let spell_is_over = true;
+/// if spell_is_over {
+///     finite!(0);
+/// }
+/// ```
+#[macro_export]
+macro_rules! finite {
+    ($code:expr) => {
+        ::std::process::exit($code)
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! { "finite", "finite!(exit code)", "finite!(0)", [$($miscast)*] }
+    };
+}
+
+/// Alias for [`std::fs::remove_file`](std::fs::remove_file), or for
+/// [`std::fs::remove_dir_all`](std::fs::remove_dir_all) if the path is prefixed with `dir`.
+/// Returns the [`io::Result`](std::io::Result) of the burning.
+/// # Example
+/// ```
+/// # use code_spells::incendio;
+/// let hut = std::env::temp_dir().join(format!("hagrids-hut-{}", std::process::id()));
+/// std::fs::create_dir_all(hut.join("norbert"))?;
+/// std::fs::write(hut.join("dragon-egg.txt"), "do not tell Dumbledore")?;
+///
+/// incendio!(hut.join("dragon-egg.txt"))?;
+/// incendio!(dir &hut)?;
+/// assert!(!hut.exists());
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[macro_export]
+macro_rules! incendio {
+    (dir $path:expr) => {
+        ::std::fs::remove_dir_all($path)
+    };
+    ($path:expr) => {
+        ::std::fs::remove_file($path)
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "incendio",
+            "incendio!(path) or incendio!(dir path)",
+            "incendio!(dir \"hagrids-hut\")?",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Removes elements from a collection. Alias for [`retain`](Vec::retain) if given a closure that returns
+/// whether an element may stay, and for the `remove` method of the collection otherwise,
+/// like [`Vec::remove`] with an index or [`HashMap::remove`](std::collections::HashMap::remove) with a key.
+/// Returns whatever the aliased method returns.
+/// # Examples
+/// ```
+/// # use code_spells::deletrius;
+/// let mut wands = vec!["holly", "yew", "elder", "vine"];
+/// assert_eq!(deletrius!(wands, 2), "elder");
+/// deletrius!(wands, |wand| wand.len() > 3);
+/// assert_eq!(wands, ["holly", "vine"]);
+/// ```
+/// ```
+/// # use code_spells::deletrius;
+/// use std::collections::HashMap;
+///
+/// let mut prior_incantato = HashMap::from([("Avada Kedavra", 2), ("Expelliarmus", 40)]);
+/// assert_eq!(deletrius!(prior_incantato, &"Avada Kedavra"), Some(2));
+/// deletrius!(prior_incantato, |_, casts| *casts < 10);
+/// assert!(prior_incantato.is_empty());
+/// ```
+#[macro_export]
+macro_rules! deletrius {
+    ($collection:expr, move |$key:pat_param, $value:pat_param| $keep:expr) => {
+        $collection.retain(move |$key, $value| $keep)
+    };
+    ($collection:expr, |$key:pat_param, $value:pat_param| $keep:expr) => {
+        $collection.retain(|$key, $value| $keep)
+    };
+    ($collection:expr, move |$element:pat_param| $keep:expr) => {
+        $collection.retain(move |$element| $keep)
+    };
+    ($collection:expr, |$element:pat_param| $keep:expr) => {
+        $collection.retain(|$element| $keep)
+    };
+    ($collection:expr, $target:expr) => {
+        $collection.remove($target)
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "deletrius",
+            "deletrius!(collection, index or key) or deletrius!(collection, |element| keep)",
+            "deletrius!(wands, |wand| !wand.is_broken())",
+            [$($miscast)*]
+        }
+    };
+}
+
 /// Alias for [`Drop::drop`](core::mem::drop).
 /// # Examples
 /// Drop the return value of an expression:
@@ -62,12 +462,22 @@ macro_rules! obliviate {
     ($memory:expr) => {
         ::core::mem::drop($memory)
     };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! { "obliviate", "obliviate!(value)", "obliviate!(vec![0; 5])", [$($miscast)*] }
+    };
 }
 
 /// Constructs the given type using either the [`Default::default`](core::default::Default::default)
 /// or `new(<optional args>)` functions.
 /// Calling it with `erecto!(type)` results in the former, while
 /// `erecto!(type: <optional args>)` results in the latter.
+/// Types without a `new` function can be constructed from their fields with `erecto!(Type { field: value })`.
+/// This is a struct expression, so fields can be given in shorthand and the rest taken from another value
+/// with `..other`.
+///
+/// The type can be any type, with generic arguments, lifetimes and a module path, written with or without a turbofish.
+/// In the struct expression form the generic arguments can be left out to have them inferred, or be given
+/// with a turbofish if each of them is a single token, like `u8`, `'a`, `3` or `{ N + 1 }`.
 /// # Examples
 /// ```
 /// # use code_spells::erecto;
@@ -87,6 +497,21 @@ macro_rules! obliviate {
 /// assert_eq!(erecto!(String:), String::new());
 /// assert_eq!(erecto!(Thing), Thing::default());
 /// assert_eq!(erecto!(Thing: 5), Thing::new(5));
+///
+/// #[derive(Debug, Default, PartialEq)]
+/// struct Tent {
+///     rooms: u8,
+///     fireplace: bool,
+/// }
+///
+/// let rooms = 3;
+/// assert_eq!(erecto!(Tent { rooms, fireplace: true }), Tent { rooms: 3, fireplace: true });
+/// assert_eq!(erecto!(Tent { fireplace: true, ..Default::default() }).rooms, 0);
+///
+/// let owls = erecto!(std::collections::HashMap<&str, u8>:);
+/// let cauldrons = erecto!(Vec::<u8>:);
+/// let shelf = erecto!(std::ops::Range::<u8> { start: 1, end: 4 });
+/// assert_eq!((owls.len(), cauldrons.len(), shelf.len()), (0, 0, 3));
 /// ```
 #[macro_export]
 macro_rules! erecto {
@@ -96,10 +521,25 @@ macro_rules! erecto {
     ($t:ty: $($arg:expr),*) => {
         <$t>::new( $($arg,)* )
     };
+    (:: $($segment:ident)::+ $(::<$($generic:tt),+ $(,)?>)? { $($fields:tt)* }) => {
+        :: $($segment)::+ $(::<$($generic),+>)? { $($fields)* }
+    };
+    ($($segment:ident)::+ $(::<$($generic:tt),+ $(,)?>)? { $($fields:tt)* }) => {
+        $($segment)::+ $(::<$($generic),+>)? { $($fields)* }
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "erecto",
+            "erecto!(Type), erecto!(Type: new_args...) or erecto!(Type { fields... })",
+            "erecto!(String: )",
+            [$($miscast)*]
+        }
+    };
 }
 
 /// Alias for dereferencing. This does not use the [`Deref`](core::ops::Deref) or [`DerefMut`](core::ops::DerefMut) traits,
 /// but prepends `*` to the start of the given expression.
+/// Summons through up to four layers of references if given the number of layers, so `accio!(x, 2)` is `**x`.
 /// # Example
 /// ```
 /// # use code_spells::accio;
@@ -109,12 +549,165 @@ macro_rules! erecto {
 ///
 /// let a = vec![0; 5];
 /// assert_eq!(accio!(a.get(0).unwrap()), 0);
+///
+/// let nimbus = Box::new(std::rc::Rc::new(2000));
+/// assert_eq!(accio!(&nimbus, 3), 2000);
 /// ```
 #[macro_export]
 macro_rules! accio {
+    ($x:expr, 1) => {
+        *$x
+    };
+    ($x:expr, 2) => {
+        **$x
+    };
+    ($x:expr, 3) => {
+        ***$x
+    };
+    ($x:expr, 4) => {
+        ****$x
+    };
     ($x:expr) => {
         *$x
     };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "accio",
+            "accio!(reference) or accio!(reference, layers), with 1 to 4 layers",
+            "accio!(&x) or accio!(&&x, 2)",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Summons the command line arguments of a program into a struct, see [`accio_argv`](mod@accio_argv).
+///
+/// Every field is listed with its kind, `flag`, `option` or `positional`, and options and positionals can have a default.
+/// The struct gets the methods `accio()`, which summons the arguments from [`std::env::args`],
+/// and `accio_from(args)`, which summons them from an iterator whose first item is the name of the program.
+/// Both return an [`Unsummonable`](accio_argv::Unsummonable) error if the arguments are wrong or `--help` was given,
+/// and `accio()` panics if an argument is not valid Unicode.
+///
+/// Without a struct, the spell is an expression that summons the arguments from the command line
+/// into an unnamed struct.
+/// # Examples
+/// ```
+/// # use code_spells::accio_argv;
+/// accio_argv! {
+///     #[derive(Debug)]
+///     pub struct Howler {
+///         /// Shouts even louder.
+///         flag loud: bool,
+///         option to: String = "Ron",
+///         positional message: String,
+///     }
+/// }
+///
+/// let howler = Howler::accio_from(["howler", "--loud", "Stealing the car!"])?;
+/// assert_eq!((howler.loud, &*howler.to, &*howler.message), (true, "Ron", "Stealing the car!"));
+/// # Ok::<(), code_spells::accio_argv::Unsummonable>(())
+/// ```
+/// ```no_run
+/// # use code_spells::accio_argv;
+/// use std::path::PathBuf;
+///
+/// let args = accio_argv! {
+///     flag verbose: bool,
+///     option name: String = "harry",
+///     positional input: PathBuf,
+/// }
+/// .unwrap_or_else(|error| error.exit());
+/// ```
+#[macro_export]
+macro_rules! accio_argv {
+    (@kind flag) => {
+        $crate::accio_argv::Kind::Flag
+    };
+    (@kind option) => {
+        $crate::accio_argv::Kind::Option
+    };
+    (@kind positional) => {
+        $crate::accio_argv::Kind::Positional
+    };
+    (@default) => {
+        ::core::option::Option::None
+    };
+    (@default $default:expr) => {
+        ::core::option::Option::Some(::std::string::ToString::to_string(&$default))
+    };
+    (@field $summoned:ident flag $field:ident) => {
+        $summoned.flag(::core::stringify!($field))
+    };
+    (@field $summoned:ident option $field:ident) => {
+        $summoned.value(::core::stringify!($field))?
+    };
+    (@field $summoned:ident positional $field:ident) => {
+        $summoned.value(::core::stringify!($field))?
+    };
+    (
+        $(#[$attr:meta])*
+        $vis:vis struct $name:ident {
+            $($(#[doc = $doc:expr])* $kind:ident $field:ident: $ty:ty $(= $default:expr)?),* $(,)?
+        }
+    ) => {
+        $(#[$attr])*
+        $vis struct $name {
+            $($(#[doc = $doc])* $vis $field: $ty,)*
+        }
+
+        impl $name {
+            /// Summons the arguments from the command line.
+            /// # Errors
+            /// Returns an error if the arguments are wrong, or if `--help` was given.
+            /// # Panics
+            /// Panics if an argument is not valid Unicode.
+            #[allow(dead_code)]
+            $vis fn accio() -> ::core::result::Result<Self, $crate::accio_argv::Unsummonable> {
+                Self::accio_from(::std::env::args())
+            }
+
+            /// Summons the arguments from `args`, whose first item is the name of the program.
+            /// # Errors
+            /// Returns an error if the arguments are wrong, or if `--help` was given.
+            #[allow(dead_code)]
+            $vis fn accio_from<I, S>(args: I) -> ::core::result::Result<Self, $crate::accio_argv::Unsummonable>
+            where
+                I: ::core::iter::IntoIterator<Item = S>,
+                S: ::core::convert::Into<::std::string::String>,
+            {
+                let spells = ::std::vec![$(
+                    $crate::accio_argv::Spell {
+                        kind: $crate::accio_argv!(@kind $kind),
+                        name: ::core::stringify!($field),
+                        ty: ::core::stringify!($ty),
+                        default: $crate::accio_argv!(@default $($default)?),
+                        doc: &[$($doc),*],
+                    }
+                ),*];
+                #[allow(unused_mut)]
+                let mut summoned = $crate::accio_argv::Summoned::summon(spells, args)?;
+                ::core::result::Result::Ok(Self {
+                    $($field: $crate::accio_argv!(@field summoned $kind $field),)*
+                })
+            }
+        }
+    };
+    ($($(#[doc = $doc:expr])* $kind:ident $field:ident: $ty:ty $(= $default:expr)?),+ $(,)?) => {{
+        $crate::accio_argv! {
+            struct Argv {
+                $($(#[doc = $doc])* $kind $field: $ty $(= $default)?),+
+            }
+        }
+        Argv::accio()
+    }};
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "accio_argv",
+            "accio_argv! { struct Args { flag name: bool, option name: Type = default, positional name: Type } }",
+            "accio_argv! { flag invisible: bool, positional target: String }",
+            [$($miscast)*]
+        }
+    };
 }
 
 /// Alias for [`Clone::clone`](core::clone::Clone::clone).
@@ -128,8 +721,45 @@ macro_rules! accio {
 /// ```
 #[macro_export]
 macro_rules! geminio {
-    ($object:expr) => {
+    ($object:expr) => {{
+        $crate::__princes_notes! { geminio ($object) }
         ::core::clone::Clone::clone($object)
+    }};
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! { "geminio", "geminio!(&value)", "geminio!(&wand)", [$($miscast)*] }
+    };
+}
+
+/// Alias for [`Rc::clone`](std::rc::Rc::clone) if the pointer is prefixed with `shared`,
+/// and for [`Arc::clone`](std::sync::Arc::clone) if it is prefixed with `sync`.
+/// The stronger doubling charm only ever copies the pointer, never the value behind it,
+/// which sets it apart from a [`geminio!`](geminio) that clones the whole value.
+/// # Example
+/// ```
+/// # use code_spells::{geminio_maxima, wingardium_leviosa};
+/// let cup = wingardium_leviosa!(shared "Hufflepuff's cup");
+/// let copy = geminio_maxima!(shared &cup);
+/// assert!(std::rc::Rc::ptr_eq(&cup, &copy));
+///
+/// let locket = wingardium_leviosa!(sync vec!["Slytherin's locket"]);
+/// let copy = geminio_maxima!(sync &locket);
+/// assert_eq!(std::sync::Arc::strong_count(&locket), 2);
+/// ```
+#[macro_export]
+macro_rules! geminio_maxima {
+    (shared $rc:expr) => {
+        ::std::rc::Rc::clone($rc)
+    };
+    (sync $arc:expr) => {
+        ::std::sync::Arc::clone($arc)
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "geminio_maxima",
+            "geminio_maxima!(shared &rc) or geminio_maxima!(sync &arc)",
+            "geminio_maxima!(sync &treasure)",
+            [$($miscast)*]
+        }
     };
 }
 
@@ -147,9 +777,21 @@ macro_rules! immobulus {
     ($item:expr) => {
         ::core::pin::Pin::new($item)
     };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "immobulus",
+            "immobulus!(&mut value)",
+            "immobulus!(&mut troll)",
+            [$($miscast)*]
+        }
+    };
 }
 
 /// Appends `.expect(message)` if given a message, otherwise appends `.unwrap()`.
+///
+/// A message that is a string literal followed by arguments is a format string, like in [`format!`].
+/// It is only formatted if the spell fails, and the panic reads like the one of `expect`.
+/// A message without arguments is passed to `expect` as it is, braces and all.
 /// # Examples
 /// ```
 /// # use code_spells::expecto_patronum;
@@ -161,29 +803,226 @@ macro_rules! immobulus {
 /// # use std::convert::TryFrom;
 /// expecto_patronum!(u8::try_from(-5), "Here be Dementors!");
 /// ```
+/// ```should_panic
+/// # use code_spells::expecto_patronum;
+/// # use std::convert::TryFrom;
+/// let position = 12;
+/// let err = "no happy memory";
+/// expecto_patronum!(u8::try_from(-5), "dementor at {} o'clock: {err}", position);
+/// ```
 #[macro_export]
 macro_rules! expecto_patronum {
-    ($danger:expr, $message:expr) => {
+    ($danger:expr, $format:literal, $($arguments:tt)+) => {{
+        $crate::__princes_notes! { expecto_patronum ($danger, $format) }
+        $crate::__private::Patronus::expecto_with($danger, || ::std::format!($format, $($arguments)+))
+    }};
+    ($danger:expr, $message:expr) => {{
+        $crate::__princes_notes! { expecto_patronum ($danger, $message) }
         $danger.expect($message)
-    };
-    ($danger:expr) => {
+    }};
+    ($danger:expr) => {{
+        $crate::__princes_notes! { expecto_patronum ($danger) }
         $danger.unwrap()
+    }};
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "expecto_patronum",
+            "expecto_patronum!(result_or_option), expecto_patronum!(result_or_option, message) or expecto_patronum!(result_or_option, \"format\", arguments...)",
+            "expecto_patronum!(u8::try_from(5), \"Dementors B-gone!\")",
+            [$($miscast)*]
+        }
     };
 }
 
 /// Alias for [`Mutex::lock`](std::sync::Mutex::lock).
-/// # Example
+/// Also locks an [`RwLock`](std::sync::RwLock) with [`RwLock::read`](std::sync::RwLock::read)
+/// or [`RwLock::write`](std::sync::RwLock::write) if the lock is prefixed with `read` or `write`.
+/// # Examples
 /// ```
 /// # use code_spells::colloportus;
 /// use std::sync::Mutex;
 /// let door = Mutex::new(5);
 /// let guard_result = colloportus!(&door);
 /// ```
+/// ```
+/// # use code_spells::colloportus;
+/// use std::sync::RwLock;
+/// let vault = RwLock::new(5);
+/// {
+///     let visitor = colloportus!(read &vault).unwrap();
+///     let other_visitor = colloportus!(read &vault).unwrap();
+///     assert_eq!(*visitor, *other_visitor);
+/// }
+/// *colloportus!(write &vault).unwrap() = 6;
+/// assert_eq!(*colloportus!(read &vault).unwrap(), 6);
+/// ```
 #[macro_export]
 macro_rules! colloportus {
+    (read $door:expr) => {
+        ::std::sync::RwLock::read($door)
+    };
+    (write $door:expr) => {
+        ::std::sync::RwLock::write($door)
+    };
     ($door:expr) => {
         ::std::sync::Mutex::lock($door)
     };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "colloportus",
+            "colloportus!(&mutex), colloportus!(read &rwlock) or colloportus!(write &rwlock)",
+            "colloportus!(read &vault)",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Unlocks a [`Mutex`](std::sync::Mutex) and hands you the guard.
+/// Expands to [`Mutex::lock`](std::sync::Mutex::lock) followed by `.expect(...)`,
+/// so it panics if the mutex was poisoned.
+/// Use `alohomora!(try &mutex)` to call [`Mutex::try_lock`](std::sync::Mutex::try_lock) instead.
+/// # Examples
+/// ```
+/// # use code_spells::alohomora;
+/// use std::sync::Mutex;
+/// let door = Mutex::new(5);
+/// {
+///     let mut room = alohomora!(&door);
+///     *room += 1;
+/// }
+/// assert_eq!(*alohomora!(&door), 6);
+/// ```
+/// ```
+/// # use code_spells::alohomora;
+/// use std::sync::Mutex;
+/// let door = Mutex::new(5);
+/// let room = alohomora!(&door);
+/// // Someone is already inside.
+/// assert!(alohomora!(try &door).is_err());
+/// ```
+#[macro_export]
+macro_rules! alohomora {
+    (try $door:expr) => {
+        ::std::sync::Mutex::try_lock($door)
+    };
+    ($door:expr) => {
+        ::std::sync::Mutex::lock($door).expect("alohomora! failed: the mutex was poisoned")
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "alohomora",
+            "alohomora!(&mutex) or alohomora!(try &mutex)",
+            "alohomora!(try &door)",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Alias for [`Box::new`](std::boxed::Box::new), or for [`Rc::new`](std::rc::Rc::new) if the value is prefixed with `shared`
+/// and for [`Arc::new`](std::sync::Arc::new) if it is prefixed with `sync`. Levitates the value onto the heap.
+/// # Example
+/// ```
+/// # use code_spells::wingardium_leviosa;
+/// let feather = wingardium_leviosa!("feather");
+/// let troll_club = wingardium_leviosa!(shared "club");
+/// let bathroom = wingardium_leviosa!(sync "girls' bathroom");
+/// assert_eq!((*feather, *troll_club, *bathroom), ("feather", "club", "girls' bathroom"));
+/// ```
+#[macro_export]
+macro_rules! wingardium_leviosa {
+    (shared $value:expr) => {
+        ::std::rc::Rc::new($value)
+    };
+    (sync $value:expr) => {
+        ::std::sync::Arc::new($value)
+    };
+    ($value:expr) => {
+        ::std::boxed::Box::new($value)
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "wingardium_leviosa",
+            "wingardium_leviosa!(value), wingardium_leviosa!(shared value) or wingardium_leviosa!(sync value)",
+            "wingardium_leviosa!(sync feather)",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Moves a value out of the [`Box`](std::boxed::Box) it was levitated into with [`wingardium_leviosa!`](wingardium_leviosa).
+/// With the `shared` or `sync` prefix this is an alias for [`Rc::try_unwrap`](std::rc::Rc::try_unwrap)
+/// or [`Arc::try_unwrap`](std::sync::Arc::try_unwrap), which return the pointer back in the `Err` variant
+/// if there are other strong pointers to the value.
+/// # Example
+/// ```
+/// # use code_spells::{descendo, wingardium_leviosa};
+/// let feather: String = descendo!(wingardium_leviosa!(String::from("feather")));
+/// assert_eq!(feather, "feather");
+///
+/// let club = wingardium_leviosa!(shared "club");
+/// let troll = std::rc::Rc::clone(&club);
+/// let club = descendo!(shared club).unwrap_err();
+/// drop(troll);
+/// assert_eq!(descendo!(shared club), Ok("club"));
+/// ```
+#[macro_export]
+macro_rules! descendo {
+    (shared $rc:expr) => {
+        ::std::rc::Rc::try_unwrap($rc)
+    };
+    (sync $arc:expr) => {
+        ::std::sync::Arc::try_unwrap($arc)
+    };
+    ($boxed:expr) => {{
+        let boxed: ::std::boxed::Box<_> = $boxed;
+        *boxed
+    }};
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "descendo",
+            "descendo!(boxed), descendo!(shared rc) or descendo!(sync arc)",
+            "descendo!(sync feather)",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Turns a [`Vec<T>`](Vec) or a [`String`] to stone: an immutable `Box<[T]>` or `Box<str>` that does not keep
+/// any spare capacity around. With the `shared` or `sync` prefix it turns into an `Rc` or an `Arc` instead,
+/// which can be cloned without copying the contents.
+/// # Example
+/// ```
+/// # use code_spells::duro;
+/// use std::sync::Arc;
+///
+/// let mut tapestry = Vec::with_capacity(10);
+/// tapestry.push("Barnabas the Barmy");
+/// let tapestry: Box<[&str]> = duro!(tapestry);
+/// assert_eq!(tapestry.len(), 1);
+///
+/// let seventh_floor: Arc<str> = duro!(sync String::from("the Room of Requirement"));
+/// let same_floor = Arc::clone(&seventh_floor);
+/// assert_eq!(&*same_floor, "the Room of Requirement");
+/// ```
+#[macro_export]
+macro_rules! duro {
+    (shared $collection:expr) => {
+        $crate::__private::Petrify::petrify_shared($collection)
+    };
+    (sync $collection:expr) => {
+        $crate::__private::Petrify::petrify_sync($collection)
+    };
+    ($collection:expr) => {
+        $crate::__private::Petrify::petrify($collection)
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "duro",
+            "duro!(collection), duro!(shared collection) or duro!(sync collection)",
+            "duro!(sync tapestry)",
+            [$($miscast)*]
+        }
+    };
 }
 
 /// Alias for [`Box::leak`](std::boxed::Box::leak). The item is still there, it's just invisible. Can be revealed with [`aparecium!`](aparecium).
@@ -206,6 +1045,7 @@ macro_rules! colloportus {
 /// This crate allows that function to be cast with [`aparecium!`].
 /// ```
 /// # use code_spells::{evanesco, aparecium};
+/// # code_spells::i_solemnly_swear!();
 /// let a: &mut Vec<i32> = evanesco!(Box::new(vec![5; 100]));
 /// assert_eq!(unsafe { aparecium!(a) }, Box::new(vec![5; 100]));
 /// ```
@@ -214,6 +1054,14 @@ macro_rules! evanesco {
     ($item:expr) => {
         ::std::boxed::Box::leak($item)
     };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "evanesco",
+            "evanesco!(boxed_value)",
+            "evanesco!(Box::new(ostrich))",
+            [$($miscast)*]
+        }
+    };
 }
 
 /// Alias for [`Box::from_raw`](std::boxed::Box::from_raw). Useful if you have made something invisible with [`evanesco!`](evanesco).
@@ -222,13 +1070,24 @@ macro_rules! evanesco {
 /// # Example
 /// ```
 /// # use code_spells::{evanesco, aparecium};
+/// # code_spells::i_solemnly_swear!();
 /// let a: &mut Vec<i32> = evanesco!(Box::new(vec![5; 100]));
 /// assert_eq!(unsafe { aparecium!(a) }, Box::new(vec![5; 100]));
 /// ```
 #[macro_export]
 macro_rules! aparecium {
-    ($item:expr) => {
+    ($item:expr) => {{
+        $crate::__marauders_oath!("aparecium");
+        $crate::__knockturn_alley!("aparecium");
         ::std::boxed::Box::from_raw($item)
+    }};
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "aparecium",
+            "unsafe { aparecium!(pointer) }",
+            "unsafe { aparecium!(invisible) }",
+            [$($miscast)*]
+        }
     };
 }
 
@@ -251,14 +1110,37 @@ macro_rules! sonorous {
     };
 }
 
-/// Alias for [`Result::unwrap_or`](core::result::Result::unwrap_or) and [`Result::unwrap_or_else`](core::result::Result::unwrap_or_else).
-/// Automatically chooses [`unwrap_or_else`](core::result::Result::unwrap_or_else) if given a closure,
-/// and [`unwrap_or`](core::result::Result::unwrap_or) if given an expression that is not a closure.
+/// Alias for [`eprintln!`]. The counterpart to [`sonorous!`](sonorous), for when you want to be heard on stderr instead.
 /// # Example
 /// ```
-/// # use code_spells::reparo;
-/// fn foo(x: u8) -> Result<u8, u8> {
-///     if x < 125 {
+/// # use code_spells::quietus;
+/// quietus!("Hello, World!");
+/// quietus!("{} chocolate", "dark");
+/// let a = 1 + 1;
+/// quietus!("{a} is not {}", 5);
+/// quietus!();
+/// ```
+#[macro_export]
+macro_rules! quietus {
+    () => {
+        eprint!("\n")
+    };
+    ($($arg:tt)*) => {
+        eprintln!($($arg)*)
+    };
+}
+
+/// Alias for [`Result::unwrap_or`](core::result::Result::unwrap_or) and [`Result::unwrap_or_else`](core::result::Result::unwrap_or_else),
+/// as well as the same methods on [`Option`](core::option::Option).
+/// Automatically chooses [`unwrap_or_else`](core::result::Result::unwrap_or_else) if given a closure,
+/// and [`unwrap_or`](core::result::Result::unwrap_or) if given an expression that is not a closure.
+/// A closure that takes an argument mends a [`Result`](core::result::Result), and one that takes no arguments mends an [`Option`](core::option::Option).
+/// Without a second argument it uses `unwrap_or_default`.
+/// # Example
+/// ```
+/// # use code_spells::reparo;
+/// fn foo(x: u8) -> Result<u8, u8> {
+///     if x < 125 {
 ///         Ok(x)
 ///     } else {
 ///         Err(x)
@@ -270,6 +1152,16 @@ macro_rules! sonorous {
 /// assert_eq!(reparo!(foo(255), |_| 5), 5); // unwrap_or_else
 /// let primes = vec![2, 3, 5];
 /// assert_eq!(reparo!(foo(255), move |_| primes.into_iter().sum()), 10); // unwrap_or_else
+/// assert_eq!(reparo!(foo(255)), 0); // unwrap_or_default
+/// ```
+/// Options are mended the same way.
+/// ```
+/// # use code_spells::reparo;
+/// let wand: Option<&str> = None;
+/// assert_eq!(reparo!(wand, "spellotaped"), "spellotaped"); // unwrap_or
+/// assert_eq!(reparo!(wand, || "borrowed"), "borrowed"); // unwrap_or_else
+/// assert_eq!(reparo!(wand), ""); // unwrap_or_default
+/// assert_eq!(reparo!(Some("holly"), || "borrowed"), "holly");
 /// ```
 /// # Note
 /// If the second argument is the name of a function this macro will not work.
@@ -301,95 +1193,3622 @@ macro_rules! reparo {
     ($result:expr, |$arg_name:pat_param| $body:expr) => {
         ::core::result::Result::unwrap_or_else($result, |$arg_name| $body)
     };
-    ($result:expr, $alt:expr) => {
-        ::core::result::Result::unwrap_or($result, $alt)
+    ($option:expr, move || $body:expr) => {
+        ::core::option::Option::unwrap_or_else($option, move || $body)
+    };
+    ($option:expr, || $body:expr) => {
+        ::core::option::Option::unwrap_or_else($option, || $body)
+    };
+    ($result:expr, $alt:expr) => {{
+        $crate::__princes_notes! { reparo ($result, $alt) }
+        $result.unwrap_or($alt)
+    }};
+    ($result:expr) => {{
+        $crate::__princes_notes! { reparo ($result) }
+        $result.unwrap_or_default()
+    }};
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "reparo",
+            "reparo!(result_or_option), reparo!(result_or_option, fallback), reparo!(result, |error| fallback) or reparo!(option, || fallback)",
+            "reparo!(u8::try_from(300), |_| u8::MAX)",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Alias for [`Option::get_or_insert`](core::option::Option::get_or_insert) and
+/// [`Option::get_or_insert_with`](core::option::Option::get_or_insert_with).
+/// Automatically chooses [`get_or_insert_with`](core::option::Option::get_or_insert_with) if given a closure,
+/// and [`get_or_insert`](core::option::Option::get_or_insert) if given an expression that is not a closure.
+/// Either way it returns a mutable reference to the healed value.
+/// Just like with [`reparo!`](reparo), the name of a function is not treated as a closure.
+/// # Example
+/// ```
+/// # use code_spells::episkey;
+/// let mut nose: Option<&str> = None;
+/// assert_eq!(*episkey!(nose, "mended"), "mended"); // get_or_insert
+/// assert_eq!(*episkey!(nose, || "broken"), "mended"); // get_or_insert_with
+///
+/// let mut wounds: Option<Vec<&str>> = None;
+/// episkey!(&mut wounds, || Vec::new()).push("split lip"); // get_or_insert_with
+/// episkey!(&mut wounds, move || vec!["bruise"]).push("scratch"); // get_or_insert_with
+/// assert_eq!(wounds, Some(vec!["split lip", "scratch"]));
+/// ```
+#[macro_export]
+macro_rules! episkey {
+    ($option:expr, move || $body:expr) => {
+        $option.get_or_insert_with(move || $body)
+    };
+    ($option:expr, || $body:expr) => {
+        $option.get_or_insert_with(|| $body)
+    };
+    ($option:expr, $value:expr) => {
+        $option.get_or_insert($value)
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "episkey",
+            "episkey!(option, value) or episkey!(option, || value)",
+            "episkey!(nose, || \"mended\")",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Alias for unsafe. What could be more unforgivable than undefined behaviour?
+///
+/// The curse can be cast with the reason it is sound, as `unforgivable!("reason" => { code })`.
+/// The reason is kept next to the code as a `&str` constant, and with the `wizengamot` feature it is also
+/// recorded with the Wizengamot, which lists the reasons with
+/// [`wizengamot_report!`](https://docs.rs/code-spells/latest/code_spells/macro.wizengamot_report.html).
+/// The report only lists the reasons that the compiler expanded before the report itself,
+/// so it depends on the order of expansion and can differ between tools:
+/// rust-analyzer may list reasons that have since been removed, and incremental builds may list fewer.
+///
+/// With the `unbreakable-vow` feature casting the curse without a reason warns,
+/// so that a crate can require every unforgivable curse to be justified with `#![deny(deprecated)]`.
+/// # Examples
+/// ```
+/// # use code_spells::unforgivable;
+/// use core::num::NonZeroU8;
+/// // Forgivability: `new_unchecked` is UB if the argument is zero, but two is not zero.
+/// const two: NonZeroU8 = unforgivable! { NonZeroU8::new_unchecked(2) };
+/// assert_eq!(two.get(), 2);
+/// ```
+/// ```
+/// # use code_spells::unforgivable;
+/// let wand = [7_u8; 4];
+/// let core = unforgivable!("the index is in bounds as the wand has four parts" => {
+///     *wand.get_unchecked(3)
+/// });
+/// assert_eq!(core, 7);
+/// ```
+/// The reason must be a string.
+/// ```compile_fail
+/// # use code_spells::unforgivable;
+/// let two = unforgivable!(2 => { core::num::NonZeroU8::new_unchecked(2) });
+/// ```
+#[cfg_attr(feature = "unbreakable-vow", doc = "```compile_fail")]
+#[cfg_attr(not(feature = "unbreakable-vow"), doc = "```ignore")]
+/// #![deny(deprecated)]
+/// # use code_spells::unforgivable;
+/// let two = unforgivable! { core::num::NonZeroU8::new_unchecked(2) }; // error: this unforgivable curse was cast without a reason ...
+/// ```
+#[macro_export]
+macro_rules! unforgivable {
+    ($reason:literal => { $($code:tt)* }) => {{
+        $crate::__wizengamot! { $reason }
+        const _: &str = $reason;
+        unsafe {
+            $($code)*
+        }
+    }};
+    ($($code:tt)+) => {{
+        $crate::__unbreakable_vow! {}
+        unsafe {
+            $($code)+
+        }
+    }};
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "unforgivable",
+            "unforgivable!(\"reason\" => { unsafe code }) or unforgivable! { unsafe code }",
+            "unforgivable!(\"two is not zero\" => { core::num::NonZeroU8::new_unchecked(2) })",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Calls the `reserve` method of a collection, like [`Vec::reserve`](std::vec::Vec::reserve),
+/// to make room for at least `additional` more elements without reallocating.
+/// Works on every collection with such a method, like [`String`], [`VecDeque`](std::collections::VecDeque),
+/// [`HashMap`](std::collections::HashMap) and [`HashSet`](std::collections::HashSet).
+///
+/// The collection can be any expression that the method can be called on, like a field or an element of a slice.
+/// # Example
+/// ```
+/// # use code_spells::capacious_extremis;
+/// let mut police_box = Vec::<i32>::new();
+/// capacious_extremis!(&mut police_box, 5);
+/// assert!(police_box.capacity() >= 5);
+/// let r = &mut police_box;
+/// capacious_extremis!(r, 10);
+/// assert!(police_box.capacity() >= 10);
+///
+/// let mut beaded_bag = std::collections::HashMap::<&str, u8>::new();
+/// capacious_extremis!(&mut beaded_bag, 100);
+/// assert!(beaded_bag.capacity() >= 100);
+///
+/// struct Tardis {
+///     rooms: Vec<Vec<&'static str>>,
+/// }
+/// let mut tardis = Tardis { rooms: vec![Vec::new()] };
+/// capacious_extremis!(tardis.rooms, 8);
+/// capacious_extremis!(tardis.rooms[0], 64);
+/// assert!(tardis.rooms.capacity() >= 8 && tardis.rooms[0].capacity() >= 64);
+/// ```
+#[macro_export]
+macro_rules! capacious_extremis {
+    ($collection:expr, $additional:expr) => {
+        $collection.reserve($additional)
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "capacious_extremis",
+            "capacious_extremis!(&mut collection, additional)",
+            "capacious_extremis!(&mut police_box, 5)",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Grows a collection. Where [`capacious_extremis!`](capacious_extremis) only makes room, this spell actually adds elements.
+/// - `engorgio!(vec, new_len, value)` is an alias for [`Vec::resize`](std::vec::Vec::resize).
+/// - `engorgio!(string, text)` is an alias for [`String::push_str`](std::string::String::push_str).
+/// - `engorgio!(collection; iter)` is an alias for [`Extend::extend`](core::iter::Extend::extend).
+/// # Examples
+/// ```
+/// # use code_spells::engorgio;
+/// let mut pumpkin = vec![1, 2];
+/// engorgio!(&mut pumpkin, 4, 0);
+/// assert_eq!(pumpkin, [1, 2, 0, 0]);
+/// engorgio!(&mut pumpkin; 5..=6);
+/// assert_eq!(pumpkin, [1, 2, 0, 0, 5, 6]);
+///
+/// let mut spider = String::from("Aragog");
+/// engorgio!(&mut spider, " the acromantula");
+/// assert_eq!(spider, "Aragog the acromantula");
+/// engorgio!(&mut spider; ['!', '!']);
+/// assert_eq!(spider, "Aragog the acromantula!!");
+/// ```
+#[macro_export]
+macro_rules! engorgio {
+    ($collection:expr; $iter:expr) => {
+        ::core::iter::Extend::extend($collection, $iter)
+    };
+    ($vec:expr, $new_len:expr, $value:expr) => {
+        ::std::vec::Vec::resize($vec, $new_len, $value)
+    };
+    ($string:expr, $text:expr) => {
+        ::std::string::String::push_str($string, $text)
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "engorgio",
+            "engorgio!(vec, new_len, value), engorgio!(string, text) or engorgio!(collection; iter)",
+            "engorgio!(&mut pumpkin, 4, 0)",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Shrinks a collection. The counterpart to [`engorgio!`](engorgio).
+/// Calls the inherent methods of the collection, so it works on [`Vec`], [`String`],
+/// [`HashMap`](std::collections::HashMap) and anything else with methods of the same names.
+/// - `reducio!(collection)` calls `shrink_to_fit()`.
+/// - `reducio!(collection, len)` calls `truncate(len)`.
+/// - `reducio!(capacity collection, min_capacity)` calls `shrink_to(min_capacity)`.
+/// # Examples
+/// ```
+/// # use code_spells::reducio;
+/// let mut trunk = Vec::with_capacity(100);
+/// trunk.extend([1, 2, 3, 4]);
+/// reducio!(trunk, 3);
+/// assert_eq!(trunk, [1, 2, 3]);
+/// reducio!(capacity trunk, 10);
+/// assert!(trunk.capacity() >= 10 && trunk.capacity() < 100);
+/// reducio!(trunk);
+/// assert!(trunk.capacity() >= 3 && trunk.capacity() < 10);
+///
+/// let mut name = String::from("Nymphadora Tonks");
+/// reducio!(name, 10);
+/// assert_eq!(name, "Nymphadora");
+///
+/// use std::collections::HashMap;
+/// let mut map: HashMap<u8, u8> = HashMap::with_capacity(100);
+/// map.insert(1, 2);
+/// reducio!(&mut map);
+/// assert!(map.capacity() < 100);
+/// ```
+#[macro_export]
+macro_rules! reducio {
+    ($collection:expr, $len:expr) => {
+        $collection.truncate($len)
+    };
+    ($collection:expr) => {
+        $collection.shrink_to_fit()
+    };
+    (capacity $collection:expr, $min_capacity:expr) => {
+        $collection.shrink_to($min_capacity)
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "reducio",
+            "reducio!(collection), reducio!(collection, len) or reducio!(capacity collection, min_capacity)",
+            "reducio!(trunk, 3)",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Cleans out collections by calling their `clear()` method.
+/// Works on anything with such a method, e.g. [`Vec`], [`String`] and [`HashMap`](std::collections::HashMap).
+/// Several collections can be cleaned with a single cast.
+/// # Example
+/// ```
+/// # use code_spells::scourgify;
+/// use std::collections::HashMap;
+/// let mut cauldron = vec![1, 2, 3];
+/// let mut blackboard = String::from("Potions");
+/// let mut cupboard = HashMap::from([("newt eyes", 5)]);
+///
+/// scourgify!(cauldron);
+/// assert!(cauldron.is_empty());
+///
+/// scourgify!(blackboard, &mut cupboard);
+/// assert!(blackboard.is_empty());
+/// assert!(cupboard.is_empty());
+/// ```
+#[macro_export]
+macro_rules! scourgify {
+    ($($mess:expr),+ $(,)?) => {{
+        $($mess.clear();)+
+    }};
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "scourgify",
+            "scourgify!(collection, ...)",
+            "scourgify!(cauldron, &mut blackboard)",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Cleans up a string by trimming it.
+/// - `tergeo!(s)` is an alias for [`str::trim`].
+/// - `tergeo!(start s)` and `tergeo!(end s)` are aliases for [`str::trim_start`] and [`str::trim_end`].
+/// - `tergeo!(s, pattern)` is an alias for [`str::trim_matches`].
+/// # Example
+/// ```
+/// # use code_spells::tergeo;
+/// let smudged = "  Hermione  ";
+/// assert_eq!(tergeo!(smudged), "Hermione");
+/// assert_eq!(tergeo!(start smudged), "Hermione  ");
+/// assert_eq!(tergeo!(end smudged), "  Hermione");
+///
+/// let ink = String::from(",,,quill,,");
+/// assert_eq!(tergeo!(ink, ','), "quill");
+/// assert_eq!(tergeo!("123parchment456", char::is_numeric), "parchment");
+/// ```
+#[macro_export]
+macro_rules! tergeo {
+    ($s:expr, $pattern:expr) => {
+        $s.trim_matches($pattern)
+    };
+    ($s:expr) => {
+        $s.trim()
+    };
+    (start $s:expr) => {
+        $s.trim_start()
+    };
+    (end $s:expr) => {
+        $s.trim_end()
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "tergeo",
+            "tergeo!(s), tergeo!(start s), tergeo!(end s) or tergeo!(s, pattern)",
+            "tergeo!(ink, ',')",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Runs a closure under a [`Bogies`](bogies::Bogies) curse, delaying it as the curse is configured.
+/// Useful for testing how timeouts and retries handle slow work.
+/// # Example
+/// ```
+/// # use code_spells::{bogies::{Bogies, Latency}, curse_of_the_bogies};
+/// use std::time::{Duration, Instant};
+///
+/// let bogies = Bogies::new().after(Latency::Fixed(Duration::from_millis(10)));
+/// let start = Instant::now();
+/// let answer = curse_of_the_bogies!(&bogies, || 6 * 7);
+/// assert_eq!(answer, 42);
+/// assert!(start.elapsed() >= Duration::from_millis(10));
+/// ```
+#[macro_export]
+macro_rules! curse_of_the_bogies {
+    ($bogies:expr, $work:expr) => {
+        $crate::bogies::Bogies::curse($bogies, $work)
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "curse_of_the_bogies",
+            "curse_of_the_bogies!(&bogies, || work)",
+            "curse_of_the_bogies!(&bogies, || fetch())",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Severs strings and slices.
+/// - `diffindo!(s, pattern)` is an alias for [`str::split`].
+/// - `diffindo!(s once pattern)` is an alias for [`str::split_once`].
+/// - `diffindo!(slice at mid)` is an alias for [`slice::split_at`] (and [`str::split_at`]).
+///
+/// For the mutable versions, see [`sectumsempra!`](sectumsempra).
+/// # Example
+/// ```
+/// # use code_spells::diffindo;
+/// let students = "Harry,Ron,Hermione";
+/// let names: Vec<&str> = diffindo!(students, ',').collect();
+/// assert_eq!(names, ["Harry", "Ron", "Hermione"]);
+///
+/// assert_eq!(diffindo!("Quidditch: Seeker" once ": "), Some(("Quidditch", "Seeker")));
+///
+/// let vault = [7, 1, 3, 5];
+/// let (left, right) = diffindo!(vault at 1);
+/// assert_eq!(left, [7]);
+/// assert_eq!(right, [1, 3, 5]);
+/// ```
+#[macro_export]
+macro_rules! diffindo {
+    (@sever [$($s:tt)+] once $pattern:expr) => {
+        ($($s)+).split_once($pattern)
+    };
+    (@sever [$($slice:tt)+] at $mid:expr) => {
+        ($($slice)+).split_at($mid)
+    };
+    (@sever [$($s:tt)*] $next:tt $($rest:tt)*) => {
+        $crate::diffindo!(@sever [$($s)* $next] $($rest)*)
+    };
+    ($s:expr, $pattern:expr) => {
+        $s.split($pattern)
+    };
+    (@sever [$($s:tt)*]) => {
+        $crate::__kwikspell! {
+            "diffindo",
+            "diffindo!(s, pattern), diffindo!(s once pattern) or diffindo!(slice at mid)",
+            "diffindo!(vault at 1)",
+            [$($s)*]
+        }
+    };
+    ($($tokens:tt)+) => {
+        $crate::diffindo!(@sever [] $($tokens)+)
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "diffindo",
+            "diffindo!(s, pattern), diffindo!(s once pattern) or diffindo!(slice at mid)",
+            "diffindo!(vault at 1)",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Creates a [`Hippogriff`](hippogriff::Hippogriff) that guards a value behind a readiness check.
+/// The value can only be [approached](hippogriff::Hippogriff::approach) after the check has accepted a
+/// [bow](hippogriff::Hippogriff::bow).
+/// # Example
+/// ```
+/// # use code_spells::hippogriff;
+/// use std::net::Ipv4Addr;
+///
+/// let mut server = hippogriff!(Ipv4Addr::LOCALHOST, |address: &Ipv4Addr| {
+///     if address.is_loopback() { Ok(()) } else { Err("refusing to talk to strangers") }
+/// });
+/// assert!(server.approach().is_err());
+/// server.bow()?;
+/// assert_eq!(*server.approach()?, Ipv4Addr::LOCALHOST);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[macro_export]
+macro_rules! hippogriff {
+    ($creature:expr, $check:expr) => {
+        $crate::hippogriff::Hippogriff::new($creature, $check)
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "hippogriff",
+            "hippogriff!(value, |value| check)",
+            "hippogriff!(buckbeak, |b: &Creature| b.accepts_bow())",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Severs slices into mutable pieces. The darker sibling of [`diffindo!`](diffindo):
+/// the pieces can be changed, and the borrow checker will fight you for them.
+/// - `sectumsempra!(slice at mid)` is an alias for [`slice::split_at_mut`].
+/// - `sectumsempra!(first slice)` and `sectumsempra!(last slice)` are aliases for
+///   [`slice::split_first_mut`] and [`slice::split_last_mut`].
+/// - `sectumsempra!(slice, chunk_size)` is an alias for [`slice::chunks_mut`].
+/// # Example
+/// ```
+/// # use code_spells::sectumsempra;
+/// let mut scars = [1, 2, 3, 4, 5];
+///
+/// let (left, right) = sectumsempra!(scars at 2);
+/// left[0] = right[0];
+/// assert_eq!(scars, [3, 2, 3, 4, 5]);
+///
+/// if let Some((first, rest)) = sectumsempra!(first scars) {
+///     *first = rest.iter().sum();
+/// }
+/// assert_eq!(scars[0], 14);
+///
+/// if let Some((last, _)) = sectumsempra!(last scars) {
+///     *last = 0;
+/// }
+/// assert_eq!(scars[4], 0);
+///
+/// for chunk in sectumsempra!(scars, 2) {
+///     chunk[0] = -1;
+/// }
+/// assert_eq!(scars, [-1, 2, -1, 4, -1]);
+/// ```
+#[macro_export]
+macro_rules! sectumsempra {
+    (@sever [$($slice:tt)+] at $mid:expr) => {
+        ($($slice)+).split_at_mut($mid)
+    };
+    (@sever [$($slice:tt)*] $next:tt $($rest:tt)*) => {
+        $crate::sectumsempra!(@sever [$($slice)* $next] $($rest)*)
+    };
+    ($slice:expr, $chunk_size:expr) => {
+        $slice.chunks_mut($chunk_size)
+    };
+    (first $slice:expr) => {
+        $slice.split_first_mut()
+    };
+    (last $slice:expr) => {
+        $slice.split_last_mut()
+    };
+    (@sever [$($s:tt)*]) => {
+        $crate::__kwikspell! {
+            "sectumsempra",
+            "sectumsempra!(slice at mid), sectumsempra!(first slice), sectumsempra!(last slice) or sectumsempra!(slice, chunk_size)",
+            "sectumsempra!(scars at 2)",
+            [$($s)*]
+        }
+    };
+    ($($tokens:tt)+) => {
+        $crate::sectumsempra!(@sever [] $($tokens)+)
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "sectumsempra",
+            "sectumsempra!(slice at mid), sectumsempra!(first slice), sectumsempra!(last slice) or sectumsempra!(slice, chunk_size)",
+            "sectumsempra!(scars at 2)",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Flies three closures to the Three Broomsticks on [scoped threads](std::thread::scope)
+/// and returns a tuple of their results once all of them have arrived.
+/// The closures can borrow from the surrounding scope.
+/// For any other number of closures, see [`broomstick_fleet!`](broomstick_fleet).
+/// # Panics
+/// If any of the closures panic the others are still run to completion,
+/// after which the panic of the first one is propagated.
+/// # Example
+/// ```
+/// # use code_spells::three_broomsticks;
+/// let order = ["butterbeer", "butterbeer", "gillywater"];
+/// let (butterbeers, gillywaters, total) = three_broomsticks!(
+///     || order.iter().filter(|&&d| d == "butterbeer").count(),
+///     || order.iter().filter(|&&d| d == "gillywater").count(),
+///     || order.len(),
+/// );
+/// assert_eq!((butterbeers, gillywaters, total), (2, 1, 3));
+/// ```
+#[macro_export]
+macro_rules! three_broomsticks {
+    ($a:expr, $b:expr, $c:expr $(,)?) => {
+        $crate::broomsticks::three($a, $b, $c)
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "three_broomsticks",
+            "three_broomsticks!(|| a, || b, || c)",
+            "three_broomsticks!(|| seek(), || chase(), || beat())",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Flies any number of closures that return the same type on [scoped threads](std::thread::scope)
+/// and returns a [`Vec`] of their results, in order, once all of them have finished.
+/// To fly the closures of an iterator, use [`broomsticks::fleet`].
+/// # Panics
+/// If any of the closures panic the others are still run to completion,
+/// after which the panic of the first one is propagated.
+/// # Example
+/// ```
+/// # use code_spells::broomstick_fleet;
+/// let team = ["Angelina", "Katie", "Alicia"];
+/// let lengths = broomstick_fleet![
+///     || team[0].len(),
+///     || team[1].len(),
+///     || team[2].len(),
+///     || team.len(),
+/// ];
+/// assert_eq!(lengths, [8, 5, 6, 3]);
+/// ```
+#[macro_export]
+macro_rules! broomstick_fleet {
+    ($($broomstick:expr),+ $(,)?) => {
+        ::std::thread::scope(|s| {
+            $crate::broomsticks::land(::std::vec![$(s.spawn($broomstick)),+])
+        })
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "broomstick_fleet",
+            "broomstick_fleet![|| work, ...]",
+            "broomstick_fleet![|| seek(), || chase()]",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Conjures a flock of [scoped threads](std::thread::scope) that can borrow from the caller.
+/// - `avis!(|s| { ... })` is an alias for [`thread::scope`](std::thread::scope).
+/// - `avis!(for x in items => body)` spawns one scoped thread per item and returns a [`Vec`] of the results
+///   in the order of the items, once all of them have finished.
+///   The body is evaluated in a `move` closure, so iterate over references to keep the items.
+/// # Panics
+/// In the `for` form, if any of the threads panic the others are still run to completion,
+/// after which the panic of the first one is propagated.
+/// # Examples
+/// ```
+/// # use code_spells::avis;
+/// let mut canaries = Vec::new();
+/// avis!(|s| {
+///     s.spawn(|| println!("tweet"));
+///     s.spawn(|| canaries.push("Neville"));
+/// });
+/// assert_eq!(canaries, ["Neville"]);
+///
+/// let letters = ["Harry", "Dudley"];
+/// let lengths = avis!(for letter in &letters => letter.len());
+/// assert_eq!(lengths, [5, 6]);
+/// ```
+#[macro_export]
+macro_rules! avis {
+    (for $item:pat in $items:expr => $body:expr) => {
+        ::std::thread::scope(|s| {
+            $crate::broomsticks::land(
+                ::core::iter::IntoIterator::into_iter($items)
+                    .map(|$item| s.spawn(move || $body))
+                    .collect(),
+            )
+        })
+    };
+    ($flock:expr) => {
+        ::std::thread::scope($flock)
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "avis",
+            "avis!(|s| { ... }) or avis!(for x in items => body)",
+            "avis!(for letter in &letters => letter.len())",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Animates the statues of the castle to work through a collection in parallel: calls a closure on every item
+/// on a bounded number of [scoped threads](std::thread::scope), and returns a [`Vec`] of the results in the order of the items.
+/// - `piertotum_locomotor!(items, f)` wakes one statue per unit of available parallelism,
+///   see [`piertotum_locomotor::animate`].
+/// - `piertotum_locomotor!(items, f, statues: n)` wakes at most `n` statues,
+///   see [`piertotum_locomotor::animate_with`].
+///
+/// The closure can borrow from the surrounding scope.
+/// # Panics
+/// If the closure panics on any item the rest are still processed,
+/// after which the panic of the first item that panicked is propagated.
+/// # Example
+/// ```
+/// # use code_spells::piertotum_locomotor;
+/// let corridors = ["first floor", "third floor", "seventh floor"];
+/// let guarded = piertotum_locomotor!(&corridors, |corridor| format!("{} is guarded", corridor), statues: 2);
+/// assert_eq!(guarded[1], "third floor is guarded");
+/// ```
+#[macro_export]
+macro_rules! piertotum_locomotor {
+    ($items:expr, $f:expr, statues: $statues:expr $(,)?) => {
+        $crate::piertotum_locomotor::animate_with($statues, $items, $f)
+    };
+    ($items:expr, $f:expr $(,)?) => {
+        $crate::piertotum_locomotor::animate($items, $f)
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "piertotum_locomotor",
+            "piertotum_locomotor!(items, |item| work) or piertotum_locomotor!(items, |item| work, statues: n)",
+            "piertotum_locomotor!(&statues, |statue| statue.march(), statues: 4)",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Sends a message by owl. Alias for [`Sender::send`](std::sync::mpsc::Sender::send),
+/// and works just as well with a [`SyncSender`](std::sync::mpsc::SyncSender)
+/// or a [`RegulatedSender`](owl_post::RegulatedSender).
+/// # Example
+/// ```
+/// # use code_spells::{await_owl, send_owl};
+/// use code_spells::owl_post::owl_post;
+///
+/// let (errol, burrow) = owl_post();
+/// send_owl!(errol, "Howler").unwrap();
+/// assert_eq!(await_owl!(burrow), Ok("Howler"));
+/// ```
+#[macro_export]
+macro_rules! send_owl {
+    ($sender:expr, $message:expr) => {
+        $sender.send($message)
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "send_owl",
+            "send_owl!(sender, message)",
+            "send_owl!(hedwig, \"Happy birthday, Harry!\")",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Waits for an owl to arrive. Alias for [`Receiver::recv`](std::sync::mpsc::Receiver::recv),
+/// or [`Receiver::recv_timeout`](std::sync::mpsc::Receiver::recv_timeout) if given a timeout.
+/// Works just as well with a [`RegulatedReceiver`](owl_post::RegulatedReceiver).
+/// # Example
+/// ```
+/// # use code_spells::{await_owl, send_owl};
+/// use code_spells::owl_post::{owl_post, RecvTimeoutError};
+/// use std::time::Duration;
+///
+/// let (pigwidgeon, sirius) = owl_post::<&str>();
+/// assert_eq!(await_owl!(sirius, Duration::from_millis(10)), Err(RecvTimeoutError::Timeout));
+/// send_owl!(pigwidgeon, "Buckbeak is safe").unwrap();
+/// assert_eq!(await_owl!(sirius, Duration::from_millis(10)), Ok("Buckbeak is safe"));
+/// ```
+#[macro_export]
+macro_rules! await_owl {
+    ($receiver:expr) => {
+        $receiver.recv()
+    };
+    ($receiver:expr, $timeout:expr) => {
+        $receiver.recv_timeout($timeout)
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "await_owl",
+            "await_owl!(receiver) or await_owl!(receiver, timeout)",
+            "await_owl!(privet_drive, Duration::from_secs(1))",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Travels by floo: sends a message to the fireplace connected under a name in a [`FlooNetwork`](floo_network::FlooNetwork).
+/// Alias for [`FlooNetwork::send`](floo_network::FlooNetwork::send).
+/// # Example
+/// ```
+/// # use code_spells::floo;
+/// use code_spells::floo_network::{FlooNetwork, Misrouted};
+///
+/// let network = FlooNetwork::new();
+/// let burrow = network.connect("the Burrow");
+/// floo!(network, "the Burrow", "Harry").unwrap();
+/// assert_eq!(burrow.recv(), Ok("Harry"));
+/// assert_eq!(floo!(network, "Diagonally", "Harry"), Err(Misrouted("Harry")));
+/// ```
+#[macro_export]
+macro_rules! floo {
+    ($network:expr, $destination:expr, $message:expr) => {
+        $network.send($destination, $message)
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "floo",
+            "floo!(network, destination, message)",
+            "floo!(network, \"the Burrow\", \"Harry\")",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Creates a [`Regulation`](owl_post::Regulation) for a [`regulated_owl_post`](owl_post::regulated_owl_post)
+/// or a [regulated fireplace](floo_network::FlooNetwork::connect_regulated): the capacity, and the
+/// [`Backpressure`](owl_post::Backpressure) that is applied when it is full, one of `block`, `drop_oldest`,
+/// `drop_newest` or `error`.
+/// # Example
+/// ```
+/// # use code_spells::{await_owl, floo_regulation, send_owl};
+/// use code_spells::owl_post::{regulated_owl_post, TrySendError};
+///
+/// let (hedwig, harry) = regulated_owl_post(floo_regulation!(1, error));
+/// send_owl!(hedwig, "from Hagrid").unwrap();
+/// assert_eq!(send_owl!(hedwig, "from Ron"), Err(TrySendError::Full("from Ron")));
+/// assert_eq!(await_owl!(harry), Ok("from Hagrid"));
+/// ```
+#[macro_export]
+macro_rules! floo_regulation {
+    ($capacity:expr, block) => {
+        $crate::owl_post::Regulation::new($capacity, $crate::owl_post::Backpressure::Block)
+    };
+    ($capacity:expr, drop_oldest) => {
+        $crate::owl_post::Regulation::new($capacity, $crate::owl_post::Backpressure::DropOldest)
+    };
+    ($capacity:expr, drop_newest) => {
+        $crate::owl_post::Regulation::new($capacity, $crate::owl_post::Backpressure::DropNewest)
+    };
+    ($capacity:expr, error) => {
+        $crate::owl_post::Regulation::new($capacity, $crate::owl_post::Backpressure::Error)
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "floo_regulation",
+            "floo_regulation!(capacity, block | drop_oldest | drop_newest | error)",
+            "floo_regulation!(16, drop_oldest)",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Creates a [`DumbledoresArmy`](dumbledores_army::DumbledoresArmy), a [`Barrier`](std::sync::Barrier)
+/// for the given number of members.
+/// # Example
+/// ```
+/// # use code_spells::dumbledores_army;
+/// use std::thread;
+///
+/// let army = dumbledores_army!(2);
+/// let leaders = thread::scope(|s| {
+///     let neville = s.spawn(|| army.meet());
+///     let luna = army.meet();
+///     usize::from(neville.join().unwrap()) + usize::from(luna)
+/// });
+/// assert_eq!(leaders, 1);
+/// ```
+#[macro_export]
+macro_rules! dumbledores_army {
+    ($members:expr) => {
+        $crate::dumbledores_army::DumbledoresArmy::new($members)
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "dumbledores_army",
+            "dumbledores_army!(members)",
+            "let army = dumbledores_army!(28);",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Uses a [`DaCoin`](dumbledores_army::DaCoin).
+/// - `da_coin!(coin)` waits until the coin is summoned.
+/// - `da_coin!(coin, timeout)` waits until the coin is summoned or the timeout has passed, and returns whether it was summoned.
+/// - `da_coin!(summon coin)` releases everyone waiting on the coin, and returns how many were released.
+/// # Example
+/// ```
+/// # use code_spells::da_coin;
+/// use code_spells::dumbledores_army::DaCoin;
+/// use std::thread;
+/// use std::time::Duration;
+///
+/// let coin = DaCoin::new();
+/// assert!(!da_coin!(coin, Duration::from_millis(10)));
+/// thread::scope(|s| {
+///     let ginny = s.spawn(|| da_coin!(coin));
+///     while coin.waiting() == 0 {
+///         thread::yield_now();
+///     }
+///     assert_eq!(da_coin!(summon coin), 1);
+///     ginny.join().unwrap();
+/// });
+/// ```
+#[macro_export]
+macro_rules! da_coin {
+    ($coin:expr) => {
+        $coin.wait()
+    };
+    ($coin:expr, $timeout:expr) => {
+        $coin.wait_timeout($timeout)
+    };
+    (summon $coin:expr) => {
+        $coin.summon()
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "da_coin",
+            "da_coin!(coin), da_coin!(coin, timeout) or da_coin!(summon coin)",
+            "da_coin!(coin, Duration::from_secs(60))",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Creates a [`Pigwidgeon`](pigwidgeon::Pigwidgeon), a bounded single-producer single-consumer queue,
+/// and splits it into its sending and receiving halves.
+/// The capacity of the queue is usually inferred from its type, but can also be given.
+/// # Example
+/// ```
+/// # use code_spells::pigwidgeon;
+/// let (mut ron, mut sirius) = pigwidgeon!(1);
+/// assert_eq!(ron.try_send("Buckbeak is safe"), Ok(()));
+/// assert_eq!(ron.try_send("Peter escaped"), Err("Peter escaped"));
+/// assert_eq!(sirius.try_recv(), Some("Buckbeak is safe"));
+/// assert_eq!(sirius.try_recv(), None);
+/// ```
+#[macro_export]
+macro_rules! pigwidgeon {
+    () => {
+        $crate::pigwidgeon::Pigwidgeon::new().split()
+    };
+    ($capacity:expr) => {
+        $crate::pigwidgeon::Pigwidgeon::<_, { $capacity }>::new().split()
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "pigwidgeon",
+            "pigwidgeon!() or pigwidgeon!(capacity)",
+            "let (mut ron, mut sirius) = pigwidgeon!(16);",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Stops runaway work: runs a closure on a helper thread and gives up on it after a timeout.
+/// Returns the result of the closure, or a [`Timeout`](arresto_momentum::Timeout) error if it did not finish in time.
+/// Alias for [`arrest`](arresto_momentum::arrest), see its module for what happens to work that is given up on.
+/// # Example
+/// ```
+/// # use code_spells::arresto_momentum;
+/// use std::time::Duration;
+///
+/// let caught = arresto_momentum!(Duration::from_secs(10), || "Harry");
+/// assert_eq!(caught, Ok("Harry"));
+/// ```
+#[macro_export]
+macro_rules! arresto_momentum {
+    ($timeout:expr, $work:expr) => {
+        $crate::arresto_momentum::arrest($timeout, $work)
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "arresto_momentum",
+            "arresto_momentum!(timeout, closure)",
+            "arresto_momentum!(Duration::from_secs(1), || fall())",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Enrolls a cleanup in the global [`ObliviatorSquad`](obliviator_squad::ObliviatorSquad) under a name,
+/// or sweeps it when cast without arguments: runs every enrolled cleanup in reverse order of enrollment
+/// and returns a [`SweepReport`](obliviator_squad::SweepReport) of the ones that panicked.
+/// Cast `obliviator_squad!(within timeout)` to give the sweep a deadline.
+/// # Example
+/// ```
+/// # use code_spells::obliviator_squad;
+/// use std::time::Duration;
+///
+/// obliviator_squad!("temporary files", || println!("removing temporary files"));
+/// obliviator_squad!("lock file", || println!("removing the lock file"));
+/// // ...
+/// let report = obliviator_squad!(within Duration::from_secs(5));
+/// assert!(report.is_clean());
+/// ```
+#[macro_export]
+macro_rules! obliviator_squad {
+    () => {
+        $crate::obliviator_squad::sweep()
+    };
+    ($name:expr, $cleanup:expr) => {
+        $crate::obliviator_squad::enroll($name, $cleanup)
+    };
+    (within $timeout:expr) => {
+        $crate::obliviator_squad::sweep_within($timeout)
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "obliviator_squad",
+            "obliviator_squad!(name, cleanup), obliviator_squad!() or obliviator_squad!(within timeout)",
+            "obliviator_squad!(\"lock file\", || remove_lock_file())",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Jinxes a block so that it runs at most a number of times per interval. Returns `Some` with the value of
+/// the block if it ran, and `None` if it was impeded.
+/// - `impedimenta!(limiter, { ... })` takes the passes from an [`Impedimenta`](impedimenta::Impedimenta).
+/// - `impedimenta!(passes, interval, { ... })` gives the call site a rate limiter of its own,
+///   which is shared by every thread that reaches it.
+/// # Example
+/// ```
+/// # use code_spells::impedimenta;
+/// use std::time::Duration;
+///
+/// let mut charges = 0;
+/// for _ in 0..10 {
+///     impedimenta!(3, Duration::from_secs(60), { charges += 1 });
+/// }
+/// assert_eq!(charges, 3);
+/// ```
+#[macro_export]
+macro_rules! impedimenta {
+    ($passes:expr, $interval:expr, $body:block) => {{
+        static IMPEDIMENTA: ::std::sync::OnceLock<$crate::impedimenta::Impedimenta> =
+            ::std::sync::OnceLock::new();
+        if IMPEDIMENTA
+            .get_or_init(|| $crate::impedimenta::Impedimenta::new($passes, $interval))
+            .try_pass()
+        {
+            ::core::option::Option::Some($body)
+        } else {
+            ::core::option::Option::None
+        }
+    }};
+    ($limiter:expr, $body:block) => {
+        if $limiter.try_pass() {
+            ::core::option::Option::Some($body)
+        } else {
+            ::core::option::Option::None
+        }
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "impedimenta",
+            "impedimenta!(limiter, { ... }) or impedimenta!(passes, interval, { ... })",
+            "impedimenta!(10, Duration::from_secs(1), { send_owl() })",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Wraps a raw string in a [`Riddle`](ravenclaw_riddle::Riddle) that is only parsed when it is first
+/// [solved](ravenclaw_riddle::Riddle::solve). Uses [`FromStr`](core::str::FromStr) unless a parser is given,
+/// and the parser can also validate the value by returning an error.
+/// # Example
+/// ```
+/// # use code_spells::ravenclaw_riddle;
+/// use code_spells::ravenclaw_riddle::Riddle;
+///
+/// let threads: Riddle<usize> = ravenclaw_riddle!("4");
+/// let house = ravenclaw_riddle!("Ravenclaw", |house: &str| match house {
+///     "Gryffindor" | "Hufflepuff" | "Ravenclaw" | "Slytherin" => Ok(house.len()),
+///     _ => Err("no such house"),
+/// });
+/// assert_eq!(threads.solve(), Ok(&4));
+/// assert_eq!(house.solve(), Ok(&9));
+/// ```
+#[macro_export]
+macro_rules! ravenclaw_riddle {
+    ($raw:expr) => {
+        $crate::ravenclaw_riddle::Riddle::new($raw)
+    };
+    ($raw:expr, $parser:expr) => {
+        $crate::ravenclaw_riddle::Riddle::with_parser($raw, $parser)
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "ravenclaw_riddle",
+            "ravenclaw_riddle!(raw) or ravenclaw_riddle!(raw, parser)",
+            "ravenclaw_riddle!(env_var, |raw: &str| raw.parse::<u16>())",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Drinks a sip of liquid luck from the [`felix_felicis`](mod@felix_felicis) module.
+/// - `felix_felicis!(range)` returns a random value in a half-open or inclusive range.
+/// - `felix_felicis!(bool)` flips a coin.
+/// - `felix_felicis!(choose &slice)` returns a random element of a slice, or `None` if it is empty.
+///
+/// Needs the `rand` feature.
+/// # Panics
+/// Panics if the range is empty.
+/// # Example
+/// ```
+/// # use code_spells::felix_felicis;
+/// let points = felix_felicis!(1..=100);
+/// assert!((1..=100).contains(&points));
+/// if felix_felicis!(bool) {
+///     println!("Harry catches the snitch");
+/// }
+/// let seeker = felix_felicis!(choose &["Harry", "Cho", "Cedric", "Draco"]);
+/// assert!(seeker.is_some());
+/// ```
+#[cfg(feature = "rand")]
+#[macro_export]
+macro_rules! felix_felicis {
+    (bool) => {
+        $crate::felix_felicis::coin_flip()
+    };
+    // `choose &slice` is also a valid expression, so this arm has to come before the range arm.
+    (choose $slice:expr) => {
+        $crate::felix_felicis::choose($slice)
+    };
+    ($range:expr) => {
+        $crate::felix_felicis::lucky($range)
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "felix_felicis",
+            "felix_felicis!(range), felix_felicis!(bool) or felix_felicis!(choose &slice)",
+            "felix_felicis!(1..=100)",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Calls a closure on every item of an iterator on a number of worker threads that share the work fairly,
+/// and collects the results in the order of the items. See [`harvest`](hufflepuff_harvest::harvest).
+/// Uses one worker per unit of available parallelism unless the number of `workers` is given.
+/// # Panics
+/// Panics if the number of workers is zero, and resumes the panic of the closure if it panicked on any item.
+/// # Example
+/// ```
+/// # use code_spells::harvest;
+/// let pumpkins = harvest!(1..=8, workers: 2, |patch: u32| (0..patch).sum::<u32>());
+/// assert_eq!(pumpkins, [0, 1, 3, 6, 10, 15, 21, 28]);
+///
+/// let shouts = harvest!(["Hufflepuff", "Hagrid"], |name: &str| name.to_uppercase());
+/// assert_eq!(shouts, ["HUFFLEPUFF", "HAGRID"]);
+/// ```
+#[macro_export]
+macro_rules! harvest {
+    ($items:expr, workers: $workers:expr, $work:expr) => {
+        $crate::hufflepuff_harvest::harvest($items, $workers, $work)
+    };
+    ($items:expr, $work:expr) => {
+        $crate::hufflepuff_harvest::harvest(
+            $items,
+            ::std::thread::available_parallelism().map_or(4, ::core::num::NonZeroUsize::get),
+            $work,
+        )
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "harvest",
+            "harvest!(items, workers: n, work) or harvest!(items, work)",
+            "harvest!(mandrakes, workers: 4, |mandrake| repot(mandrake))",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Sends a flock of birds at every item of a collection on the thread pool of [`rayon`](https://docs.rs/rayon).
+/// - `oppugno!(collection, |x| ...)` is an alias for `collection.par_iter().for_each(|x| ...)`.
+/// - `oppugno!(map collection, |x| ...)` is an alias for `collection.par_iter().map(|x| ...).collect()`,
+///   which can collect into anything that implements `FromParallelIterator`, like a [`Vec`] in the order of the items.
+///
+/// Needs the `rayon` feature.
+/// # Example
+/// ```
+/// # use code_spells::oppugno;
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+///
+/// let targets = vec!["Ron", "Ron", "Ron"];
+/// let pecks = AtomicUsize::new(0);
+/// oppugno!(targets, |target| {
+///     pecks.fetch_add(target.len(), Ordering::Relaxed);
+/// });
+/// assert_eq!(pecks.into_inner(), 9);
+///
+/// let canaries: Vec<String> = oppugno!(map targets, |target| format!("{} ducks", target));
+/// assert_eq!(canaries[0], "Ron ducks");
+/// ```
+#[cfg(feature = "rayon")]
+#[macro_export]
+macro_rules! oppugno {
+    (map $collection:expr, $f:expr $(,)?) => {{
+        #[allow(unused_imports)]
+        use $crate::__private::{IntoParallelRefIterator as _, ParallelIterator as _};
+        $collection.par_iter().map($f).collect()
+    }};
+    ($collection:expr, $f:expr $(,)?) => {{
+        #[allow(unused_imports)]
+        use $crate::__private::{IntoParallelRefIterator as _, ParallelIterator as _};
+        $collection.par_iter().for_each($f)
+    }};
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "oppugno",
+            "oppugno!(collection, |x| ...) or oppugno!(map collection, |x| ...)",
+            "oppugno!(map canaries, |canary| canary.attack())",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Confunds a mutable slice so that its elements end up in a random order.
+/// Cast `confundo!(slice, seed)` to get the same order every time for the same seed, e.g. in tests.
+///
+/// Needs the `rand` feature.
+/// # Example
+/// ```
+/// # use code_spells::confundo;
+/// let mut goblet = ["Cedric", "Fleur", "Viktor", "Harry"];
+/// confundo!(&mut goblet);
+/// assert!(goblet.contains(&"Harry"));
+///
+/// let mut again = [1, 2, 3, 4, 5];
+/// let mut once_more = again;
+/// confundo!(&mut again, 4);
+/// confundo!(&mut once_more, 4);
+/// assert_eq!(again, once_more);
+/// ```
+#[cfg(feature = "rand")]
+#[macro_export]
+macro_rules! confundo {
+    ($slice:expr) => {
+        $crate::felix_felicis::shuffle($slice)
+    };
+    ($slice:expr, $seed:expr) => {
+        $crate::felix_felicis::shuffle_seeded($slice, $seed)
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "confundo",
+            "confundo!(slice) or confundo!(slice, seed)",
+            "confundo!(&mut deck, 42)",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Puts a value in an armed [`Locket`](slytherin_locket::Locket), which has to be
+/// [destroyed](slytherin_locket::Locket::destroy_with) with a [`BasiliskFang`](slytherin_locket::BasiliskFang)
+/// instead of being dropped. Dropping it panics in debug builds.
+/// # Example
+/// ```
+/// # use code_spells::slytherin_locket;
+/// use code_spells::slytherin_locket::BasiliskFang;
+///
+/// let diary = slytherin_locket!(String::from("Tom Riddle"));
+/// assert_eq!(diary.len(), 10);
+/// let name = diary.destroy_with(BasiliskFang::new());
+/// ```
+#[macro_export]
+macro_rules! slytherin_locket {
+    ($value:expr) => {
+        $crate::slytherin_locket::Locket::new($value)
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "slytherin_locket",
+            "slytherin_locket!(value)",
+            "slytherin_locket!(connection)",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Defines a function that remembers its results in a [`Pensieve`](pensieve::Pensieve),
+/// so that it only runs its body once for every combination of arguments.
+/// Cast it on `local fn` to get a [`LocalPensieve`](pensieve::LocalPensieve) per thread instead.
+///
+/// The arguments have to be [`Clone`], [`Hash`](core::hash::Hash) and [`Eq`], the result has to be [`Clone`],
+/// and the function can not be generic. The body may call the function recursively.
+/// # Example
+/// ```
+/// # use code_spells::pensieve;
+/// pensieve! {
+///     fn fib(n: u64) -> u64 {
+///         if n < 2 { n } else { fib(n - 1) + fib(n - 2) }
+///     }
+/// }
+/// assert_eq!(fib(90), 2_880_067_194_370_816_120);
+///
+/// pensieve! {
+///     pub local fn shout(word: String) -> String {
+///         word.to_uppercase()
+///     }
+/// }
+/// assert_eq!(shout(String::from("Legilimens")), "LEGILIMENS");
+/// ```
+#[macro_export]
+macro_rules! pensieve {
+    (
+        $(#[$attr:meta])*
+        $vis:vis fn $name:ident($($arg:ident: $ty:ty),* $(,)?) -> $ret:ty $body:block
+    ) => {
+        $(#[$attr])*
+        $vis fn $name($($arg: $ty),*) -> $ret {
+            static PENSIEVE: ::std::sync::OnceLock<$crate::pensieve::Pensieve<($($ty,)*), $ret>> =
+                ::std::sync::OnceLock::new();
+            PENSIEVE
+                .get_or_init($crate::pensieve::Pensieve::new)
+                .remember(($(::core::clone::Clone::clone(&$arg),)*), || $body)
+        }
+    };
+    (
+        $(#[$attr:meta])*
+        $vis:vis local fn $name:ident($($arg:ident: $ty:ty),* $(,)?) -> $ret:ty $body:block
+    ) => {
+        $(#[$attr])*
+        $vis fn $name($($arg: $ty),*) -> $ret {
+            ::std::thread_local! {
+                static PENSIEVE: $crate::pensieve::LocalPensieve<($($ty,)*), $ret> =
+                    $crate::pensieve::LocalPensieve::new();
+            }
+            PENSIEVE.with(|pensieve| {
+                pensieve.remember(($(::core::clone::Clone::clone(&$arg),)*), || $body)
+            })
+        }
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "pensieve",
+            "pensieve! { fn name(args) -> Type { ... } } or pensieve! { local fn name(args) -> Type { ... } }",
+            "pensieve! { fn fib(n: u64) -> u64 { if n < 2 { n } else { fib(n - 1) + fib(n - 2) } } }",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Borrows a [`Debug`](core::fmt::Debug) value and returns a [`Quibbler`](quibbler::Quibbler) that displays it
+/// in another [`Style`](quibbler::Style): `compact` (the default), `tree` or `conspiracy`.
+/// The style can also be given as an expression of type [`Style`](quibbler::Style).
+/// # Example
+/// ```
+/// # use code_spells::quibbler;
+/// let specs = Some(vec![(1, "Spectrespecs"), (2, "Nargles")]);
+/// assert_eq!(quibbler!(specs).to_string(), r#"Some([(1,"Spectrespecs"),(2,"Nargles")])"#);
+/// println!("{}", quibbler!(specs, tree));
+/// assert_eq!(quibbler!(f64::NAN, conspiracy).to_string(), "!!NaN!!");
+/// ```
+#[macro_export]
+macro_rules! quibbler {
+    ($value:expr) => {
+        $crate::quibbler::Quibbler::new(&$value, $crate::quibbler::Style::Compact)
+    };
+    ($value:expr, compact) => {
+        $crate::quibbler::Quibbler::new(&$value, $crate::quibbler::Style::Compact)
+    };
+    ($value:expr, tree) => {
+        $crate::quibbler::Quibbler::new(&$value, $crate::quibbler::Style::Tree)
+    };
+    ($value:expr, conspiracy) => {
+        $crate::quibbler::Quibbler::new(&$value, $crate::quibbler::Style::Conspiracy)
+    };
+    ($value:expr, $style:expr) => {
+        $crate::quibbler::Quibbler::new(&$value, $style)
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "quibbler",
+            "quibbler!(value), quibbler!(value, compact), quibbler!(value, tree), quibbler!(value, conspiracy) or quibbler!(value, style)",
+            "log::info!(\"{}\", quibbler!(request, tree))",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Creates a [`RoomOfHiddenThings`](room_of_hidden_things::RoomOfHiddenThings): a content-addressed store
+/// that keeps one copy of every blob of bytes hidden in it. Without arguments the room is only kept in memory,
+/// and given a directory it [opens](room_of_hidden_things::RoomOfHiddenThings::open) the room kept there,
+/// which returns a `Result`.
+/// # Example
+/// ```
+/// # use code_spells::room_of_hidden_things;
+/// let room = room_of_hidden_things!();
+/// let book = room.hide("Advanced Potion-Making")?;
+/// assert_eq!(room.hide(b"Advanced Potion-Making")?, book);
+/// assert_eq!(room.len(), 1);
+///
+/// # let directory = std::env::temp_dir().join(format!("code_spells_hidden_{}", std::process::id()));
+/// let kept = room_of_hidden_things!(&directory)?;
+/// let book = kept.hide("Advanced Potion-Making")?;
+/// drop(kept);
+/// assert!(room_of_hidden_things!(&directory)?.seek(book).is_some());
+/// # std::fs::remove_dir_all(&directory).unwrap();
+/// # Ok::<(), code_spells::room_of_hidden_things::Lost>(())
+/// ```
+#[macro_export]
+macro_rules! room_of_hidden_things {
+    () => {
+        $crate::room_of_hidden_things::RoomOfHiddenThings::new()
+    };
+    ($directory:expr $(,)?) => {
+        $crate::room_of_hidden_things::RoomOfHiddenThings::open($directory)
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "room_of_hidden_things",
+            "room_of_hidden_things!() or room_of_hidden_things!(directory)",
+            "room_of_hidden_things!(\"/seventh/floor\")",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Creates a [`RoomOfRequirement`](room_of_requirement::RoomOfRequirement): a pool that creates objects with a closure
+/// when it runs out, and takes them back for reuse. Returned objects are [tidied](room_of_requirement::Tidy),
+/// or reset with a closure if one is given.
+/// # Example
+/// ```
+/// # use code_spells::room_of_requirement;
+/// let buffers = room_of_requirement!(|| Vec::<u8>::with_capacity(4096));
+/// for _ in 0..1000 {
+///     let mut buffer = buffers.require();
+///     buffer.extend_from_slice(b"I need a place to practice");
+/// }
+/// assert_eq!(buffers.idle(), 1);
+///
+/// let brooms = room_of_requirement!(|| 100_u8, |fuel: &mut u8| *fuel = 100);
+/// *brooms.require() -= 30;
+/// assert_eq!(*brooms.require(), 100);
+/// ```
+#[macro_export]
+macro_rules! room_of_requirement {
+    ($create:expr) => {
+        $crate::room_of_requirement::RoomOfRequirement::new($create)
+    };
+    ($create:expr, $reset:expr) => {
+        $crate::room_of_requirement::RoomOfRequirement::with_reset($create, $reset)
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "room_of_requirement",
+            "room_of_requirement!(create) or room_of_requirement!(create, reset)",
+            "room_of_requirement!(|| Vec::<u8>::with_capacity(4096))",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Declares a struct for a [`Portrait`](portrait::Portrait): a static slot for a callback with a fixed signature,
+/// which answers when it is addressed with [`address_portrait!`].
+///
+/// `portrait!(Name = "name", |argument: Type, ...| -> Return)` generates a unit struct with the methods
+/// - `hang(answer)`, which hangs a callback with the signature in the portrait, and returns whether it replaced another,
+/// - `address(arguments...)`, which calls the callback, or returns an [`EmptyFrame`](portrait::EmptyFrame) error if there is none,
+/// - `take_down()` and `is_hung()`,
+///
+/// and the associated constant `NAME`. The methods have the visibility of the struct.
+/// # Example
+/// ```
+/// # use code_spells::{address_portrait, portrait};
+/// portrait!(
+///     /// Guards the entrance to the Gryffindor common room.
+///     pub FatLady = "the Fat Lady",
+///     |password: &str| -> bool
+/// );
+///
+/// FatLady.hang(|password| password == "Mimbulus mimbletonia");
+/// assert_eq!(address_portrait!(FatLady, "Mimbulus mimbletonia"), Ok(true));
+/// ```
+#[macro_export]
+macro_rules! portrait {
+    (
+        $(#[$attr:meta])*
+        $vis:vis $portrait:ident = $name:expr, |$($argument:ident: $ty:ty),* $(,)?| -> $ret:ty $(,)?
+    ) => {
+        $(#[$attr])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        $vis struct $portrait;
+
+        // A private portrait does not have to use every method.
+        #[allow(dead_code)]
+        impl $portrait {
+            /// The name of the portrait.
+            $vis const NAME: &'static str = $name;
+
+            fn frame() -> &'static $crate::portrait::Portrait<
+                dyn ::core::ops::Fn($($ty),*) -> $ret + ::core::marker::Send + ::core::marker::Sync,
+            > {
+                static FRAME: $crate::portrait::Portrait<
+                    dyn ::core::ops::Fn($($ty),*) -> $ret + ::core::marker::Send + ::core::marker::Sync,
+                > = $crate::portrait::Portrait::new($name);
+                &FRAME
+            }
+
+            /// Hangs an answer in the portrait, and returns whether it replaced another.
+            $vis fn hang(
+                self,
+                answer: impl ::core::ops::Fn($($ty),*) -> $ret
+                    + ::core::marker::Send
+                    + ::core::marker::Sync
+                    + 'static,
+            ) -> bool {
+                Self::frame().hang(::std::sync::Arc::new(answer)).is_some()
+            }
+
+            /// Takes the answer down from the portrait, and returns whether there was one.
+            $vis fn take_down(self) -> bool {
+                Self::frame().take_down().is_some()
+            }
+
+            /// Returns whether an answer is hung in the portrait.
+            $vis fn is_hung(self) -> bool {
+                Self::frame().is_hung()
+            }
+
+            /// Calls the answer of the portrait.
+            /// # Errors
+            /// Returns an error if no answer is hung in the portrait.
+            $vis fn address(
+                self,
+                $($argument: $ty),*
+            ) -> ::core::result::Result<$ret, $crate::portrait::EmptyFrame> {
+                Self::frame().address(|answer| answer($($argument),*))
+            }
+        }
+    };
+    (
+        $(#[$attr:meta])*
+        $vis:vis $portrait:ident = $name:expr, |$($argument:ident: $ty:ty),* $(,)?| $(,)?
+    ) => {
+        $crate::portrait! { $(#[$attr])* $vis $portrait = $name, |$($argument: $ty),*| -> () }
+    };
+    ($(#[$attr:meta])* $vis:vis $portrait:ident = $name:expr, || -> $ret:ty $(,)?) => {
+        $crate::portrait! { $(#[$attr])* $vis $portrait = $name, | | -> $ret }
+    };
+    ($(#[$attr:meta])* $vis:vis $portrait:ident = $name:expr, || $(,)?) => {
+        $crate::portrait! { $(#[$attr])* $vis $portrait = $name, | | -> () }
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "portrait",
+            "portrait!(Name = \"name\", |argument: Type, ...| -> Return)",
+            "portrait!(pub FatLady = \"the Fat Lady\", |password: &str| -> bool)",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Addresses a portrait that was declared with [`portrait!`]: calls the answer that is hung in it with the given arguments.
+/// Returns an [`EmptyFrame`](portrait::EmptyFrame) error if no answer is hung in it.
+/// # Example
+/// ```
+/// # use code_spells::{address_portrait, portrait};
+/// portrait!(Phineas = "Phineas Nigellus", || -> &'static str);
+///
+/// assert!(address_portrait!(Phineas).is_err());
+/// Phineas.hang(|| "I am the least popular headmaster Hogwarts has ever had");
+/// assert!(address_portrait!(Phineas).unwrap().starts_with("I am"));
+/// ```
+#[macro_export]
+macro_rules! address_portrait {
+    ($portrait:expr $(, $argument:expr)* $(,)?) => {
+        $portrait.address($($argument),*)
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "address_portrait",
+            "address_portrait!(Portrait, arguments...)",
+            "address_portrait!(FatLady, \"caput draconis\")",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Opens a [`Gringotts`](gringotts::Gringotts) arena, optionally with room for a number of values in its first vault.
+/// Values that are [deposited](gringotts::Gringotts::deposit) in it live as long as the arena, and are dropped together with it.
+/// # Example
+/// ```
+/// # use code_spells::gringotts;
+/// let bank = gringotts!(64);
+/// let galleons = bank.deposit(713);
+/// let knuts = bank.deposit_iter([1, 2, 3]);
+/// assert_eq!((*galleons, knuts.len()), (713, 3));
+/// ```
+#[macro_export]
+macro_rules! gringotts {
+    () => {
+        $crate::gringotts::Gringotts::new()
+    };
+    ($capacity:expr) => {
+        $crate::gringotts::Gringotts::with_capacity($capacity)
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "gringotts",
+            "gringotts!() or gringotts!(capacity)",
+            "gringotts!(1024)",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Hides a secret in a [`Fidelius`](fidelius::Fidelius) keeper, which never prints it,
+/// only [reveals](fidelius::Fidelius::reveal_to) it to a closure, and wipes it when dropped.
+/// # Example
+/// ```
+/// # use code_spells::fidelius;
+/// let keeper = fidelius!(*b"Shell Cottage");
+/// assert_eq!(keeper.to_string(), "[the secret is kept]");
+/// assert_eq!(keeper.reveal_to(|secret| secret[0]), b'S');
+/// ```
+#[macro_export]
+macro_rules! fidelius {
+    ($secret:expr) => {
+        $crate::fidelius::Fidelius::new($secret)
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "fidelius",
+            "fidelius!(secret)",
+            "fidelius!(String::from(\"12 Grimmauld Place\"))",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Runs a block in a [time room](mod@time_room), where time is virtual: the spells that wait
+/// or read the time on this thread use a [`VirtualClock`](time_room::VirtualClock) that only moves
+/// when it is slept on or [advanced](time_room::advance).
+/// With an `Arc` of a [`Clock`](time_room::Clock) as the first argument, the block uses that clock instead,
+/// e.g. to join the virtual clock of another time room.
+/// # Example
+/// ```
+/// use code_spells::{petrificus_totalus, time_room};
+/// use code_spells::time_room::VirtualClock;
+/// use std::sync::Arc;
+/// use std::time::Duration;
+///
+/// let slept = time_room!({
+///     let start = time_room::now();
+///     petrificus_totalus!(Duration::from_secs(60));
+///     time_room::now() - start
+/// });
+/// assert_eq!(slept, Duration::from_secs(60));
+///
+/// let clock = Arc::new(VirtualClock::new());
+/// time_room!(clock, { petrificus_totalus!(Duration::from_secs(60)) });
+/// time_room!(clock, { time_room::advance(Duration::from_secs(1)) });
+/// assert_eq!(clock.elapsed(), Duration::from_secs(61));
+/// ```
+#[macro_export]
+macro_rules! time_room {
+    ($body:block) => {{
+        let _room = $crate::time_room::enter();
+        $body
+    }};
+    ($clock:expr, $body:block) => {{
+        let _room = $crate::time_room::enter_with(::std::sync::Arc::clone(&$clock));
+        $body
+    }};
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "time_room",
+            "time_room!({ work }) or time_room!(clock, { work })",
+            "time_room!({ petrificus_totalus!(Duration::from_secs(60)) })",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Splits data into a number of [`Horcrux`](horcrux::Horcrux) shares, which are all needed to
+/// [reassemble](horcrux::reassemble) it.
+///
+/// Needs the `rand` feature.
+/// # Example
+/// ```
+/// # use code_spells::horcrux;
+/// let horcruxes = horcrux!("the diary", 7);
+/// assert_eq!(code_spells::horcrux::reassemble(&horcruxes).unwrap(), b"the diary");
+/// assert!(code_spells::horcrux::reassemble(&horcruxes[1..]).is_err());
+/// ```
+#[cfg(feature = "rand")]
+#[macro_export]
+macro_rules! horcrux {
+    ($data:expr, $count:expr) => {
+        $crate::horcrux::split($data, $count)
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "horcrux",
+            "horcrux!(data, count)",
+            "horcrux!(b\"Tom Marvolo Riddle\", 7)",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Makes a prophecy: returns a [`Prophet`](prophecy_orb::Prophet) that fulfills it once, and a
+/// [`ProphecyOrb`](prophecy_orb::ProphecyOrb) that hears it once.
+/// # Example
+/// ```
+/// # use code_spells::prophecy_orb;
+/// let (prophet, orb) = prophecy_orb!();
+/// std::thread::spawn(move || prophet.fulfill(1980));
+/// assert_eq!(orb.hear(), Ok(1980));
+/// assert!(orb.hear().is_err());
+/// ```
+#[macro_export]
+macro_rules! prophecy_orb {
+    () => {
+        $crate::prophecy_orb::prophecy()
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "prophecy_orb",
+            "prophecy_orb!()",
+            "let (prophet, orb) = prophecy_orb!();",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Starts recording [footprints](crate::footprints!) on the global [Marauder's Map](marauders_map::MaraudersMap).
+/// # Example
+/// ```
+/// # use code_spells::{mischief_managed, solemnly_swear};
+/// solemnly_swear!();
+/// assert!(mischief_managed!(map).is_empty());
+/// ```
+#[macro_export]
+macro_rules! solemnly_swear {
+    () => {
+        $crate::marauders_map::solemnly_swear()
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "solemnly_swear",
+            "solemnly_swear!()",
+            "solemnly_swear!();",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Evaluates an expression on a [trail](marauders_map::Trail) of the global [Marauder's Map](marauders_map::MaraudersMap),
+/// which records when it was entered and left if the map is [sworn](crate::solemnly_swear!).
+/// The trail is named after the expression, unless it is given a name.
+/// # Example
+/// ```
+/// # use code_spells::{footprints, mischief_managed, solemnly_swear};
+/// solemnly_swear!();
+/// let password = footprints!("the Fat Lady", "Fortuna Major");
+/// assert_eq!(password, "Fortuna Major");
+/// footprints!(password.len());
+/// assert_eq!(mischief_managed!(map).footprints()[1].name(), "password.len()");
+/// ```
+#[macro_export]
+macro_rules! footprints {
+    ($name:literal, $body:expr) => {{
+        let _trail = $crate::marauders_map::trail($name);
+        $body
+    }};
+    ($body:expr) => {{
+        let _trail = $crate::marauders_map::trail(::core::stringify!($body));
+        $body
+    }};
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "footprints",
+            "footprints!(expression) or footprints!(\"name\", expression)",
+            "footprints!(\"Honeydukes\", buy_sweets())",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Takes back the [oath](marauders_oath) of [`i_solemnly_swear!`](i_solemnly_swear), which disarms the dark spells.
+///
+/// With `map` it instead stops recording footprints on the global [Marauder's Map](marauders_map::MaraudersMap),
+/// and returns the [`Trace`](marauders_map::Trace) of what was recorded.
+/// # Examples
+/// ```
+/// # use code_spells::{i_solemnly_swear, mischief_managed};
+/// i_solemnly_swear!();
+/// mischief_managed!();
+/// assert!(!code_spells::marauders_oath::is_sworn());
+/// ```
+/// ```
+/// # use code_spells::{footprints, mischief_managed, solemnly_swear};
+/// solemnly_swear!();
+/// footprints!("Hogsmeade", ());
+/// let trace = mischief_managed!(map);
+/// println!("{}", trace);
+/// assert_eq!(trace.len(), 1);
+/// ```
+#[macro_export]
+macro_rules! mischief_managed {
+    () => {
+        $crate::marauders_oath::disarm()
+    };
+    (map) => {
+        $crate::marauders_map::mischief_managed()
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "mischief_managed",
+            "mischief_managed!() or mischief_managed!(map)",
+            "let trace = mischief_managed!(map);",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Lets a [`Ghoul`](ghoul::Ghoul) haunt the background with CPU, allocation and lock contention load
+/// until the returned [`Haunting`](ghoul::Haunting) is dropped.
+/// Every `knob: value` pair calls the builder method of the same name, and `rattle: &mutex` can be repeated.
+/// # Example
+/// ```
+/// # use code_spells::ghoul_in_the_attic;
+/// use std::sync::{Arc, Mutex};
+///
+/// let burrow = Arc::new(Mutex::new(0));
+/// {
+///     let _ghoul = ghoul_in_the_attic!(spin_threads: 2, churn_bytes: 1 << 16, rattle: &burrow);
+///     *burrow.lock().unwrap() += 1;
+/// }
+/// let _default = ghoul_in_the_attic!();
+/// ```
+#[macro_export]
+macro_rules! ghoul_in_the_attic {
+    () => {
+        $crate::ghoul::Ghoul::new().haunt()
+    };
+    ($($knob:ident: $value:expr),+ $(,)?) => {
+        $crate::ghoul::Ghoul::new()$(.$knob($value))+.haunt()
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "ghoul_in_the_attic",
+            "ghoul_in_the_attic!() or ghoul_in_the_attic!(knob: value, ...)",
+            "ghoul_in_the_attic!(spin_threads: 2, rattle: &shared_mutex)",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Installs a [`Howler`](howler_mail::Howler) panic hook that screams every panic message, and returns a
+/// [`Howling`](howler_mail::Howling) guard that puts the previous hook back when it is dropped.
+/// Every `knob: value` pair calls the builder method of the same name.
+/// Cast `howler!(restore)` to put back the hook that the most recent howler replaced without a guard.
+/// # Example
+/// ```
+/// # use code_spells::howler;
+/// {
+///     let _howling = howler!(red: false, backtrace: true);
+///     let _ = std::panic::catch_unwind(|| panic!("Ronald Weasley, how dare you steal that car"));
+/// }
+/// howler!(format: "{MESSAGE}!!!").keep();
+/// assert!(howler!(restore));
+/// ```
+#[macro_export]
+macro_rules! howler {
+    (restore) => {
+        $crate::howler_mail::restore()
+    };
+    () => {
+        $crate::howler_mail::Howler::new().install()
+    };
+    ($($knob:ident: $value:expr),+ $(,)?) => {
+        $crate::howler_mail::Howler::new()$(.$knob($value))+.install()
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "howler",
+            "howler!(), howler!(knob: value, ...) or howler!(restore)",
+            "let _howling = howler!(red: false, backtrace: true);",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Swears the [oath](marauders_oath) that arms the dark spells, until [`mischief_managed!`](mischief_managed) takes it back.
+/// With the `marauders-oath` feature, [`imperio!`](imperio) and [`aparecium!`](aparecium) panic in debug builds
+/// unless the oath has been sworn.
+/// # Example
+/// ```
+/// # use code_spells::{evanesco, aparecium, i_solemnly_swear};
+/// i_solemnly_swear!();
+/// let map = evanesco!(Box::new("the Marauder's Map"));
+/// assert_eq!(*unsafe { aparecium!(map) }, "the Marauder's Map");
+/// ```
+#[macro_export]
+macro_rules! i_solemnly_swear {
+    () => {
+        $crate::marauders_oath::swear()
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "i_solemnly_swear",
+            "i_solemnly_swear!()",
+            "i_solemnly_swear!();",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Truth serum for assertions: a value under veritaserum can not lie.
+/// - `veritaserum!(condition)` is an alias for [`assert!`].
+/// - `veritaserum!(a == b)` and `veritaserum!(a != b)` are aliases for [`assert_eq!`] and [`assert_ne!`],
+///   so a failure shows both sides.
+/// - `veritaserum!(debug ...)` does the same with [`debug_assert!`], [`debug_assert_eq!`] and [`debug_assert_ne!`].
+///
+/// A custom message with format arguments can follow the condition, like for the std macros.
+/// A condition that combines comparisons with `&&` or `||` is asserted as a whole with [`assert!`].
+/// # Examples
+/// ```
+/// # use code_spells::veritaserum;
+/// let culprit = "Peter Pettigrew";
+/// veritaserum!(culprit.starts_with("Peter"));
+/// veritaserum!(culprit.len() == 15, "{} has a long name", culprit);
+/// veritaserum!(culprit != "Sirius Black");
+/// veritaserum!(debug culprit.contains(' ') && !culprit.is_empty());
+/// ```
+/// ```should_panic
+/// # use code_spells::veritaserum;
+/// let betrayed_the_potters = "Peter Pettigrew";
+/// // Panics with `left: "Peter Pettigrew"` and `right: "Sirius Black"`.
+/// veritaserum!(betrayed_the_potters == "Sirius Black");
+/// ```
+#[macro_export]
+macro_rules! veritaserum {
+    (@sip [$assert:ident $eq:ident $ne:ident] $all:tt [$($left:tt)+] == $($rest:tt)+) => {
+        $crate::veritaserum!(@swallow [$assert $eq] $all [$($left)+] [] $($rest)+)
+    };
+    (@sip [$assert:ident $eq:ident $ne:ident] $all:tt [$($left:tt)+] != $($rest:tt)+) => {
+        $crate::veritaserum!(@swallow [$assert $ne] $all [$($left)+] [] $($rest)+)
+    };
+    (@sip [$assert:ident $($_:ident)*] [$($all:tt)+] [$($left:tt)*] && $($rest:tt)*) => {
+        ::core::$assert!($($all)+)
+    };
+    (@sip [$assert:ident $($_:ident)*] [$($all:tt)+] [$($left:tt)*] || $($rest:tt)*) => {
+        ::core::$assert!($($all)+)
+    };
+    (@sip [$assert:ident $($_:ident)*] [$($all:tt)+] [$($left:tt)*] , $($rest:tt)*) => {
+        ::core::$assert!($($all)+)
+    };
+    (@sip [$assert:ident $($_:ident)*] [$($all:tt)+] [$($left:tt)*]) => {
+        ::core::$assert!($($all)+)
+    };
+    (@sip $spells:tt $all:tt [$($left:tt)*] $next:tt $($rest:tt)*) => {
+        $crate::veritaserum!(@sip $spells $all [$($left)* $next] $($rest)*)
+    };
+    (@swallow [$assert:ident $cmp:ident] $all:tt [$($left:tt)+] [$($right:tt)+] , $($message:tt)+) => {
+        ::core::$cmp!($($left)+, $($right)+, $($message)+)
+    };
+    (@swallow [$assert:ident $cmp:ident] $all:tt [$($left:tt)+] [$($right:tt)+] $(,)?) => {
+        ::core::$cmp!($($left)+, $($right)+)
+    };
+    (@swallow [$assert:ident $cmp:ident] [$($all:tt)+] $left:tt [$($right:tt)*] && $($rest:tt)*) => {
+        ::core::$assert!($($all)+)
+    };
+    (@swallow [$assert:ident $cmp:ident] [$($all:tt)+] $left:tt [$($right:tt)*] || $($rest:tt)*) => {
+        ::core::$assert!($($all)+)
+    };
+    (@swallow [$assert:ident $cmp:ident] [$($all:tt)+] $left:tt [$($right:tt)*] == $($rest:tt)*) => {
+        ::core::$assert!($($all)+)
+    };
+    (@swallow [$assert:ident $cmp:ident] [$($all:tt)+] $left:tt [$($right:tt)*] != $($rest:tt)*) => {
+        ::core::$assert!($($all)+)
+    };
+    (@swallow $spells:tt $all:tt $left:tt [$($right:tt)*] $next:tt $($rest:tt)*) => {
+        $crate::veritaserum!(@swallow $spells $all $left [$($right)* $next] $($rest)*)
+    };
+    (debug $($truth:tt)+) => {
+        $crate::veritaserum!(
+            @sip [debug_assert debug_assert_eq debug_assert_ne] [$($truth)+] [] $($truth)+
+        )
+    };
+    ($($truth:tt)+) => {
+        $crate::veritaserum!(@sip [assert assert_eq assert_ne] [$($truth)+] [] $($truth)+)
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "veritaserum",
+            "veritaserum!(condition), veritaserum!(a == b), veritaserum!(a != b) or veritaserum!(debug ...), optionally followed by a message",
+            "veritaserum!(culprit == \"Peter Pettigrew\", \"the rat did it\")",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Makes one of the test allocators of [`elder_wand`](mod@elder_wand), to hand to
+/// [`elder_wand_vec!`](elder_wand_vec) and [`elder_wand_box!`](elder_wand_box).
+/// - `elder_wand!(counting)` makes a [`CountingWand`](elder_wand::CountingWand) that counts allocations.
+/// - `elder_wand!(failing after n)` makes a [`FailingWand`](elder_wand::FailingWand) that fails every allocation after the first `n`.
+///
+/// Both wrap the global allocator. Needs the `allocator-api` feature.
+/// # Example
+/// ```
+/// # use code_spells::{elder_wand, elder_wand_vec};
+/// let wand = elder_wand!(failing after 1);
+/// let mut wands = elder_wand_vec!(in &wand; "elder");
+/// assert!(wands.try_reserve(10).is_err());
+/// assert_eq!(wand.failures(), 1);
+/// ```
+#[cfg(feature = "allocator-api")]
+#[macro_export]
+macro_rules! elder_wand {
+    (counting) => {
+        $crate::elder_wand::CountingWand::new()
+    };
+    (failing after $charges:expr) => {
+        $crate::elder_wand::FailingWand::after($charges)
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "elder_wand",
+            "elder_wand!(counting) or elder_wand!(failing after n)",
+            "let wand = elder_wand!(failing after 3);",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Makes a [`Vec`](elder_wand::Vec) that allocates with the given [`Allocator`](elder_wand::Allocator),
+/// with the syntax of [`vec!`] after `in allocator;`.
+/// Prefix it with `try` to get a [`TryReserveError`](elder_wand::TryReserveError) instead of a panic
+/// if the elements can not be allocated. Needs the `allocator-api` feature.
+/// # Example
+/// ```
+/// # use code_spells::{elder_wand, elder_wand_vec};
+/// use code_spells::elder_wand::Global;
+///
+/// let mut owners = elder_wand_vec!(in Global);
+/// owners.push("Antioch Peverell");
+/// assert_eq!(elder_wand_vec!(in Global; 1, 2, 3), [1, 2, 3]);
+///
+/// let wand = elder_wand!(failing after 0);
+/// assert!(elder_wand_vec!(try in &wand; "Grindelwald"; 2).is_err());
+/// ```
+#[cfg(feature = "allocator-api")]
+#[macro_export]
+macro_rules! elder_wand_vec {
+    (in $wand:expr $(;)?) => {
+        $crate::elder_wand::Vec::new_in($wand)
+    };
+    (in $wand:expr; $element:expr; $n:expr) => {{
+        let (element, n) = ($element, $n);
+        let mut vec = $crate::elder_wand::Vec::with_capacity_in(n, $wand);
+        vec.resize(n, element);
+        vec
+    }};
+    (in $wand:expr; $($element:expr),+ $(,)?) => {{
+        let mut vec = $crate::elder_wand::Vec::new_in($wand);
+        vec.extend([$($element),+]);
+        vec
+    }};
+    (try in $wand:expr; $element:expr; $n:expr) => {{
+        let (element, n) = ($element, $n);
+        let mut vec = $crate::elder_wand::Vec::new_in($wand);
+        vec.try_reserve_exact(n).map(|()| {
+            vec.resize(n, element);
+            vec
+        })
+    }};
+    (try in $wand:expr; $($element:expr),+ $(,)?) => {{
+        let elements = [$($element),+];
+        let mut vec = $crate::elder_wand::Vec::new_in($wand);
+        vec.try_reserve_exact(elements.len()).map(|()| {
+            vec.extend(elements);
+            vec
+        })
+    }};
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "elder_wand_vec",
+            "elder_wand_vec!(in allocator), elder_wand_vec!(in allocator; elements...) or elder_wand_vec!(in allocator; element; n), optionally prefixed with try",
+            "elder_wand_vec!(try in &wand; 0_u8; 1024)",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Alias for [`Box::new_in`](elder_wand::Box::new_in), which puts a value in a [`Box`](elder_wand::Box) that is
+/// allocated with the given [`Allocator`](elder_wand::Allocator).
+/// Prefix it with `try` to get an [`AllocError`](elder_wand::AllocError) instead of a panic
+/// if the box can not be allocated. Needs the `allocator-api` feature.
+/// # Example
+/// ```
+/// # use code_spells::{elder_wand, elder_wand_box};
+/// let wand = elder_wand!(counting);
+/// let stone = elder_wand_box!(in &wand; "the Resurrection Stone");
+/// assert_eq!(wand.allocations(), 1);
+/// assert!(elder_wand_box!(try in &wand; *stone).is_ok());
+/// ```
+#[cfg(feature = "allocator-api")]
+#[macro_export]
+macro_rules! elder_wand_box {
+    (in $wand:expr; $value:expr) => {
+        $crate::elder_wand::Box::new_in($value, $wand)
+    };
+    (try in $wand:expr; $value:expr) => {
+        $crate::elder_wand::Box::try_new_in($value, $wand)
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "elder_wand_box",
+            "elder_wand_box!(in allocator; value) or elder_wand_box!(try in allocator; value)",
+            "elder_wand_box!(try in &wand; cloak)",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Puts a value behind a [`Veil`](veil_of_death::Veil), which tells the observers that register with it
+/// when the value is dropped.
+/// # Example
+/// ```
+/// # use code_spells::veil_of_death;
+/// let sirius = veil_of_death!(String::from("Padfoot"));
+/// sirius.on_passing(|passing| assert_eq!(passing.accesses(), 1));
+/// assert_eq!(*sirius, "Padfoot");
+/// ```
+#[macro_export]
+macro_rules! veil_of_death {
+    ($value:expr) => {
+        $crate::veil_of_death::Veil::new($value)
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "veil_of_death",
+            "veil_of_death!(value)",
+            "let sirius = veil_of_death!(String::from(\"Padfoot\"));",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Conjures a [`Patronus`](patronus::Patronus) that guards the scope: it runs the closure when it is dropped,
+/// also on an early return or a panic. With `on_panic` it only runs the closure if the scope is left by a panic.
+/// # Example
+/// ```
+/// # use code_spells::patronus;
+/// use std::cell::RefCell;
+///
+/// let spells = RefCell::new(Vec::new());
+/// {
+///     let _stag = patronus!(|| spells.borrow_mut().push("expecto patronum"));
+///     let _otter = patronus!(on_panic || spells.borrow_mut().push("riddikulus"));
+/// }
+/// assert_eq!(*spells.borrow(), ["expecto patronum"]);
+/// ```
+#[macro_export]
+macro_rules! patronus {
+    (on_panic $charm:expr) => {
+        $crate::patronus::Patronus::on_panic($charm)
+    };
+    ($charm:expr) => {
+        $crate::patronus::Patronus::new($charm)
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "patronus",
+            "patronus!(closure) or patronus!(on_panic closure)",
+            "let _stag = patronus!(|| cleanup());",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Hashes a string at compile time with [`taboo::taboo_hash`], which computes the same hash at runtime.
+/// The string must be a constant expression.
+/// # Example
+/// ```
+/// # use code_spells::taboo_hash;
+/// const THE_DARK_LORD: u64 = taboo_hash!("Voldemort");
+/// assert_eq!(code_spells::taboo::taboo_hash("Voldemort"), THE_DARK_LORD);
+/// ```
+#[macro_export]
+macro_rules! taboo_hash {
+    ($name:expr) => {{
+        const TABOO: u64 = $crate::taboo::taboo_hash($name);
+        TABOO
+    }};
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "taboo_hash",
+            "taboo_hash!(\"name\")",
+            "const TABOO: u64 = taboo_hash!(\"Voldemort\");",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Dispatches on the [hash](taboo::taboo_hash) of a string: evaluates the arm of the name that hashes the same,
+/// or the `_` arm if none does. The names are only stored in the program as their hashes,
+/// and the spell fails to compile if two of them have the same hash.
+///
+/// A string that is not one of the names, but has the same hash as one of them, also selects its arm.
+/// # Examples
+/// ```
+/// # use code_spells::taboo_match;
+/// fn speak(name: &str) -> &'static str {
+///     taboo_match!(name, {
+///         "Voldemort" => "the taboo is broken, the Snatchers are coming",
+///         "Tom" => "a common name",
+///         _ => "nothing happens",
+///     })
+/// }
+/// assert_eq!(speak("Voldemort"), "the taboo is broken, the Snatchers are coming");
+/// assert_eq!(speak("Harry"), "nothing happens");
+/// ```
+/// ```compile_fail
+/// # use code_spells::taboo_match;
+/// let twice = taboo_match!("Tom", { "Tom" => 1, "Tom" => 2, _ => 0 });
+/// ```
+#[macro_export]
+macro_rules! taboo_match {
+    ($spoken:expr, { $($name:literal => $arm:expr,)* _ => $default:expr $(,)? }) => {{
+        const _: () = $crate::taboo::forbid_collisions(&[$($crate::taboo::taboo_hash($name)),*]);
+        match $crate::taboo::taboo_hash($spoken) {
+            $(hash if hash == $crate::taboo_hash!($name) => $arm,)*
+            _ => $default,
+        }
+    }};
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "taboo_match",
+            "taboo_match!(string, { \"name\" => arm, ..., _ => default })",
+            "taboo_match!(spoken, { \"Voldemort\" => snatch(), _ => () })",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Pairs up two values that should be equal in [`Twins`](weasley_twins::Twins), which compares them
+/// on every access and reports when they diverge.
+/// Every `knob: value` pair after the twins calls the builder method of the same name.
+/// # Example
+/// ```
+/// # use code_spells::weasley_twins;
+/// use code_spells::weasley_twins::Policy;
+///
+/// let old = |galleons: u32| galleons * 17;
+/// let new = |galleons: u32| galleons * 17 * 29 / 29;
+/// let sickles = weasley_twins!(old(1000), new(1000), policy: Policy::Panic);
+/// assert_eq!(*sickles, 17_000);
+/// ```
+#[macro_export]
+macro_rules! weasley_twins {
+    ($fred:expr, $george:expr $(, $knob:ident: $value:expr)* $(,)?) => {
+        $crate::weasley_twins::Twins::new($fred, $george)$(.$knob($value))*
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "weasley_twins",
+            "weasley_twins!(fred, george) or weasley_twins!(fred, george, knob: value, ...)",
+            "weasley_twins!(old(x), new(x), policy: Policy::George)",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Opens a [`Workshop`](dervish_and_banges::Workshop) that builds a shared resource with the given factory,
+/// and repairs or replaces it when a borrower reports it as faulty.
+/// The knobs after the factory call the builder methods of the same name,
+/// [`time_turner`](dervish_and_banges::Workshop::time_turner) and [`max_repairs`](dervish_and_banges::Workshop::max_repairs).
+/// # Example
+/// ```
+/// # use code_spells::dervish_and_banges;
+/// use code_spells::time_turner::TimeTurner;
+/// use std::time::Duration;
+///
+/// let workshop = dervish_and_banges!(
+///     || "sneakoscope".parse::<String>(),
+///     time_turner: TimeTurner::new().attempts(3).delay(Duration::from_millis(1)),
+///     max_repairs: 5,
+/// );
+/// let sneakoscope = workshop.borrow()?;
+/// if sneakoscope.is_empty() {
+///     sneakoscope.report_faulty();
+/// }
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[macro_export]
+macro_rules! dervish_and_banges {
+    ($factory:expr $(, $knob:ident: $value:expr)* $(,)?) => {
+        $crate::dervish_and_banges::Workshop::new($factory)$(.$knob($value))*
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "dervish_and_banges",
+            "dervish_and_banges!(factory) or dervish_and_banges!(factory, knob: value, ...)",
+            "dervish_and_banges!(|| connect(), max_repairs: 3)",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Casts a raw-pointer spell from [`knockturn_alley`](mod@knockturn_alley), which records the cast in its ledger.
+/// `aparecium` is [`Box::from_raw`], `borrowck_bypass` is [`slice::from_raw_parts`](core::slice::from_raw_parts),
+/// `borrowck_bypass mut` is [`slice::from_raw_parts_mut`](core::slice::from_raw_parts_mut)
+/// and `non_null` is [`NonNull::new_unchecked`](core::ptr::NonNull::new_unchecked).
+/// This is `unsafe` like the functions it calls. Needs the `dark-arts` feature.
+/// # Example
+/// ```
+/// # use code_spells::knockturn_alley;
+/// # code_spells::i_solemnly_swear!();
+/// let mut cursed_opals = [3_u8, 1, 4];
+/// let necklace = unsafe { knockturn_alley!(non_null cursed_opals.as_mut_ptr()) };
+/// let opals = unsafe { knockturn_alley!(borrowck_bypass mut necklace.as_ptr(), 3) };
+/// opals.sort();
+/// assert_eq!(cursed_opals, [1, 3, 4]);
+/// ```
+#[cfg(feature = "dark-arts")]
+#[macro_export]
+macro_rules! knockturn_alley {
+    (aparecium $invisible:expr) => {{
+        $crate::__marauders_oath!("knockturn_alley");
+        $crate::knockturn_alley::aparecium($invisible)
+    }};
+    (borrowck_bypass mut $data:expr, $len:expr) => {{
+        $crate::__marauders_oath!("knockturn_alley");
+        $crate::knockturn_alley::borrowck_bypass_mut($data, $len)
+    }};
+    (borrowck_bypass $data:expr, $len:expr) => {{
+        $crate::__marauders_oath!("knockturn_alley");
+        $crate::knockturn_alley::borrowck_bypass($data, $len)
+    }};
+    (non_null $pointer:expr) => {{
+        $crate::__marauders_oath!("knockturn_alley");
+        $crate::knockturn_alley::non_null($pointer)
+    }};
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "knockturn_alley",
+            "unsafe { knockturn_alley!(aparecium pointer) }, or with borrowck_bypass [mut] pointer, len, or non_null pointer",
+            "unsafe { knockturn_alley!(borrowck_bypass hand.as_ptr(), 5) }",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Engorges a file: maps it into memory, see [`engorgement_charm`](mod@engorgement_charm).
+/// - `engorgement_charm!(path)` is a read-only map, see [`engorge`](engorgement_charm::engorge).
+/// - `engorgement_charm!(mut path)` is a map whose changes are written to the file,
+///   see [`engorge_mut`](engorgement_charm::engorge_mut).
+///
+/// Both forms are `unsafe`, as the file can change under the map, so they have to be cast inside
+/// [`unforgivable!`](unforgivable).
+///
+/// Both return a [`Result`] with a [`Deflated`](engorgement_charm::Deflated) error. Needs the `mmap` feature.
+/// # Example
+/// ```
+/// # use code_spells::{engorgement_charm, unforgivable};
+/// # let path = std::env::temp_dir().join(format!("code_spells_engorgement_doc_{}", std::process::id()));
+/// # std::fs::write(&path, b"blown up")?;
+/// let mut aunt = unforgivable!("nothing else has the file open while it is engorged" => {
+///     engorgement_charm!(mut &path)
+/// })?;
+/// aunt[..4].copy_from_slice(b"BLOW");
+/// aunt.flush()?;
+/// # drop(aunt);
+/// let aunt = unforgivable!("nothing else writes to the file while it is engorged" => {
+///     engorgement_charm!(&path)
+/// })?;
+/// assert_eq!(&aunt[..], b"BLOWn up");
+/// # drop(aunt);
+/// # std::fs::remove_file(&path)?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+/// Neither form compiles outside of `unforgivable!`.
+/// ```compile_fail
+/// # use code_spells::engorgement_charm;
+/// let aunt = engorgement_charm!("aunt_marge.bin");
+/// ```
+/// ```compile_fail
+/// # use code_spells::engorgement_charm;
+/// let aunt = engorgement_charm!(mut "aunt_marge.bin");
+/// ```
+#[cfg(feature = "mmap")]
+#[macro_export]
+macro_rules! engorgement_charm {
+    (mut $path:expr) => {
+        $crate::engorgement_charm::engorge_mut($path)
+    };
+    ($path:expr) => {
+        $crate::engorgement_charm::engorge($path)
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "engorgement_charm",
+            "unforgivable! { engorgement_charm!(path) } or unforgivable! { engorgement_charm!(mut path) }",
+            "unforgivable! { engorgement_charm!(\"aunt_marge.bin\") }",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Marks a mutation point: evaluates to `original`, unless the point is confunded by a test in
+/// [`owl_exam_results`](mod@owl_exam_results), in which case it evaluates to `mutant`.
+/// Needs the `mutation` feature to do anything but evaluate `original`.
+/// # Example
+/// ```
+/// # use code_spells::confunded_if_mutating;
+/// fn is_of_age(age: u8) -> bool {
+///     confunded_if_mutating!(age >= 17, age > 17)
+/// }
+/// assert!(is_of_age(17));
+/// ```
+#[cfg(feature = "mutation")]
+#[macro_export]
+macro_rules! confunded_if_mutating {
+    ($original:expr, $mutant:expr $(,)?) => {
+        if $crate::owl_exam_results::confunded(
+            ::core::stringify!($original),
+            ::core::stringify!($mutant),
+        ) {
+            $mutant
+        } else {
+            $original
+        }
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "confunded_if_mutating",
+            "confunded_if_mutating!(original, mutant)",
+            "confunded_if_mutating!(age >= 17, age > 17)",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Marks a mutation point: evaluates to `original`, unless the point is confunded by a test in
+/// `owl_exam_results`, in which case it evaluates to `mutant`.
+/// Needs the `mutation` feature to do anything but evaluate `original`.
+/// # Example
+/// ```
+/// # use code_spells::confunded_if_mutating;
+/// fn is_of_age(age: u8) -> bool {
+///     confunded_if_mutating!(age >= 17, age > 17)
+/// }
+/// assert!(is_of_age(17));
+/// ```
+#[cfg(not(feature = "mutation"))]
+#[macro_export]
+macro_rules! confunded_if_mutating {
+    ($original:expr, $mutant:expr $(,)?) => {
+        $original
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "confunded_if_mutating",
+            "confunded_if_mutating!(original, mutant)",
+            "confunded_if_mutating!(age >= 17, age > 17)",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Returns every mutation point of [`confunded_if_mutating!`](confunded_if_mutating) that has been reached,
+/// or, given a test, grades the test on whether it catches the mutant of each point it reaches,
+/// see [`sit_exam`](owl_exam_results::sit_exam). Needs the `mutation` feature.
+/// # Example
+/// ```
+/// # use code_spells::{confunded_if_mutating, owl_exam_results};
+/// fn is_of_age(age: u8) -> bool {
+///     confunded_if_mutating!(age >= 17, age > 17)
+/// }
+/// let results = owl_exam_results!(|| assert!(is_of_age(17)));
+/// assert!(results.passed());
+/// assert_eq!(owl_exam_results!()[0].original(), "age >= 17");
+/// ```
+#[cfg(feature = "mutation")]
+#[macro_export]
+macro_rules! owl_exam_results {
+    () => {
+        $crate::owl_exam_results::reached()
+    };
+    ($test:expr) => {
+        $crate::owl_exam_results::sit_exam($test)
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "owl_exam_results",
+            "owl_exam_results!() or owl_exam_results!(test)",
+            "owl_exam_results!(|| assert!(is_of_age(17)))",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Starts the current executable as a helper process that runs `entry`, and returns a
+/// [`FordAnglia`](ford_anglia::FordAnglia) that talks to it, see [`ford_anglia`](mod@ford_anglia).
+/// The helper is passed the arguments of the program, unless other `args` are given.
+/// # Example
+/// ```
+/// # use code_spells::ford_anglia;
+/// use code_spells::ford_anglia::Cabin;
+///
+/// let mut car = ford_anglia!("Whomping Willow", |cabin: Cabin<String, String>| {
+///     for branch in cabin.iter() {
+///         cabin.send(&branch.to_uppercase()).unwrap();
+///     }
+/// }, args: ["--quietly"])?;
+/// car.send(&String::from("whack"))?;
+/// assert_eq!(car.recv()?, "WHACK");
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[macro_export]
+macro_rules! ford_anglia {
+    ($name:expr, $entry:expr, args: $args:expr $(,)?) => {
+        $crate::ford_anglia::ford_anglia_with_args($name, $args, $entry)
+    };
+    ($name:expr, $entry:expr $(,)?) => {
+        $crate::ford_anglia::ford_anglia($name, $entry)
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "ford_anglia",
+            "ford_anglia!(name, |cabin| entry) or ford_anglia!(name, |cabin| entry, args: args)",
+            "ford_anglia!(\"Whomping Willow\", |cabin: Cabin<String, String>| { ... })",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Freezes values the first time they are used. Declares `static` items that are [`Glacier`](glacius::Glacier)s
+/// computed from the given expressions, or, with the `cell` prefix, creates an [`Icicle`](glacius::Icicle) that
+/// can capture its surroundings and hold values that are not [`Sync`].
+/// # Examples
+/// ```
+/// # use code_spells::glacius;
+/// glacius! {
+///     static HOUSES: Vec<String> = ["Gryffindor", "Hufflepuff", "Ravenclaw", "Slytherin"]
+///         .iter()
+///         .map(|house| house.to_uppercase())
+///         .collect();
+/// }
+/// assert_eq!(HOUSES[3], "SLYTHERIN");
+/// ```
+/// ```
+/// # use code_spells::glacius;
+/// use std::rc::Rc;
+///
+/// let name = "Moaning Myrtle";
+/// let ghost = glacius!(cell Rc::new(name.to_uppercase()));
+/// assert_eq!(ghost.as_str(), "MOANING MYRTLE");
+/// ```
+#[macro_export]
+macro_rules! glacius {
+    (cell $thaw:expr) => {
+        $crate::glacius::Icicle::new(|| $thaw)
+    };
+    () => {};
+    ($(#[$attr:meta])* $vis:vis static $name:ident: $ty:ty = $thaw:expr; $($rest:tt)*) => {
+        $(#[$attr])*
+        $vis static $name: $crate::glacius::Glacier<$ty> = $crate::glacius::Glacier::new(|| $thaw);
+        $crate::glacius! { $($rest)* }
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "glacius",
+            "glacius! { static NAME: Type = value; ... } or glacius!(cell value)",
+            "glacius! { static LOOKUP: HashMap<u8, &str> = build_map(); }",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Triages an error: returns the [`Ward`](st_mungos::Ward) of St Mungo's that it should be treated in,
+/// see [`st_mungos`](mod@st_mungos).
+/// # Example
+/// ```
+/// # use code_spells::st_mungos;
+/// use code_spells::st_mungos::Ward;
+///
+/// let error = std::io::Error::from(std::io::ErrorKind::ConnectionReset);
+/// let ward = st_mungos!(error);
+/// assert_eq!(ward, Ward::Transient);
+/// assert!(ward.is_worth_retrying());
+/// ```
+#[macro_export]
+macro_rules! st_mungos {
+    ($error:expr) => {
+        $crate::st_mungos::Triage::ward(&$error)
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "st_mungos",
+            "st_mungos!(error)",
+            "st_mungos!(io_error).is_worth_retrying()",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Conjures an iterator out of a closure, like a snake out of the tip of a wand.
+/// - `serpensortia!(|| ...)` is an alias for [`iter::from_fn`](core::iter::from_fn),
+///   which yields items until the closure returns `None`.
+/// - `serpensortia!(repeat || ...)` is an alias for [`iter::repeat_with`](core::iter::repeat_with),
+///   which yields the items the closure returns forever.
+/// - `serpensortia!(first, |x| ...)` is an alias for [`iter::successors`](core::iter::successors),
+///   which starts with `first` and computes every next item from the previous one until the closure returns `None`.
+/// # Example
+/// ```
+/// # use code_spells::serpensortia;
+/// let mut eggs = 3;
+/// let hatched: Vec<_> = serpensortia!(|| {
+///     eggs -= 1;
+///     (eggs > 0).then(|| "snake")
+/// })
+/// .collect();
+/// assert_eq!(hatched, ["snake", "snake"]);
+///
+/// let nagini = String::from("hiss");
+/// let hisses: Vec<_> = serpensortia!(repeat || nagini.clone()).take(2).collect();
+/// assert_eq!(hisses, ["hiss", "hiss"]);
+///
+/// let basilisk: Vec<u32> = serpensortia!(1, |n| (*n < 1000).then(|| n * 10)).collect();
+/// assert_eq!(basilisk, [1, 10, 100, 1000]);
+/// ```
+#[macro_export]
+macro_rules! serpensortia {
+    (repeat $f:expr) => {
+        ::core::iter::repeat_with($f)
+    };
+    ($first:expr, $successor:expr $(,)?) => {
+        ::core::iter::successors(::core::option::Option::Some($first), $successor)
+    };
+    ($f:expr) => {
+        ::core::iter::from_fn($f)
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "serpensortia",
+            "serpensortia!(|| next), serpensortia!(repeat || item) or serpensortia!(first, |previous| next)",
+            "serpensortia!(1, |n| Some(n * 2))",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Makes a [`Portkey`](portkey::Portkey): runs a closure on a background thread once a delay has passed.
+/// Returns a handle that can [cancel](portkey::Portkey::cancel) the portkey before it activates,
+/// or [wait](portkey::Portkey::wait) for what the closure returns.
+/// # Example
+/// ```
+/// # use code_spells::portkey;
+/// use std::time::Duration;
+///
+/// let boot = portkey!(Duration::from_millis(10), || 1994);
+/// assert_eq!(boot.wait(), Some(1994));
+///
+/// let cup = portkey!(Duration::from_secs(3600), || "the graveyard");
+/// assert!(cup.cancel());
+/// assert_eq!(cup.wait(), None);
+/// ```
+#[macro_export]
+macro_rules! portkey {
+    ($delay:expr, $f:expr) => {
+        $crate::portkey::Portkey::new($delay, $f)
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "portkey",
+            "portkey!(delay, closure)",
+            "portkey!(Duration::from_secs(5), || touch_the_boot())",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Turns back time to retry a closure that returns a [`Result`] up to the given number of times.
+/// Returns the first success, or the error of the last attempt if every attempt failed.
+/// Cast `time_turner!(triage attempts, closure)` to stop early when an error is not
+/// [worth retrying](st_mungos::Ward::is_worth_retrying), see [`st_mungos`](mod@st_mungos).
+/// Use a [`TimeTurner`](time_turner::TimeTurner) to wait between the attempts.
+/// # Example
+/// ```
+/// # use code_spells::time_turner;
+/// let mut attempts = 0;
+/// let rescue = time_turner!(3, || {
+///     attempts += 1;
+///     if attempts < 3 { Err("too late") } else { Ok("Sirius is free") }
+/// });
+/// assert_eq!(rescue, Ok("Sirius is free"));
+///
+/// let mut attempts = 0;
+/// let wand: Result<u8, _> = time_turner!(triage 3, || {
+///     attempts += 1;
+///     "phoenix feather".parse::<u8>()
+/// });
+/// assert!(wand.is_err());
+/// assert_eq!(attempts, 1);
+/// ```
+#[macro_export]
+macro_rules! time_turner {
+    (triage $attempts:expr, $work:expr) => {
+        $crate::time_turner::TimeTurner::new()
+            .attempts($attempts)
+            .turn_triaged($work)
+    };
+    ($attempts:expr, $work:expr) => {
+        $crate::time_turner::TimeTurner::new()
+            .attempts($attempts)
+            .turn($work)
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "time_turner",
+            "time_turner!(attempts, closure) or time_turner!(triage attempts, closure)",
+            "time_turner!(3, || save_buckbeak())",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Creates a [`Broom`](firebolt::Broom), a fixed-capacity vector that lives on the stack, with [`vec!`]-like syntax.
+/// The capacity of the broom is usually inferred from its type.
+/// - `firebolt![a, b, c]` creates a broom with the given elements, and `firebolt![]` an empty one.
+/// - `firebolt![value; n]` creates a broom with `n` clones of `value`.
+/// - `firebolt!(mount vec)` moves the elements of a [`Vec`] onto a broom, returning the vector as an error if it does not fit.
+/// - `firebolt!(dismount broom)` moves the elements of a broom into a [`Vec`].
+///
+/// Needs the `firebolt` feature.
+/// # Panics
+/// Panics if there are more elements than the broom can carry.
+/// # Examples
+/// ```
+/// # use code_spells::firebolt;
+/// use code_spells::firebolt::Broom;
+///
+/// let mut team: Broom<&str, 7> = firebolt!["Harry", "Ron", "Ginny"];
+/// team.push("Katie");
+/// assert_eq!(team, ["Harry", "Ron", "Ginny", "Katie"]);
+///
+/// let bludgers: Broom<u8, 2> = firebolt![0; 2];
+/// assert!(bludgers.is_full());
+///
+/// let reserves: Vec<&str> = firebolt!(dismount team);
+/// assert_eq!(reserves.len(), 4);
+/// let too_many: Result<Broom<&str, 2>, _> = firebolt!(mount reserves);
+/// assert!(too_many.is_err());
+/// ```
+#[cfg(feature = "firebolt")]
+#[macro_export]
+macro_rules! firebolt {
+    () => {
+        $crate::firebolt::Broom::new()
+    };
+    ($value:expr; $n:expr) => {{
+        let mut broom = $crate::firebolt::Broom::new();
+        ::core::iter::Extend::extend(&mut broom, ::core::iter::repeat($value).take($n));
+        broom
+    }};
+    ($($value:expr),+ $(,)?) => {{
+        let mut broom = $crate::firebolt::Broom::new();
+        $(broom.push($value);)+
+        broom
+    }};
+    (mount $vec:expr) => {
+        $crate::firebolt::Broom::try_from_vec($vec)
+    };
+    (dismount $broom:expr) => {
+        ::std::vec::Vec::from($broom)
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "firebolt",
+            "firebolt![a, b, ...], firebolt![value; n], firebolt!(mount vec) or firebolt!(dismount broom)",
+            "firebolt![\"Harry\", \"Ron\", \"Ginny\"]",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Creates a [`CommonRoom`](common_room::CommonRoom) of sharded state, with one house per unit of
+/// available parallelism or the given number of houses.
+/// # Example
+/// ```
+/// # use code_spells::common_room;
+/// use code_spells::common_room::CommonRoom;
+/// use std::thread;
+///
+/// let house_points: CommonRoom<u32> = common_room!(4);
+/// thread::scope(|s| {
+///     s.spawn(|| *house_points.enter() += 50);
+///     s.spawn(|| *house_points.enter() += 10);
+/// });
+/// assert_eq!(house_points.gather(0, |total, points| total + points), 60);
+/// ```
+#[macro_export]
+macro_rules! common_room {
+    () => {
+        $crate::common_room::CommonRoom::new()
+    };
+    ($houses:expr) => {
+        $crate::common_room::CommonRoom::with_houses($houses)
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "common_room",
+            "common_room!() or common_room!(houses)",
+            "common_room!(4)",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Creates a [`GalleonCounter`](galleon::GalleonCounter) with the default or the given number of vaults.
+/// The spell can be cast in constants and statics.
+/// # Example
+/// ```
+/// # use code_spells::galleon_counter;
+/// use code_spells::galleon::GalleonCounter;
+///
+/// static HOUSE_POINTS: GalleonCounter<4> = galleon_counter!(4);
+/// HOUSE_POINTS.add(10);
+/// HOUSE_POINTS.add(50);
+/// assert_eq!(HOUSE_POINTS.total(), 60);
+/// ```
+#[macro_export]
+macro_rules! galleon_counter {
+    () => {
+        $crate::galleon::GalleonCounter::new()
+    };
+    ($vaults:expr) => {
+        $crate::galleon::GalleonCounter::<{ $vaults }>::new()
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "galleon_counter",
+            "galleon_counter!() or galleon_counter!(vaults)",
+            "galleon_counter!(4)",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Creates a [`Ledger`](gringotts_ledger::Ledger), an append-only hash-chained audit log,
+/// with [`vec!`]-like syntax: `gringotts_ledger![a, b, c]` records the given events in order,
+/// and `gringotts_ledger!()` creates an empty ledger.
+/// # Example
+/// ```
+/// # use code_spells::gringotts_ledger;
+/// let mut ledger = gringotts_ledger!["vault 713 opened", "small package removed"];
+/// ledger.record("vault 713 closed");
+/// assert_eq!(ledger.len(), 3);
+/// assert!(ledger.verify().is_ok());
+/// ```
+#[macro_export]
+macro_rules! gringotts_ledger {
+    () => {
+        $crate::gringotts_ledger::Ledger::new()
+    };
+    ($($event:expr),+ $(,)?) => {{
+        let mut ledger = $crate::gringotts_ledger::Ledger::new();
+        $(ledger.record($event);)+
+        ledger
+    }};
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "gringotts_ledger",
+            "gringotts_ledger!() or gringotts_ledger![event, ...]",
+            "gringotts_ledger![\"vault 713 opened\", \"small package removed\"]",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Summons implementations of a trait from the [`summons`] registry.
+/// `accio_trait!(dyn Trait)` returns a `Vec<Box<dyn Trait>>` with every registered implementation,
+/// while `accio_trait!(dyn Trait, name)` returns an `Option<Box<dyn Trait>>` with the one registered under `name`.
+///
+/// Implementations are registered with [`summons::register`], or by casting
+/// the `#[answers_summons(Trait)]` attribute on their impl block when the `macros` feature is enabled.
+/// # Examples
+/// ```
+/// # use code_spells::{accio_trait, summons};
+/// trait Broom {
+///     fn top_speed(&self) -> u32;
+/// }
+///
+/// struct Nimbus2000;
+/// impl Broom for Nimbus2000 {
+///     fn top_speed(&self) -> u32 { 100 }
+/// }
+///
+/// struct Firebolt;
+/// impl Broom for Firebolt {
+///     fn top_speed(&self) -> u32 { 150 }
+/// }
+///
+/// summons::register::<dyn Broom>("nimbus", || Box::new(Nimbus2000));
+/// summons::register::<dyn Broom>("firebolt", || Box::new(Firebolt));
+///
+/// let fastest = accio_trait!(dyn Broom).iter().map(|b| b.top_speed()).max();
+/// assert_eq!(fastest, Some(150));
+/// assert_eq!(accio_trait!(dyn Broom, "nimbus").map(|b| b.top_speed()), Some(100));
+/// ```
+#[cfg_attr(feature = "macros", doc = "```")]
+#[cfg_attr(not(feature = "macros"), doc = "```ignore")]
+/// # use code_spells::{accio_trait, answers_summons};
+/// trait Pet {
+///     fn name(&self) -> &'static str;
+/// }
+///
+/// #[derive(Default)]
+/// struct Hedwig;
+///
+/// #[answers_summons(Pet)]
+/// impl Pet for Hedwig {
+///     fn name(&self) -> &'static str { "Hedwig" }
+/// }
+///
+/// #[derive(Default)]
+/// struct Crookshanks;
+///
+/// #[answers_summons(Pet, name = "cat")]
+/// impl Pet for Crookshanks {
+///     fn name(&self) -> &'static str { "Crookshanks" }
+/// }
+///
+/// assert_eq!(accio_trait!(dyn Pet, "Hedwig").unwrap().name(), "Hedwig");
+/// assert_eq!(accio_trait!(dyn Pet, "cat").unwrap().name(), "Crookshanks");
+/// ```
+#[macro_export]
+macro_rules! accio_trait {
+    (dyn $($summoned:tt)+) => {
+        $crate::accio_trait!(@all [] $($summoned)+)
+    };
+    (@all [$($summoned:tt)+] , $name:expr) => {
+        $crate::summons::summon::<dyn $($summoned)+>($name)
+    };
+    (@all [$($summoned:tt)+]) => {
+        $crate::summons::summon_all::<dyn $($summoned)+>()
+    };
+    (@all [$($summoned:tt)*] $next:tt $($rest:tt)*) => {
+        $crate::accio_trait!(@all [$($summoned)* $next] $($rest)*)
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "accio_trait",
+            "accio_trait!(dyn Trait) or accio_trait!(dyn Trait, name)",
+            "accio_trait!(dyn Broom, \"nimbus\")",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Busy-waits until a condition holds, calling [`spin_loop`](core::hint::spin_loop) between every check
+/// and never backing off, which tortures the CPU but answers as fast as possible.
+///
+/// Pass the maximum number of waits as the second argument to give up after that many,
+/// in which case the spell returns whether the condition held.
+/// A bounded wait goes easier on the CPU and backs off between the checks with a default
+/// [`Backoff`](pepper_imps::Backoff), like [`backoff_spin!`](crate::backoff_spin).
+/// # Examples
+/// ```
+/// # use code_spells::crucio;
+/// use std::sync::atomic::{AtomicBool, Ordering};
+/// use std::sync::Arc;
+///
+/// let confessed = Arc::new(AtomicBool::new(false));
+/// let prisoner = {
+///     let confessed = Arc::clone(&confessed);
+///     std::thread::spawn(move || confessed.store(true, Ordering::Release))
+/// };
+/// crucio!(|| confessed.load(Ordering::Acquire));
+/// # prisoner.join().unwrap();
+/// ```
+/// ```
+/// # use code_spells::crucio;
+/// let mut screams = 0;
+/// let broken = crucio!(|| { screams += 1; screams > 100 }, 10);
+/// assert!(!broken);
+/// ```
+#[macro_export]
+macro_rules! crucio {
+    ($condition:expr, $max_waits:expr) => {
+        $crate::pepper_imps::Backoff::new()
+            .try_spin_until($condition, $max_waits)
+            .is_ok()
+    };
+    ($condition:expr) => {
+        $crate::pepper_imps::spin_until($condition)
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "crucio",
+            "crucio!(|| condition) or crucio!(|| condition, max waits)",
+            "crucio!(|| ready.load(Ordering::Acquire), 1000)",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Spins until a condition holds, backing off with a [`Backoff`](pepper_imps::Backoff) between every check:
+/// first with [`spin_loop`](core::hint::spin_loop) hints, then by [yielding](std::thread::yield_now)
+/// to the scheduler, and finally by sleeping.
+/// Pass a configured [`Backoff`](pepper_imps::Backoff) as the first argument to change when it escalates.
+/// Returns the [`BackoffStats`](pepper_imps::BackoffStats) of the wait.
+/// # Examples
+/// ```
+/// # use code_spells::backoff_spin;
+/// use std::sync::atomic::{AtomicBool, Ordering};
+/// use std::sync::Arc;
+///
+/// let owl_arrived = Arc::new(AtomicBool::new(false));
+/// let owl = {
+///     let owl_arrived = Arc::clone(&owl_arrived);
+///     std::thread::spawn(move || owl_arrived.store(true, Ordering::Release))
+/// };
+/// backoff_spin!(|| owl_arrived.load(Ordering::Acquire));
+/// # owl.join().unwrap();
+/// ```
+/// ```
+/// # use code_spells::{backoff_spin, pepper_imps::Backoff};
+/// use std::time::Duration;
+/// let mut checks = 0;
+/// let backoff = Backoff::new().spin_limit(0).yield_limit(0).sleep(Duration::from_micros(1));
+/// let stats = backoff_spin!(backoff, || { checks += 1; checks > 3 });
+/// assert_eq!(stats.sleeps(), 3);
+/// ```
+#[macro_export]
+macro_rules! backoff_spin {
+    ($backoff:expr, $condition:expr) => {
+        $crate::pepper_imps::Backoff::spin_until($backoff, $condition)
+    };
+    ($condition:expr) => {
+        $crate::backoff_spin!($crate::pepper_imps::Backoff::new(), $condition)
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "backoff_spin",
+            "backoff_spin!(|| condition) or backoff_spin!(backoff, || condition)",
+            "backoff_spin!(|| owl_arrived.load(Ordering::Acquire))",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Alias for [`mem::transmute`](core::mem::transmute). Disregard the rules, force the type system to do what you want!
+///
+/// When the source and destination types are given explicitly the spell first casts [`imperius_resistance!`](imperius_resistance)
+/// on them, so that a size mismatch is caught at compile time with a themed error.
+/// Prefixing the arguments with `same_layout` additionally requires the two types to have the same alignment.
+///
+/// Prefixing a reference with `copy` casts [`mem::transmute_copy`](core::mem::transmute_copy) instead,
+/// which reads a `Dst` out of the `Src` behind the reference and leaves the value where it is.
+/// The types may then differ in size, as long as `Dst` is not larger than `Src`, which is also checked at compile time.
+/// # Safety
+/// This spell is unforgivable for a reason, see the documentation of [`mem::transmute`](core::mem::transmute) for more details.
+/// The `copy` form has the dangers of [`mem::transmute_copy`](core::mem::transmute_copy) on top of that:
+/// the value is read unaligned, and the original and the copy must not both be dropped if the type owns something.
+/// # Examples
+/// ```
+/// # use code_spells::{imperio, unforgivable};
+/// # code_spells::i_solemnly_swear!();
+/// let a = [0_u8; 4];
+/// let b: u32 = unforgivable! { imperio!(a) };
+/// assert_eq!(b, 0);
+/// let mut c = unforgivable! { imperio!(b, u32 => [u8; 4]) };
+/// assert_eq!(c, [0; 4]);
+/// let d = &mut c;
+/// assert_eq!(unforgivable! { imperio!(d, &mut [u8; 4] => &[u8; 4]) }, &c)
+/// ```
+/// Force a pointer to become a function pointer!
+/// ```
+/// # use code_spells::{imperio, unforgivable};
+/// # code_spells::i_solemnly_swear!();
+/// fn foo() -> i32 { 0 }
+/// let pointer = foo as *const ();
+/// let function = unforgivable! {
+///     imperio!(pointer, *const () => fn() -> i32)
+/// };
+/// assert_eq!(function(), 0);
+/// ```
+/// Demand that the types agree on alignment as well as size.
+/// ```
+/// # use code_spells::{imperio, unforgivable};
+/// # code_spells::i_solemnly_swear!();
+/// let c = unforgivable! { imperio!(same_layout 0x1F9D9_u32, u32 => char) };
+/// assert_eq!(c, '🧙');
+/// ```
+/// ```compile_fail
+/// # use code_spells::{imperio, unforgivable};
+/// // [u8; 4] has the size of a u32, but not its alignment.
+/// let c = unforgivable! { imperio!(same_layout [0_u8; 4], [u8; 4] => u32) };
+/// ```
+/// Copy out the start of a value, without moving it.
+/// ```
+/// # use code_spells::{imperio, unforgivable};
+/// # code_spells::i_solemnly_swear!();
+/// let horcruxes = [7_u16, 6, 5, 4];
+/// let first_two = unforgivable! { imperio!(copy &horcruxes, [u16; 4] => [u16; 2]) };
+/// assert_eq!(first_two, [7, 6]);
+/// assert_eq!(horcruxes.len(), 4);
+/// ```
+/// ```compile_fail
+/// # use code_spells::{imperio, unforgivable};
+/// // A u64 can not be read out of a u32.
+/// let big = unforgivable! { imperio!(copy &0_u32, u32 => u64) };
+/// ```
+#[macro_export]
+macro_rules! imperio {
+    // Type to type with identical layout
+    (same_layout $will:expr, $src:ty => $dst:ty) => {{
+        $crate::imperius_resistance!(same_layout $src => $dst);
+        $crate::__marauders_oath!("imperio");
+        ::core::mem::transmute::<$src, $dst>($will)
+    }};
+    // Copy out of a reference, leaving the value in place
+    (copy $will:expr, $src:ty => $dst:ty) => {{
+        $crate::imperius_resistance!(copy $src => $dst);
+        $crate::__marauders_oath!("imperio");
+        ::core::mem::transmute_copy::<$src, $dst>($will)
+    }};
+    // Elision
+    ($will:expr) => {{
+        $crate::__marauders_oath!("imperio");
+        ::core::mem::transmute($will)
+    }};
+    // Pure type to type
+    ($will:expr, $src:ty => $dst:ty) => {{
+        $crate::imperius_resistance!($src => $dst);
+        $crate::__marauders_oath!("imperio");
+        ::core::mem::transmute::<$src, $dst>($will)
+    }};
+    // Attempt to match against any expression
+    ($will:expr, $src:expr => $dst:expr) => {{
+        $crate::__marauders_oath!("imperio");
+        ::core::mem::transmute::<$src, $dst>($will)
+    }};
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "imperio",
+            "imperio!(value), imperio!(value, Src => Dst), imperio!(same_layout value, Src => Dst) or imperio!(copy &value, Src => Dst), inside unforgivable!",
+            "unforgivable! { imperio!(b, u32 => [u8; 4]) }",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Casts a list of [`hexes`] on a reference to a value and collects every [`Violation`](hexes::Violation) they report,
+/// not just the first one.
+/// Returns `Ok(())` if the value passed all of them, and `Err` with the violations in the order the hexes were given otherwise.
+/// # Examples
+/// ```
+/// # use code_spells::{bat_bogey_hex, hexes::{max_len, not_empty}};
+/// assert!(bat_bogey_hex!("Ginny", [not_empty(), max_len(10)]).is_ok());
+///
+/// let name = String::new();
+/// let violations = bat_bogey_hex!(&name, [not_empty(), max_len(10)]).unwrap_err();
+/// assert_eq!(violations.len(), 1);
+/// assert_eq!(violations[0].hex(), "not_empty");
+/// ```
+/// Closures can be cast as hexes as well.
+/// ```
+/// # use code_spells::{bat_bogey_hex, hexes::{not_empty, Violation}};
+/// let violations = bat_bogey_hex!(&5, [|x: &i32| {
+///     if x % 2 == 0 { Ok(()) } else { Err(Violation::new("even", "the value is odd")) }
+/// }]).unwrap_err();
+/// assert_eq!(violations[0].to_string(), "struck by the `even` hex: the value is odd");
+/// ```
+#[macro_export]
+macro_rules! bat_bogey_hex {
+    ($value:expr, [$($hex:expr),* $(,)?]) => {{
+        let value = $value;
+        let mut violations = ::std::vec::Vec::<$crate::hexes::Violation>::new();
+        $(
+            if let ::core::result::Result::Err(violation) = $crate::hexes::Hex::cast(&$hex, value) {
+                violations.push(violation);
+            }
+        )*
+        if violations.is_empty() {
+            ::core::result::Result::Ok(())
+        } else {
+            ::core::result::Result::Err(violations)
+        }
+    }};
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "bat_bogey_hex",
+            "bat_bogey_hex!(&value, [hex, ...])",
+            "bat_bogey_hex!(&name, [not_empty(), max_len(10)])",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Declares one or more thread-local [`ShieldHat`](shield_hat::ShieldHat)s whose values can be overridden in a scope.
+/// The expression after `=` is the value each thread starts out with.
+/// # Example
+/// ```
+/// # use code_spells::shield_hat;
+/// shield_hat! {
+///     static HOUSE: &'static str = "Gryffindor";
+///     pub static POINTS: i32 = 0;
+/// }
+///
+/// fn sorting() -> &'static str {
+///     HOUSE.current()
+/// }
+///
+/// assert_eq!(sorting(), "Gryffindor");
+/// let _hat = HOUSE.wear("Slytherin");
+/// assert_eq!(sorting(), "Slytherin");
+/// ```
+#[macro_export]
+macro_rules! shield_hat {
+    ($($(#[$attr:meta])* $vis:vis static $name:ident: $t:ty = $init:expr;)+) => {
+        $(
+            $(#[$attr])*
+            $vis static $name: $crate::shield_hat::ShieldHat<$t> = {
+                ::std::thread_local! {
+                    static STACK: ::core::cell::RefCell<::std::vec::Vec<$t>> =
+                        ::core::cell::RefCell::new(::std::vec![$init]);
+                }
+                $crate::shield_hat::ShieldHat::new(&STACK)
+            };
+        )+
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "shield_hat",
+            "shield_hat! { static NAME: Type = initial_value; }",
+            "shield_hat! { static VOLUME: u8 = 5; }",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Evaluates an expression and asserts that its [`Debug`](core::fmt::Debug) output is exactly the expected string,
+/// panicking with a line-by-line [`Diff`](examples::Diff) if it is not.
+/// Prefix the expression with `pretty` to compare against the pretty-printed (`{:#?}`) output instead.
+/// The value of the expression is returned, so the spell can be cast in the middle of an example.
+/// # Examples
+/// ```
+/// # use code_spells::ten_points_to;
+/// let house = ten_points_to!(String::from("Gryffindor"), r#""Gryffindor""#);
+/// ten_points_to!(pretty (house, 10), r#"(
+///     "Gryffindor",
+///     10,
+/// )"#);
+/// ```
+/// ```should_panic
+/// # use code_spells::ten_points_to;
+/// ten_points_to!(vec!["Slytherin"], r#"["Gryffindor"]"#);
+/// ```
+#[macro_export]
+macro_rules! ten_points_to {
+    (pretty $value:expr, $expected:expr) => {{
+        let value = $value;
+        $crate::examples::award(&::std::format!("{:#?}", value), $expected);
+        value
+    }};
+    ($value:expr, $expected:expr) => {{
+        let value = $value;
+        $crate::examples::award(&::std::format!("{:?}", value), $expected);
+        value
+    }};
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "ten_points_to",
+            "ten_points_to!(value, expected_debug) or ten_points_to!(pretty value, expected_debug)",
+            "ten_points_to!(5 + 5, \"10\")",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Checks a condition at compile time, failing the build with a themed error if it does not hold.
+/// The spell expands to a `const` item, so it can be cast wherever items are allowed,
+/// including inside the bodies of functions and `const fn`s.
+///
+/// The following forms are available:
+/// - `spell_check!(condition)` and `spell_check!(condition, "message")` check a boolean constant expression.
+/// - `spell_check!(impl Trait for Type)` checks that a type implements one or more traits, e.g. `impl Send + Sync for Type`.
+/// - `spell_check!(same_size A, B)` checks that two types have the same size.
+///
+/// The types given to the spell can not depend on generic parameters.
+/// # Examples
+/// ```
+/// # use code_spells::spell_check;
+/// const SIZE: usize = 32;
+/// spell_check!(SIZE <= 64);
+/// spell_check!(SIZE % 2 == 0, "the size must be even");
+///
+/// struct Wand(u32);
+/// spell_check!(impl Send for Wand);
+/// spell_check!(impl Send + Sync + core::marker::Unpin for Wand);
+/// spell_check!(impl Into<u64> + PartialEq<u32> for u32);
+/// spell_check!(same_size Wand, u32);
+///
+/// const fn wand_size() -> usize {
+///     spell_check!(same_size Wand, [u8; 4]);
+///     core::mem::size_of::<Wand>()
+/// }
+/// ```
+/// ```compile_fail
+/// # use code_spells::spell_check;
+/// const SIZE: usize = 128;
+/// spell_check!(SIZE <= 64);
+/// ```
+/// ```compile_fail
+/// # use code_spells::spell_check;
+/// spell_check!(impl Send for std::rc::Rc<u8>); // error: the trait bound `Rc<u8>: PassesTheSpellCheck` is not satisfied
+/// ```
+/// ```compile_fail
+/// # use code_spells::spell_check;
+/// spell_check!(same_size u8, u16);
+/// ```
+#[macro_export]
+macro_rules! spell_check {
+    (impl $($bounds:tt)+) => {
+        $crate::spell_check!(@impl [] $($bounds)+);
+    };
+    // The bounds are read one token at a time up to the `for` of the checked type,
+    // skipping the `for` of higher-ranked bounds like `for<'a> Fn(&'a str)`.
+    (@impl [$($bound:tt)*] for < $($rest:tt)+) => {
+        $crate::spell_check!(@impl [$($bound)* for <] $($rest)+);
+    };
+    (@impl [$($bound:tt)+] for $t:ty) => {
+        const _: () = {
+            // Named so that a type without the traits fails with
+            // "the trait bound `Type: PassesTheSpellCheck` is not satisfied".
+            trait PassesTheSpellCheck {}
+            impl<T: ?::core::marker::Sized + $($bound)+> PassesTheSpellCheck for T {}
+            fn spell_check<T: ?::core::marker::Sized + PassesTheSpellCheck>() {}
+            let _: fn() = spell_check::<$t>;
+        };
+    };
+    (@impl [$($bound:tt)*] $next:tt $($rest:tt)+) => {
+        $crate::spell_check!(@impl [$($bound)* $next] $($rest)+);
+    };
+    (same_size $a:ty, $b:ty) => {
+        const _: () = ::core::assert!(
+            ::core::mem::size_of::<$a>() == ::core::mem::size_of::<$b>(),
+            ::core::concat!(
+                "spell check failed: `",
+                ::core::stringify!($a),
+                "` and `",
+                ::core::stringify!($b),
+                "` differ in size"
+            )
+        );
+    };
+    ($condition:expr, $message:literal) => {
+        const _: () = ::core::assert!($condition, ::core::concat!("spell check failed: ", $message));
+    };
+    ($condition:expr) => {
+        const _: () = ::core::assert!(
+            $condition,
+            ::core::concat!("spell check failed: ", ::core::stringify!($condition))
+        );
+    };
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "spell_check",
+            "spell_check!(condition), spell_check!(condition, \"message\"), spell_check!(impl Trait for Type) or spell_check!(same_size A, B)",
+            "spell_check!(impl Send + Sync for Wand)",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Resists the Imperius curse: checks at compile time that a value of type `Src`
+/// has the same size as a value of type `Dst`, which is what [`imperio!`](imperio) needs
+/// in order to transmute one into the other without undefined behaviour.
+/// With `same_layout` the two types must also have the same alignment,
+/// and with `copy` a `Dst` must merely not be larger than a `Src`, which is what `imperio!(copy ...)` needs.
+/// # Examples
+/// ```
+/// # use code_spells::imperius_resistance;
+/// imperius_resistance!(u32 => [u8; 4]);
+/// imperius_resistance!(same_layout u32 => char);
+/// imperius_resistance!(copy u64 => u16);
+/// ```
+/// A mismatch in size fails to compile.
+/// ```compile_fail
+/// # use code_spells::imperius_resistance;
+/// imperius_resistance!(u32 => u64);
+/// ```
+/// As does a mismatch in alignment when `same_layout` is requested.
+/// ```compile_fail
+/// # use code_spells::imperius_resistance;
+/// imperius_resistance!(same_layout [u8; 4] => u32);
+/// ```
+/// And a larger destination when `copy` is requested.
+/// ```compile_fail
+/// # use code_spells::imperius_resistance;
+/// imperius_resistance!(copy u16 => u64);
+/// ```
+#[macro_export]
+macro_rules! imperius_resistance {
+    (same_layout $src:ty => $dst:ty) => {{
+        let _ = $crate::__private::ImperiusResistance::<$src, $dst>::SAME_SIZE;
+        let _ = $crate::__private::ImperiusResistance::<$src, $dst>::SAME_ALIGN;
+    }};
+    (copy $src:ty => $dst:ty) => {{
+        let _ = $crate::__private::ImperiusResistance::<$src, $dst>::NOT_LARGER;
+    }};
+    ($src:ty => $dst:ty) => {{
+        let _ = $crate::__private::ImperiusResistance::<$src, $dst>::SAME_SIZE;
+    }};
+    ($($miscast:tt)*) => {
+        $crate::__kwikspell! {
+            "imperius_resistance",
+            "imperius_resistance!(Src => Dst), imperius_resistance!(same_layout Src => Dst) or imperius_resistance!(copy Src => Dst)",
+            "imperius_resistance!(u32 => [u8; 4])",
+            [$($miscast)*]
+        }
+    };
+}
+
+/// Reports a miscast spell. With the `kwikspell` feature the error teaches the usage of the spell.
+#[cfg(feature = "kwikspell")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __kwikspell {
+    ($spell:literal, $usage:literal, $example:literal, [$($miscast:tt)*]) => {
+        ::core::compile_error! {
+            ::core::concat!(
+                "`", $spell, "!` was miscast. Kwikspell lesson:\n",
+                "usage:   ", $usage, "\n",
+                "example: ", $example,
+            )
+        }
+    };
+}
+
+/// Reports a miscast spell. With the `kwikspell` feature the error teaches the usage of the spell.
+#[cfg(not(feature = "kwikspell"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __kwikspell {
+    // The miscast tokens are matched against no rules, so that the error is the usual one
+    // of the compiler, pointing at the tokens that were miscast.
+    ($spell:literal, $usage:literal, $example:literal, [$($miscast:tt)*]) => {
+        $crate::__no_rules! { $($miscast)* }
+    };
+}
+
+/// Expects no tokens. Passing it the tokens of a miscast spell makes the compiler point at them.
+#[cfg(not(feature = "kwikspell"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __no_rules {
+    () => {
+        ::core::compile_error! { "unexpected end of macro invocation" }
+    };
+}
+
+/// Reads the margin notes of a spell. With the `princes-notes` feature the notes warn about casts that have better alternatives.
+#[cfg(feature = "princes-notes")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __princes_notes {
+    ($spell:ident ($($argument:expr),+)) => {
+        $crate::half_blood_prince! { $spell ($($argument),+) }
+    };
+}
+
+/// Reads the margin notes of a spell. With the `princes-notes` feature the notes warn about casts that have better alternatives.
+#[cfg(not(feature = "princes-notes"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __princes_notes {
+    ($spell:ident ($($argument:expr),+)) => {};
+}
+
+/// Checks the [oath](marauders_oath) before a dark spell is cast, with the `marauders-oath` feature.
+#[cfg(feature = "marauders-oath")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __marauders_oath {
+    ($spell:literal) => {
+        $crate::marauders_oath::enforce($spell)
     };
 }
 
-/// Alias for unsafe. What could be more unforgivable than undefined behaviour?
-/// # Example
-/// ```
-/// # use code_spells::unforgivable;
-/// use core::num::NonZeroU8;
-/// // Forgivability: `new_unchecked` is UB if the argument is zero, but two is not zero.
-/// const two: NonZeroU8 = unforgivable! { NonZeroU8::new_unchecked(2) };
-/// assert_eq!(two.get(), 2);
-/// ```
+/// Checks the [oath](marauders_oath) before a dark spell is cast, with the `marauders-oath` feature.
+#[cfg(not(feature = "marauders-oath"))]
+#[doc(hidden)]
 #[macro_export]
-macro_rules! unforgivable {
-    ($($code:tt)+) => {
-        unsafe {
-            $($code)+
-        }
-    };
+macro_rules! __marauders_oath {
+    ($spell:literal) => {};
 }
 
-/// Alias for [`Vec::reserve`](std::vec::Vec::reserve).
-/// # Example
-/// ```
-/// # use code_spells::capacious_extremis;
-/// let mut police_box = Vec::<i32>::new();
-/// capacious_extremis!(&mut police_box, 5);
-/// assert!(police_box.capacity() >= 5);
-/// let r = &mut police_box;
-/// capacious_extremis!(r, 10);
-/// assert!(police_box.capacity() >= 10);
-/// ```
+/// Records the reason for an unforgivable curse with the Wizengamot, with the `wizengamot` feature.
+#[cfg(feature = "wizengamot")]
+#[doc(hidden)]
 #[macro_export]
-macro_rules! capacious_extremis {
-    (&mut $vec:ident, $capacity:expr) => {
-        ::std::vec::Vec::reserve(&mut $vec, $capacity)
-    };
-    ($vec:ident, $capacity:expr) => {
-        ::std::vec::Vec::reserve($vec, $capacity)
+macro_rules! __wizengamot {
+    ($reason:literal) => {
+        $crate::wizengamot! { $reason }
     };
 }
 
-/// Alias for [`mem::transmute`](core::mem::transmute). Disregard the rules, force the type system to do what you want!
-/// # Safety
-/// This spell is unforgivable for a reason, see the documentation of [`mem::transmute`](core::mem::transmute) for more details.
-/// # Examples
-/// ```
-/// # use code_spells::{imperio, unforgivable};
-/// let a = [0_u8; 4];
-/// let b: u32 = unforgivable! { imperio!(a) };
-/// assert_eq!(b, 0);
-/// let mut c = unforgivable! { imperio!(b, u32 => [u8; 4]) };
-/// assert_eq!(c, [0; 4]);
-/// let d = &mut c;
-/// assert_eq!(unforgivable! { imperio!(d, &mut [u8; 4] => &[u8; 4]) }, &c)
-/// ```
-/// Force a pointer to become a function pointer!
-/// ```
-/// # use code_spells::{imperio, unforgivable};
-/// fn foo() -> i32 { 0 }
-/// let pointer = foo as *const ();
-/// let function = unforgivable! {
-///     imperio!(pointer, *const () => fn() -> i32)
-/// };
-/// assert_eq!(function(), 0);
-/// ```
+/// Records the reason for an unforgivable curse with the Wizengamot, with the `wizengamot` feature.
+#[cfg(not(feature = "wizengamot"))]
+#[doc(hidden)]
 #[macro_export]
-macro_rules! imperio {
-    // Elision
-    ($will:expr) => {
-        ::core::mem::transmute($will)
-    };
-    // Pure type to type
-    ($will:expr, $src:ty => $dst:ty) => {
-        ::core::mem::transmute::<$src, $dst>($will)
+macro_rules! __wizengamot {
+    ($reason:literal) => {};
+}
+
+/// Warns about an unforgivable curse cast without a reason, with the `unbreakable-vow` feature.
+#[cfg(feature = "unbreakable-vow")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __unbreakable_vow {
+    () => {
+        #[allow(clippy::let_unit_value)]
+        let _ = $crate::__private::UNBREAKABLE_VOW;
     };
-    // Attempt to match against any expression
-    ($will:expr, $src:expr => $dst:expr) => {
-        ::core::mem::transmute::<$src, $dst>($will)
+}
+
+/// Warns about an unforgivable curse cast without a reason, with the `unbreakable-vow` feature.
+#[cfg(not(feature = "unbreakable-vow"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __unbreakable_vow {
+    () => {};
+}
+
+/// Records a dark spell in the ledger of [`knockturn_alley`](mod@knockturn_alley), with the `dark-arts` feature.
+#[cfg(feature = "dark-arts")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __knockturn_alley {
+    ($spell:literal) => {
+        $crate::knockturn_alley::record($spell)
     };
 }
 
+/// Records a dark spell in the ledger of `knockturn_alley`, with the `dark-arts` feature.
+#[cfg(not(feature = "dark-arts"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __knockturn_alley {
+    ($spell:literal) => {};
+}
+
+#[doc(hidden)]
+pub mod __private {
+    use core::fmt;
+    use core::marker::PhantomData;
+    use core::mem::{align_of, size_of};
+    use std::rc::Rc;
+    use std::sync::Arc;
+
+    #[cfg(feature = "rayon")]
+    pub use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+
+    /// Broken by every [`unforgivable!`](crate::unforgivable) curse cast without a reason, with the `unbreakable-vow` feature.
+    #[cfg(feature = "unbreakable-vow")]
+    #[deprecated(
+        note = "this unforgivable curse was cast without a reason, cast it as `unforgivable!(\"reason\" => { code })` instead"
+    )]
+    pub const UNBREAKABLE_VOW: () = ();
+
+    /// The collections that [`duro!`](crate::duro) turns to stone, and the immutable forms they turn into.
+    pub trait Petrify {
+        type Stone: ?Sized;
+
+        fn petrify(self) -> Box<Self::Stone>;
+
+        fn petrify_shared(self) -> Rc<Self::Stone>;
+
+        fn petrify_sync(self) -> Arc<Self::Stone>;
+    }
+
+    impl<T> Petrify for Vec<T> {
+        type Stone = [T];
+
+        fn petrify(self) -> Box<[T]> {
+            self.into_boxed_slice()
+        }
+
+        fn petrify_shared(self) -> Rc<[T]> {
+            Rc::from(self)
+        }
+
+        fn petrify_sync(self) -> Arc<[T]> {
+            Arc::from(self)
+        }
+    }
+
+    impl Petrify for String {
+        type Stone = str;
+
+        fn petrify(self) -> Box<str> {
+            self.into_boxed_str()
+        }
+
+        fn petrify_shared(self) -> Rc<str> {
+            Rc::from(self)
+        }
+
+        fn petrify_sync(self) -> Arc<str> {
+            Arc::from(self)
+        }
+    }
+
+    /// The types that [`expecto_patronum!`](crate::expecto_patronum) can unwrap with a formatted message.
+    pub trait Patronus {
+        type Output;
+
+        fn expecto_with(self, message: impl FnOnce() -> String) -> Self::Output;
+    }
+
+    impl<T> Patronus for Option<T> {
+        type Output = T;
+
+        #[track_caller]
+        fn expecto_with(self, message: impl FnOnce() -> String) -> T {
+            match self {
+                Some(value) => value,
+                None => panic!("{}", message()),
+            }
+        }
+    }
+
+    impl<T, E: fmt::Debug> Patronus for Result<T, E> {
+        type Output = T;
+
+        #[track_caller]
+        fn expecto_with(self, message: impl FnOnce() -> String) -> T {
+            match self {
+                Ok(value) => value,
+                Err(error) => panic!("{}: {:?}", message(), error),
+            }
+        }
+    }
+
+    /// Carries the compile-time checks of [`imperius_resistance!`](crate::imperius_resistance).
+    pub struct ImperiusResistance<Src, Dst>(PhantomData<(Src, Dst)>);
+
+    impl<Src, Dst> ImperiusResistance<Src, Dst> {
+        pub const SAME_SIZE: () = assert!(
+            size_of::<Src>() == size_of::<Dst>(),
+            "the Imperius curse was resisted: the source and destination types of `imperio!` differ in size"
+        );
+        pub const SAME_ALIGN: () = assert!(
+            align_of::<Src>() == align_of::<Dst>(),
+            "the Imperius curse was resisted: the source and destination types of `imperio!` differ in alignment"
+        );
+        pub const NOT_LARGER: () = assert!(
+            size_of::<Dst>() <= size_of::<Src>(),
+            "the Imperius curse was resisted: the destination type of `imperio!(copy ...)` is larger than its source"
+        );
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    #[allow(unused_imports)]
     use super::*;
     use std::convert::TryFrom;
 
+    #[cfg(feature = "macros")]
+    #[test]
+    fn practice_muffliato() {
+        #[crate::muffliato(clippy::too_many_arguments, dead_code)]
+        fn hidden(_: u8, _: u8, _: u8, _: u8, _: u8, _: u8, _: u8, _: u8) {}
+    }
+
     #[test]
     fn practice_obliviate() {
         let x = vec![0; 5];
@@ -401,6 +4820,12 @@ mod tests {
         let x = 5;
         let y = &x;
         assert_eq!(accio!(y), 5);
+        let z = &y;
+        assert_eq!(accio!(z, 2), 5);
+        let mut trunk = Box::new(Box::new(vec![1]));
+        accio!(&mut trunk, 3).push(2);
+        assert_eq!(accio!(&&trunk, 4), [1, 2]);
+        assert_eq!(accio!(z, 1), &5);
     }
 
     #[test]
@@ -446,6 +4871,60 @@ mod tests {
         );
         assert_eq!(erecto!(Thing: 5 != 2, x), Thing::new(5 != 2, x));
         assert_eq!(erecto!(String:), String::new());
+        let s = String::from("Erected");
+        let thing = erecto!(Thing {
+            y4: x,
+            s,
+            ..erecto!(Thing: b, 1)
+        });
+        assert_eq!((thing.y4, thing.b, &*thing.s), (5, true, "Erected"));
+        let hut = erecto!(std::ops::Range { start: 1, end: 4 });
+        assert_eq!(hut.len(), 3);
+        assert_eq!(erecto!(::core::ops::RangeTo { end: 2 }), ..2);
+    }
+
+    #[test]
+    fn erecto_handles_generics() {
+        mod vault {
+            #[derive(Debug, PartialEq)]
+            pub struct Vault<'a, T, const N: usize> {
+                pub owner: &'a str,
+                pub galleons: [T; N],
+            }
+
+            impl<'a, T: Copy, const N: usize> Vault<'a, T, N> {
+                pub fn new(owner: &'a str, galleons: T) -> Self {
+                    Self {
+                        owner,
+                        galleons: [galleons; N],
+                    }
+                }
+            }
+        }
+
+        fn generic<T: Copy + Default>() -> vault::Vault<'static, T, 2> {
+            erecto!(vault::Vault::<'static, T, 2> {
+                owner: "Lestrange",
+                galleons: [T::default(); 2],
+            })
+        }
+
+        let potters = erecto!(vault::Vault::<u8, 3>: "Potter", 7);
+        assert_eq!(potters.galleons, [7; 3]);
+        let weasleys = erecto!(vault::Vault<'static, u16, 1>: "Weasley", 2);
+        assert_eq!(weasleys, vault::Vault::new("Weasley", 2));
+        let malfoys = erecto!(vault::Vault::<'_, u32, { 1 + 1 }> {
+            owner: "Malfoy",
+            galleons: [1000; 2],
+        });
+        assert_eq!(malfoys.galleons.len(), 2);
+        assert_eq!(generic::<i8>().owner, "Lestrange");
+
+        let owls: std::collections::HashMap<&str, u8> =
+            erecto!(std::collections::HashMap<&str, u8>:);
+        assert!(owls.is_empty());
+        let cow = erecto!(::std::borrow::Cow<'static, str>);
+        assert_eq!(cow, "");
     }
 
     #[test]
@@ -457,6 +4936,19 @@ mod tests {
         assert_eq!(b, vec![0; 5]);
     }
 
+    #[test]
+    fn practice_geminio_maxima() {
+        let cup = &std::rc::Rc::new(vec!["Hufflepuff's cup"]);
+        let copy = geminio_maxima!(shared cup);
+        assert!(std::rc::Rc::ptr_eq(cup, &copy));
+        let locket = &std::sync::Arc::new(String::from("Slytherin's locket"));
+        let copies: Vec<_> = (0..3).map(|_| geminio_maxima!(sync locket)).collect();
+        assert_eq!(std::sync::Arc::strong_count(locket), 4);
+        assert!(copies
+            .iter()
+            .all(|copy| std::sync::Arc::ptr_eq(copy, locket)));
+    }
+
     #[test]
     fn practice_immobulus() {
         let mut val = 5;
@@ -468,16 +4960,78 @@ mod tests {
     #[test]
     fn practice_expecto_patronum() {
         expecto_patronum!(u8::try_from(5));
+        let clock = 12;
+        let patronus = [5].first();
+        assert_eq!(
+            expecto_patronum!(patronus, "dementor at {} o'clock", clock),
+            &5
+        );
+        let stag = Ok::<_, ()>("stag");
+        assert_eq!(expecto_patronum!(stag, "{clock} {}", "o'clock",), "stag");
+        assert_eq!(expecto_patronum!(patronus, "  patronus  ".trim()), &5);
+    }
+
+    #[test]
+    fn expecto_patronum_formats_its_message_when_it_fails() {
+        let clock = 12;
+        let err = "no happy memory";
+        let dementor = std::panic::catch_unwind(|| {
+            expecto_patronum!(u8::try_from(-5), "dementor at {} o'clock: {err}", clock)
+        })
+        .unwrap_err();
+        assert_eq!(
+            dementor.downcast_ref::<String>().map(String::as_str),
+            Some("dementor at 12 o'clock: no happy memory: TryFromIntError(())")
+        );
+        let lonely =
+            std::panic::catch_unwind(|| expecto_patronum!(None::<u8>, "{} dementors", clock + 88))
+                .unwrap_err();
+        assert_eq!(
+            lonely.downcast_ref::<String>().map(String::as_str),
+            Some("100 dementors")
+        );
+        let braces =
+            std::panic::catch_unwind(|| expecto_patronum!(None::<u8>, "dementor at {clock}"))
+                .unwrap_err();
+        assert_eq!(
+            braces
+                .downcast_ref::<String>()
+                .map(String::as_str)
+                .or_else(|| braces.downcast_ref::<&str>().copied()),
+            Some("dementor at {clock}")
+        );
     }
 
     #[test]
     fn practice_colloportus() {
         let door = std::sync::Mutex::new(5);
         let _guard = colloportus!(&door);
+        let vault = &std::sync::RwLock::new(5);
+        {
+            let _reader = colloportus!(read vault).unwrap();
+            assert!(vault.try_write().is_err());
+        }
+        *colloportus!(write vault).unwrap() += 1;
+        assert_eq!(*colloportus!(read vault).unwrap(), 6);
+    }
+
+    #[test]
+    fn practice_alohomora() {
+        let door = std::sync::Mutex::new(5);
+        {
+            let mut room = alohomora!(&door);
+            *room = 10;
+            assert!(alohomora!(try &door).is_err());
+        }
+        assert_eq!(*alohomora!(try &door).unwrap(), 10);
     }
 
     #[test]
     fn practice_evanesco_and_apericium() {
+        let _oath = crate::marauders_oath::OATH_TAKERS
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        i_solemnly_swear!();
         let a = Box::new(vec![5; 100]);
         let b: &mut Vec<i32> = evanesco!(a);
         assert_eq!(unsafe { aparecium!(b) }, Box::new(vec![5; 100]));
@@ -500,6 +5054,116 @@ mod tests {
         assert_eq!(reparo!(foo(255), u8::MAX), u8::MAX);
         assert_eq!(reparo!(foo(255), |_| 5), 5);
         assert_eq!(reparo!(foo(255), |_| identity(10)), 10);
+        assert_eq!(reparo!(foo(255)), 0);
+    }
+
+    #[test]
+    fn practice_reparo_on_options() {
+        let broken: Option<u8> = None;
+        let five = 5;
+        assert_eq!(reparo!(broken, five), 5);
+        let whole = Some(3);
+        assert_eq!(reparo!(whole, five), 3);
+        assert_eq!(reparo!(broken, || 7), 7);
+        let pieces = vec![1, 2, 3];
+        assert_eq!(reparo!(broken, move || pieces.into_iter().sum()), 6);
+        assert_eq!(reparo!(broken), 0);
+        let wand = Some(String::from("wand"));
+        assert_eq!(reparo!(wand), "wand");
+    }
+
+    #[test]
+    fn practice_anapneo() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+        let breathing = AtomicBool::new(false);
+        std::thread::scope(|s| {
+            s.spawn(|| breathing.store(true, Ordering::Release));
+            while !breathing.load(Ordering::Acquire) {
+                anapneo!();
+            }
+        });
+        let breaths = 0;
+        anapneo!(breaths);
+        anapneo!(5_u8);
+    }
+
+    #[test]
+    fn practice_stupefy_and_rennervate() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+        let revived = AtomicBool::new(false);
+        std::thread::scope(|s| {
+            let ron = s.spawn(|| {
+                while !revived.load(Ordering::Acquire) {
+                    stupefy!();
+                }
+            });
+            revived.store(true, Ordering::Release);
+            rennervate!(ron.thread());
+        });
+        let start = std::time::Instant::now();
+        stupefy!(std::time::Duration::from_millis(5));
+        assert!(start.elapsed() < std::time::Duration::from_secs(5));
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn practice_oppugno() {
+        let map = [1_u64, 2, 3, 4];
+        let total = std::sync::atomic::AtomicU64::new(0);
+        oppugno!(map, |bird| {
+            total.fetch_add(*bird, std::sync::atomic::Ordering::Relaxed);
+        });
+        assert_eq!(total.into_inner(), 10);
+        let doubled: Vec<u64> = oppugno!(map map, |bird| bird * 2);
+        assert_eq!(doubled, [2, 4, 6, 8]);
+        let ravens = vec!["Ron", "Ron"];
+        let canaries: std::collections::HashSet<&str> = oppugno!(map ravens, |name: &&str| *name);
+        assert_eq!(canaries.len(), 1);
+    }
+
+    #[cfg(feature = "macros")]
+    #[test]
+    fn practice_remembrall() {
+        crate::remembrall!("give Neville his Remembrall back", expires: "2999-09-01");
+        crate::remembrall!("ask Madam Hooch about \"flying\"", expires: "2999-02-28",);
+        crate::remembrall!("give Neville his Remembrall back", expires: "2999-09-01");
+        let notes = crate::remembrall_report!();
+        let nevilles = notes.iter().filter(|(note, _)| note.contains("Neville"));
+        assert_eq!(nevilles.count(), 1);
+        assert!(notes.contains(&("ask Madam Hooch about \"flying\"", "2999-02-28")));
+    }
+
+    #[cfg(feature = "macros")]
+    #[test]
+    fn practice_parseltongue() {
+        const HISS: &[u8] = crate::parseltongue!("ssss", 0x00, b"\xFF");
+        assert_eq!(HISS, b"ssss\x00\xFF");
+        assert_eq!(crate::parseltongue!('🐍'), "🐍".as_bytes());
+        let riddle = crate::parseltongue!(c "Tom", b' ', r"Marvolo", 32_u8, "Riddle");
+        assert_eq!(riddle.to_bytes(), b"Tom Marvolo Riddle");
+    }
+
+    #[test]
+    fn practice_apparate() {
+        let hogsmeade = String::from("Honeydukes");
+        let shops = apparate!({ hogsmeade.len() });
+        assert_eq!(shops.join().unwrap(), 10);
+        let name = String::from("chaser");
+        let chaser = apparate!(name, {
+            std::thread::current().name().unwrap().to_uppercase()
+        });
+        assert_eq!(chaser.join().unwrap(), "CHASER");
+    }
+
+    #[test]
+    fn practice_episkey() {
+        let mut scar: Option<u8> = None;
+        *episkey!(scar, 1) += 1;
+        assert_eq!(scar, Some(2));
+        assert_eq!(*episkey!(scar, 10), 2);
+        let mut lip: Option<String> = None;
+        episkey!(&mut lip, || String::from("split")).push_str(" lip");
+        assert_eq!(*episkey!(&mut lip, || String::new()), "split lip");
     }
 
     #[test]
@@ -509,14 +5173,349 @@ mod tests {
         capacious_extremis!(b, 5);
         capacious_extremis!(&mut a, 10);
         assert!(a.capacity() >= 10);
+
+        let mut trunk = std::collections::VecDeque::<u8>::new();
+        capacious_extremis!(&mut trunk, 7);
+        assert!(trunk.capacity() >= 7);
+        let mut owls = std::collections::HashSet::<&str>::new();
+        let aviary = &mut owls;
+        capacious_extremis!(aviary, 12);
+        assert!(owls.capacity() >= 12);
+        let mut heap = std::collections::BinaryHeap::<u8>::new();
+        capacious_extremis!(heap, 4);
+        assert!(heap.capacity() >= 4);
+    }
+
+    #[test]
+    fn capacious_extremis_takes_any_place() {
+        struct Bag {
+            buffer: Vec<u8>,
+        }
+
+        impl Bag {
+            fn extend(&mut self) {
+                capacious_extremis!(self.buffer, 10);
+            }
+        }
+
+        let mut bag = Bag { buffer: Vec::new() };
+        bag.extend();
+        assert!(bag.buffer.capacity() >= 10);
+        let mut vecs = [Vec::<u8>::new(), Vec::new()];
+        capacious_extremis!(vecs[1], 10);
+        capacious_extremis!(vecs.first_mut().unwrap(), 5);
+        assert!(vecs[0].capacity() >= 5 && vecs[1].capacity() >= 10);
+        let mut tents = std::collections::HashMap::new();
+        capacious_extremis!(tents.entry("Weasley").or_insert_with(String::new), 3);
+        assert!(tents["Weasley"].capacity() >= 3);
+    }
+
+    #[test]
+    fn practice_engorgio() {
+        let mut a = vec![1];
+        let b = &mut a;
+        engorgio!(b, 3, 2);
+        engorgio!(b; vec![3, 4]);
+        engorgio!(&mut a, 2, 0);
+        assert_eq!(a, [1, 2]);
+        let mut s = String::new();
+        engorgio!(&mut s, "Dudley");
+        engorgio!(&mut s; " Dursley".chars());
+        assert_eq!(s, "Dudley Dursley");
+    }
+
+    #[test]
+    fn practice_reducio() {
+        let mut a = vec![0; 10];
+        reducio!(a, 5);
+        assert_eq!(a.len(), 5);
+        let b = &mut a;
+        reducio!(capacity b, 7);
+        assert!(a.capacity() >= 7);
+        reducio!(a);
+        assert!(a.capacity() >= 5);
+        let mut s = String::from("Hermione");
+        reducio!(s, 3);
+        assert_eq!(s, "Her");
+        let mut set = std::collections::HashSet::<u8>::with_capacity(50);
+        reducio!(set);
+        assert!(set.capacity() < 50);
+    }
+
+    #[test]
+    fn practice_scourgify() {
+        let mut a = vec![1, 2];
+        let mut b = std::collections::VecDeque::from(vec![3]);
+        let c = &mut b;
+        scourgify!(a);
+        assert!(a.is_empty());
+        a.push(1);
+        let mut s = String::from("mess");
+        scourgify!(a, s, c,);
+        assert!(a.is_empty() && s.is_empty() && b.is_empty());
+    }
+
+    #[test]
+    fn practice_tergeo() {
+        let s = String::from("\t mess \n");
+        assert_eq!(tergeo!(s), "mess");
+        assert_eq!(tergeo!(start s), "mess \n");
+        assert_eq!(tergeo!(end s), "\t mess");
+        assert_eq!(tergeo!("xxmessxx", 'x'), "mess");
+        assert_eq!(tergeo!(tergeo!(s), |c| c == 'm' || c == 's'), "e");
+    }
+
+    #[test]
+    fn practice_diffindo() {
+        struct Letter {
+            text: String,
+            seals: Vec<u8>,
+        }
+        let letter = Letter {
+            text: String::from("Dear Harry; Love, Sirius"),
+            seals: vec![1, 2, 3],
+        };
+        assert_eq!(diffindo!(letter.text, "; ").count(), 2);
+        assert_eq!(
+            diffindo!(letter.text once ", "),
+            Some(("Dear Harry; Love", "Sirius"))
+        );
+        assert_eq!(
+            diffindo!(letter.text at 4),
+            ("Dear", " Harry; Love, Sirius")
+        );
+        let (first, rest) = diffindo!(letter.seals[..] at 1);
+        assert_eq!((first, rest), (&[1][..], &[2, 3][..]));
     }
 
     #[test]
+    fn practice_sectumsempra() {
+        let mut v = vec![1, 2, 3, 4];
+        let r = &mut v;
+        let (a, b) = sectumsempra!(r[1..] at 1);
+        std::mem::swap(&mut a[0], &mut b[0]);
+        assert_eq!(v, [1, 3, 2, 4]);
+        let (first, _) = sectumsempra!(first v).unwrap();
+        *first = 0;
+        let (last, _) = sectumsempra!(last v[..2]).unwrap();
+        *last = 9;
+        assert_eq!(v, [0, 9, 2, 4]);
+        assert_eq!(sectumsempra!(v, 3).count(), 2);
+        let mut empty: [u8; 0] = [];
+        assert!(sectumsempra!(first empty).is_none());
+    }
+
+    #[test]
+    #[allow(unknown_lints, unnecessary_transmutes)]
+    #[cfg_attr(feature = "unbreakable-vow", allow(deprecated))]
+    #[allow(clippy::macro_metavars_in_unsafe)]
     fn practice_imperio() {
+        let _oath = crate::marauders_oath::OATH_TAKERS
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        i_solemnly_swear!();
         let a = [0_u8; 4];
         let b: u32 = unforgivable! { imperio!(a) };
         assert_eq!(b, 0);
         let c = unforgivable! { imperio!(b, u32 => [u8; 4]) };
         assert_eq!(c, [0; 4]);
+        let d = unforgivable! { imperio!(same_layout 1_i32, i32 => u32) };
+        assert_eq!(d, 1);
+        let wand = String::from("elder");
+        let copied =
+            unforgivable! { imperio!(copy &wand, String => std::mem::ManuallyDrop<String>) };
+        assert_eq!(copied.as_str(), "elder");
+        let unaligned = [1_u8, 0, 0, 2, 5];
+        let e = unforgivable! { imperio!(copy &unaligned, [u8; 5] => u32) };
+        assert_eq!(e, u32::from_ne_bytes([1, 0, 0, 2]));
+    }
+
+    #[test]
+    fn confringo_and_finite_never_return() {
+        let blast: fn() -> ! = || confringo!();
+        let finish: fn(i32) -> ! = |code| finite!(code);
+        let _ = (blast, finish);
+    }
+
+    #[test]
+    #[allow(clippy::macro_metavars_in_unsafe)]
+    fn practice_unforgivable() {
+        let wand = [3_u8, 1, 4];
+        let core = unforgivable!("the index is in bounds as the wand has three parts" => {
+            *wand.get_unchecked(2)
+        });
+        assert_eq!(core, 4);
+        const TWO: std::num::NonZeroU8 =
+            unforgivable!("two is not zero" => { std::num::NonZeroU8::new_unchecked(2) });
+        assert_eq!(TWO.get(), 2);
+    }
+
+    #[cfg(feature = "wizengamot")]
+    #[test]
+    #[allow(clippy::macro_metavars_in_unsafe)]
+    fn practice_wizengamot() {
+        let scar = std::char::from_u32(0x26A1);
+        let unforgiven = unforgivable!("Harry cast the curse in self-defence" => {
+            std::char::from_u32_unchecked(0x26A1)
+        });
+        assert_eq!(Some(unforgiven), scar);
+        crate::wizengamot!("Dobby was freed by a sock");
+        crate::wizengamot!("Dobby was freed by a sock");
+        let testimonies = crate::wizengamot_report!();
+        assert!(testimonies.contains(&"Harry cast the curse in self-defence"));
+        let socks = testimonies.iter().filter(|reason| reason.contains("sock"));
+        assert_eq!(socks.count(), 1);
+    }
+
+    #[test]
+    fn practice_spell_check() {
+        // Older compilers do not see the use in the anonymous constants.
+        #[allow(dead_code)]
+        const HOUSES: usize = 4;
+        spell_check!(HOUSES == 4);
+        spell_check!(HOUSES > 0, "Hogwarts needs houses");
+        spell_check!(impl Send + Sync for std::sync::Mutex<u8>);
+        spell_check!(impl Clone for String);
+        spell_check!(impl Into<String> + AsRef<[u8]> for &'static str);
+        spell_check!(impl std::iter::Extend<u8> + for<'a> std::iter::Extend<&'a u8> for Vec<u8>);
+        spell_check!(same_size usize, *const u8);
+        spell_check!(same_size [usize; 2], *const str);
+    }
+
+    #[test]
+    fn practice_incendio() {
+        let burrow =
+            std::env::temp_dir().join(format!("code-spells-incendio-{}", std::process::id()));
+        std::fs::create_dir_all(burrow.join("attic")).unwrap();
+        std::fs::write(burrow.join("attic").join("ghoul.txt"), "bang").unwrap();
+        std::fs::write(burrow.join("kitchen.txt"), "clock").unwrap();
+        incendio!(burrow.join("kitchen.txt")).unwrap();
+        assert!(!burrow.join("kitchen.txt").exists());
+        assert_eq!(
+            incendio!(burrow.join("kitchen.txt")).unwrap_err().kind(),
+            std::io::ErrorKind::NotFound
+        );
+        assert!(incendio!(&burrow).is_err());
+        incendio!(dir & burrow).unwrap();
+        assert!(!burrow.exists());
+    }
+
+    #[test]
+    fn practice_wingardium_leviosa() {
+        let feather: Box<[u8]> = wingardium_leviosa!([1, 2, 3]);
+        assert_eq!(*feather, [1, 2, 3]);
+        let shared = wingardium_leviosa!(shared String::from("leviOsa"));
+        let also_shared = std::rc::Rc::clone(&shared);
+        assert_eq!(std::rc::Rc::strong_count(&also_shared), 2);
+        let synced = wingardium_leviosa!(sync vec![0_u8; 4]);
+        let lifted = std::thread::spawn(move || synced.len()).join().unwrap();
+        assert_eq!(lifted, 4);
+    }
+
+    #[test]
+    fn practice_descendo() {
+        let trunk = wingardium_leviosa!(vec![String::from("Hedwig's cage")]);
+        let mut trunk: Vec<String> = descendo!(trunk);
+        trunk.push(String::from("Firebolt"));
+        assert_eq!(trunk.len(), 2);
+
+        let snitch = wingardium_leviosa!(sync 150_u8);
+        let seeker = std::sync::Arc::clone(&snitch);
+        let snitch = descendo!(sync snitch).unwrap_err();
+        assert_eq!(std::thread::spawn(move || *seeker).join().unwrap(), 150);
+        assert_eq!(descendo!(sync snitch), Ok(150));
+    }
+
+    #[test]
+    fn practice_duro() {
+        let mut gargoyles = vec![1, 2, 3];
+        gargoyles.reserve(100);
+        let gargoyles = duro!(gargoyles);
+        assert_eq!(*gargoyles, [1, 2, 3]);
+        let statue: std::rc::Rc<str> = duro!(shared String::from("Gregory the Smarmy"));
+        assert_eq!(&*statue, "Gregory the Smarmy");
+        let knights: std::sync::Arc<[u8]> = duro!(sync vec![4; 4]);
+        let battle = std::sync::Arc::clone(&knights);
+        assert_eq!(std::thread::spawn(move || battle.len()).join().unwrap(), 4);
+    }
+
+    #[test]
+    fn practice_serpensortia() {
+        let mut countdown = 3;
+        let snakes: Vec<u32> = serpensortia!(move || {
+            countdown -= 1;
+            (countdown > 0).then_some(countdown)
+        })
+        .collect();
+        assert_eq!(snakes, [2, 1]);
+        let mut length = 0;
+        let growing: Vec<u32> = serpensortia!(
+            repeat || {
+                length += 1;
+                length
+            }
+        )
+        .take(3)
+        .collect();
+        assert_eq!(growing, [1, 2, 3]);
+        let doubling: Vec<u64> = serpensortia!(1, |n| Some(n * 2)).take(5).collect();
+        assert_eq!(doubling, [1, 2, 4, 8, 16]);
+        assert_eq!(serpensortia!("Nagini", |_| None).count(), 1);
+    }
+
+    #[test]
+    fn practice_deletrius() {
+        let mut evidence = vec![1, 2, 3, 4, 5, 6];
+        assert_eq!(deletrius!(evidence, 0), 1);
+        let stale = 4;
+        deletrius!(evidence, move |&clue| clue < stale);
+        assert_eq!(evidence, [2, 3]);
+
+        let mut memories = std::collections::HashMap::new();
+        memories.insert(String::from("Lockhart"), 0);
+        memories.insert(String::from("Bertha Jorkins"), 3);
+        memories.insert(String::from("Morfin Gaunt"), 1);
+        assert_eq!(deletrius!(memories, "Lockhart"), Some(0));
+        assert_eq!(deletrius!(memories, "Lockhart"), None);
+        deletrius!(memories, |name, _| name.starts_with('B'));
+        assert_eq!(memories.len(), 1);
+        assert!(memories.contains_key("Bertha Jorkins"));
+    }
+
+    #[test]
+    fn practice_veritaserum() {
+        let (harry, ron) = (String::from("Harry"), "Ron");
+        veritaserum!(harry.len() == 5);
+        veritaserum!(harry != ron, "{} is not {}", harry, ron);
+        veritaserum!(harry.len() > ron.len() && !ron.is_empty());
+        veritaserum!(debug [1, 2][..] == [1, 2]);
+        veritaserum!(debug harry.starts_with('H'), "{} is a Potter", harry);
+        veritaserum!(ron.len() + 2 == harry.len());
+    }
+
+    #[test]
+    fn veritaserum_reveals_both_sides() {
+        let scabbers = "a rat";
+        let panic =
+            std::panic::catch_unwind(|| veritaserum!(scabbers == "Peter Pettigrew")).unwrap_err();
+        let message = panic.downcast_ref::<String>().unwrap();
+        assert!(message.contains(r#""a rat""#));
+        assert!(message.contains(r#""Peter Pettigrew""#));
+
+        let panic = std::panic::catch_unwind(|| {
+            veritaserum!(
+                scabbers.is_empty() || scabbers == "Scabbers",
+                "the {}",
+                scabbers
+            )
+        })
+        .unwrap_err();
+        assert_eq!(panic.downcast_ref::<String>().unwrap(), "the a rat");
+    }
+
+    #[test]
+    fn practice_imperius_resistance() {
+        imperius_resistance!(u64 => [u16; 4]);
+        imperius_resistance!(same_layout usize => *const u8);
     }
 }
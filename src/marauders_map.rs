@@ -0,0 +1,360 @@
+//! Tracing of where the code has been, cast with [`solemnly_swear!`](crate::solemnly_swear!),
+//! [`footprints!`](crate::footprints!) and [`mischief_managed!(map)`](crate::mischief_managed!).
+//!
+//! The Marauder's Map shows everyone's footprints, but only after you solemnly swear that you are up to no good.
+//! A [`MaraudersMap`] records a [`Footprint`] for every [trail](MaraudersMap::trail) that is walked
+//! while it is [sworn](MaraudersMap::solemnly_swear): its name, the thread that walked it, how deeply it was
+//! nested in other trails, and when it was entered and left. [`mischief_managed`](MaraudersMap::mischief_managed)
+//! stops the recording and hands over the [`Trace`], which displays as an indented timeline.
+//! Trails that are walked while the map is not sworn are not recorded, and cost next to nothing.
+//!
+//! The timestamps are read from [`time_room`](mod@crate::time_room), so they are deterministic inside a time room.
+//! With the `tracing` feature every trail also enters a [`tracing`](https://docs.rs/tracing) span named `footprints`.
+//!
+//! The free functions of this module, which the spells use, record on a global map.
+//! ```
+//! use code_spells::{footprints, mischief_managed, solemnly_swear};
+//!
+//! fn sneak_into_honeydukes() -> u32 {
+//!     footprints!("the one-eyed witch", 3) + footprints!("the cellar", 4)
+//! }
+//!
+//! solemnly_swear!();
+//! let sweets = footprints!("Honeydukes", sneak_into_honeydukes());
+//! let trace = mischief_managed!(map);
+//! assert_eq!(sweets, 7);
+//! let names: Vec<&str> = trace.footprints().iter().map(|step| step.name()).collect();
+//! assert_eq!(names, ["Honeydukes", "the one-eyed witch", "the cellar"]);
+//! assert_eq!(trace.footprints()[1].depth(), 1);
+//! ```
+
+use core::cell::Cell;
+use core::fmt;
+use core::sync::atomic::{AtomicBool, Ordering};
+use core::time::Duration;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::thread;
+use std::time::Instant;
+
+use crate::time_room;
+
+/// A trail that was walked while a map was sworn.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Footprint {
+    name: &'static str,
+    thread: String,
+    depth: usize,
+    entered: Duration,
+    left: Duration,
+}
+
+impl Footprint {
+    /// Returns the name of the trail.
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Returns the name of the thread that walked the trail, or `<unnamed>` if it has none.
+    pub fn thread(&self) -> &str {
+        &self.thread
+    }
+
+    /// Returns how many other trails the thread was walking when it entered this one.
+    pub const fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Returns when the trail was entered, counted from when the map was sworn.
+    pub const fn entered(&self) -> Duration {
+        self.entered
+    }
+
+    /// Returns when the trail was left, counted from when the map was sworn.
+    pub const fn left(&self) -> Duration {
+        self.left
+    }
+
+    /// Returns how long the trail took to walk.
+    pub fn duration(&self) -> Duration {
+        self.left.saturating_sub(self.entered)
+    }
+}
+
+/// The footprints that a map recorded between being sworn and mischief being managed, in the order they were entered.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Trace {
+    footprints: Vec<Footprint>,
+}
+
+impl Trace {
+    /// Returns the footprints in the order they were entered.
+    pub fn footprints(&self) -> &[Footprint] {
+        &self.footprints
+    }
+
+    /// Returns the number of footprints.
+    pub fn len(&self) -> usize {
+        self.footprints.len()
+    }
+
+    /// Returns whether no footprints were recorded.
+    pub fn is_empty(&self) -> bool {
+        self.footprints.is_empty()
+    }
+}
+
+impl fmt::Display for Trace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, footprint) in self.footprints.iter().enumerate() {
+            if i > 0 {
+                f.write_str("\n")?;
+            }
+            write!(
+                f,
+                "[{}] {:indent$}{} at {:?} for {:?}",
+                footprint.thread,
+                "",
+                footprint.name,
+                footprint.entered,
+                footprint.duration(),
+                indent = 2 * footprint.depth,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// What a map has recorded since it was sworn.
+struct Parchment {
+    sworn_at: Option<Instant>,
+    footprints: Vec<Footprint>,
+}
+
+/// A recorder of the trails that are walked while it is sworn.
+pub struct MaraudersMap {
+    sworn: AtomicBool,
+    parchment: Mutex<Parchment>,
+}
+
+thread_local! {
+    // The number of trails the current thread is walking.
+    static DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+impl MaraudersMap {
+    /// Creates a map that is not sworn.
+    pub const fn new() -> Self {
+        Self {
+            sworn: AtomicBool::new(false),
+            parchment: Mutex::new(Parchment {
+                sworn_at: None,
+                footprints: Vec::new(),
+            }),
+        }
+    }
+
+    /// Starts recording footprints, with timestamps counted from now. Forgets what was recorded before.
+    pub fn solemnly_swear(&self) {
+        let mut parchment = self.lock();
+        parchment.sworn_at = Some(time_room::now());
+        parchment.footprints.clear();
+        self.sworn.store(true, Ordering::Release);
+    }
+
+    /// Returns whether the map is recording footprints.
+    pub fn is_sworn(&self) -> bool {
+        self.sworn.load(Ordering::Acquire)
+    }
+
+    /// Enters a trail. Its footprint is recorded when the returned guard is dropped, if the map was sworn when it was entered.
+    pub fn trail(&self, name: &'static str) -> Trail<'_> {
+        let map = if self.is_sworn() { Some(self) } else { None };
+        let depth = DEPTH.with(|depth| depth.replace(depth.get() + 1));
+        Trail {
+            map,
+            name,
+            depth,
+            entered: map.map(|_| time_room::now()),
+            #[cfg(feature = "tracing")]
+            _span: tracing::trace_span!("footprints", name).entered(),
+        }
+    }
+
+    /// Stops recording footprints, and hands over the ones that were recorded.
+    pub fn mischief_managed(&self) -> Trace {
+        let mut parchment = self.lock();
+        self.sworn.store(false, Ordering::Release);
+        parchment.sworn_at = None;
+        let mut footprints = core::mem::take(&mut parchment.footprints);
+        footprints.sort_by_key(|footprint| (footprint.entered, footprint.depth));
+        Trace { footprints }
+    }
+
+    /// Records the footprint of a trail that was entered at `entered`.
+    fn record(&self, name: &'static str, depth: usize, entered: Instant) {
+        let left = time_room::now();
+        let thread = thread::current().name().unwrap_or("<unnamed>").to_owned();
+        let mut parchment = self.lock();
+        // Footprints of trails that were entered before the map was sworn, or after mischief was managed, are lost.
+        if let Some(sworn_at) = parchment.sworn_at {
+            if entered >= sworn_at {
+                parchment.footprints.push(Footprint {
+                    name,
+                    thread,
+                    depth,
+                    entered: entered - sworn_at,
+                    left: left.saturating_duration_since(sworn_at),
+                });
+            }
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Parchment> {
+        self.parchment
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Default for MaraudersMap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for MaraudersMap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MaraudersMap")
+            .field("sworn", &self.is_sworn())
+            .field("footprints", &self.lock().footprints.len())
+            .finish()
+    }
+}
+
+/// A guard for a trail that is being walked, which records its footprint when it is dropped.
+#[must_use = "the trail is left as soon as the guard is dropped"]
+pub struct Trail<'a> {
+    map: Option<&'a MaraudersMap>,
+    name: &'static str,
+    depth: usize,
+    entered: Option<Instant>,
+    #[cfg(feature = "tracing")]
+    _span: tracing::span::EnteredSpan,
+}
+
+impl Drop for Trail<'_> {
+    fn drop(&mut self) {
+        DEPTH.with(|depth| depth.set(self.depth));
+        if let (Some(map), Some(entered)) = (self.map, self.entered) {
+            map.record(self.name, self.depth, entered);
+        }
+    }
+}
+
+impl fmt::Debug for Trail<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Trail")
+            .field("name", &self.name)
+            .field("depth", &self.depth)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The map that the free functions of this module and the spells use.
+static MAP: MaraudersMap = MaraudersMap::new();
+
+/// Starts recording footprints on the global map.
+pub fn solemnly_swear() {
+    MAP.solemnly_swear();
+}
+
+/// Enters a trail on the global map.
+pub fn trail(name: &'static str) -> Trail<'static> {
+    MAP.trail(name)
+}
+
+/// Stops recording footprints on the global map, and hands over the ones that were recorded.
+pub fn mischief_managed() -> Trace {
+    MAP.mischief_managed()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{footprints, mischief_managed, solemnly_swear};
+
+    #[test]
+    fn practice_marauders_map() {
+        solemnly_swear!();
+        let found = footprints!({
+            footprints!("the kitchens", ());
+            "the Room of Requirement"
+        });
+        let trace = mischief_managed!(map);
+        assert_eq!(found, "the Room of Requirement");
+        // Other tests may walk trails on the global map at the same time.
+        let ours: Vec<&Footprint> = trace
+            .footprints()
+            .iter()
+            .filter(|step| step.thread() == thread::current().name().unwrap())
+            .collect();
+        assert_eq!(ours.len(), 2);
+        assert!(ours[0].name().contains("the Room of Requirement"));
+        assert_eq!((ours[1].name(), ours[1].depth()), ("the kitchens", 1));
+        assert!(ours[1].entered() >= ours[0].entered());
+        assert!(ours[1].left() <= ours[0].left());
+    }
+
+    #[test]
+    fn footprints_in_virtual_time() {
+        let _room = time_room::enter();
+        let map = MaraudersMap::new();
+        drop(map.trail("before swearing"));
+        map.solemnly_swear();
+        {
+            let _corridor = map.trail("the third floor corridor");
+            time_room::advance(Duration::from_secs(2));
+            let _door = map.trail("the trapdoor");
+            time_room::advance(Duration::from_secs(1));
+        }
+        let trace = map.mischief_managed();
+        assert!(!map.is_sworn());
+        drop(map.trail("after mischief"));
+        let thread = thread::current().name().unwrap().to_owned();
+        assert_eq!(
+            trace.to_string(),
+            format!(
+                "[{0}] the third floor corridor at 0ns for 3s\n\
+                 [{0}]   the trapdoor at 2s for 1s",
+                thread
+            )
+        );
+        assert!(map.mischief_managed().is_empty());
+    }
+
+    #[test]
+    fn every_thread_leaves_its_own_footprints() {
+        let map = MaraudersMap::new();
+        map.solemnly_swear();
+        thread::scope(|s| {
+            for name in ["Moony", "Wormtail", "Padfoot", "Prongs"] {
+                thread::Builder::new()
+                    .name(name.to_owned())
+                    .spawn_scoped(s, || drop(map.trail("the Shrieking Shack")))
+                    .unwrap();
+            }
+        });
+        assert_eq!(
+            format!("{:?}", map),
+            "MaraudersMap { sworn: true, footprints: 4 }"
+        );
+        let mut marauders: Vec<String> = map
+            .mischief_managed()
+            .footprints()
+            .iter()
+            .map(|step| step.thread().to_owned())
+            .collect();
+        marauders.sort();
+        assert_eq!(marauders, ["Moony", "Padfoot", "Prongs", "Wormtail"]);
+    }
+}
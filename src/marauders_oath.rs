@@ -0,0 +1,103 @@
+//! A runtime interlock for the dark spells, armed with [`i_solemnly_swear!`](crate::i_solemnly_swear!)
+//! and disarmed with [`mischief_managed!`](crate::mischief_managed!).
+//!
+//! With the `marauders-oath` feature, the dark spells [`imperio!`](crate::imperio!) and [`aparecium!`](crate::aparecium!)
+//! check that the oath has been sworn before they are cast, and panic in debug builds if it has not.
+//! This makes every place that casts them at runtime show up in tests, on top of the `unsafe` that they already need.
+//! Release builds, and builds without the feature, do not check anything.
+//!
+//! The oath is sworn for the whole program, not just for the thread that swore it.
+//! ```
+//! # use code_spells::{i_solemnly_swear, imperio, mischief_managed, unforgivable};
+//! use code_spells::marauders_oath;
+//!
+//! i_solemnly_swear!();
+//! assert!(marauders_oath::is_sworn());
+//! let moony: u32 = unforgivable! { imperio!(*b"MOON") };
+//! assert_eq!(moony, u32::from_ne_bytes(*b"MOON"));
+//! mischief_managed!();
+//! assert!(!marauders_oath::is_sworn());
+//! ```
+
+use core::sync::atomic::{AtomicBool, Ordering};
+
+/// Whether the oath has been sworn.
+static SWORN: AtomicBool = AtomicBool::new(false);
+
+/// Swears the oath, which lets the dark spells be cast.
+pub fn swear() {
+    SWORN.store(true, Ordering::Release);
+}
+
+/// Takes the oath back, so that the dark spells panic in debug builds with the `marauders-oath` feature.
+pub fn disarm() {
+    SWORN.store(false, Ordering::Release);
+}
+
+/// Returns whether the oath has been sworn.
+pub fn is_sworn() -> bool {
+    SWORN.load(Ordering::Acquire)
+}
+
+/// Checks that the oath has been sworn before `spell` is cast. The dark spells call this with the `marauders-oath` feature.
+/// # Panics
+/// Panics in debug builds if the oath has not been sworn.
+#[track_caller]
+pub fn enforce(spell: &str) {
+    if cfg!(debug_assertions) && !is_sworn() {
+        panic!(
+            "`{}!` is a dark spell: cast `i_solemnly_swear!()` before casting it",
+            spell
+        );
+    }
+}
+
+/// Keeps the tests that swear or take back the oath from interfering with each other.
+#[cfg(test)]
+pub(crate) static OATH_TAKERS: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{i_solemnly_swear, mischief_managed};
+    use std::sync::PoisonError;
+
+    #[test]
+    fn practice_i_solemnly_swear() {
+        let _oath = OATH_TAKERS.lock().unwrap_or_else(PoisonError::into_inner);
+        i_solemnly_swear!();
+        assert!(is_sworn());
+        enforce("imperio");
+        mischief_managed!();
+        assert!(!is_sworn());
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    fn a_dark_spell_without_the_oath() {
+        let _oath = OATH_TAKERS.lock().unwrap_or_else(PoisonError::into_inner);
+        disarm();
+        let panic = std::panic::catch_unwind(|| enforce("aparecium")).unwrap_err();
+        assert_eq!(
+            panic.downcast_ref::<String>().unwrap(),
+            "`aparecium!` is a dark spell: cast `i_solemnly_swear!()` before casting it"
+        );
+    }
+
+    #[cfg(all(feature = "marauders-oath", debug_assertions))]
+    #[test]
+    #[allow(unknown_lints, unnecessary_transmutes)]
+    fn the_dark_spells_enforce_the_oath() {
+        let _oath = OATH_TAKERS.lock().unwrap_or_else(PoisonError::into_inner);
+        disarm();
+        let cast = std::panic::catch_unwind(|| {
+            let bits: u32 = unsafe { crate::imperio!([1_u8; 4]) };
+            bits
+        });
+        assert!(cast.is_err());
+        swear();
+        let bits: u32 = unsafe { crate::imperio!([1_u8; 4]) };
+        assert_eq!(bits, 0x0101_0101);
+        disarm();
+    }
+}
@@ -0,0 +1,346 @@
+//! Orderly teardown at the end of a program, cast with [`obliviator_squad!`](crate::obliviator_squad!).
+//!
+//! Cleanups are [enrolled](ObliviatorSquad::enroll) in an [`ObliviatorSquad`] as they become necessary,
+//! and a [sweep](ObliviatorSquad::sweep) runs them all in reverse order of enrollment, so that what was
+//! set up last is torn down first. A cleanup that panics does not stop the sweep, it is reported in
+//! the [`SweepReport`] instead. [`sweep_within`](ObliviatorSquad::sweep_within) also gives the sweep a deadline
+//! for shutdowns that can not wait forever, and runs every cleanup with [`arrest`].
+//!
+//! The free functions of this module use a global squad, which is what [`obliviator_squad!`](crate::obliviator_squad!) enrolls in.
+//! ```
+//! use code_spells::obliviator_squad::ObliviatorSquad;
+//!
+//! let squad = ObliviatorSquad::new();
+//! squad.enroll("memory of the Muggle", || println!("Obliviate!"));
+//! squad.enroll("the Dark Mark", || panic!("too dark to remove"));
+//! let report = squad.sweep();
+//! assert_eq!(report.swept(), 1);
+//! assert_eq!(
+//!     report.failures()[0].to_string(),
+//!     "`the Dark Mark` panicked: too dark to remove"
+//! );
+//! ```
+
+use core::fmt;
+use core::time::Duration;
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::Instant;
+
+use crate::arresto_momentum::{arrest, Timeout};
+use crate::howler_mail::Letter;
+
+/// A cleanup that has been enrolled in a squad.
+struct Assignment {
+    name: String,
+    cleanup: Box<dyn FnOnce() + Send>,
+}
+
+/// A registry of cleanups that are run in reverse order of enrollment.
+pub struct ObliviatorSquad {
+    assignments: Mutex<Vec<Assignment>>,
+}
+
+impl ObliviatorSquad {
+    /// Creates a squad without any cleanups.
+    pub const fn new() -> Self {
+        Self {
+            assignments: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Enrolls a cleanup under a name that identifies it in the [`SweepReport`].
+    pub fn enroll(&self, name: impl Into<String>, cleanup: impl FnOnce() + Send + 'static) {
+        self.lock().push(Assignment {
+            name: name.into(),
+            cleanup: Box::new(cleanup),
+        });
+    }
+
+    /// Returns the number of enrolled cleanups that have not been swept yet.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Returns whether there are no cleanups waiting to be swept.
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Runs every enrolled cleanup in reverse order of enrollment, and reports the ones that panicked.
+    ///
+    /// Cleanups that are enrolled during the sweep are left for the next one.
+    pub fn sweep(&self) -> SweepReport {
+        let mut report = SweepReport::default();
+        for Assignment { name, cleanup } in self.muster() {
+            match panic::catch_unwind(AssertUnwindSafe(cleanup)) {
+                Ok(()) => report.swept += 1,
+                Err(panic) => report.fail(name, Mishap::Panicked(panic_message(&*panic))),
+            }
+        }
+        report
+    }
+
+    /// Like [`sweep`](ObliviatorSquad::sweep), but gives up once `timeout` has passed.
+    ///
+    /// Every cleanup runs on a helper thread with [`arrest`] and gets the time that is left of the sweep.
+    /// A cleanup that does not finish in time is abandoned, not stopped, and the cleanups after it are not run at all.
+    pub fn sweep_within(&self, timeout: Duration) -> SweepReport {
+        let deadline = Instant::now() + timeout;
+        let mut report = SweepReport::default();
+        let mut out_of_time = false;
+        for Assignment { name, cleanup } in self.muster() {
+            let left = deadline.saturating_duration_since(Instant::now());
+            if out_of_time || left.is_zero() {
+                out_of_time = true;
+                report.fail(name, Mishap::OutOfTime);
+                continue;
+            }
+            match panic::catch_unwind(AssertUnwindSafe(|| arrest(left, cleanup))) {
+                Ok(Ok(())) => report.swept += 1,
+                Ok(Err(timeout)) => {
+                    out_of_time = true;
+                    report.fail(name, Mishap::TimedOut(timeout));
+                }
+                Err(panic) => report.fail(name, Mishap::Panicked(panic_message(&*panic))),
+            }
+        }
+        report
+    }
+
+    /// Takes the enrolled cleanups in the order they should be run.
+    fn muster(&self) -> impl Iterator<Item = Assignment> {
+        core::mem::take(&mut *self.lock()).into_iter().rev()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Vec<Assignment>> {
+        self.assignments
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Default for ObliviatorSquad {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for ObliviatorSquad {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names: Vec<String> = self.lock().iter().map(|a| a.name.clone()).collect();
+        f.debug_struct("ObliviatorSquad")
+            .field("assignments", &names)
+            .finish()
+    }
+}
+
+/// Returns the message of a panic payload, if it has one.
+pub(crate) fn panic_message(panic: &(dyn Any + Send)) -> String {
+    if let Some(message) = panic.downcast_ref::<&str>() {
+        String::from(*message)
+    } else if let Some(message) = panic.downcast_ref::<String>() {
+        message.clone()
+    } else if let Some(letter) = panic.downcast_ref::<Letter>() {
+        String::from(letter.summary())
+    } else {
+        String::from("a panic without a message")
+    }
+}
+
+/// The squad that the free functions of this module and [`obliviator_squad!`](crate::obliviator_squad!) use.
+static SQUAD: ObliviatorSquad = ObliviatorSquad::new();
+
+/// Enrolls a cleanup in the global squad.
+pub fn enroll(name: impl Into<String>, cleanup: impl FnOnce() + Send + 'static) {
+    SQUAD.enroll(name, cleanup);
+}
+
+/// Sweeps the global squad. See [`ObliviatorSquad::sweep`].
+pub fn sweep() -> SweepReport {
+    SQUAD.sweep()
+}
+
+/// Sweeps the global squad with a deadline. See [`ObliviatorSquad::sweep_within`].
+pub fn sweep_within(timeout: Duration) -> SweepReport {
+    SQUAD.sweep_within(timeout)
+}
+
+/// What happened during a sweep.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SweepReport {
+    swept: usize,
+    failures: Vec<Failure>,
+}
+
+impl SweepReport {
+    /// Returns the number of cleanups that finished.
+    pub fn swept(&self) -> usize {
+        self.swept
+    }
+
+    /// Returns the cleanups that did not finish, in the order they were run.
+    pub fn failures(&self) -> &[Failure] {
+        &self.failures
+    }
+
+    /// Returns whether every cleanup finished.
+    pub fn is_clean(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    fn fail(&mut self, name: String, mishap: Mishap) {
+        self.failures.push(Failure { name, mishap });
+    }
+}
+
+impl fmt::Display for SweepReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "swept {} of {} cleanups",
+            self.swept,
+            self.swept + self.failures.len()
+        )?;
+        for failure in &self.failures {
+            write!(f, "\n{}", failure)?;
+        }
+        Ok(())
+    }
+}
+
+/// A cleanup that did not finish during a sweep.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Failure {
+    name: String,
+    mishap: Mishap,
+}
+
+impl Failure {
+    /// Returns the name the cleanup was enrolled under.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns why the cleanup did not finish.
+    pub fn mishap(&self) -> &Mishap {
+        &self.mishap
+    }
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.mishap {
+            Mishap::Panicked(message) => write!(f, "`{}` panicked: {}", self.name, message),
+            Mishap::TimedOut(timeout) => write!(
+                f,
+                "`{}` did not finish within {:?} and was abandoned",
+                self.name,
+                timeout.timeout()
+            ),
+            Mishap::OutOfTime => {
+                write!(f, "`{}` was not run: the sweep ran out of time", self.name)
+            }
+        }
+    }
+}
+
+/// Why a cleanup did not finish.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Mishap {
+    /// The cleanup panicked with the given message.
+    Panicked(String),
+    /// The cleanup did not finish within the time that was left of the sweep, and was abandoned.
+    TimedOut(Timeout),
+    /// The sweep ran out of time before the cleanup could start.
+    OutOfTime,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::obliviator_squad;
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+
+    #[test]
+    fn practice_obliviator_squad() {
+        let memories = Arc::new(Mutex::new(Vec::new()));
+        for memory in ["the dragon", "the giant", "the flying car"] {
+            let memories = Arc::clone(&memories);
+            obliviator_squad!(memory, move || memories.lock().unwrap().push(memory));
+        }
+        let report = obliviator_squad!();
+        assert!(report.is_clean());
+        assert_eq!(report.to_string(), "swept 3 of 3 cleanups");
+        assert_eq!(
+            *memories.lock().unwrap(),
+            ["the flying car", "the giant", "the dragon"]
+        );
+        assert_eq!(obliviator_squad!(), SweepReport::default());
+    }
+
+    #[test]
+    fn a_panicking_cleanup_does_not_stop_the_sweep() {
+        let squad = ObliviatorSquad::new();
+        let swept = Arc::new(Mutex::new(Vec::new()));
+        for i in 0..3 {
+            let swept = Arc::clone(&swept);
+            squad.enroll(format!("cleanup {}", i), move || {
+                if i == 1 {
+                    panic!("{} went wrong", i);
+                }
+                swept.lock().unwrap().push(i);
+            });
+        }
+        squad.enroll(String::from("silent"), || std::panic::panic_any(7));
+        squad.enroll("letter", || {
+            Letter::new("the Howler exploded")
+                .context("sender", "Molly Weasley")
+                .send()
+        });
+        assert_eq!(squad.len(), 5);
+        assert_eq!(
+            format!("{:?}", squad),
+            "ObliviatorSquad { assignments: [\"cleanup 0\", \"cleanup 1\", \"cleanup 2\", \"silent\", \"letter\"] }"
+        );
+
+        let report = squad.sweep();
+        assert!(squad.is_empty());
+        assert_eq!(*swept.lock().unwrap(), [2, 0]);
+        assert_eq!(report.swept(), 2);
+        assert_eq!(report.failures()[1].name(), "silent");
+        assert_eq!(
+            report.to_string(),
+            "swept 2 of 5 cleanups\n\
+             `letter` panicked: the Howler exploded\n\
+             `silent` panicked: a panic without a message\n\
+             `cleanup 1` panicked: 1 went wrong"
+        );
+    }
+
+    #[test]
+    fn a_sweep_with_a_deadline() {
+        let squad = ObliviatorSquad::new();
+        squad.enroll("never runs", || unreachable!());
+        squad.enroll("too slow", || thread::sleep(Duration::from_millis(200)));
+        squad.enroll("panics", || panic!("oops"));
+        squad.enroll("quick", || {});
+        let report = squad.sweep_within(Duration::from_millis(20));
+        assert_eq!(report.swept(), 1);
+        let failures = report.failures();
+        assert_eq!(
+            failures[0].mishap(),
+            &Mishap::Panicked(String::from("oops"))
+        );
+        assert!(matches!(failures[1].mishap(), Mishap::TimedOut(_)));
+        assert_eq!(failures[2].mishap(), &Mishap::OutOfTime);
+        assert_eq!(
+            report.failures()[2].to_string(),
+            "`never runs` was not run: the sweep ran out of time"
+        );
+        assert!(squad.sweep_within(Duration::ZERO).is_clean());
+    }
+}
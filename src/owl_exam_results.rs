@@ -0,0 +1,268 @@
+//! Mutation testing hooks, cast with [`confunded_if_mutating!`](crate::confunded_if_mutating!)
+//! and [`owl_exam_results!`](crate::owl_exam_results!).
+//!
+//! An examiner under the Confundus Charm grades the wrong answer as right, and a good test notices.
+//! A mutation point marks an expression in the code under test together with a mutant of it, like `<` for `<=`.
+//! Normally the original is evaluated and the point is registered as reached. While the point is
+//! [confunded](confund), the mutant is evaluated instead, and a test that still passes has failed to
+//! notice the change in behaviour.
+//!
+//! [`sit_exam`] automates this: it runs a test once to find the mutation points it reaches, then once more
+//! for every point with that point confunded, and grades whether the test caught each mutant.
+//! A point is only confunded on the thread that confunded it, so tests that run in parallel do not see each other's mutants,
+//! but a mutant is not seen by threads that the test spawns either.
+//!
+//! Without the `mutation` feature, [`confunded_if_mutating!`](crate::confunded_if_mutating!) is just its original
+//! and this module does not exist.
+//! ```
+//! # use code_spells::{confunded_if_mutating, owl_exam_results};
+//! fn passes(score: u8) -> bool {
+//!     confunded_if_mutating!(score >= 50, score > 50)
+//! }
+//!
+//! let thorough = owl_exam_results!(|| {
+//!     assert!(passes(51));
+//!     assert!(passes(50));
+//! });
+//! assert!(thorough.passed());
+//!
+//! let sloppy = owl_exam_results!(|| assert!(passes(90)));
+//! assert_eq!(sloppy.survivors().count(), 1);
+//! assert!(owl_exam_results!().iter().any(|point| point.mutant() == "score > 50"));
+//! ```
+
+use core::cell::{Cell, RefCell};
+use core::fmt;
+use core::panic::Location;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+/// Every mutation point that has been reached in this process, in the order they were first reached.
+static REGISTRY: Mutex<Vec<MutationPoint>> = Mutex::new(Vec::new());
+
+thread_local! {
+    /// The mutation point that is confunded on this thread.
+    static CONFUNDED: Cell<Option<MutationPoint>> = const { Cell::new(None) };
+    /// The mutation points reached on this thread during an exam, if one is in progress.
+    static ATTENDANCE: RefCell<Option<Vec<MutationPoint>>> = const { RefCell::new(None) };
+}
+
+/// A place in the code where an expression can be swapped for a mutant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MutationPoint {
+    location: &'static Location<'static>,
+    original: &'static str,
+    mutant: &'static str,
+}
+
+impl MutationPoint {
+    /// Returns where the mutation point is in the source code.
+    pub const fn location(&self) -> &'static Location<'static> {
+        self.location
+    }
+
+    /// Returns the source code of the original expression.
+    pub const fn original(&self) -> &'static str {
+        self.original
+    }
+
+    /// Returns the source code of the mutant.
+    pub const fn mutant(&self) -> &'static str {
+        self.mutant
+    }
+}
+
+impl fmt::Display for MutationPoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: `{}` -> `{}`",
+            self.location, self.original, self.mutant
+        )
+    }
+}
+
+/// Registers the mutation point at the location of the caller as reached,
+/// and returns whether it is confunded on this thread, in which case the mutant should be evaluated.
+/// [`confunded_if_mutating!`](crate::confunded_if_mutating!) calls this.
+#[track_caller]
+pub fn confunded(original: &'static str, mutant: &'static str) -> bool {
+    let point = MutationPoint {
+        location: Location::caller(),
+        original,
+        mutant,
+    };
+    let mut registry = lock();
+    if !registry.contains(&point) {
+        registry.push(point);
+    }
+    drop(registry);
+    ATTENDANCE.with(|attendance| {
+        if let Some(attendance) = attendance.borrow_mut().as_mut() {
+            if !attendance.contains(&point) {
+                attendance.push(point);
+            }
+        }
+    });
+    CONFUNDED.with(Cell::get) == Some(point)
+}
+
+/// Returns every mutation point that has been reached in this process, in the order they were first reached.
+pub fn reached() -> Vec<MutationPoint> {
+    lock().clone()
+}
+
+/// Confunds `point` on this thread until the returned guard is dropped, which makes it evaluate its mutant.
+pub fn confund(point: MutationPoint) -> Confundus {
+    Confundus {
+        previous: CONFUNDED.with(|confunded| confunded.replace(Some(point))),
+    }
+}
+
+/// Keeps a mutation point confunded on this thread until it is dropped, cast with [`confund`].
+#[must_use = "the mutation point is only confunded until the guard is dropped"]
+#[derive(Debug)]
+pub struct Confundus {
+    previous: Option<MutationPoint>,
+}
+
+impl Drop for Confundus {
+    fn drop(&mut self) {
+        CONFUNDED.with(|confunded| confunded.set(self.previous));
+    }
+}
+
+/// How well a test did at catching the mutant of a mutation point.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Grade {
+    /// The test panicked with the mutant in place.
+    Outstanding,
+    /// The test passed with the mutant in place, so it does not notice that change in behaviour.
+    Troll,
+}
+
+/// The grades of a test for every mutation point it reached, cast with [`sit_exam`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Results {
+    grades: Vec<(MutationPoint, Grade)>,
+}
+
+impl Results {
+    /// Returns the grade for every mutation point that the test reached, in the order they were first reached.
+    pub fn grades(&self) -> &[(MutationPoint, Grade)] {
+        &self.grades
+    }
+
+    /// Returns the mutation points whose mutants the test did not catch.
+    pub fn survivors(&self) -> impl Iterator<Item = MutationPoint> + '_ {
+        self.grades
+            .iter()
+            .filter(|(_, grade)| *grade == Grade::Troll)
+            .map(|(point, _)| *point)
+    }
+
+    /// Returns whether the test caught every mutant.
+    pub fn passed(&self) -> bool {
+        self.survivors().next().is_none()
+    }
+}
+
+impl fmt::Display for Results {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (point, grade) in &self.grades {
+            writeln!(f, "{:?}: {}", grade, point)?;
+        }
+        Ok(())
+    }
+}
+
+/// Runs `test` once to find the mutation points it reaches on this thread, and then once for each of those points
+/// with the point confunded, and grades whether the test panics with each mutant in place.
+///
+/// The panics of the mutants that are caught are reported by the panic hook like any other panic.
+/// # Panics
+/// Panics if `test` panics without any mutant in place, since a failing test can not catch anything.
+pub fn sit_exam(test: impl Fn()) -> Results {
+    let previous = ATTENDANCE.with(|attendance| attendance.replace(Some(Vec::new())));
+    let rehearsal = panic::catch_unwind(AssertUnwindSafe(&test));
+    let attendance = ATTENDANCE.with(|attendance| attendance.replace(previous));
+    if let Err(payload) = rehearsal {
+        panic::resume_unwind(payload);
+    }
+    let grades = attendance
+        .unwrap_or_default()
+        .into_iter()
+        .map(|point| {
+            let _confundus = confund(point);
+            let grade = match panic::catch_unwind(AssertUnwindSafe(&test)) {
+                Ok(()) => Grade::Troll,
+                Err(_) => Grade::Outstanding,
+            };
+            (point, grade)
+        })
+        .collect();
+    Results { grades }
+}
+
+fn lock() -> MutexGuard<'static, Vec<MutationPoint>> {
+    REGISTRY.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{confunded_if_mutating, owl_exam_results};
+
+    fn grade(score: u32) -> &'static str {
+        if confunded_if_mutating!(score >= 90, score > 90) {
+            "Outstanding"
+        } else if score >= confunded_if_mutating!(70, 60) {
+            "Exceeds Expectations"
+        } else {
+            "Troll"
+        }
+    }
+
+    #[test]
+    fn practice_owl_exam_results() {
+        let results = owl_exam_results!(|| {
+            assert_eq!(grade(90), "Outstanding");
+            assert_eq!(grade(65), "Troll");
+        });
+        assert!(results.passed());
+        assert_eq!(results.grades().len(), 2);
+        let (lower_bound, caught) = results.grades()[1];
+        assert_eq!(caught, Grade::Outstanding);
+        assert_eq!(lower_bound.location().file(), file!());
+        assert!(lower_bound.to_string().ends_with(": `70` -> `60`"));
+
+        let lazy = owl_exam_results!(|| assert_eq!(grade(100), "Outstanding"));
+        let survivors: Vec<_> = lazy.survivors().collect();
+        assert_eq!(survivors.len(), 1);
+        assert_eq!(survivors[0].original(), "score >= 90");
+        assert!(lazy.to_string().starts_with("Troll: "));
+    }
+
+    #[test]
+    fn a_confundus_only_affects_its_thread() {
+        assert_eq!(grade(95), "Outstanding");
+        let point = *owl_exam_results!()
+            .iter()
+            .find(|point| point.original() == "score >= 90")
+            .unwrap();
+        let confundus = confund(point);
+        assert_eq!(grade(90), "Exceeds Expectations");
+        assert_eq!(
+            std::thread::spawn(|| grade(90)).join().unwrap(),
+            "Outstanding"
+        );
+        drop(confundus);
+        assert_eq!(grade(90), "Outstanding");
+    }
+
+    #[test]
+    #[should_panic(expected = "the examiner was not confunded")]
+    fn a_failing_test_fails_the_exam() {
+        let _ = sit_exam(|| panic!("the examiner was not confunded"));
+    }
+}
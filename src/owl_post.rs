@@ -0,0 +1,437 @@
+//! Message passing between threads, cast with [`send_owl!`](crate::send_owl) and [`await_owl!`](crate::await_owl).
+//!
+//! An [`owl_post`] is a [`std::sync::mpsc`] channel: any number of threads can send owls,
+//! and a single thread awaits them.
+//! ```
+//! # use code_spells::{await_owl, send_owl};
+//! use code_spells::owl_post::owl_post;
+//! use std::thread;
+//!
+//! let (hedwig, privet_drive) = owl_post();
+//! thread::spawn(move || send_owl!(hedwig, "Happy birthday, Harry!").unwrap());
+//! assert_eq!(await_owl!(privet_drive), Ok("Happy birthday, Harry!"));
+//! ```
+//!
+//! The owlery of an owl post has room for every owl that is sent. A [`regulated_owl_post`] only has room for
+//! as many owls as its [`Regulation`] allows, cast with [`floo_regulation!`](crate::floo_regulation!),
+//! and its [`Backpressure`] decides what happens to the owls that are sent when it is full.
+//! The fireplaces of a [`FlooNetwork`](crate::floo_network::FlooNetwork) can be regulated the same way.
+//! ```
+//! # use code_spells::{await_owl, floo_regulation, send_owl};
+//! use code_spells::owl_post::regulated_owl_post;
+//!
+//! let (owls, privet_drive) = regulated_owl_post(floo_regulation!(2, drop_oldest));
+//! for letter in ["first", "second", "third"] {
+//!     send_owl!(owls, letter).unwrap();
+//! }
+//! assert_eq!(privet_drive.dropped(), 1);
+//! assert_eq!(await_owl!(privet_drive), Ok("second"));
+//! ```
+
+use core::fmt;
+use core::time::Duration;
+use std::collections::VecDeque;
+use std::sync::mpsc;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+use std::time::Instant;
+
+pub use std::sync::mpsc::{
+    Receiver, RecvError, RecvTimeoutError, SendError, Sender, TryRecvError, TrySendError,
+};
+
+/// Opens an owl post, returning the sending and receiving halves of an unbounded channel.
+/// Alias for [`mpsc::channel`].
+pub fn owl_post<T>() -> (Sender<T>, Receiver<T>) {
+    mpsc::channel()
+}
+
+/// What happens to an owl that is sent to a full owlery.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Backpressure {
+    /// The sender waits until there is room.
+    Block,
+    /// The oldest owl in the owlery is dropped to make room.
+    DropOldest,
+    /// The owl that is sent is dropped.
+    DropNewest,
+    /// The owl is handed back to the sender in a [`TrySendError::Full`].
+    Error,
+}
+
+/// How many owls an owlery has room for, and what happens when it is full. Cast with [`floo_regulation!`](crate::floo_regulation!).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Regulation {
+    capacity: usize,
+    backpressure: Backpressure,
+}
+
+impl Regulation {
+    /// Regulates an owlery to have room for `capacity` owls.
+    /// # Panics
+    /// Panics if `capacity` is zero.
+    pub const fn new(capacity: usize, backpressure: Backpressure) -> Self {
+        assert!(capacity > 0, "an owlery needs room for at least one owl");
+        Self {
+            capacity,
+            backpressure,
+        }
+    }
+
+    /// Returns how many owls the owlery has room for.
+    pub const fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns what happens to an owl that is sent to a full owlery.
+    pub const fn backpressure(&self) -> Backpressure {
+        self.backpressure
+    }
+}
+
+/// The owls of a regulated owl post that have not been received yet.
+struct Perch<T> {
+    owls: VecDeque<T>,
+    senders: usize,
+    receiver: bool,
+    dropped: u64,
+}
+
+/// The state shared between the halves of a regulated owl post.
+struct Owlery<T> {
+    perch: Mutex<Perch<T>>,
+    regulation: Regulation,
+    arrived: Condvar,
+    left: Condvar,
+}
+
+impl<T> Owlery<T> {
+    fn lock(&self) -> MutexGuard<'_, Perch<T>> {
+        self.perch.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Opens an owl post whose owlery is regulated by `regulation`,
+/// returning the sending and receiving halves of a bounded channel.
+pub fn regulated_owl_post<T>(regulation: Regulation) -> (RegulatedSender<T>, RegulatedReceiver<T>) {
+    let owlery = Arc::new(Owlery {
+        perch: Mutex::new(Perch {
+            owls: VecDeque::with_capacity(regulation.capacity),
+            senders: 1,
+            receiver: true,
+            dropped: 0,
+        }),
+        regulation,
+        arrived: Condvar::new(),
+        left: Condvar::new(),
+    });
+    (
+        RegulatedSender {
+            owlery: Arc::clone(&owlery),
+        },
+        RegulatedReceiver { owlery },
+    )
+}
+
+/// The sending half of a [`regulated_owl_post`], which can be cloned to send from several threads.
+pub struct RegulatedSender<T> {
+    owlery: Arc<Owlery<T>>,
+}
+
+impl<T> RegulatedSender<T> {
+    /// Sends `message`, and applies the [`Backpressure`] of the owlery if it is full.
+    ///
+    /// An owl that is dropped by [`Backpressure::DropOldest`] or [`Backpressure::DropNewest`] counts as sent,
+    /// and is counted by [`dropped`](RegulatedSender::dropped).
+    /// # Errors
+    /// Returns the message in a [`TrySendError::Disconnected`] if the receiver has been dropped,
+    /// or in a [`TrySendError::Full`] if the owlery is full and its backpressure is [`Backpressure::Error`].
+    pub fn send(&self, message: T) -> Result<(), TrySendError<T>> {
+        let regulation = self.owlery.regulation;
+        let mut perch = self.owlery.lock();
+        let mut evicted = None;
+        loop {
+            if !perch.receiver {
+                return Err(TrySendError::Disconnected(message));
+            }
+            if perch.owls.len() < regulation.capacity {
+                break;
+            }
+            match regulation.backpressure {
+                Backpressure::Block => {
+                    perch = self
+                        .owlery
+                        .left
+                        .wait(perch)
+                        .unwrap_or_else(PoisonError::into_inner);
+                }
+                Backpressure::DropOldest => {
+                    evicted = perch.owls.pop_front();
+                    perch.dropped += 1;
+                    break;
+                }
+                Backpressure::DropNewest => {
+                    perch.dropped += 1;
+                    return Ok(());
+                }
+                Backpressure::Error => return Err(TrySendError::Full(message)),
+            }
+        }
+        perch.owls.push_back(message);
+        drop(perch);
+        self.owlery.arrived.notify_one();
+        // The evicted owl is dropped without holding the lock.
+        drop(evicted);
+        Ok(())
+    }
+
+    /// Returns the regulation of the owlery.
+    pub fn regulation(&self) -> Regulation {
+        self.owlery.regulation
+    }
+
+    /// Returns how many owls have been dropped by the backpressure of the owlery.
+    pub fn dropped(&self) -> u64 {
+        self.owlery.lock().dropped
+    }
+
+    /// Returns whether `self` and `other` send to the same owlery.
+    pub(crate) fn same_owlery(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.owlery, &other.owlery)
+    }
+}
+
+impl<T> Clone for RegulatedSender<T> {
+    fn clone(&self) -> Self {
+        self.owlery.lock().senders += 1;
+        Self {
+            owlery: Arc::clone(&self.owlery),
+        }
+    }
+}
+
+impl<T> Drop for RegulatedSender<T> {
+    fn drop(&mut self) {
+        let mut perch = self.owlery.lock();
+        perch.senders -= 1;
+        if perch.senders == 0 {
+            drop(perch);
+            self.owlery.arrived.notify_all();
+        }
+    }
+}
+
+impl<T> fmt::Debug for RegulatedSender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RegulatedSender")
+            .field("regulation", &self.owlery.regulation)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The receiving half of a [`regulated_owl_post`].
+pub struct RegulatedReceiver<T> {
+    owlery: Arc<Owlery<T>>,
+}
+
+impl<T> RegulatedReceiver<T> {
+    /// Waits for an owl to arrive, and returns its message.
+    /// # Errors
+    /// Returns [`RecvError`] if the owlery is empty and every sender has been dropped.
+    pub fn recv(&self) -> Result<T, RecvError> {
+        let mut perch = self.owlery.lock();
+        loop {
+            if let Some(message) = self.take(&mut perch) {
+                return Ok(message);
+            }
+            if perch.senders == 0 {
+                return Err(RecvError);
+            }
+            perch = self
+                .owlery
+                .arrived
+                .wait(perch)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+    }
+
+    /// Waits at most `timeout` for an owl to arrive, and returns its message.
+    /// # Errors
+    /// Returns [`RecvTimeoutError::Timeout`] if no owl arrived in time, and [`RecvTimeoutError::Disconnected`]
+    /// if the owlery is empty and every sender has been dropped.
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        let deadline = Instant::now() + timeout;
+        let mut perch = self.owlery.lock();
+        loop {
+            if let Some(message) = self.take(&mut perch) {
+                return Ok(message);
+            }
+            if perch.senders == 0 {
+                return Err(RecvTimeoutError::Disconnected);
+            }
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Err(RecvTimeoutError::Timeout);
+            }
+            perch = self
+                .owlery
+                .arrived
+                .wait_timeout(perch, remaining)
+                .unwrap_or_else(PoisonError::into_inner)
+                .0;
+        }
+    }
+
+    /// Returns the message of an owl that has already arrived, without waiting.
+    /// # Errors
+    /// Returns [`TryRecvError::Empty`] if no owl has arrived, and [`TryRecvError::Disconnected`]
+    /// if the owlery is empty and every sender has been dropped.
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        let mut perch = self.owlery.lock();
+        match self.take(&mut perch) {
+            Some(message) => Ok(message),
+            None if perch.senders == 0 => Err(TryRecvError::Disconnected),
+            None => Err(TryRecvError::Empty),
+        }
+    }
+
+    /// Returns an iterator over the messages of the owls that have already arrived, without waiting.
+    pub fn try_iter(&self) -> impl Iterator<Item = T> + '_ {
+        core::iter::from_fn(move || self.try_recv().ok())
+    }
+
+    /// Returns how many owls are waiting in the owlery.
+    pub fn len(&self) -> usize {
+        self.owlery.lock().owls.len()
+    }
+
+    /// Returns whether no owls are waiting in the owlery.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the regulation of the owlery.
+    pub fn regulation(&self) -> Regulation {
+        self.owlery.regulation
+    }
+
+    /// Returns how many owls have been dropped by the backpressure of the owlery.
+    pub fn dropped(&self) -> u64 {
+        self.owlery.lock().dropped
+    }
+
+    /// Takes the oldest owl off the perch, and makes room for a blocked sender.
+    fn take(&self, perch: &mut Perch<T>) -> Option<T> {
+        let message = perch.owls.pop_front()?;
+        self.owlery.left.notify_one();
+        Some(message)
+    }
+}
+
+impl<T> Drop for RegulatedReceiver<T> {
+    fn drop(&mut self) {
+        let mut perch = self.owlery.lock();
+        perch.receiver = false;
+        let owls = core::mem::take(&mut perch.owls);
+        drop(perch);
+        self.owlery.left.notify_all();
+        drop(owls);
+    }
+}
+
+impl<T> fmt::Debug for RegulatedReceiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let perch = self.owlery.lock();
+        f.debug_struct("RegulatedReceiver")
+            .field("regulation", &self.owlery.regulation)
+            .field("len", &perch.owls.len())
+            .field("dropped", &perch.dropped)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{await_owl, floo_regulation, send_owl};
+    use std::thread;
+
+    #[test]
+    fn practice_owl_post() {
+        let (owlery, hogwarts) = owl_post::<u32>();
+        let senders: Vec<_> = (1..=3)
+            .map(|year| {
+                let owlery = owlery.clone();
+                thread::spawn(move || send_owl!(owlery, year).unwrap())
+            })
+            .collect();
+        for sender in senders {
+            sender.join().unwrap();
+        }
+        let mut years: Vec<u32> = (0..3).map(|_| await_owl!(hogwarts).unwrap()).collect();
+        years.sort_unstable();
+        assert_eq!(years, [1, 2, 3]);
+
+        assert_eq!(
+            await_owl!(hogwarts, Duration::from_millis(1)),
+            Err(RecvTimeoutError::Timeout)
+        );
+        drop(owlery);
+        assert_eq!(await_owl!(hogwarts), Err(RecvError));
+        let (lost, _) = owl_post();
+        assert_eq!(send_owl!(lost, 4), Err(SendError(4)));
+    }
+
+    #[test]
+    fn practice_floo_regulation() {
+        let (errol, burrow) = regulated_owl_post(floo_regulation!(2, drop_newest));
+        for letter in 1..=4 {
+            send_owl!(errol, letter).unwrap();
+        }
+        assert_eq!(burrow.try_iter().collect::<Vec<_>>(), [1, 2]);
+        assert_eq!(errol.dropped(), 2);
+        assert_eq!(
+            format!("{:?}", burrow),
+            "RegulatedReceiver { regulation: Regulation { capacity: 2, backpressure: DropNewest }, \
+             len: 0, dropped: 2, .. }"
+        );
+
+        let (howler, ron) = regulated_owl_post(floo_regulation!(1, error));
+        send_owl!(howler, "RONALD WEASLEY").unwrap();
+        assert_eq!(
+            send_owl!(howler, "how dare you"),
+            Err(TrySendError::Full("how dare you"))
+        );
+        assert_eq!(ron.len(), 1);
+        drop(ron);
+        assert_eq!(
+            send_owl!(howler, "steal the car"),
+            Err(TrySendError::Disconnected("steal the car"))
+        );
+    }
+
+    #[test]
+    fn a_blocking_owlery_waits_for_room() {
+        let (pigwidgeon, sirius) = regulated_owl_post(floo_regulation!(1, block));
+        let sent = thread::spawn(move || {
+            for letter in 0..10 {
+                send_owl!(pigwidgeon, letter).unwrap();
+            }
+        });
+        let received: Vec<u32> = (0..10).map(|_| await_owl!(sirius).unwrap()).collect();
+        sent.join().unwrap();
+        assert_eq!(received, (0..10).collect::<Vec<_>>());
+        assert_eq!(sirius.dropped(), 0);
+        assert_eq!(await_owl!(sirius), Err(RecvError));
+        assert_eq!(
+            await_owl!(sirius, Duration::from_millis(1)),
+            Err(RecvTimeoutError::Disconnected)
+        );
+        assert_eq!(sirius.try_recv(), Err(TryRecvError::Disconnected));
+    }
+
+    #[test]
+    #[should_panic(expected = "room for at least one owl")]
+    fn an_owlery_without_room() {
+        let _ = Regulation::new(0, Backpressure::Block);
+    }
+}
@@ -0,0 +1,135 @@
+//! Scope guards that run a closure when a scope ends, cast with [`patronus!`](crate::patronus!).
+//!
+//! A patronus stands guard over a scope and casts its charm when the scope is left, whether it is left
+//! normally, with an early `return` or `?`, or by unwinding from a panic. A [`Patronus`] that is
+//! [conjured on panic](Patronus::on_panic) only casts its charm when the scope is left by unwinding,
+//! which makes it a place to undo half-finished work. A patronus can be [dismissed](Patronus::dismiss)
+//! before the scope ends, so that it never casts its charm.
+//! ```
+//! # use code_spells::patronus;
+//! use std::cell::Cell;
+//!
+//! let dementors = Cell::new(3);
+//! {
+//!     let _stag = patronus!(|| dementors.set(0));
+//!     assert_eq!(dementors.get(), 3);
+//! }
+//! assert_eq!(dementors.get(), 0);
+//! ```
+
+use core::fmt;
+use std::thread;
+
+/// When a [`Patronus`] casts its charm.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Ward {
+    Always,
+    OnPanic,
+}
+
+/// A guard that runs a closure when it is dropped.
+#[must_use = "the patronus casts its charm as soon as it is dropped"]
+pub struct Patronus<F: FnOnce()> {
+    charm: Option<F>,
+    ward: Ward,
+}
+
+impl<F: FnOnce()> Patronus<F> {
+    /// Conjures a patronus that runs `charm` when it is dropped.
+    pub const fn new(charm: F) -> Self {
+        Self {
+            charm: Some(charm),
+            ward: Ward::Always,
+        }
+    }
+
+    /// Conjures a patronus that only runs `charm` if it is dropped while the thread unwinds from a panic.
+    pub const fn on_panic(charm: F) -> Self {
+        Self {
+            charm: Some(charm),
+            ward: Ward::OnPanic,
+        }
+    }
+
+    /// Dismisses the patronus, so that it never runs its charm.
+    pub fn dismiss(mut self) {
+        self.charm = None;
+    }
+}
+
+impl<F: FnOnce()> Drop for Patronus<F> {
+    fn drop(&mut self) {
+        if let Some(charm) = self.charm.take() {
+            if self.ward == Ward::Always || thread::panicking() {
+                charm();
+            }
+        }
+    }
+}
+
+impl<F: FnOnce()> fmt::Debug for Patronus<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Patronus")
+            .field("on_panic", &(self.ward == Ward::OnPanic))
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::patronus;
+    use core::cell::{Cell, RefCell};
+    use std::panic::{self, AssertUnwindSafe};
+
+    #[test]
+    fn practice_patronus() {
+        let cast = RefCell::new(Vec::new());
+        {
+            let _stag = patronus!(|| cast.borrow_mut().push("stag"));
+            let _otter = patronus!(on_panic || cast.borrow_mut().push("otter"));
+            assert_eq!(format!("{:?}", _otter), "Patronus { on_panic: true, .. }");
+            let _doe = patronus!(|| cast.borrow_mut().push("doe"));
+            assert!(cast.borrow().is_empty());
+        }
+        // Guards are dropped in reverse order, and the otter only guards against panics.
+        assert_eq!(*cast.borrow(), ["doe", "stag"]);
+    }
+
+    #[test]
+    fn a_patronus_guards_early_returns() {
+        let cast = Cell::new(0);
+        let hogsmeade = |dementors: Option<u8>| -> Option<u8> {
+            let _patronus = patronus!(|| cast.set(cast.get() + 1));
+            let dementors = dementors?;
+            if dementors > 100 {
+                return None;
+            }
+            Some(dementors)
+        };
+        assert_eq!(hogsmeade(None), None);
+        assert_eq!(hogsmeade(Some(150)), None);
+        assert_eq!(hogsmeade(Some(1)), Some(1));
+        assert_eq!(cast.get(), 3);
+    }
+
+    #[test]
+    fn a_patronus_guards_panics() {
+        let cast = RefCell::new(Vec::new());
+        let attack = panic::catch_unwind(AssertUnwindSafe(|| {
+            let _stag = patronus!(|| cast.borrow_mut().push("stag"));
+            let _otter = patronus!(on_panic || cast.borrow_mut().push("otter"));
+            panic!("the dementors attack");
+        }));
+        assert!(attack.is_err());
+        assert_eq!(*cast.borrow(), ["otter", "stag"]);
+    }
+
+    #[test]
+    fn a_dismissed_patronus() {
+        let cast = Cell::new(false);
+        let patronus = Patronus::new(|| cast.set(true));
+        patronus.dismiss();
+        assert!(!cast.get());
+    }
+}
@@ -0,0 +1,347 @@
+//! Memoization, cast with [`pensieve!`](crate::pensieve!).
+//!
+//! A pensieve stores memories so that they can be revisited later. A [`Pensieve`] remembers the value
+//! that was computed for a key, and returns a clone of it the next time the same key comes along instead of
+//! computing it again. A [`LocalPensieve`] does the same for a single thread, without any locking.
+//! Both keep [statistics](Stats) of how often a memory could be recalled.
+//!
+//! The computation runs without holding on to the memories, so it can use the same pensieve recursively.
+//! ```
+//! use code_spells::pensieve::Pensieve;
+//!
+//! let pensieve = Pensieve::new();
+//! let answer = pensieve.remember("the ultimate question", || 6 * 7);
+//! let again = pensieve.remember("the ultimate question", || unreachable!());
+//! assert_eq!((answer, again), (42, 42));
+//! assert_eq!(pensieve.stats().hits(), 1);
+//! ```
+
+use core::cell::RefCell;
+use core::fmt;
+use core::hash::Hash;
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+/// How often memories could be recalled.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Stats {
+    hits: u64,
+    misses: u64,
+    memories: usize,
+}
+
+impl Stats {
+    /// Returns the number of times a value was recalled instead of computed.
+    pub const fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// Returns the number of times a value had to be computed.
+    pub const fn misses(&self) -> u64 {
+        self.misses
+    }
+
+    /// Returns the number of memories that are stored.
+    pub const fn memories(&self) -> usize {
+        self.memories
+    }
+
+    /// Returns the fraction of lookups that were hits, or zero if there have been none.
+    pub fn hit_ratio(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            0.0
+        } else {
+            self.hits as f64 / lookups as f64
+        }
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} memories, {} hits, {} misses",
+            self.memories, self.hits, self.misses
+        )
+    }
+}
+
+/// The memories of a pensieve, together with its statistics.
+#[derive(Debug)]
+struct Basin<K, V> {
+    memories: HashMap<K, V>,
+    hits: u64,
+    misses: u64,
+}
+
+impl<K: Hash + Eq, V: Clone> Basin<K, V> {
+    fn new() -> Self {
+        Self {
+            memories: HashMap::new(),
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Looks up the memory of `key`, and counts whether it was there.
+    fn recall(&mut self, key: &K) -> Option<V> {
+        let memory = self.memories.get(key).cloned();
+        match memory {
+            Some(_) => self.hits += 1,
+            None => self.misses += 1,
+        }
+        memory
+    }
+
+    /// Stores a memory, unless one was stored for the same key in the meantime, and returns the stored one.
+    fn store(&mut self, key: K, value: V) -> V {
+        self.memories.entry(key).or_insert(value).clone()
+    }
+
+    fn stats(&self) -> Stats {
+        Stats {
+            hits: self.hits,
+            misses: self.misses,
+            memories: self.memories.len(),
+        }
+    }
+}
+
+/// A thread-safe memoization cache.
+pub struct Pensieve<K, V> {
+    basin: Mutex<Basin<K, V>>,
+}
+
+impl<K: Hash + Eq, V: Clone> Pensieve<K, V> {
+    /// Creates an empty pensieve.
+    pub fn new() -> Self {
+        Self {
+            basin: Mutex::new(Basin::new()),
+        }
+    }
+
+    /// Returns the memory of `key`, or computes it with `compute` and remembers it.
+    ///
+    /// If two threads compute the same memory at the same time, both get the one that was stored first.
+    pub fn remember(&self, key: K, compute: impl FnOnce() -> V) -> V {
+        if let Some(memory) = self.lock().recall(&key) {
+            return memory;
+        }
+        let value = compute();
+        self.lock().store(key, value)
+    }
+
+    /// Forgets the memory of `key`, and returns it if there was one.
+    pub fn forget(&self, key: &K) -> Option<V> {
+        self.lock().memories.remove(key)
+    }
+
+    /// Forgets every memory. The statistics are kept.
+    pub fn clear(&self) {
+        self.lock().memories.clear();
+    }
+
+    /// Returns the number of stored memories.
+    pub fn len(&self) -> usize {
+        self.lock().memories.len()
+    }
+
+    /// Returns whether there are no stored memories.
+    pub fn is_empty(&self) -> bool {
+        self.lock().memories.is_empty()
+    }
+
+    /// Returns how often memories could be recalled.
+    pub fn stats(&self) -> Stats {
+        self.lock().stats()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Basin<K, V>> {
+        self.basin.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<K: Hash + Eq, V: Clone> Default for Pensieve<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Hash + Eq, V: Clone> fmt::Debug for Pensieve<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Pensieve")
+            .field("stats", &self.stats())
+            .finish_non_exhaustive()
+    }
+}
+
+/// A memoization cache for a single thread.
+pub struct LocalPensieve<K, V> {
+    basin: RefCell<Basin<K, V>>,
+}
+
+impl<K: Hash + Eq, V: Clone> LocalPensieve<K, V> {
+    /// Creates an empty pensieve.
+    pub fn new() -> Self {
+        Self {
+            basin: RefCell::new(Basin::new()),
+        }
+    }
+
+    /// Returns the memory of `key`, or computes it with `compute` and remembers it.
+    pub fn remember(&self, key: K, compute: impl FnOnce() -> V) -> V {
+        // The borrow has to end before `compute` runs, since it may use this pensieve again.
+        let memory = self.basin.borrow_mut().recall(&key);
+        if let Some(memory) = memory {
+            return memory;
+        }
+        let value = compute();
+        self.basin.borrow_mut().store(key, value)
+    }
+
+    /// Forgets the memory of `key`, and returns it if there was one.
+    pub fn forget(&self, key: &K) -> Option<V> {
+        self.basin.borrow_mut().memories.remove(key)
+    }
+
+    /// Forgets every memory. The statistics are kept.
+    pub fn clear(&self) {
+        self.basin.borrow_mut().memories.clear();
+    }
+
+    /// Returns the number of stored memories.
+    pub fn len(&self) -> usize {
+        self.basin.borrow().memories.len()
+    }
+
+    /// Returns whether there are no stored memories.
+    pub fn is_empty(&self) -> bool {
+        self.basin.borrow().memories.is_empty()
+    }
+
+    /// Returns how often memories could be recalled.
+    pub fn stats(&self) -> Stats {
+        self.basin.borrow().stats()
+    }
+}
+
+impl<K: Hash + Eq, V: Clone> Default for LocalPensieve<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Hash + Eq, V: Clone> fmt::Debug for LocalPensieve<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LocalPensieve")
+            .field("stats", &self.stats())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pensieve;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread;
+
+    static FIB_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    pensieve! {
+        /// The `n`th Fibonacci number.
+        fn fib(n: u64) -> u64 {
+            FIB_CALLS.fetch_add(1, Ordering::Relaxed);
+            if n < 2 {
+                n
+            } else {
+                fib(n - 1) + fib(n - 2)
+            }
+        }
+    }
+
+    pensieve! {
+        local fn greet(house: &'static str, year: u8) -> String {
+            format!("Welcome to {}, year {}", house, year)
+        }
+    }
+
+    #[test]
+    fn practice_pensieve() {
+        assert_eq!(fib(80), 23_416_728_348_467_685);
+        assert_eq!(FIB_CALLS.load(Ordering::Relaxed), 81);
+        assert_eq!(fib(80), 23_416_728_348_467_685);
+        assert_eq!(FIB_CALLS.load(Ordering::Relaxed), 81);
+
+        assert_eq!(greet("Hufflepuff", 1), "Welcome to Hufflepuff, year 1");
+        assert_eq!(greet("Hufflepuff", 1), "Welcome to Hufflepuff, year 1");
+    }
+
+    #[test]
+    fn a_pensieve_keeps_statistics() {
+        let pensieve = Pensieve::new();
+        for n in [1, 2, 1, 1, 3] {
+            assert_eq!(pensieve.remember(n, || n * 10), n * 10);
+        }
+        let stats = pensieve.stats();
+        assert_eq!((stats.hits(), stats.misses(), stats.memories()), (2, 3, 3));
+        assert_eq!(stats.hit_ratio(), 0.4);
+        assert_eq!(
+            format!("{:?}", pensieve),
+            "Pensieve { stats: Stats { hits: 2, misses: 3, memories: 3 }, .. }"
+        );
+
+        assert_eq!(pensieve.forget(&1), Some(10));
+        assert_eq!(pensieve.forget(&1), None);
+        assert_eq!(pensieve.remember(1, || 11), 11);
+        pensieve.clear();
+        assert!(pensieve.is_empty());
+        assert_eq!(pensieve.stats().to_string(), "0 memories, 2 hits, 4 misses");
+        assert_eq!(Stats::default().hit_ratio(), 0.0);
+    }
+
+    #[test]
+    fn a_shared_pensieve() {
+        let pensieve = Pensieve::new();
+        let computed = AtomicUsize::new(0);
+        thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| {
+                    for n in 0..100_u32 {
+                        let square = pensieve.remember(n, || {
+                            computed.fetch_add(1, Ordering::Relaxed);
+                            n * n
+                        });
+                        assert_eq!(square, n * n);
+                    }
+                });
+            }
+        });
+        assert_eq!(pensieve.len(), 100);
+        let stats = pensieve.stats();
+        assert_eq!(stats.hits() + stats.misses(), 400);
+        assert_eq!(stats.misses(), computed.load(Ordering::Relaxed) as u64);
+    }
+
+    #[test]
+    fn a_local_pensieve_can_be_used_recursively() {
+        let pensieve = LocalPensieve::new();
+        fn steps(pensieve: &LocalPensieve<u64, u64>, n: u64) -> u64 {
+            pensieve.remember(n, || match n {
+                0 | 1 => 1,
+                n => steps(pensieve, n - 1) + steps(pensieve, n - 2),
+            })
+        }
+        assert_eq!(steps(&pensieve, 50), 20_365_011_074);
+        assert_eq!(pensieve.len(), 51);
+        assert_eq!(pensieve.stats().misses(), 51);
+        assert_eq!(pensieve.forget(&50), Some(20_365_011_074));
+        pensieve.clear();
+        assert!(pensieve.is_empty());
+        assert_eq!(
+            format!("{:?}", pensieve),
+            "LocalPensieve { stats: Stats { hits: 48, misses: 51, memories: 0 }, .. }"
+        );
+    }
+}
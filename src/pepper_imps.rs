@@ -0,0 +1,271 @@
+//! Escalating backoff for spin loops, cast with [`backoff_spin!`](crate::backoff_spin)
+//! and the bounded form of [`crucio!`](crate::crucio), and plain busy-waiting, cast with the unbounded form of `crucio!`.
+//!
+//! Like a Pepper Imp, a spinning thread should start out small and hot and only later
+//! let off steam. A [`Backoff`] first busy-waits with [`spin_loop`](core::hint::spin_loop) hints,
+//! doubling the number of hints every step, then starts [yielding](std::thread::yield_now) to the scheduler,
+//! and finally falls asleep for a short while between every check.
+
+use core::hint;
+use core::time::Duration;
+use std::thread;
+
+/// An escalating backoff strategy for spin loops.
+/// # Example
+/// ```
+/// # use code_spells::pepper_imps::Backoff;
+/// use std::sync::atomic::{AtomicBool, Ordering};
+/// use std::time::Duration;
+///
+/// let ready = AtomicBool::new(true);
+/// let mut backoff = Backoff::new()
+///     .spin_limit(4)
+///     .yield_limit(8)
+///     .sleep(Duration::from_micros(50));
+/// while !ready.load(Ordering::Acquire) {
+///     backoff.snooze();
+/// }
+/// assert_eq!(backoff.stats().snoozes(), 0);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Backoff {
+    step: u32,
+    spin_limit: u32,
+    yield_limit: u32,
+    sleep: Duration,
+    stats: BackoffStats,
+}
+
+impl Backoff {
+    /// The default number of steps spent spinning.
+    pub const DEFAULT_SPIN_LIMIT: u32 = 6;
+    /// The default step after which the backoff stops yielding and starts sleeping.
+    pub const DEFAULT_YIELD_LIMIT: u32 = 10;
+    /// The default duration of each sleep.
+    pub const DEFAULT_SLEEP: Duration = Duration::from_micros(100);
+
+    /// Creates a new backoff with the default limits.
+    pub const fn new() -> Self {
+        Self {
+            step: 0,
+            spin_limit: Self::DEFAULT_SPIN_LIMIT,
+            yield_limit: Self::DEFAULT_YIELD_LIMIT,
+            sleep: Self::DEFAULT_SLEEP,
+            stats: BackoffStats::new(),
+        }
+    }
+
+    /// Sets the number of steps spent spinning. Step `n` issues `2^n` spin loop hints.
+    /// Values above 16 are treated as 16.
+    pub const fn spin_limit(mut self, steps: u32) -> Self {
+        self.spin_limit = if steps > 16 { 16 } else { steps };
+        self
+    }
+
+    /// Sets the step after which the backoff stops yielding and starts sleeping.
+    /// If this is not larger than the spin limit the backoff goes straight from spinning to sleeping.
+    pub const fn yield_limit(mut self, step: u32) -> Self {
+        self.yield_limit = step;
+        self
+    }
+
+    /// Sets how long to sleep once spinning and yielding have not been enough.
+    pub const fn sleep(mut self, duration: Duration) -> Self {
+        self.sleep = duration;
+        self
+    }
+
+    /// Waits a little, escalating the wait every time this is called.
+    pub fn snooze(&mut self) {
+        if self.step < self.spin_limit {
+            for _ in 0..1_u32 << self.step {
+                hint::spin_loop();
+            }
+            self.stats.spins += 1;
+        } else if self.step < self.yield_limit {
+            thread::yield_now();
+            self.stats.yields += 1;
+        } else {
+            thread::sleep(self.sleep);
+            self.stats.sleeps += 1;
+        }
+        self.step = self.step.saturating_add(1);
+    }
+
+    /// Snoozes until `condition` returns `true` and returns the statistics of the wait.
+    /// This is what [`backoff_spin!`](crate::backoff_spin) casts.
+    pub fn spin_until(mut self, mut condition: impl FnMut() -> bool) -> BackoffStats {
+        while !condition() {
+            self.snooze();
+        }
+        self.stats
+    }
+
+    /// Snoozes until `condition` returns `true`, but gives up after `max_snoozes` snoozes.
+    /// Returns the statistics of the wait, in `Err` if the backoff gave up.
+    /// This is what [`crucio!`](crate::crucio) casts when it is given a limit.
+    pub fn try_spin_until(
+        mut self,
+        mut condition: impl FnMut() -> bool,
+        max_snoozes: u64,
+    ) -> Result<BackoffStats, BackoffStats> {
+        while !condition() {
+            if self.stats.snoozes() == max_snoozes {
+                return Err(self.stats);
+            }
+            self.snooze();
+        }
+        Ok(self.stats)
+    }
+
+    /// Returns `true` if the backoff has escalated to sleeping.
+    pub fn is_sleeping(&self) -> bool {
+        self.step >= self.spin_limit && self.step >= self.yield_limit
+    }
+
+    /// Starts over from the smallest wait, but keeps the statistics.
+    pub fn reset(&mut self) {
+        self.step = 0;
+    }
+
+    /// Returns how many times each strategy has been used so far.
+    pub fn stats(&self) -> BackoffStats {
+        self.stats
+    }
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Busy-waits with [`spin_loop`](hint::spin_loop) hints until `condition` returns `true`, without ever backing off.
+/// This is what [`crucio!`](crate::crucio) casts when it is not given a limit.
+pub fn spin_until(mut condition: impl FnMut() -> bool) {
+    while !condition() {
+        hint::spin_loop();
+    }
+}
+
+/// Counts how many times a [`Backoff`] has used each of its strategies.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct BackoffStats {
+    spins: u64,
+    yields: u64,
+    sleeps: u64,
+}
+
+impl BackoffStats {
+    const fn new() -> Self {
+        Self {
+            spins: 0,
+            yields: 0,
+            sleeps: 0,
+        }
+    }
+
+    /// The number of snoozes spent spinning.
+    pub fn spins(&self) -> u64 {
+        self.spins
+    }
+
+    /// The number of snoozes spent yielding to the scheduler.
+    pub fn yields(&self) -> u64 {
+        self.yields
+    }
+
+    /// The number of snoozes spent sleeping.
+    pub fn sleeps(&self) -> u64 {
+        self.sleeps
+    }
+
+    /// The total number of snoozes.
+    pub fn snoozes(&self) -> u64 {
+        self.spins + self.yields + self.sleeps
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{backoff_spin, crucio};
+    use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+    use std::sync::Arc;
+
+    #[test]
+    fn practice_backoff() {
+        let mut backoff = Backoff::new()
+            .spin_limit(2)
+            .yield_limit(3)
+            .sleep(Duration::from_micros(1));
+        for _ in 0..5 {
+            backoff.snooze();
+        }
+        assert!(backoff.is_sleeping());
+        let stats = backoff.stats();
+        assert_eq!((stats.spins(), stats.yields(), stats.sleeps()), (2, 1, 2));
+        backoff.reset();
+        assert!(!backoff.is_sleeping());
+        backoff.snooze();
+        assert_eq!(backoff.stats().spins(), 3);
+    }
+
+    #[test]
+    fn practice_backoff_spin() {
+        let count = AtomicU32::new(0);
+        let stats = backoff_spin!(|| count.fetch_add(1, Ordering::Relaxed) == 20);
+        assert_eq!(stats.snoozes(), 20);
+
+        let flag = Arc::new(AtomicBool::new(false));
+        let setter = {
+            let flag = Arc::clone(&flag);
+            std::thread::spawn(move || flag.store(true, Ordering::Release))
+        };
+        let backoff = Backoff::new().sleep(Duration::from_micros(10));
+        backoff_spin!(backoff, || flag.load(Ordering::Acquire));
+        setter.join().unwrap();
+    }
+
+    #[test]
+    fn practice_crucio() {
+        let count = AtomicU32::new(0);
+        crucio!(|| count.fetch_add(1, Ordering::Relaxed) == 20);
+        assert_eq!(count.load(Ordering::Relaxed), 21);
+
+        let mut checks = 0;
+        assert!(!crucio!(
+            || {
+                checks += 1;
+                false
+            },
+            5
+        ));
+        assert_eq!(checks, 6);
+        assert!(crucio!(|| true, 0));
+        assert!(crucio!(|| count.fetch_add(1, Ordering::Relaxed) == 23, 10));
+    }
+
+    #[test]
+    fn a_backoff_can_give_up() {
+        let backoff = Backoff::new().spin_limit(1).yield_limit(2);
+        let stats = backoff.clone().try_spin_until(|| false, 4).unwrap_err();
+        assert_eq!((stats.spins(), stats.yields(), stats.sleeps()), (1, 1, 2));
+        let count = AtomicU32::new(0);
+        let stats = backoff
+            .try_spin_until(|| count.fetch_add(1, Ordering::Relaxed) == 2, 4)
+            .unwrap();
+        assert_eq!(stats.snoozes(), 2);
+    }
+
+    #[test]
+    fn a_bounded_crucio_backs_off() {
+        // The default backoff sleeps from its eleventh snooze on.
+        let start = std::time::Instant::now();
+        assert!(!crucio!(
+            || false,
+            u64::from(Backoff::DEFAULT_YIELD_LIMIT) + 2
+        ));
+        assert!(start.elapsed() >= 2 * Backoff::DEFAULT_SLEEP);
+    }
+}
@@ -0,0 +1,148 @@
+//! A parallel map over a collection, cast with [`piertotum_locomotor!`](crate::piertotum_locomotor!).
+//!
+//! When the castle is under attack, its statues come to life and march out to defend it.
+//! [`animate`] wakes a bounded number of statues, one per [scoped thread](std::thread::scope),
+//! and each of them keeps taking the next item of the collection until there are none left.
+//! The results are returned in the order of the items, however the work was shared out.
+//!
+//! Like the [broomsticks](crate::broomsticks), every item is always processed: if the closure panics on an item,
+//! the statues carry on with the rest, and the panic of the first item that panicked is then propagated to the caller.
+//! ```
+//! # use code_spells::piertotum_locomotor;
+//! let suits_of_armour = vec!["helmet", "breastplate", "gauntlet", "greave"];
+//! let lengths = piertotum_locomotor!(suits_of_armour, |piece| piece.len(), statues: 2);
+//! assert_eq!(lengths, [6, 11, 8, 6]);
+//! ```
+
+use core::num::NonZeroUsize;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Mutex, PoisonError};
+use std::thread;
+
+/// Calls `f` on every item on one scoped thread per unit of available parallelism,
+/// and returns the results in the order of the items.
+/// # Panics
+/// If `f` panics on any item, the panic of the first such item is propagated once every item has been processed.
+pub fn animate<I, F, R>(items: I, f: F) -> Vec<R>
+where
+    I: IntoIterator,
+    I::Item: Send,
+    F: Fn(I::Item) -> R + Sync,
+    R: Send,
+{
+    animate_with(
+        thread::available_parallelism().map_or(4, NonZeroUsize::get),
+        items,
+        f,
+    )
+}
+
+/// Calls `f` on every item on at most `statues` scoped threads, and returns the results in the order of the items.
+/// # Panics
+/// Panics if `statues` is zero.
+///
+/// If `f` panics on any item, the panic of the first such item is propagated once every item has been processed.
+pub fn animate_with<I, F, R>(statues: usize, items: I, f: F) -> Vec<R>
+where
+    I: IntoIterator,
+    I::Item: Send,
+    F: Fn(I::Item) -> R + Sync,
+    R: Send,
+{
+    assert!(
+        statues > 0,
+        "the castle needs at least one statue to defend it"
+    );
+    let items: Vec<I::Item> = items.into_iter().collect();
+    let len = items.len();
+    let orders = Mutex::new(items.into_iter().enumerate());
+    let march = || {
+        let mut done = Vec::new();
+        loop {
+            let order = orders.lock().unwrap_or_else(PoisonError::into_inner).next();
+            match order {
+                Some((index, item)) => {
+                    done.push((index, panic::catch_unwind(AssertUnwindSafe(|| f(item)))));
+                }
+                None => return done,
+            }
+        }
+    };
+
+    let mut results: Vec<Option<thread::Result<R>>> = (0..len).map(|_| None).collect();
+    thread::scope(|s| {
+        let statues: Vec<_> = (0..statues.min(len)).map(|_| s.spawn(march)).collect();
+        for statue in statues {
+            let done = statue
+                .join()
+                .expect("a statue can not fall, as every order is caught");
+            for (index, result) in done {
+                results[index] = Some(result);
+            }
+        }
+    });
+    results
+        .into_iter()
+        .map(|result| {
+            result
+                .expect("every order is carried out")
+                .unwrap_or_else(|e| panic::resume_unwind(e))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::piertotum_locomotor;
+    use std::collections::HashSet;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn practice_piertotum_locomotor() {
+        let statues = ["knight", "gargoyle", "suit of armour"];
+        let shouts = piertotum_locomotor!(&statues, |statue| statue.to_uppercase());
+        assert_eq!(shouts, ["KNIGHT", "GARGOYLE", "SUIT OF ARMOUR"]);
+        let squares = piertotum_locomotor!(0..100_u64, |n| n * n, statues: 3);
+        assert_eq!(squares, (0..100).map(|n| n * n).collect::<Vec<_>>());
+        let none: Vec<usize> = piertotum_locomotor!(Vec::<&str>::new(), str::len);
+        assert!(none.is_empty());
+    }
+
+    #[test]
+    fn the_statues_are_bounded() {
+        let marching = Mutex::new(HashSet::new());
+        let orders = animate_with(2, 0..50, |n| {
+            marching.lock().unwrap().insert(thread::current().id());
+            n + 1
+        });
+        assert_eq!(orders, (1..51).collect::<Vec<_>>());
+        let statues = marching.into_inner().unwrap().len();
+        assert!((1..=2).contains(&statues), "{} statues marched", statues);
+    }
+
+    #[test]
+    fn every_order_is_carried_out_before_a_fall_is_reported() {
+        let carried_out = AtomicUsize::new(0);
+        let result = panic::catch_unwind(|| {
+            animate_with(3, 0..20, |n| {
+                if n == 5 || n == 12 {
+                    panic!("statue {} crumbled", n);
+                }
+                carried_out.fetch_add(1, Ordering::SeqCst)
+            })
+        });
+        let payload = result.unwrap_err();
+        assert_eq!(
+            payload.downcast_ref::<String>().map(String::as_str),
+            Some("statue 5 crumbled")
+        );
+        assert_eq!(carried_out.load(Ordering::SeqCst), 18);
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one statue")]
+    fn a_castle_without_statues() {
+        let _ = animate_with(0, [1], |n: i32| n);
+    }
+}
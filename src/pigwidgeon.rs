@@ -0,0 +1,385 @@
+//! A small, fast, bounded single-producer single-consumer queue, cast with [`pigwidgeon!`](crate::pigwidgeon!).
+//!
+//! A [`Pigwidgeon`] is a ring buffer of `N` slots that is [split](Pigwidgeon::split) into a [`Sender`]
+//! and a [`Receiver`], which can be moved to different threads. Since exactly one thread sends and
+//! exactly one thread receives, neither side ever takes a lock or retries a compare-and-swap,
+//! which makes it a good fit for per-thread pipelines where [`std::sync::mpsc`] is more than is needed.
+//! Neither side blocks: [`try_send`](Sender::try_send) hands the letter back if the queue is full,
+//! and [`try_recv`](Receiver::try_recv) returns `None` if it is empty.
+//! ```
+//! # use code_spells::pigwidgeon;
+//! use std::thread;
+//!
+//! let (mut ron, mut sirius) = pigwidgeon!(4);
+//! let delivery = thread::spawn(move || {
+//!     for letter in ["Buckbeak", "is", "safe"] {
+//!         let mut letter = letter;
+//!         while let Err(returned) = ron.try_send(letter) {
+//!             letter = returned;
+//!             thread::yield_now();
+//!         }
+//!     }
+//! });
+//! let mut letters = Vec::new();
+//! while letters.len() < 3 {
+//!     match sirius.try_recv() {
+//!         Some(letter) => letters.push(letter),
+//!         None => thread::yield_now(),
+//!     }
+//! }
+//! delivery.join().unwrap();
+//! assert_eq!(letters.join(" "), "Buckbeak is safe");
+//! ```
+
+use core::fmt;
+use core::mem::MaybeUninit;
+
+use self::apparition::{Arc, AtomicUsize, Ordering, UnsafeCell};
+
+// With the `apparition-test` feature and `--cfg loom` the tests of this module swap in the primitives of `loom`,
+// which checks every interleaving of the threads they spawn:
+// `RUSTFLAGS="--cfg loom" cargo test --release --features apparition-test --lib pigwidgeon`.
+// `loom` needs a newer compiler than the rest of the crate.
+#[cfg(not(all(test, loom, feature = "apparition-test")))]
+mod apparition {
+    pub(super) use std::sync::atomic::{AtomicUsize, Ordering};
+    pub(super) use std::sync::Arc;
+
+    /// The subset of `loom::cell::UnsafeCell` that the queue uses.
+    pub(super) struct UnsafeCell<T>(core::cell::UnsafeCell<T>);
+
+    impl<T> UnsafeCell<T> {
+        pub(super) fn new(value: T) -> Self {
+            Self(core::cell::UnsafeCell::new(value))
+        }
+
+        pub(super) fn with_mut<R>(&self, f: impl FnOnce(*mut T) -> R) -> R {
+            f(self.0.get())
+        }
+    }
+}
+
+#[cfg(all(test, loom, feature = "apparition-test"))]
+mod apparition {
+    pub(super) use loom::cell::UnsafeCell;
+    pub(super) use loom::sync::atomic::{AtomicUsize, Ordering};
+    pub(super) use loom::sync::Arc;
+}
+
+/// A position in the queue, padded to its own cache lines so that the sender and receiver do not contend.
+#[repr(align(128))]
+struct Perch(AtomicUsize);
+
+/// A bounded single-producer single-consumer queue with room for `N` letters.
+///
+/// Positions run over `0..2 * N`, so that a full queue can be told apart from an empty one
+/// without giving up a slot.
+pub struct Pigwidgeon<T, const N: usize> {
+    // The next position to receive from, only written by the receiver.
+    head: Perch,
+    // The next position to send to, only written by the sender.
+    tail: Perch,
+    // The slots from `head` up to `tail` are initialized.
+    slots: [UnsafeCell<MaybeUninit<T>>; N],
+}
+
+// SAFETY: the sender and receiver never access the same slot at the same time,
+// and hand letters over with release-acquire ordering.
+unsafe impl<T: Send, const N: usize> Sync for Pigwidgeon<T, N> {}
+
+impl<T, const N: usize> Pigwidgeon<T, N> {
+    /// Creates an empty queue.
+    /// # Panics
+    /// Panics if `N` is zero, or so large that `2 * N` overflows.
+    pub fn new() -> Self {
+        assert!(
+            N > 0 && N <= usize::MAX / 2,
+            "a pigwidgeon needs room for at least one letter"
+        );
+        Self {
+            head: Perch(AtomicUsize::new(0)),
+            tail: Perch(AtomicUsize::new(0)),
+            slots: core::array::from_fn(|_| UnsafeCell::new(MaybeUninit::uninit())),
+        }
+    }
+
+    /// Returns the number of letters the queue has room for.
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Splits the queue into its sending and receiving halves.
+    pub fn split(self) -> (Sender<T, N>, Receiver<T, N>) {
+        let nest = Arc::new(self);
+        (
+            Sender {
+                nest: Arc::clone(&nest),
+                tail: 0,
+                head: 0,
+            },
+            Receiver {
+                nest,
+                head: 0,
+                tail: 0,
+            },
+        )
+    }
+
+    /// Returns the number of letters between the positions `head` and `tail`.
+    const fn letters(head: usize, tail: usize) -> usize {
+        if tail >= head {
+            tail - head
+        } else {
+            tail + 2 * N - head
+        }
+    }
+
+    /// Returns the position after `position`.
+    const fn next(position: usize) -> usize {
+        if position + 1 == 2 * N {
+            0
+        } else {
+            position + 1
+        }
+    }
+
+    /// Returns the slot at `position`.
+    fn slot(&self, position: usize) -> &UnsafeCell<MaybeUninit<T>> {
+        &self.slots[if position < N { position } else { position - N }]
+    }
+}
+
+impl<T, const N: usize> Default for Pigwidgeon<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> Drop for Pigwidgeon<T, N> {
+    fn drop(&mut self) {
+        let mut head = self.head.0.load(Ordering::Relaxed);
+        let tail = self.tail.0.load(Ordering::Relaxed);
+        while head != tail {
+            // SAFETY: the letters from `head` up to `tail` were sent but never received.
+            self.slot(head)
+                .with_mut(|slot| unsafe { (*slot).assume_init_drop() });
+            head = Self::next(head);
+        }
+    }
+}
+
+impl<T, const N: usize> fmt::Debug for Pigwidgeon<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Pigwidgeon")
+            .field("capacity", &N)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The sending half of a [`Pigwidgeon`].
+pub struct Sender<T, const N: usize> {
+    nest: Arc<Pigwidgeon<T, N>>,
+    // The position this sender sends to next.
+    tail: usize,
+    // The last position of the receiver that this sender saw.
+    head: usize,
+}
+
+impl<T, const N: usize> Sender<T, N> {
+    /// Sends a letter without blocking.
+    /// # Errors
+    /// Returns the letter if the queue is full.
+    pub fn try_send(&mut self, letter: T) -> Result<(), T> {
+        if Pigwidgeon::<T, N>::letters(self.head, self.tail) == N {
+            self.head = self.nest.head.0.load(Ordering::Acquire);
+            if Pigwidgeon::<T, N>::letters(self.head, self.tail) == N {
+                return Err(letter);
+            }
+        }
+        // SAFETY: the slot is outside of `head..tail`, so the receiver does not touch it.
+        self.nest
+            .slot(self.tail)
+            .with_mut(|slot| unsafe { (*slot).write(letter) });
+        self.tail = Pigwidgeon::<T, N>::next(self.tail);
+        self.nest.tail.0.store(self.tail, Ordering::Release);
+        Ok(())
+    }
+
+    /// Returns the number of letters the queue has room for.
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+}
+
+impl<T, const N: usize> fmt::Debug for Sender<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sender")
+            .field("capacity", &N)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The receiving half of a [`Pigwidgeon`].
+pub struct Receiver<T, const N: usize> {
+    nest: Arc<Pigwidgeon<T, N>>,
+    // The position this receiver receives from next.
+    head: usize,
+    // The last position of the sender that this receiver saw.
+    tail: usize,
+}
+
+impl<T, const N: usize> Receiver<T, N> {
+    /// Receives the oldest letter without blocking, or returns `None` if the queue is empty.
+    pub fn try_recv(&mut self) -> Option<T> {
+        if self.head == self.tail {
+            self.tail = self.nest.tail.0.load(Ordering::Acquire);
+            if self.head == self.tail {
+                return None;
+            }
+        }
+        // SAFETY: the slot is inside of `head..tail`, so the sender initialized it and does not touch it.
+        let letter = self
+            .nest
+            .slot(self.head)
+            .with_mut(|slot| unsafe { (*slot).assume_init_read() });
+        self.head = Pigwidgeon::<T, N>::next(self.head);
+        self.nest.head.0.store(self.head, Ordering::Release);
+        Some(letter)
+    }
+
+    /// Returns the number of letters the queue has room for.
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+}
+
+impl<T, const N: usize> fmt::Debug for Receiver<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Receiver")
+            .field("capacity", &N)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(all(test, not(all(loom, feature = "apparition-test"))))]
+mod tests {
+    use super::*;
+    use crate::pigwidgeon;
+    use std::rc::Rc;
+    use std::thread;
+
+    #[test]
+    fn practice_pigwidgeon() {
+        let (mut ron, mut sirius) = pigwidgeon!(2);
+        assert_eq!(ron.capacity(), 2);
+        assert_eq!(sirius.try_recv(), None);
+        for round in 0..5 {
+            assert_eq!(ron.try_send(2 * round), Ok(()));
+            assert_eq!(ron.try_send(2 * round + 1), Ok(()));
+            assert_eq!(ron.try_send(99), Err(99));
+            assert_eq!(sirius.try_recv(), Some(2 * round));
+            assert_eq!(sirius.try_recv(), Some(2 * round + 1));
+            assert_eq!(sirius.try_recv(), None);
+        }
+
+        let (mut hermione, mut harry) = pigwidgeon!();
+        let hermione: &mut Sender<u32, 16> = &mut hermione;
+        assert_eq!(hermione.try_send(3), Ok(()));
+        assert_eq!(harry.try_recv(), Some(3));
+    }
+
+    #[test]
+    fn a_pigwidgeon_across_threads() {
+        let (mut ron, mut sirius) = Pigwidgeon::<u32, 8>::new().split();
+        let delivery = thread::spawn(move || {
+            for letter in 0..10_000 {
+                let mut letter = letter;
+                while let Err(returned) = ron.try_send(letter) {
+                    letter = returned;
+                    thread::yield_now();
+                }
+            }
+        });
+        let mut expected = 0;
+        while expected < 10_000 {
+            match sirius.try_recv() {
+                Some(letter) => {
+                    assert_eq!(letter, expected);
+                    expected += 1;
+                }
+                None => thread::yield_now(),
+            }
+        }
+        delivery.join().unwrap();
+        assert_eq!(sirius.try_recv(), None);
+    }
+
+    #[test]
+    fn a_pigwidgeon_drops_undelivered_letters() {
+        let letter = Rc::new("Hogwarts");
+        let (mut ron, mut sirius) = Pigwidgeon::<_, 3>::new().split();
+        for _ in 0..3 {
+            ron.try_send(Rc::clone(&letter)).unwrap();
+        }
+        drop(sirius.try_recv());
+        assert_eq!(Rc::strong_count(&letter), 3);
+        drop(ron);
+        drop(sirius);
+        assert_eq!(Rc::strong_count(&letter), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one letter")]
+    fn a_pigwidgeon_without_room() {
+        let _ = Pigwidgeon::<u8, 0>::new();
+    }
+}
+
+#[cfg(all(test, loom, feature = "apparition-test"))]
+mod apparition_tests {
+    use super::*;
+    use loom::thread;
+
+    #[test]
+    fn letters_arrive_in_order() {
+        loom::model(|| {
+            let (mut ron, mut sirius) = Pigwidgeon::<u8, 2>::new().split();
+            let delivery = thread::spawn(move || {
+                for letter in 0..3 {
+                    let mut letter = letter;
+                    while let Err(returned) = ron.try_send(letter) {
+                        letter = returned;
+                        thread::yield_now();
+                    }
+                }
+            });
+            let mut letters = Vec::new();
+            while letters.len() < 3 {
+                match sirius.try_recv() {
+                    Some(letter) => letters.push(letter),
+                    None => thread::yield_now(),
+                }
+            }
+            delivery.join().unwrap();
+            assert_eq!(letters, [0, 1, 2]);
+        });
+    }
+
+    #[test]
+    fn undelivered_letters_are_dropped() {
+        loom::model(|| {
+            let letter = Arc::new(());
+            let (mut ron, mut sirius) = Pigwidgeon::<_, 1>::new().split();
+            let delivery = {
+                let letter = Arc::clone(&letter);
+                thread::spawn(move || {
+                    let _ = ron.try_send(letter);
+                })
+            };
+            drop(sirius.try_recv());
+            drop(sirius);
+            delivery.join().unwrap();
+            assert_eq!(Arc::strong_count(&letter), 1);
+        });
+    }
+}
@@ -0,0 +1,182 @@
+//! Delayed execution, cast with [`portkey!`](crate::portkey!).
+//!
+//! A [`Portkey`] runs a closure on a background thread once a delay has passed,
+//! unless it is [cancelled](Portkey::cancel) before it activates.
+//! ```
+//! # use code_spells::portkey;
+//! use std::time::Duration;
+//!
+//! let boot = portkey!(Duration::from_millis(10), || "Stoatshead Hill");
+//! let decoy = portkey!(Duration::from_secs(60), || "Little Hangleton");
+//! assert!(decoy.cancel());
+//! assert_eq!(boot.wait(), Some("Stoatshead Hill"));
+//! assert_eq!(decoy.wait(), None);
+//! ```
+
+use core::fmt;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// The state of a [`Portkey`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Activation {
+    Waiting,
+    Cancelled,
+    Activated,
+}
+
+/// The state shared between a [`Portkey`] and its timer thread.
+#[derive(Debug)]
+struct Charm {
+    activation: Mutex<Activation>,
+    cancelled: Condvar,
+}
+
+impl Charm {
+    fn lock(&self) -> MutexGuard<'_, Activation> {
+        self.activation
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// A handle to a closure that runs on a background thread after a delay.
+///
+/// Dropping the handle does not cancel the portkey, it still activates when its delay has passed.
+pub struct Portkey<T> {
+    charm: Arc<Charm>,
+    traveller: JoinHandle<Option<T>>,
+}
+
+impl<T: Send + 'static> Portkey<T> {
+    /// Spawns a timer thread that runs `f` once `delay` has passed, unless the portkey is cancelled first.
+    /// # Panics
+    /// Panics if the timer thread could not be spawned.
+    pub fn new<F>(delay: Duration, f: F) -> Self
+    where
+        F: FnOnce() -> T + Send + 'static,
+    {
+        let charm = Arc::new(Charm {
+            activation: Mutex::new(Activation::Waiting),
+            cancelled: Condvar::new(),
+        });
+        let deadline = Instant::now() + delay;
+        let traveller = {
+            let charm = Arc::clone(&charm);
+            thread::Builder::new()
+                .name(String::from("portkey"))
+                .spawn(move || {
+                    let mut activation = charm.lock();
+                    while *activation == Activation::Waiting {
+                        let now = Instant::now();
+                        if now >= deadline {
+                            *activation = Activation::Activated;
+                            drop(activation);
+                            return Some(f());
+                        }
+                        activation = charm
+                            .cancelled
+                            .wait_timeout(activation, deadline - now)
+                            .unwrap_or_else(PoisonError::into_inner)
+                            .0;
+                    }
+                    None
+                })
+                .expect("portkey! failed to spawn its timer thread")
+        };
+        Self { charm, traveller }
+    }
+}
+
+impl<T> Portkey<T> {
+    /// Cancels the portkey. Returns `true` if it had not activated yet, in which case its closure will never run.
+    pub fn cancel(&self) -> bool {
+        let mut activation = self.charm.lock();
+        if *activation == Activation::Waiting {
+            *activation = Activation::Cancelled;
+            self.charm.cancelled.notify_one();
+            true
+        } else {
+            *activation == Activation::Cancelled
+        }
+    }
+
+    /// Returns whether the portkey has activated, which means that its closure has started to run.
+    pub fn is_activated(&self) -> bool {
+        *self.charm.lock() == Activation::Activated
+    }
+
+    /// Waits for the portkey to activate and its closure to finish, and returns what the closure returned,
+    /// or `None` if the portkey was cancelled.
+    /// # Panics
+    /// Resumes the panic of the closure if it panicked.
+    pub fn wait(self) -> Option<T> {
+        self.traveller
+            .join()
+            .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+    }
+}
+
+impl<T> fmt::Debug for Portkey<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Portkey")
+            .field("activation", &*self.charm.lock())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::owl_post::owl_post;
+    use crate::portkey;
+
+    #[test]
+    fn practice_portkey() {
+        let boot = portkey!(Duration::ZERO, || 4);
+        assert_eq!(boot.wait(), Some(4));
+
+        let kettle = portkey!(Duration::from_secs(60), || unreachable!());
+        assert!(!kettle.is_activated());
+        assert!(kettle.cancel());
+        assert!(kettle.cancel());
+        assert_eq!(
+            format!("{:?}", kettle),
+            "Portkey { activation: Cancelled, .. }"
+        );
+        assert_eq!(kettle.wait(), None);
+
+        let cup = portkey!(Duration::ZERO, || "graveyard");
+        while !cup.is_activated() {
+            thread::yield_now();
+        }
+        assert!(!cup.cancel());
+        assert_eq!(cup.wait(), Some("graveyard"));
+    }
+
+    #[test]
+    fn portkeys_activate_in_order_of_their_delays() {
+        let (arrivals, arrived) = owl_post();
+        let portkeys: Vec<_> = [3, 1, 4, 2]
+            .iter()
+            .map(|&traveller| {
+                let arrivals = arrivals.clone();
+                portkey!(Duration::from_millis(40 * traveller), move || arrivals
+                    .send(traveller)
+                    .unwrap())
+            })
+            .collect();
+        assert!(portkeys[2].cancel());
+        for portkey in portkeys {
+            portkey.wait();
+        }
+        assert_eq!(arrived.try_iter().collect::<Vec<_>>(), [1, 2, 3]);
+    }
+
+    #[test]
+    #[should_panic(expected = "splinched")]
+    fn a_portkey_that_panics() {
+        portkey!(Duration::ZERO, || panic!("splinched")).wait();
+    }
+}
@@ -0,0 +1,182 @@
+//! Callback slots that answer when addressed, declared with [`portrait!`](crate::portrait!)
+//! and called with [`address_portrait!`](crate::address_portrait!).
+//!
+//! The portraits of Hogwarts can only answer once someone has been painted into them.
+//! A [`Portrait`] is a static slot for a callback, which code elsewhere can [hang](Portrait::hang)
+//! an answer in, e.g. a plugin that customizes what happens at an extension point.
+//! [Addressing](Portrait::address) an empty portrait returns an [`EmptyFrame`] error.
+//!
+//! [`portrait!`](crate::portrait!) declares a struct for a portrait with a fixed signature,
+//! so that every answer that is hung in it, and every call to it, is type checked.
+//! ```
+//! # use code_spells::{address_portrait, portrait};
+//! portrait!(FatLady = "the Fat Lady", |password: &str| -> bool);
+//!
+//! assert!(address_portrait!(FatLady, "caput draconis").is_err());
+//! FatLady.hang(|password| password == "caput draconis");
+//! assert_eq!(address_portrait!(FatLady, "caput draconis"), Ok(true));
+//! assert_eq!(address_portrait!(FatLady, "pig snout"), Ok(false));
+//! ```
+
+use core::fmt;
+use std::sync::{Arc, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+/// A named slot that holds at most one callback of type `F`, usually a `dyn Fn(..) -> ..`.
+pub struct Portrait<F: ?Sized> {
+    name: &'static str,
+    answer: RwLock<Option<Arc<F>>>,
+}
+
+impl<F: ?Sized> Portrait<F> {
+    /// Creates an empty portrait with a name for error messages.
+    pub const fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            answer: RwLock::new(None),
+        }
+    }
+
+    /// Returns the name of the portrait.
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Hangs an answer in the portrait, and returns the answer it replaced, if there was one.
+    pub fn hang(&self, answer: Arc<F>) -> Option<Arc<F>> {
+        self.write().replace(answer)
+    }
+
+    /// Takes the answer down from the portrait, and returns it if there was one.
+    pub fn take_down(&self) -> Option<Arc<F>> {
+        self.write().take()
+    }
+
+    /// Returns whether an answer is hung in the portrait.
+    pub fn is_hung(&self) -> bool {
+        self.read().is_some()
+    }
+
+    /// Calls `ask` with the answer of the portrait, and returns what it returns.
+    ///
+    /// The portrait is not locked while `ask` runs, so the answer can hang another answer or address the portrait again.
+    /// # Errors
+    /// Returns [`EmptyFrame`] if no answer is hung in the portrait.
+    pub fn address<R>(&self, ask: impl FnOnce(&F) -> R) -> Result<R, EmptyFrame> {
+        let answer = self.read().clone();
+        match answer {
+            Some(answer) => Ok(ask(&answer)),
+            None => Err(EmptyFrame { name: self.name }),
+        }
+    }
+
+    fn read(&self) -> RwLockReadGuard<'_, Option<Arc<F>>> {
+        self.answer.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, Option<Arc<F>>> {
+        self.answer.write().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<F: ?Sized> fmt::Debug for Portrait<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Portrait")
+            .field("name", &self.name)
+            .field("hung", &self.is_hung())
+            .finish()
+    }
+}
+
+/// The error returned when a [`Portrait`] without an answer is addressed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EmptyFrame {
+    name: &'static str,
+}
+
+impl EmptyFrame {
+    /// Returns the name of the portrait that was addressed.
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+}
+
+impl fmt::Display for EmptyFrame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "nobody answered: the frame of the portrait `{}` is empty",
+            self.name
+        )
+    }
+}
+
+impl std::error::Error for EmptyFrame {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{address_portrait, portrait};
+
+    portrait!(
+        /// Decides who may enter the Ravenclaw common room.
+        EagleKnocker = "the eagle knocker",
+        |riddle: &str, answer: String| -> bool
+    );
+
+    portrait!(pub(crate) SirCadogan = "Sir Cadogan", |_challenger: u8|);
+
+    #[test]
+    fn practice_portrait() {
+        assert_eq!(EagleKnocker::NAME, "the eagle knocker");
+        assert_eq!(
+            address_portrait!(EagleKnocker, "phoenix or flame?", String::from("a circle")),
+            Err(EmptyFrame {
+                name: "the eagle knocker"
+            })
+        );
+        EagleKnocker.hang(|riddle, answer| riddle.starts_with("phoenix") && answer == "a circle");
+        assert!(EagleKnocker.is_hung());
+        assert_eq!(
+            address_portrait!(EagleKnocker, "phoenix or flame?", String::from("a circle")),
+            Ok(true)
+        );
+        assert!(EagleKnocker.take_down());
+        assert!(!EagleKnocker.take_down());
+
+        SirCadogan.hang(|challenger| assert!(challenger > 0));
+        assert_eq!(address_portrait!(SirCadogan, 3), Ok(()));
+    }
+
+    #[test]
+    fn a_portrait_can_be_repainted() {
+        let portrait: Portrait<dyn Fn(u32) -> u32 + Send + Sync> = Portrait::new("the Grey Lady");
+        assert_eq!(
+            portrait
+                .address(|answer| answer(1))
+                .unwrap_err()
+                .to_string(),
+            "nobody answered: the frame of the portrait `the Grey Lady` is empty"
+        );
+        assert!(portrait.hang(Arc::new(|n| n + 1)).is_none());
+        assert_eq!(portrait.address(|answer| answer(1)), Ok(2));
+        let previous = portrait.hang(Arc::new(|n| n * 10)).unwrap();
+        assert_eq!(previous(1), 2);
+        assert_eq!(portrait.address(|answer| answer(1)), Ok(10));
+        assert_eq!(
+            format!("{:?}", portrait),
+            "Portrait { name: \"the Grey Lady\", hung: true }"
+        );
+        assert!(portrait.take_down().is_some());
+        assert_eq!(portrait.name(), "the Grey Lady");
+    }
+
+    #[test]
+    fn an_answer_can_address_its_own_portrait() {
+        static ECHO: Portrait<dyn Fn(u32) -> u32 + Send + Sync> = Portrait::new("echo");
+        ECHO.hang(Arc::new(|n| match n {
+            0 => 0,
+            n => 1 + ECHO.address(|answer| answer(n - 1)).unwrap(),
+        }));
+        assert_eq!(ECHO.address(|answer| answer(5)), Ok(5));
+    }
+}
@@ -0,0 +1,235 @@
+//! A value that is promised by one thread and heard by another, cast with [`prophecy_orb!`](crate::prophecy_orb!).
+//!
+//! A prophecy is made once and can only be heard once. [`prophecy`] returns a [`Prophet`], which
+//! [fulfills](Prophet::fulfill) the prophecy with a value, and a [`ProphecyOrb`], which [hears](ProphecyOrb::hear)
+//! the value. The orb can be shared between threads, but only the first listener hears the prophecy.
+//! The orb shatters with an error for every listener after that, and for every listener if the prophet
+//! leaves without fulfilling the prophecy.
+//! ```
+//! # use code_spells::prophecy_orb;
+//! use code_spells::prophecy_orb::Shattered;
+//! use std::thread;
+//!
+//! let (trelawney, orb) = prophecy_orb!();
+//! thread::spawn(move || trelawney.fulfill("the one with the power to vanquish the Dark Lord approaches"));
+//! assert!(orb.hear().unwrap().starts_with("the one"));
+//! assert_eq!(orb.hear(), Err(Shattered::AlreadyHeard));
+//! ```
+
+use core::fmt;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+
+/// What has become of a prophecy.
+enum Prophecy<T> {
+    Unspoken,
+    Spoken(T),
+    Heard,
+    Abandoned,
+}
+
+/// The state shared between a [`Prophet`] and its [`ProphecyOrb`].
+struct HallOfProphecy<T> {
+    prophecy: Mutex<Prophecy<T>>,
+    spoken: Condvar,
+}
+
+impl<T> HallOfProphecy<T> {
+    fn lock(&self) -> MutexGuard<'_, Prophecy<T>> {
+        self.prophecy.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Makes a prophecy, returning the [`Prophet`] that fulfills it and the [`ProphecyOrb`] that hears it.
+pub fn prophecy<T>() -> (Prophet<T>, ProphecyOrb<T>) {
+    let hall = Arc::new(HallOfProphecy {
+        prophecy: Mutex::new(Prophecy::Unspoken),
+        spoken: Condvar::new(),
+    });
+    (
+        Prophet {
+            hall: Arc::clone(&hall),
+        },
+        ProphecyOrb { hall },
+    )
+}
+
+/// The sending half of a prophecy, which fulfills it exactly once.
+///
+/// Dropping the prophet without fulfilling the prophecy shatters the orb.
+pub struct Prophet<T> {
+    hall: Arc<HallOfProphecy<T>>,
+}
+
+impl<T> Prophet<T> {
+    /// Fulfills the prophecy with `value`, and wakes the listener that is waiting for it, if any.
+    /// # Errors
+    /// Gives the value back if the orb has been dropped, since nobody can hear the prophecy anymore.
+    pub fn fulfill(self, value: T) -> Result<(), T> {
+        if Arc::strong_count(&self.hall) == 1 {
+            return Err(value);
+        }
+        *self.hall.lock() = Prophecy::Spoken(value);
+        self.hall.spoken.notify_all();
+        Ok(())
+    }
+}
+
+impl<T> Drop for Prophet<T> {
+    fn drop(&mut self) {
+        let mut prophecy = self.hall.lock();
+        if let Prophecy::Unspoken = *prophecy {
+            *prophecy = Prophecy::Abandoned;
+            self.hall.spoken.notify_all();
+        }
+    }
+}
+
+impl<T> fmt::Debug for Prophet<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Prophet").finish_non_exhaustive()
+    }
+}
+
+/// The receiving half of a prophecy, which can be heard exactly once.
+pub struct ProphecyOrb<T> {
+    hall: Arc<HallOfProphecy<T>>,
+}
+
+impl<T> ProphecyOrb<T> {
+    /// Waits for the prophecy to be fulfilled, and returns its value.
+    /// # Errors
+    /// Returns [`Shattered`] if the prophecy has already been heard, or if the prophet left without fulfilling it.
+    pub fn hear(&self) -> Result<T, Shattered> {
+        let mut prophecy = self.hall.lock();
+        while let Prophecy::Unspoken = *prophecy {
+            prophecy = self
+                .hall
+                .spoken
+                .wait(prophecy)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+        Self::take(&mut prophecy).map(|value| value.expect("the prophecy has been spoken"))
+    }
+
+    /// Returns the value of the prophecy if it has been fulfilled, or `None` if it has not been yet, without waiting.
+    /// # Errors
+    /// Returns [`Shattered`] if the prophecy has already been heard, or if the prophet left without fulfilling it.
+    pub fn try_hear(&self) -> Result<Option<T>, Shattered> {
+        Self::take(&mut self.hall.lock())
+    }
+
+    /// Returns whether the prophecy has been fulfilled and is waiting to be heard.
+    pub fn is_fulfilled(&self) -> bool {
+        matches!(*self.hall.lock(), Prophecy::Spoken(_))
+    }
+
+    /// Takes the value out of a spoken prophecy, leaving it heard.
+    fn take(prophecy: &mut Prophecy<T>) -> Result<Option<T>, Shattered> {
+        match core::mem::replace(prophecy, Prophecy::Heard) {
+            Prophecy::Spoken(value) => Ok(Some(value)),
+            Prophecy::Unspoken => {
+                *prophecy = Prophecy::Unspoken;
+                Ok(None)
+            }
+            Prophecy::Heard => Err(Shattered::AlreadyHeard),
+            Prophecy::Abandoned => {
+                *prophecy = Prophecy::Abandoned;
+                Err(Shattered::Abandoned)
+            }
+        }
+    }
+}
+
+impl<T> fmt::Debug for ProphecyOrb<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProphecyOrb")
+            .field("fulfilled", &self.is_fulfilled())
+            .finish_non_exhaustive()
+    }
+}
+
+/// The error returned when a [`ProphecyOrb`] can not be heard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Shattered {
+    /// The prophecy has already been heard by another listener.
+    AlreadyHeard,
+    /// The prophet was dropped without fulfilling the prophecy.
+    Abandoned,
+}
+
+impl fmt::Display for Shattered {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AlreadyHeard => {
+                f.write_str("the orb shattered: the prophecy has already been heard")
+            }
+            Self::Abandoned => {
+                f.write_str("the orb shattered: the prophet left without fulfilling the prophecy")
+            }
+        }
+    }
+}
+
+impl std::error::Error for Shattered {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prophecy_orb;
+    use std::thread;
+
+    #[test]
+    fn practice_prophecy_orb() {
+        let (prophet, orb) = prophecy_orb!();
+        assert_eq!(orb.try_hear(), Ok(None));
+        assert_eq!(format!("{:?}", orb), "ProphecyOrb { fulfilled: false, .. }");
+        prophet.fulfill(7_u8).unwrap();
+        assert!(orb.is_fulfilled());
+        assert_eq!(orb.try_hear(), Ok(Some(7)));
+        assert_eq!(orb.try_hear(), Err(Shattered::AlreadyHeard));
+    }
+
+    #[test]
+    fn only_one_listener_hears_the_prophecy() {
+        let (prophet, orb) = prophecy::<&str>();
+        let heard = thread::scope(|s| {
+            let listeners: Vec<_> = (0..4).map(|_| s.spawn(|| orb.hear())).collect();
+            prophet
+                .fulfill("neither can live while the other survives")
+                .unwrap();
+            listeners
+                .into_iter()
+                .map(|listener| listener.join().unwrap())
+                .collect::<Vec<_>>()
+        });
+        assert_eq!(heard.iter().filter(|hearing| hearing.is_ok()).count(), 1);
+        assert_eq!(
+            heard
+                .iter()
+                .filter(|hearing| **hearing == Err(Shattered::AlreadyHeard))
+                .count(),
+            3
+        );
+    }
+
+    #[test]
+    fn an_abandoned_prophecy_shatters() {
+        let (prophet, orb) = prophecy::<u32>();
+        let listener = thread::spawn(move || orb.hear());
+        drop(prophet);
+        let shattered = listener.join().unwrap().unwrap_err();
+        assert_eq!(shattered, Shattered::Abandoned);
+        assert_eq!(
+            shattered.to_string(),
+            "the orb shattered: the prophet left without fulfilling the prophecy"
+        );
+
+        let (prophet, orb) = prophecy();
+        drop(orb);
+        assert_eq!(
+            prophet.fulfill(String::from("lost")),
+            Err(String::from("lost"))
+        );
+    }
+}
@@ -0,0 +1,436 @@
+//! Alternative takes on [`Debug`] output, cast with [`quibbler!`](crate::quibbler!).
+//!
+//! The Quibbler prints the same news as everyone else, just from a different angle.
+//! A [`Quibbler`] formats any [`Debug`] value in one of a few [`Style`]s that are handy in logs:
+//! - [`Style::Compact`] leaves out every optional space, to fit as much as possible on one line.
+//! - [`Style::Tree`] draws the value as an indented tree.
+//! - [`Style::Conspiracy`] looks like the usual output, but points out everything suspicious:
+//!   `NaN`s, `None`s and empty collections.
+//!
+//! The styles are built from the usual [`Debug`] output with a small parser. Output that the parser does not
+//! understand, e.g. from a hand-written [`Debug`] implementation with unbalanced brackets, is printed as it is.
+//! ```
+//! # use code_spells::quibbler;
+//! #[derive(Debug)]
+//! struct Sighting {
+//!     creature: &'static str,
+//!     witnesses: Vec<&'static str>,
+//!     proof: Option<f64>,
+//! }
+//!
+//! let sighting = Sighting { creature: "Crumple-Horned Snorkack", witnesses: vec![], proof: None };
+//! assert_eq!(
+//!     quibbler!(sighting, compact).to_string(),
+//!     r#"Sighting{creature:"Crumple-Horned Snorkack",witnesses:[],proof:None}"#
+//! );
+//! assert_eq!(
+//!     quibbler!(sighting, conspiracy).to_string(),
+//!     r#"Sighting { creature: "Crumple-Horned Snorkack", witnesses: !![]!!, proof: !!None!! }"#
+//! );
+//! assert_eq!(
+//!     quibbler!(sighting, tree).to_string(),
+//!     "Sighting\n\
+//!      ├── creature: \"Crumple-Horned Snorkack\"\n\
+//!      ├── witnesses: []\n\
+//!      └── proof: None"
+//! );
+//! ```
+
+use core::fmt::{self, Debug, Display, Write};
+
+/// A way to format [`Debug`] output.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Style {
+    /// On a single line without any optional spaces.
+    #[default]
+    Compact,
+    /// As an indented tree with one line per value.
+    Tree,
+    /// Like the usual output, with `NaN`s, `None`s and empty collections marked with `!!`.
+    Conspiracy,
+}
+
+/// Formats a [`Debug`] value in a [`Style`] when it is displayed.
+#[derive(Debug, Clone, Copy)]
+pub struct Quibbler<'a, T: ?Sized> {
+    value: &'a T,
+    style: Style,
+}
+
+impl<'a, T: Debug + ?Sized> Quibbler<'a, T> {
+    /// Creates a quibbler that displays `value` in `style`.
+    pub const fn new(value: &'a T, style: Style) -> Self {
+        Self { value, style }
+    }
+}
+
+impl<T: Debug + ?Sized> Display for Quibbler<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let debug = format!("{:?}", self.value);
+        let value = match parse(&debug) {
+            Some(value) => value,
+            None => return f.write_str(&debug),
+        };
+        match self.style {
+            Style::Compact => value.compact(f),
+            Style::Conspiracy => value.conspire(f),
+            Style::Tree => value.tree(f, ""),
+        }
+    }
+}
+
+/// A token of [`Debug`] output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Token<'a> {
+    /// Anything that is not punctuation, including string and character literals.
+    Word(&'a str),
+    Open(char),
+    Close(char),
+    Comma,
+    Colon,
+}
+
+/// Splits [`Debug`] output into tokens. Returns `None` if a literal is not terminated.
+fn tokenize(debug: &str) -> Option<Vec<Token<'_>>> {
+    let mut tokens = Vec::new();
+    let mut chars = debug.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '{' | '[' | '(' => Token::Open(c),
+            '}' | ']' | ')' => Token::Close(c),
+            ',' => Token::Comma,
+            ':' if chars.peek().map(|&(_, c)| c) != Some(':') => Token::Colon,
+            '"' | '\'' => {
+                let mut escaped = false;
+                let (end, _) = chars.find(|&(_, next)| {
+                    let closes = next == c && !escaped;
+                    escaped = next == '\\' && !escaped;
+                    closes
+                })?;
+                Token::Word(&debug[start..=end])
+            }
+            _ => {
+                let mut end = start + c.len_utf8();
+                while let Some(&(i, next)) = chars.peek() {
+                    let path = next == ':' && debug[i + 1..].starts_with(':');
+                    if next.is_whitespace() || "{}[](),\"".contains(next) || next == ':' && !path {
+                        break;
+                    }
+                    chars.next();
+                    end = i + next.len_utf8();
+                    if path {
+                        chars.next();
+                        end += 1;
+                    }
+                }
+                Token::Word(&debug[start..end])
+            }
+        };
+        tokens.push(token);
+    }
+    Some(tokens)
+}
+
+/// A value in [`Debug`] output, like `Some(1)`, `[1, 2]` or `Point { x: 1 }`.
+#[derive(Debug)]
+struct Value<'a> {
+    /// The words before the brackets, e.g. the name of a struct.
+    head: Vec<&'a str>,
+    group: Option<Group<'a>>,
+}
+
+/// The bracketed part of a value.
+#[derive(Debug)]
+struct Group<'a> {
+    open: char,
+    close: char,
+    entries: Vec<Entry<'a>>,
+}
+
+/// An element of a group, with a key for struct fields and map entries.
+#[derive(Debug)]
+struct Entry<'a> {
+    key: Option<Value<'a>>,
+    value: Value<'a>,
+}
+
+/// Parses [`Debug`] output. Returns `None` if it could not be parsed.
+fn parse(debug: &str) -> Option<Value<'_>> {
+    let tokens = tokenize(debug)?;
+    let mut tokens = tokens.into_iter().peekable();
+    let value = parse_value(&mut tokens)?;
+    match tokens.next() {
+        None => Some(value),
+        Some(_) => None,
+    }
+}
+
+fn parse_value<'a>(
+    tokens: &mut core::iter::Peekable<impl Iterator<Item = Token<'a>>>,
+) -> Option<Value<'a>> {
+    let mut head = Vec::new();
+    while let Some(&Token::Word(word)) = tokens.peek() {
+        head.push(word);
+        tokens.next();
+    }
+    let group = match tokens.peek() {
+        Some(&Token::Open(open)) => {
+            tokens.next();
+            Some(parse_group(tokens, open)?)
+        }
+        _ => None,
+    };
+    if head.is_empty() && group.is_none() {
+        None
+    } else {
+        Some(Value { head, group })
+    }
+}
+
+fn parse_group<'a>(
+    tokens: &mut core::iter::Peekable<impl Iterator<Item = Token<'a>>>,
+    open: char,
+) -> Option<Group<'a>> {
+    let close = match open {
+        '{' => '}',
+        '[' => ']',
+        _ => ')',
+    };
+    let mut entries = Vec::new();
+    loop {
+        if tokens.peek() == Some(&Token::Close(close)) {
+            tokens.next();
+            return Some(Group {
+                open,
+                close,
+                entries,
+            });
+        }
+        let first = parse_value(tokens)?;
+        let entry = if tokens.peek() == Some(&Token::Colon) {
+            tokens.next();
+            Entry {
+                key: Some(first),
+                value: parse_value(tokens)?,
+            }
+        } else {
+            Entry {
+                key: None,
+                value: first,
+            }
+        };
+        entries.push(entry);
+        match tokens.next()? {
+            Token::Comma => {}
+            Token::Close(c) if c == close => {
+                return Some(Group {
+                    open,
+                    close,
+                    entries,
+                })
+            }
+            _ => return None,
+        }
+    }
+}
+
+impl Value<'_> {
+    fn write_head(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, word) in self.head.iter().enumerate() {
+            if i > 0 {
+                f.write_char(' ')?;
+            }
+            f.write_str(word)?;
+        }
+        Ok(())
+    }
+
+    fn compact(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_head(f)?;
+        if let Some(group) = &self.group {
+            f.write_char(group.open)?;
+            for (i, entry) in group.entries.iter().enumerate() {
+                if i > 0 {
+                    f.write_char(',')?;
+                }
+                if let Some(key) = &entry.key {
+                    key.compact(f)?;
+                    f.write_char(':')?;
+                }
+                entry.value.compact(f)?;
+            }
+            f.write_char(group.close)?;
+        }
+        Ok(())
+    }
+
+    /// Returns whether the value is suspicious enough to be pointed out in the conspiracy style.
+    fn is_suspicious(&self) -> bool {
+        match (&self.head[..], &self.group) {
+            (["NaN"], None) | (["None"], None) => true,
+            ([], Some(group)) => group.open != '(' && group.entries.is_empty(),
+            _ => false,
+        }
+    }
+
+    fn conspire(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_suspicious() {
+            f.write_str("!!")?;
+        }
+        self.write_head(f)?;
+        if let Some(group) = &self.group {
+            // Only named structs get spaces inside their braces, like `Point { x: 1 }`.
+            let spaced = group.open == '{' && !self.head.is_empty();
+            if spaced {
+                f.write_str(" { ")?;
+            } else {
+                f.write_char(group.open)?;
+            }
+            for (i, entry) in group.entries.iter().enumerate() {
+                if i > 0 {
+                    f.write_str(", ")?;
+                }
+                if let Some(key) = &entry.key {
+                    key.conspire(f)?;
+                    f.write_str(": ")?;
+                }
+                entry.value.conspire(f)?;
+            }
+            if spaced {
+                f.write_str(" }")?;
+            } else {
+                f.write_char(group.close)?;
+            }
+        }
+        if self.is_suspicious() {
+            f.write_str("!!")?;
+        }
+        Ok(())
+    }
+
+    /// Writes the label of the value on the current line, and its entries on the lines below.
+    fn tree(&self, f: &mut fmt::Formatter<'_>, indent: &str) -> fmt::Result {
+        self.write_head(f)?;
+        let group = match &self.group {
+            Some(group) => group,
+            None => return Ok(()),
+        };
+        if self.head.is_empty() || group.entries.is_empty() {
+            if !self.head.is_empty() {
+                f.write_char(' ')?;
+            }
+            f.write_char(group.open)?;
+            f.write_char(group.close)?;
+        }
+        for (i, entry) in group.entries.iter().enumerate() {
+            let last = i + 1 == group.entries.len();
+            write!(f, "\n{}{}", indent, if last { "└── " } else { "├── " })?;
+            if let Some(key) = &entry.key {
+                key.compact(f)?;
+                f.write_str(": ")?;
+            }
+            let indent = format!("{}{}", indent, if last { "    " } else { "│   " });
+            entry.value.tree(f, &indent)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quibbler;
+    use std::collections::BTreeMap;
+
+    // The fields are only read through `Debug`.
+    #[allow(dead_code)]
+    #[derive(Debug)]
+    struct Article {
+        title: String,
+        pages: (u8, u8),
+        rating: f64,
+        tags: BTreeMap<&'static str, Vec<char>>,
+        author: Option<&'static str>,
+    }
+
+    fn article() -> Article {
+        let mut tags = BTreeMap::new();
+        tags.insert("nargles", vec!['n', '\'']);
+        tags.insert("wrackspurts", vec![]);
+        Article {
+            title: String::from("Sirius \"Stubby\" Boardman, {innocent}"),
+            pages: (4, 5),
+            rating: f64::NAN,
+            tags,
+            author: Some("Xenophilius"),
+        }
+    }
+
+    #[test]
+    fn practice_quibbler() {
+        let article = article();
+        assert_eq!(
+            quibbler!(article).to_string(),
+            r#"Article{title:"Sirius \"Stubby\" Boardman, {innocent}",pages:(4,5),rating:NaN,tags:{"nargles":['n','\''],"wrackspurts":[]},author:Some("Xenophilius")}"#
+        );
+        assert_eq!(
+            quibbler!(article, conspiracy).to_string(),
+            r#"Article { title: "Sirius \"Stubby\" Boardman, {innocent}", pages: (4, 5), rating: !!NaN!!, tags: {"nargles": ['n', '\''], "wrackspurts": !![]!!}, author: Some("Xenophilius") }"#
+        );
+        assert_eq!(
+            quibbler!(article, Style::Tree).to_string(),
+            "Article\n\
+             ├── title: \"Sirius \\\"Stubby\\\" Boardman, {innocent}\"\n\
+             ├── pages: ()\n\
+             │   ├── 4\n\
+             │   └── 5\n\
+             ├── rating: NaN\n\
+             ├── tags: {}\n\
+             │   ├── \"nargles\": []\n\
+             │   │   ├── 'n'\n\
+             │   │   └── '\\''\n\
+             │   └── \"wrackspurts\": []\n\
+             └── author: Some\n    \
+                 └── \"Xenophilius\""
+        );
+    }
+
+    #[test]
+    fn the_usual_output_survives_a_conspiracy() {
+        let values: [&dyn Debug; 6] = [
+            &(),
+            &Some(Ok::<_, ()>(vec![1.5, -2e-9])),
+            &"a: b",
+            &core::marker::PhantomData::<std::time::Instant>,
+            &[(); 2],
+            &BTreeMap::<u8, u8>::new(),
+        ];
+        for value in values {
+            let usual = format!("{:?}", value).replace("{}", "!!{}!!");
+            assert_eq!(Quibbler::new(value, Style::Conspiracy).to_string(), usual);
+        }
+    }
+
+    #[test]
+    fn nonsense_is_printed_as_it_is() {
+        struct Nonsense;
+        impl Debug for Nonsense {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("Blibbering {Humdinger)")
+            }
+        }
+        for style in [Style::Compact, Style::Tree, Style::Conspiracy] {
+            assert_eq!(
+                Quibbler::new(&Nonsense, style).to_string(),
+                "Blibbering {Humdinger)"
+            );
+        }
+        assert_eq!(
+            quibbler!("\"unterminated", tree).to_string(),
+            r#""\"unterminated""#
+        );
+        assert_eq!(quibbler!([0; 0], compact).to_string(), "[]");
+    }
+}
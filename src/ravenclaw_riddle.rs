@@ -0,0 +1,204 @@
+//! Lazily parsed and validated values, cast with [`ravenclaw_riddle!`](crate::ravenclaw_riddle!).
+//!
+//! The door to Ravenclaw tower asks a riddle, and only opens once it has been answered.
+//! A [`Riddle`] holds a raw string, e.g. from a configuration file or an environment variable, together
+//! with the parser that turns it into a value. The string is only parsed the first time the riddle is
+//! [solved](Riddle::solve), and the answer is kept for every later call, whether it was a value or an error.
+//! That keeps configuration fields that are expensive to parse, or rarely needed, from being parsed at startup.
+//! ```
+//! # use code_spells::ravenclaw_riddle;
+//! use code_spells::ravenclaw_riddle::Riddle;
+//!
+//! let port: Riddle<u16> = ravenclaw_riddle!("8080");
+//! let timeout: Riddle<u64> = ravenclaw_riddle!("forever");
+//! assert_eq!(port.solve(), Ok(&8080));
+//! assert_eq!(
+//!     timeout.solve().unwrap_err().to_string(),
+//!     "the eagle knocker rejected the answer `forever`: invalid digit found in string"
+//! );
+//! ```
+
+use core::fmt;
+use core::str::FromStr;
+use std::sync::OnceLock;
+
+/// The parser of a [`Riddle`], with its error already turned into a reason.
+type Parser<T> = Box<dyn Fn(&str) -> Result<T, String> + Send + Sync>;
+
+/// A raw string that is parsed into a `T` the first time it is needed.
+pub struct Riddle<T> {
+    raw: String,
+    parser: Parser<T>,
+    answer: OnceLock<Result<T, WrongAnswer>>,
+}
+
+impl<T: FromStr + 'static> Riddle<T>
+where
+    T::Err: fmt::Display,
+{
+    /// Creates a riddle that is solved with [`FromStr`].
+    pub fn new(raw: impl Into<String>) -> Self {
+        Self::with_parser(raw, str::parse::<T>)
+    }
+}
+
+impl<T> Riddle<T> {
+    /// Creates a riddle that is solved with `parser`. The parser can also validate the value, by returning an error.
+    pub fn with_parser<E: fmt::Display>(
+        raw: impl Into<String>,
+        parser: impl Fn(&str) -> Result<T, E> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            raw: raw.into(),
+            parser: Box::new(move |raw| parser(raw).map_err(|error| error.to_string())),
+            answer: OnceLock::new(),
+        }
+    }
+
+    /// Returns the raw string of the riddle.
+    pub fn raw(&self) -> &str {
+        &self.raw
+    }
+
+    /// Parses the raw string the first time it is called, and returns the same answer every time.
+    /// # Errors
+    /// Returns a [`WrongAnswer`] if the parser rejected the raw string.
+    pub fn solve(&self) -> Result<&T, &WrongAnswer> {
+        self.answer
+            .get_or_init(|| {
+                (self.parser)(&self.raw).map_err(|reason| WrongAnswer {
+                    answer: self.raw.clone(),
+                    reason,
+                })
+            })
+            .as_ref()
+    }
+
+    /// Returns whether the riddle has been solved, successfully or not.
+    pub fn is_solved(&self) -> bool {
+        self.answer.get().is_some()
+    }
+
+    /// Solves the riddle if it has not been solved already, and returns the answer.
+    /// # Errors
+    /// Returns a [`WrongAnswer`] if the parser rejected the raw string.
+    pub fn into_answer(self) -> Result<T, WrongAnswer> {
+        let _ = self.solve();
+        self.answer
+            .into_inner()
+            .expect("the riddle was just solved")
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Riddle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Riddle")
+            .field("raw", &self.raw)
+            .field("answer", &self.answer.get())
+            .finish_non_exhaustive()
+    }
+}
+
+/// The error returned when the parser of a [`Riddle`] rejected its raw string.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct WrongAnswer {
+    answer: String,
+    reason: String,
+}
+
+impl WrongAnswer {
+    /// Returns the raw string that was rejected.
+    pub fn answer(&self) -> &str {
+        &self.answer
+    }
+
+    /// Returns why the parser rejected the raw string.
+    pub fn reason(&self) -> &str {
+        &self.reason
+    }
+}
+
+impl fmt::Display for WrongAnswer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "the eagle knocker rejected the answer `{}`: {}",
+            self.answer, self.reason
+        )
+    }
+}
+
+impl std::error::Error for WrongAnswer {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ravenclaw_riddle;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    /// Creates a riddle that counts how often it is parsed, and only accepts even numbers.
+    fn counted(raw: &str) -> (Riddle<u32>, Arc<AtomicUsize>) {
+        let parses = Arc::new(AtomicUsize::new(0));
+        let riddle = {
+            let parses = Arc::clone(&parses);
+            ravenclaw_riddle!(raw, move |raw: &str| {
+                parses.fetch_add(1, Ordering::Relaxed);
+                match raw.parse::<u32>() {
+                    Ok(n) if n % 2 == 0 => Ok(n),
+                    Ok(n) => Err(format!("{} is odd", n)),
+                    Err(error) => Err(error.to_string()),
+                }
+            })
+        };
+        (riddle, parses)
+    }
+
+    #[test]
+    fn practice_ravenclaw_riddle() {
+        let riddle: Riddle<f64> = ravenclaw_riddle!(String::from("3.5"));
+        assert_eq!(riddle.raw(), "3.5");
+        assert!(!riddle.is_solved());
+        assert_eq!(
+            format!("{:?}", riddle),
+            "Riddle { raw: \"3.5\", answer: None, .. }"
+        );
+        assert_eq!(riddle.solve(), Ok(&3.5));
+        assert!(riddle.is_solved());
+        assert_eq!(riddle.into_answer(), Ok(3.5));
+
+        let riddle: Riddle<bool> = ravenclaw_riddle!("maybe");
+        assert_eq!(riddle.into_answer().unwrap_err().answer(), "maybe");
+    }
+
+    #[test]
+    fn a_riddle_keeps_its_answer() {
+        let (riddle, parses) = counted("42");
+        assert_eq!(parses.load(Ordering::Relaxed), 0);
+        for _ in 0..3 {
+            assert_eq!(riddle.solve(), Ok(&42));
+        }
+        assert_eq!(parses.load(Ordering::Relaxed), 1);
+        assert_eq!(riddle.into_answer(), Ok(42));
+        assert_eq!(parses.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn a_riddle_keeps_its_wrong_answer() {
+        let (riddle, parses) = counted("41");
+        for _ in 0..3 {
+            let wrong = riddle.solve().unwrap_err();
+            assert_eq!(wrong.reason(), "41 is odd");
+            assert_eq!(
+                wrong.to_string(),
+                "the eagle knocker rejected the answer `41`: 41 is odd"
+            );
+        }
+        assert_eq!(parses.load(Ordering::Relaxed), 1);
+        assert_eq!(riddle.into_answer().unwrap_err().reason(), "41 is odd");
+
+        let (riddle, parses) = counted("a phoenix");
+        assert!(riddle.into_answer().is_err());
+        assert_eq!(parses.load(Ordering::Relaxed), 1);
+    }
+}
@@ -0,0 +1,170 @@
+//! Token-gated methods, restricted with the `#[restricted_section]` attribute from the `macros` feature.
+//!
+//! The methods of an impl block that are marked with `#[restricted]` can only be called with a
+//! [`LibrarianPass`] for the type. Passes are only issued by the `librarian_pass()` function the attribute
+//! adds to the type, which only exists in tests or when the feature named in the attribute is enabled,
+//! as only the [librarian](RestrictedSection::Librarian) of the section can [issue](LibrarianPass::issue) them.
+//! Code that is not allowed in the restricted section has to be handed a pass by someone who is.
+#![cfg_attr(feature = "macros", doc = "```")]
+#![cfg_attr(not(feature = "macros"), doc = "```ignore")]
+//! use code_spells::restricted_section;
+//! use code_spells::restricted_section::LibrarianPass;
+//!
+//! struct Library {
+//!     books: Vec<&'static str>,
+//! }
+//!
+//! #[restricted_section(feature = "librarian")]
+//! impl Library {
+//!     fn borrow(&self, index: usize) -> Option<&str> {
+//!         self.books.get(index).copied()
+//!     }
+//!
+//!     #[restricted]
+//!     fn burn(&mut self, index: usize) -> &'static str {
+//!         self.books.remove(index)
+//!     }
+//! }
+//!
+//! fn tidy_up(library: &mut Library, pass: LibrarianPass<Library>) {
+//!     library.burn(pass, 0);
+//! }
+//! ```
+//! Without a pass the restricted methods can not be called.
+#![cfg_attr(feature = "macros", doc = "```compile_fail")]
+#![cfg_attr(not(feature = "macros"), doc = "```ignore")]
+//! # use code_spells::restricted_section;
+//! # struct Library {
+//! #     books: Vec<&'static str>,
+//! # }
+//! #[restricted_section]
+//! impl Library {
+//!     #[restricted]
+//!     fn burn(&mut self, index: usize) -> &'static str {
+//!         self.books.remove(index)
+//!     }
+//! }
+//!
+//! let mut library = Library { books: vec!["Secrets of the Darkest Art"] };
+//! library.burn(0);
+//! ```
+//! Nor can a pass be issued anywhere but where the section is declared.
+#![cfg_attr(feature = "macros", doc = "```compile_fail")]
+#![cfg_attr(not(feature = "macros"), doc = "```ignore")]
+//! # use code_spells::restricted_section;
+//! use code_spells::restricted_section::{LibrarianPass, RestrictedSection};
+//!
+//! mod hogwarts {
+//!     # use code_spells::restricted_section;
+//!     pub struct Library;
+//!
+//!     #[restricted_section]
+//!     impl Library {
+//!         #[restricted]
+//!         pub fn burn(&mut self) {}
+//!     }
+//! }
+//!
+//! type Librarian = <hogwarts::Library as RestrictedSection>::Librarian;
+//! let pass = LibrarianPass::<hogwarts::Library>::issue(Librarian { 0: () });
+//! ```
+
+use core::fmt;
+use core::marker::PhantomData;
+
+/// Permission to call the restricted methods of `S`.
+///
+/// Passes carry no data, so they can be copied freely by whoever has one.
+pub struct LibrarianPass<S: ?Sized> {
+    section: PhantomData<fn() -> *const S>,
+}
+
+impl<S: ?Sized + RestrictedSection> LibrarianPass<S> {
+    /// Issues a pass to the restricted section of `S`, which only its librarian can do.
+    pub fn issue(librarian: S::Librarian) -> Self {
+        let _ = librarian;
+        Self {
+            section: PhantomData,
+        }
+    }
+}
+
+/// A type with a restricted section, implemented by the `#[restricted_section]` attribute.
+///
+/// The librarian of the section can only be found where the section is declared,
+/// so passes to it can not be issued anywhere else.
+pub trait RestrictedSection {
+    /// The librarian who issues passes to the section.
+    type Librarian;
+}
+
+impl<S: ?Sized> Clone for LibrarianPass<S> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<S: ?Sized> Copy for LibrarianPass<S> {}
+
+impl<S: ?Sized> fmt::Debug for LibrarianPass<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "LibrarianPass<{}>", core::any::type_name::<S>())
+    }
+}
+
+#[cfg(all(test, feature = "macros"))]
+mod tests {
+    use crate::restricted_section;
+
+    struct Library {
+        books: Vec<&'static str>,
+    }
+
+    #[restricted_section]
+    impl Library {
+        fn count(&self) -> usize {
+            self.books.len()
+        }
+
+        #[restricted]
+        fn burn(&mut self, index: usize) -> &'static str {
+            self.books.remove(index)
+        }
+
+        /// Shelves a book in the restricted section.
+        #[restricted]
+        pub(crate) fn shelve<S: Into<&'static str>>(&mut self, book: S) {
+            self.books.push(book.into())
+        }
+
+        #[restricted]
+        fn catalogue() -> &'static str {
+            "restricted"
+        }
+    }
+
+    struct Vault<T>(Vec<T>);
+
+    #[restricted_section(feature = "macros")]
+    impl<T: Clone> Vault<T> {
+        #[restricted]
+        fn open(&self, f: impl Fn(&T) -> bool) -> Vec<T> {
+            self.0.iter().filter(|t| f(t)).cloned().collect()
+        }
+    }
+
+    #[test]
+    fn practice_restricted_section() {
+        let mut library = Library { books: Vec::new() };
+        let pass = Library::librarian_pass();
+        library.shelve(pass, "Moste Potente Potions");
+        library.shelve(pass, "Magick Moste Evile");
+        assert_eq!(library.count(), 2);
+        assert_eq!(library.burn(pass, 1), "Magick Moste Evile");
+        assert_eq!(Library::catalogue(pass), "restricted");
+        assert!(format!("{:?}", pass).starts_with("LibrarianPass<"));
+
+        let vault = Vault(vec![1, 2, 3]);
+        assert_eq!(vault.open(Vault::librarian_pass(), |&x| x > 1), [2, 3]);
+    }
+}
@@ -0,0 +1,398 @@
+//! A content-addressed blob store, cast with [`room_of_hidden_things!`](crate::room_of_hidden_things!).
+//!
+//! Generations of students have hidden things in the Room of Requirement, and the room keeps every one of them,
+//! but never the same thing twice. A [`RoomOfHiddenThings`] stores blobs of bytes under a [`Key`] that is computed
+//! from their contents, so [hiding](RoomOfHiddenThings::hide) the same bytes again returns the same key
+//! without storing them again, and the key is all that is needed to [seek](RoomOfHiddenThings::seek) them later.
+//!
+//! A blob can [link](RoomOfHiddenThings::hide_linked) to other blobs, like a directory links to its files.
+//! A [sweep](RoomOfHiddenThings::sweep) is given the keys that are still in use, and removes every blob
+//! that can not be reached from them through the links.
+//!
+//! A room is kept in memory, and can also be [opened](RoomOfHiddenThings::open) in a directory,
+//! where every blob is written to a file so that the room can be opened again later.
+//! ```
+//! # use code_spells::room_of_hidden_things;
+//! let room = room_of_hidden_things!();
+//! let diadem = room.hide("Ravenclaw's diadem")?;
+//! assert_eq!(room.hide("Ravenclaw's diadem")?, diadem);
+//! let cabinet = room.hide_linked("the Vanishing Cabinet", &[diadem])?;
+//! assert_eq!(room.len(), 2);
+//!
+//! room.sweep([cabinet])?;
+//! assert_eq!(room.seek(diadem).as_deref(), Some(&b"Ravenclaw's diadem"[..]));
+//! room.sweep([])?;
+//! assert!(room.is_empty());
+//! # Ok::<(), code_spells::room_of_hidden_things::Lost>(())
+//! ```
+
+use core::convert::{TryFrom, TryInto};
+use core::fmt;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+/// The key that a blob is hidden under, computed from its contents and links.
+///
+/// It is written as 32 hexadecimal digits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Key(u128);
+
+impl Key {
+    /// Reads a key from the 32 hexadecimal digits it is written as, or returns `None` if it is not a key.
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        if hex.len() != 32 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+        u128::from_str_radix(hex, 16).ok().map(Self)
+    }
+
+    /// Computes the key of a blob with FNV-1a, which is stable across platforms and versions of Rust,
+    /// so that a room opened again finds the blobs under the same keys.
+    fn of(bytes: &[u8], links: &[Key]) -> Self {
+        const OFFSET: u128 = 0x6c62_272e_07bb_0142_62b8_2175_6295_c58d;
+        const PRIME: u128 = 0x0000_0000_0100_0000_0000_0000_0000_013b;
+        let links_len = (links.len() as u64).to_le_bytes();
+        let link_bytes = links.iter().flat_map(|link| link.0.to_be_bytes());
+        let hash = links_len
+            .iter()
+            .copied()
+            .chain(link_bytes)
+            .chain(bytes.iter().copied())
+            .fold(OFFSET, |hash, byte| {
+                (hash ^ u128::from(byte)).wrapping_mul(PRIME)
+            });
+        Self(hash)
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:032x}", self.0)
+    }
+}
+
+/// The error returned when something could not be hidden in, or removed from, a [`RoomOfHiddenThings`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Lost {
+    /// A blob was linked to a key that is not hidden in the room.
+    UnknownLink(Key),
+    /// A file in the directory of the room is not a blob that the room wrote.
+    Corrupted(Key),
+    /// Reading or writing the directory of the room failed with an error of this kind.
+    Io(io::ErrorKind),
+}
+
+impl fmt::Display for Lost {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownLink(key) => {
+                write!(f, "nothing is hidden in the room under the key {}", key)
+            }
+            Self::Corrupted(key) => write!(f, "the thing hidden under the key {} is broken", key),
+            Self::Io(kind) => write!(
+                f,
+                "the room of hidden things could not be reached: {}",
+                kind
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Lost {}
+
+impl From<io::Error> for Lost {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error.kind())
+    }
+}
+
+/// A blob and the keys it links to.
+#[derive(Debug)]
+struct Hidden {
+    bytes: Arc<[u8]>,
+    links: Vec<Key>,
+}
+
+impl Hidden {
+    /// Encodes the blob as the number of links, the links and the bytes.
+    fn encode(&self) -> Vec<u8> {
+        let mut file = Vec::with_capacity(8 + 16 * self.links.len() + self.bytes.len());
+        file.extend_from_slice(&(self.links.len() as u64).to_le_bytes());
+        for link in &self.links {
+            file.extend_from_slice(&link.0.to_be_bytes());
+        }
+        file.extend_from_slice(&self.bytes);
+        file
+    }
+
+    fn decode(file: &[u8]) -> Option<Self> {
+        let (links_len, rest) = split_array::<8>(file)?;
+        let links_len = usize::try_from(u64::from_le_bytes(links_len)).ok()?;
+        let mut links = Vec::with_capacity(links_len.min(rest.len() / 16));
+        let mut rest = rest;
+        for _ in 0..links_len {
+            let (link, tail) = split_array::<16>(rest)?;
+            links.push(Key(u128::from_be_bytes(link)));
+            rest = tail;
+        }
+        Some(Self {
+            bytes: rest.into(),
+            links,
+        })
+    }
+}
+
+fn split_array<const N: usize>(bytes: &[u8]) -> Option<([u8; N], &[u8])> {
+    if bytes.len() < N {
+        return None;
+    }
+    let (head, tail) = bytes.split_at(N);
+    Some((head.try_into().ok()?, tail))
+}
+
+/// A content-addressed store of blobs of bytes, that only keeps one copy of each.
+pub struct RoomOfHiddenThings {
+    blobs: Mutex<HashMap<Key, Hidden>>,
+    directory: Option<PathBuf>,
+}
+
+impl RoomOfHiddenThings {
+    /// Creates an empty room that is only kept in memory.
+    pub fn new() -> Self {
+        Self {
+            blobs: Mutex::new(HashMap::new()),
+            directory: None,
+        }
+    }
+
+    /// Opens the room that is kept in `directory`, creating the directory if it does not exist.
+    /// Every blob in the directory is read into memory, and every blob hidden from now on is also written to it.
+    /// # Errors
+    /// Returns an error if the directory can not be created or read,
+    /// and [`Lost::Corrupted`] if a file named like a key is not a blob.
+    pub fn open(directory: impl AsRef<Path>) -> Result<Self, Lost> {
+        let directory = directory.as_ref();
+        fs::create_dir_all(directory)?;
+        let mut blobs = HashMap::new();
+        for entry in fs::read_dir(directory)? {
+            let entry = entry?;
+            let key = match entry.file_name().to_str().and_then(Key::from_hex) {
+                Some(key) => key,
+                None => continue,
+            };
+            let hidden = Hidden::decode(&fs::read(entry.path())?).ok_or(Lost::Corrupted(key))?;
+            blobs.insert(key, hidden);
+        }
+        Ok(Self {
+            blobs: Mutex::new(blobs),
+            directory: Some(directory.to_owned()),
+        })
+    }
+
+    /// Hides `bytes` in the room and returns its key. Bytes that are already hidden are not stored again.
+    /// # Errors
+    /// Returns an error if the blob could not be written to the directory of the room.
+    /// A room that is only kept in memory never fails.
+    pub fn hide(&self, bytes: impl AsRef<[u8]>) -> Result<Key, Lost> {
+        self.hide_linked(bytes, &[])
+    }
+
+    /// Hides `bytes` in the room with links to other blobs, and returns its key.
+    /// The blobs that it links to are kept by a [sweep](RoomOfHiddenThings::sweep) as long as it is.
+    ///
+    /// The same bytes with different links are different blobs.
+    /// # Errors
+    /// Returns [`Lost::UnknownLink`] if a link is not hidden in the room,
+    /// and an error if the blob could not be written to the directory of the room.
+    pub fn hide_linked(&self, bytes: impl AsRef<[u8]>, links: &[Key]) -> Result<Key, Lost> {
+        let bytes = bytes.as_ref();
+        let mut blobs = self.lock();
+        if let Some(&unknown) = links.iter().find(|link| !blobs.contains_key(link)) {
+            return Err(Lost::UnknownLink(unknown));
+        }
+        // Two different blobs with the same hash are told apart by moving the later one to the next free key.
+        let mut key = Key::of(bytes, links);
+        while let Some(hidden) = blobs.get(&key) {
+            if &*hidden.bytes == bytes && hidden.links == links {
+                return Ok(key);
+            }
+            key = Key(key.0.wrapping_add(1));
+        }
+        let hidden = Hidden {
+            bytes: bytes.into(),
+            links: links.to_vec(),
+        };
+        if let Some(directory) = &self.directory {
+            let path = directory.join(key.to_string());
+            let unfinished = path.with_extension("unfinished");
+            fs::write(&unfinished, hidden.encode())?;
+            fs::rename(&unfinished, &path)?;
+        }
+        blobs.insert(key, hidden);
+        Ok(key)
+    }
+
+    /// Returns the bytes hidden under `key`, if there are any.
+    pub fn seek(&self, key: Key) -> Option<Arc<[u8]>> {
+        self.lock()
+            .get(&key)
+            .map(|hidden| Arc::clone(&hidden.bytes))
+    }
+
+    /// Returns the keys that the blob hidden under `key` links to, if there is one.
+    pub fn links(&self, key: Key) -> Option<Vec<Key>> {
+        self.lock().get(&key).map(|hidden| hidden.links.clone())
+    }
+
+    /// Returns whether a blob is hidden under `key`.
+    pub fn contains(&self, key: Key) -> bool {
+        self.lock().contains_key(&key)
+    }
+
+    /// Returns the number of blobs in the room.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Returns whether the room is empty.
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Removes every blob that can not be reached from `roots` through the links, and returns how many were removed.
+    /// Roots that are not hidden in the room are ignored.
+    /// # Errors
+    /// Returns an error if the file of a removed blob could not be deleted from the directory of the room.
+    /// The blobs that were removed before that stay removed.
+    pub fn sweep(&self, roots: impl IntoIterator<Item = Key>) -> Result<usize, Lost> {
+        let mut blobs = self.lock();
+        let mut reachable = HashSet::new();
+        let mut unvisited: Vec<Key> = roots.into_iter().collect();
+        while let Some(key) = unvisited.pop() {
+            if let Some(hidden) = blobs.get(&key) {
+                if reachable.insert(key) {
+                    unvisited.extend_from_slice(&hidden.links);
+                }
+            }
+        }
+
+        let unreachable: Vec<Key> = blobs
+            .keys()
+            .filter(|key| !reachable.contains(key))
+            .copied()
+            .collect();
+        for key in &unreachable {
+            if let Some(directory) = &self.directory {
+                if let Err(error) = fs::remove_file(directory.join(key.to_string())) {
+                    if error.kind() != io::ErrorKind::NotFound {
+                        return Err(error.into());
+                    }
+                }
+            }
+            blobs.remove(key);
+        }
+        Ok(unreachable.len())
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<Key, Hidden>> {
+        self.blobs.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Default for RoomOfHiddenThings {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for RoomOfHiddenThings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RoomOfHiddenThings")
+            .field("blobs", &self.len())
+            .field("directory", &self.directory)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::room_of_hidden_things;
+
+    #[test]
+    fn practice_room_of_hidden_things() {
+        let room = room_of_hidden_things!();
+        let book = room.hide(b"Advanced Potion-Making").unwrap();
+        let again = room.hide(String::from("Advanced Potion-Making")).unwrap();
+        assert_eq!(book, again);
+        assert_eq!(room.len(), 1);
+        assert_eq!(&*room.seek(book).unwrap(), b"Advanced Potion-Making");
+        assert_eq!(Key::from_hex(&book.to_string()), Some(book));
+        assert_eq!(Key::from_hex("not a key"), None);
+
+        let shelf = room
+            .hide_linked(b"Advanced Potion-Making", &[book])
+            .unwrap();
+        assert_ne!(shelf, book);
+        assert_eq!(room.links(shelf), Some(vec![book]));
+        let nowhere = Key(0);
+        assert_eq!(
+            room.hide_linked(b"tiara", &[nowhere]).unwrap_err(),
+            Lost::UnknownLink(nowhere)
+        );
+        assert_eq!(
+            format!("{:?}", room),
+            "RoomOfHiddenThings { blobs: 2, directory: None, .. }"
+        );
+    }
+
+    #[test]
+    fn a_sweep_keeps_what_is_reachable() {
+        let room = RoomOfHiddenThings::new();
+        let sword = room.hide("sword").unwrap();
+        let wand = room.hide("wand").unwrap();
+        let cupboard = room.hide_linked("cupboard", &[sword, wand]).unwrap();
+        let shelf = room.hide_linked("shelf", &[cupboard]).unwrap();
+        let junk = room.hide("junk").unwrap();
+        let pile = room.hide_linked("pile", &[junk, wand]).unwrap();
+
+        assert_eq!(room.sweep([shelf, Key(7)]).unwrap(), 2);
+        assert!(!room.contains(pile) && !room.contains(junk));
+        assert!([shelf, cupboard, sword, wand]
+            .iter()
+            .all(|&key| room.contains(key)));
+        assert_eq!(room.sweep([sword]).unwrap(), 3);
+        assert_eq!(room.len(), 1);
+    }
+
+    #[test]
+    fn a_room_in_a_directory_can_be_opened_again() {
+        let directory = std::env::temp_dir().join(format!(
+            "code_spells_room_of_hidden_things_{}",
+            std::process::id()
+        ));
+        let room = room_of_hidden_things!(&directory).unwrap();
+        let diary = room.hide("Tom Riddle's diary").unwrap();
+        let locket = room.hide("Slytherin's locket").unwrap();
+        let stash = room.hide_linked(b"", &[diary]).unwrap();
+        drop(room);
+
+        let room = RoomOfHiddenThings::open(&directory).unwrap();
+        assert_eq!(room.len(), 3);
+        assert_eq!(room.links(stash), Some(vec![diary]));
+        assert_eq!(room.hide("Tom Riddle's diary").unwrap(), diary);
+        assert_eq!(room.sweep([stash]).unwrap(), 1);
+        assert!(!directory.join(locket.to_string()).exists());
+        drop(room);
+
+        fs::write(directory.join(Key(1).to_string()), [1, 2]).unwrap();
+        assert_eq!(
+            RoomOfHiddenThings::open(&directory).unwrap_err(),
+            Lost::Corrupted(Key(1))
+        );
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
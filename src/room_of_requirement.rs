@@ -0,0 +1,274 @@
+//! An object pool, cast with [`room_of_requirement!`](crate::room_of_requirement!).
+//!
+//! The Room of Requirement appears with whatever you need when you pace past it, and it is still
+//! there the next time you need it. A [`RoomOfRequirement`] hands out objects, like buffers, that are
+//! expensive to create. Each object goes back to the room when its [`Requirement`] guard is dropped,
+//! [tidied](Tidy) so that the next user finds it empty but with its allocations intact.
+//! ```
+//! use code_spells::room_of_requirement::RoomOfRequirement;
+//!
+//! let room = RoomOfRequirement::<Vec<u8>>::new(|| Vec::with_capacity(4096));
+//! for spell in ["Lumos", "Nox"] {
+//!     let mut buffer = room.require();
+//!     buffer.extend_from_slice(spell.as_bytes());
+//!     assert_eq!(&buffer[..], spell.as_bytes());
+//! }
+//! assert_eq!(room.idle(), 1);
+//! ```
+
+use core::fmt;
+use core::hash::BuildHasher;
+use core::mem::ManuallyDrop;
+use core::ops::{Deref, DerefMut};
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap, HashSet, VecDeque};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+/// Objects that can be tidied up for reuse, usually by clearing them without giving up their allocations.
+pub trait Tidy {
+    /// Tidies up the object for the next user.
+    fn tidy(&mut self);
+}
+
+impl Tidy for String {
+    fn tidy(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T> Tidy for Vec<T> {
+    fn tidy(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T> Tidy for VecDeque<T> {
+    fn tidy(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T> Tidy for BinaryHeap<T> {
+    fn tidy(&mut self) {
+        self.clear();
+    }
+}
+
+impl<K, V, S: BuildHasher> Tidy for HashMap<K, V, S> {
+    fn tidy(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T, S: BuildHasher> Tidy for HashSet<T, S> {
+    fn tidy(&mut self) {
+        self.clear();
+    }
+}
+
+impl<K: Ord, V> Tidy for BTreeMap<K, V> {
+    fn tidy(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T: Ord> Tidy for BTreeSet<T> {
+    fn tidy(&mut self) {
+        self.clear();
+    }
+}
+
+/// A pool that creates objects when it runs out, and takes them back for reuse.
+pub struct RoomOfRequirement<T> {
+    idle: Mutex<Vec<T>>,
+    create: Box<dyn Fn() -> T + Send + Sync>,
+    reset: Box<dyn Fn(&mut T) + Send + Sync>,
+    max_idle: usize,
+}
+
+impl<T: Tidy + 'static> RoomOfRequirement<T> {
+    /// Creates an empty room that creates objects with `create`, and [tidies](Tidy) them when they come back.
+    pub fn new(create: impl Fn() -> T + Send + Sync + 'static) -> Self {
+        Self::with_reset(create, T::tidy)
+    }
+}
+
+impl<T> RoomOfRequirement<T> {
+    /// Creates an empty room that creates objects with `create`, and resets them with `reset` when they come back.
+    pub fn with_reset(
+        create: impl Fn() -> T + Send + Sync + 'static,
+        reset: impl Fn(&mut T) + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            idle: Mutex::new(Vec::new()),
+            create: Box::new(create),
+            reset: Box::new(reset),
+            max_idle: usize::MAX,
+        }
+    }
+
+    /// Sets how many objects the room keeps at most. Objects that come back to a full room are dropped.
+    #[must_use]
+    pub fn max_idle(mut self, max_idle: usize) -> Self {
+        self.max_idle = max_idle;
+        self
+    }
+
+    /// Hands out an idle object, or creates a new one if there is none.
+    pub fn require(&self) -> Requirement<'_, T> {
+        let object = self.lock().pop().unwrap_or_else(|| (self.create)());
+        Requirement {
+            room: self,
+            object: ManuallyDrop::new(object),
+        }
+    }
+
+    /// Returns the number of objects that are waiting in the room.
+    pub fn idle(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Creates objects until there are `count` idle ones, so that the first users do not have to wait for them.
+    pub fn prepare(&self, count: usize) {
+        let count = count.min(self.max_idle);
+        let missing = count.saturating_sub(self.idle());
+        let fresh: Vec<T> = (0..missing).map(|_| (self.create)()).collect();
+        self.lock().extend(fresh);
+    }
+
+    /// Resets an object and keeps it, unless the room is full.
+    fn give_back(&self, mut object: T) {
+        (self.reset)(&mut object);
+        let mut idle = self.lock();
+        if idle.len() < self.max_idle {
+            idle.push(object);
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Vec<T>> {
+        self.idle.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<T> fmt::Debug for RoomOfRequirement<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RoomOfRequirement")
+            .field("idle", &self.idle())
+            .field("max_idle", &self.max_idle)
+            .finish_non_exhaustive()
+    }
+}
+
+/// An object from a [`RoomOfRequirement`], which goes back to the room when this guard is dropped.
+pub struct Requirement<'a, T> {
+    room: &'a RoomOfRequirement<T>,
+    object: ManuallyDrop<T>,
+}
+
+impl<T> Requirement<'_, T> {
+    /// Takes the object out of the room for good, so that it is not given back.
+    pub fn keep(this: Self) -> T {
+        let mut this = ManuallyDrop::new(this);
+        // SAFETY: the guard is never used or dropped again, so the object is taken out exactly once.
+        unsafe { ManuallyDrop::take(&mut this.object) }
+    }
+}
+
+impl<T> Deref for Requirement<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.object
+    }
+}
+
+impl<T> DerefMut for Requirement<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.object
+    }
+}
+
+impl<T> Drop for Requirement<'_, T> {
+    fn drop(&mut self) {
+        // SAFETY: this is the only place besides `keep` that takes the object, and `keep` skips this.
+        let object = unsafe { ManuallyDrop::take(&mut self.object) };
+        self.room.give_back(object);
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Requirement<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Requirement").field(&*self.object).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::room_of_requirement;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn practice_room_of_requirement() {
+        let room = room_of_requirement!(|| String::with_capacity(64));
+        {
+            let mut first = room.require();
+            let mut second = room.require();
+            first.push_str("Dumbledore's Army");
+            second.push_str("a place to hide things");
+            assert_eq!(format!("{:?}", first), "Requirement(\"Dumbledore's Army\")");
+        }
+        assert_eq!(room.idle(), 2);
+        let reused = room.require();
+        assert!(reused.is_empty());
+        assert!(reused.capacity() >= 64);
+
+        let counted = room_of_requirement!(|| 0_u32, |n: &mut u32| *n += 1);
+        drop(counted.require());
+        assert_eq!(*counted.require(), 1);
+    }
+
+    #[test]
+    fn objects_are_only_created_when_needed() {
+        let created = Arc::new(AtomicUsize::new(0));
+        let room = {
+            let created = Arc::clone(&created);
+            RoomOfRequirement::<Vec<u8>>::new(move || {
+                created.fetch_add(1, Ordering::Relaxed);
+                Vec::new()
+            })
+        };
+        room.prepare(2);
+        assert_eq!(created.load(Ordering::Relaxed), 2);
+        thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| {
+                    for i in 0..100 {
+                        room.require().push(i);
+                    }
+                });
+            }
+        });
+        assert!(created.load(Ordering::Relaxed) <= 4);
+        assert_eq!(room.idle(), created.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn a_full_room_and_kept_objects() {
+        let room = RoomOfRequirement::<Vec<u8>>::new(Vec::new).max_idle(1);
+        let (a, b) = (room.require(), room.require());
+        drop((a, b));
+        assert_eq!(room.idle(), 1);
+        room.prepare(5);
+        assert_eq!(
+            format!("{:?}", room),
+            "RoomOfRequirement { idle: 1, max_idle: 1, .. }"
+        );
+
+        let mut kept = room.require();
+        kept.push(7);
+        assert_eq!(Requirement::keep(kept), [7]);
+        assert_eq!(room.idle(), 0);
+    }
+}
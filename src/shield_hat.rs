@@ -0,0 +1,150 @@
+//! Thread-local override stacks, declared with [`shield_hat!`](crate::shield_hat!).
+//!
+//! A [`ShieldHat`] holds a value for every thread. Wearing the hat with a new value
+//! shields the current thread from the old one until the returned [`HatGuard`] is dropped.
+//! Hats can be worn on top of each other, and taking one off reveals the one beneath.
+//! ```
+//! # use code_spells::shield_hat;
+//! shield_hat! {
+//!     static VOLUME: u8 = 5;
+//! }
+//!
+//! assert_eq!(VOLUME.current(), 5);
+//! {
+//!     let _quiet = VOLUME.wear(1);
+//!     assert_eq!(VOLUME.current(), 1);
+//!     {
+//!         let _silent = VOLUME.wear(0);
+//!         assert_eq!(VOLUME.current(), 0);
+//!     }
+//!     assert_eq!(VOLUME.current(), 1);
+//! }
+//! assert_eq!(VOLUME.current(), 5);
+//! ```
+
+use core::cell::RefCell;
+use core::marker::PhantomData;
+use std::thread::{self, LocalKey};
+
+/// A thread-local value that can be temporarily overridden. Declared with [`shield_hat!`](crate::shield_hat!).
+pub struct ShieldHat<T: 'static> {
+    stack: &'static LocalKey<RefCell<Vec<T>>>,
+}
+
+impl<T: 'static> ShieldHat<T> {
+    #[doc(hidden)]
+    pub const fn new(stack: &'static LocalKey<RefCell<Vec<T>>>) -> Self {
+        Self { stack }
+    }
+
+    /// Overrides the value on the current thread until the returned guard is dropped.
+    #[must_use = "the hat falls off as soon as the guard is dropped"]
+    pub fn wear(&self, value: T) -> HatGuard<T> {
+        let depth = self.stack.with(|stack| {
+            let mut stack = stack.borrow_mut();
+            stack.push(value);
+            stack.len() - 1
+        });
+        HatGuard {
+            stack: self.stack,
+            depth,
+            _not_send: PhantomData,
+        }
+    }
+
+    /// Returns a clone of the value that is currently in effect on this thread.
+    pub fn current(&self) -> T
+    where
+        T: Clone,
+    {
+        self.with_current(T::clone)
+    }
+
+    /// Calls `f` with a reference to the value that is currently in effect on this thread.
+    /// # Panics
+    /// Panics if `f` tries to wear this hat.
+    pub fn with_current<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        self.stack.with(|stack| {
+            let stack = stack.borrow();
+            f(stack
+                .last()
+                .expect("the bottom of a shield hat is never taken off"))
+        })
+    }
+
+    /// Returns how many overrides are currently worn on this thread.
+    pub fn layers(&self) -> usize {
+        self.stack.with(|stack| stack.borrow().len() - 1)
+    }
+}
+
+/// Removes an override made with [`ShieldHat::wear`] when dropped.
+/// Hats must be taken off in the opposite order they were worn.
+pub struct HatGuard<T: 'static> {
+    stack: &'static LocalKey<RefCell<Vec<T>>>,
+    depth: usize,
+    // The override lives on the thread that made it.
+    _not_send: PhantomData<*const ()>,
+}
+
+impl<T: 'static> Drop for HatGuard<T> {
+    /// # Panics
+    /// Panics if another hat has been worn on top of this one and not taken off yet.
+    fn drop(&mut self) {
+        // The value is dropped after the borrow ends, in case its destructor uses the hat.
+        let removed = self.stack.try_with(|stack| {
+            let mut stack = stack.borrow_mut();
+            if stack.len() == self.depth + 1 {
+                Some(stack.pop())
+            } else {
+                None
+            }
+        });
+        if let Ok(None) = removed {
+            if !thread::panicking() {
+                panic!("shield hats must be taken off in the opposite order they were worn");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::shield_hat;
+
+    shield_hat! {
+        static NAME: String = String::from("Harry");
+        pub(crate) static YEAR: u32 = 1;
+    }
+
+    #[test]
+    fn practice_shield_hat() {
+        assert_eq!(NAME.current(), "Harry");
+        let _cloak = NAME.wear(String::from("Ron"));
+        assert_eq!(NAME.current(), "Ron");
+        let outer = YEAR.wear(2);
+        let inner = YEAR.wear(3);
+        assert_eq!(YEAR.layers(), 2);
+        drop(inner);
+        assert_eq!(YEAR.current(), 2);
+        drop(outer);
+        assert_eq!(YEAR.current(), 1);
+        assert_eq!(YEAR.layers(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "opposite order they were worn")]
+    fn shield_hats_are_taken_off_in_order() {
+        let outer = YEAR.wear(2);
+        let _inner = YEAR.wear(3);
+        drop(outer);
+    }
+
+    #[test]
+    fn shield_hats_are_thread_local() {
+        let _hat = YEAR.wear(7);
+        let other = std::thread::spawn(|| YEAR.current()).join().unwrap();
+        assert_eq!(other, 1);
+        assert_eq!(YEAR.current(), 7);
+    }
+}
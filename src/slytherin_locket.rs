@@ -0,0 +1,182 @@
+//! Values that must be torn down explicitly, cast with [`slytherin_locket!`](crate::slytherin_locket!).
+//!
+//! Slytherin's locket can not be destroyed by ordinary means. A [`Locket`] holds a value, like a connection
+//! that has to be closed or a transaction that has to be committed, that must not be dropped silently.
+//! It can be used like the value it holds, but it has to be [destroyed](Locket::destroy_with) with a
+//! [`BasiliskFang`] to get the value back for its teardown. Dropping an armed locket panics in debug builds,
+//! so a forgotten teardown is found in tests instead of in production, where the value is just dropped.
+//!
+//! A locket can be [disarmed](Locket::disarm) when it is fine to drop it, e.g. in tests that are about something else.
+//! ```
+//! # use code_spells::slytherin_locket;
+//! use code_spells::slytherin_locket::BasiliskFang;
+//!
+//! let mut transaction = slytherin_locket!(vec!["debit 10 galleons"]);
+//! transaction.push("credit 10 galleons");
+//! let steps = transaction.destroy_with(BasiliskFang::new());
+//! assert_eq!(steps.len(), 2);
+//! ```
+
+use core::fmt;
+use core::mem::ManuallyDrop;
+use core::ops::{Deref, DerefMut};
+
+/// The only thing that can destroy a [`Locket`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BasiliskFang(());
+
+impl BasiliskFang {
+    /// Pulls a fang from the basilisk in the Chamber of Secrets.
+    ///
+    /// Anyone can do this. The fang does not make destroying a locket harder,
+    /// it makes it explicit at the place where the locket is destroyed.
+    pub const fn new() -> Self {
+        Self(())
+    }
+}
+
+impl Default for BasiliskFang {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A value that has to be destroyed with a [`BasiliskFang`] instead of being dropped.
+pub struct Locket<T> {
+    value: ManuallyDrop<T>,
+    armed: bool,
+}
+
+impl<T> Locket<T> {
+    /// Puts a value in an armed locket, which panics in debug builds if it is dropped.
+    pub const fn new(value: T) -> Self {
+        Self {
+            value: ManuallyDrop::new(value),
+            armed: true,
+        }
+    }
+
+    /// Puts a value in a disarmed locket, which can be dropped like any other value.
+    pub const fn disarmed(value: T) -> Self {
+        Self {
+            value: ManuallyDrop::new(value),
+            armed: false,
+        }
+    }
+
+    /// Disarms the locket, so that dropping it no longer panics.
+    pub fn disarm(&mut self) {
+        self.armed = false;
+    }
+
+    /// Returns whether dropping the locket panics in debug builds.
+    pub const fn is_armed(&self) -> bool {
+        self.armed
+    }
+
+    /// Destroys the locket and returns the value it held, for its teardown.
+    pub fn destroy_with(self, fang: BasiliskFang) -> T {
+        let _ = fang;
+        let mut locket = ManuallyDrop::new(self);
+        // SAFETY: the locket is never used or dropped again, so the value is taken out exactly once.
+        unsafe { ManuallyDrop::take(&mut locket.value) }
+    }
+}
+
+impl<T> Deref for Locket<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T> DerefMut for Locket<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+impl<T> Drop for Locket<T> {
+    fn drop(&mut self) {
+        // SAFETY: this is the only place besides `destroy_with` that takes the value, and `destroy_with` skips this.
+        let value = unsafe { ManuallyDrop::take(&mut self.value) };
+        // Panicking while the thread is already panicking would abort, and the missed teardown is not the real problem then.
+        if cfg!(debug_assertions) && self.armed && !std::thread::panicking() {
+            // The value is still dropped while the panic unwinds.
+            let _value = value;
+            panic!(
+                "a Locket<{}> was dropped without being destroyed with a basilisk fang",
+                core::any::type_name::<T>()
+            );
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Locket<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Locket")
+            .field("value", &*self.value)
+            .field("armed", &self.armed)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::slytherin_locket;
+    use std::rc::Rc;
+
+    #[test]
+    fn practice_slytherin_locket() {
+        let mut locket = slytherin_locket!(String::from("Salazar"));
+        locket.push_str(" Slytherin");
+        assert!(locket.is_armed());
+        assert_eq!(
+            format!("{:?}", locket),
+            "Locket { value: \"Salazar Slytherin\", armed: true }"
+        );
+        assert_eq!(
+            locket.destroy_with(BasiliskFang::default()),
+            "Salazar Slytherin"
+        );
+    }
+
+    #[test]
+    fn a_disarmed_locket_can_be_dropped() {
+        let held = Rc::new(());
+        let locket = Locket::disarmed(Rc::clone(&held));
+        assert!(!locket.is_armed());
+        drop(locket);
+        let mut locket = Locket::new(Rc::clone(&held));
+        locket.disarm();
+        drop(locket);
+        assert_eq!(Rc::strong_count(&held), 1);
+
+        let locket = Locket::new(Rc::clone(&held));
+        let inner = locket.destroy_with(BasiliskFang::new());
+        assert_eq!(Rc::strong_count(&held), 2);
+        drop(inner);
+        assert_eq!(Rc::strong_count(&held), 1);
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(
+        expected = "a Locket<u8> was dropped without being destroyed with a basilisk fang"
+    )]
+    fn dropping_an_armed_locket() {
+        let _ = Locket::new(7_u8);
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    fn an_armed_locket_still_drops_its_value() {
+        let held = Rc::new(());
+        let locket = Locket::new(Rc::clone(&held));
+        let dropped = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| drop(locket)));
+        assert!(dropped.is_err());
+        assert_eq!(Rc::strong_count(&held), 1);
+    }
+}
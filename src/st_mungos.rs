@@ -0,0 +1,233 @@
+//! Error triage, cast with [`st_mungos!`](crate::st_mungos!).
+//!
+//! At St Mungo's Hospital for Magical Maladies and Injuries every patient is sent to the ward that can treat them.
+//! An error that implements [`Triage`] knows which [`Ward`] it belongs in, and that tells retry logic whether
+//! trying again could help: a [`TimeTurner`](crate::time_turner::TimeTurner) that
+//! [turns with triage](crate::time_turner::TimeTurner::turn_triaged) stops as soon as an error can not be cured
+//! by waiting, instead of wasting its remaining turns.
+//!
+//! [`Triage`] is implemented for the errors of std that have an obvious ward, like [`io::Error`](std::io::Error),
+//! and can be derived for error enums and structs with the `macros` feature.
+#![cfg_attr(feature = "macros", doc = "```")]
+#![cfg_attr(not(feature = "macros"), doc = "```ignore")]
+//! use code_spells::st_mungos::{Triage, Ward};
+//!
+//! #[derive(Debug, Triage)]
+//! #[ward(Permanent)]
+//! enum PatientError {
+//!     #[ward(Transient)]
+//!     HealerBusy,
+//!     #[ward(SpellDamage)]
+//!     Backfired { spell: &'static str },
+//!     UnknownCurse(String),
+//!     #[ward(inherit)]
+//!     Cursed(std::io::Error),
+//! }
+//!
+//! assert_eq!(PatientError::HealerBusy.ward(), Ward::Transient);
+//! assert_eq!(PatientError::UnknownCurse("Cruciatus".into()).ward(), Ward::Permanent);
+//! assert!(!PatientError::Backfired { spell: "Obliviate" }.ward().is_worth_retrying());
+//! let necklace = std::io::Error::from(std::io::ErrorKind::TimedOut);
+//! assert!(PatientError::Cursed(necklace).ward().is_worth_retrying());
+//! ```
+
+use core::fmt;
+use core::num::{ParseFloatError, ParseIntError, TryFromIntError};
+use core::str::{ParseBoolError, Utf8Error};
+use std::io;
+use std::string::FromUtf8Error;
+use std::sync::mpsc::{RecvError, RecvTimeoutError, TryRecvError};
+
+/// Derives [`Triage`](trait@Triage) for an enum or a struct, with the `macros` feature.
+///
+/// Every variant of an enum is sent to the ward named by its `#[ward(...)]` attribute, or by the attribute
+/// on the enum itself if it has none. A struct is sent to the ward named by its attribute.
+/// The wards are `Transient`, `Permanent`, `SpellDamage` and `ArtifactAccident`,
+/// and `#[ward(inherit)]` sends a variant or struct to the ward of its first field, which must implement `Triage`.
+/// Generic types are not supported.
+#[cfg(feature = "macros")]
+pub use code_spells_macros::Triage;
+
+/// The ward of St Mungo's that an error is treated in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Ward {
+    /// The error is passing, like a timeout or a busy resource, and trying again later may succeed.
+    Transient,
+    /// The error will happen again every time, like invalid input or a missing file.
+    Permanent,
+    /// The error is a bug in the program, like a broken invariant. Trying again does not help.
+    SpellDamage,
+    /// An input or output operation failed for a reason that is not known to be passing or permanent.
+    /// These are worth trying again, since the outside world can change.
+    ArtifactAccident,
+}
+
+impl Ward {
+    /// Returns whether an error in this ward might go away if the work is tried again,
+    /// which is the case for [`Transient`](Ward::Transient) errors and [`ArtifactAccident`](Ward::ArtifactAccident)s.
+    pub const fn is_worth_retrying(self) -> bool {
+        matches!(self, Self::Transient | Self::ArtifactAccident)
+    }
+}
+
+impl fmt::Display for Ward {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ward = match self {
+            Self::Transient => "the ward for passing ailments",
+            Self::Permanent => "the Janus Thickey ward for long-term residents",
+            Self::SpellDamage => "the Spell Damage ward",
+            Self::ArtifactAccident => "the Artefact Accidents ward",
+        };
+        f.write_str(ward)
+    }
+}
+
+/// An error that knows which [`Ward`] it should be treated in.
+pub trait Triage {
+    /// Returns the ward that the error should be treated in.
+    fn ward(&self) -> Ward;
+}
+
+impl Triage for Ward {
+    fn ward(&self) -> Ward {
+        *self
+    }
+}
+
+impl<T: Triage + ?Sized> Triage for &T {
+    fn ward(&self) -> Ward {
+        (**self).ward()
+    }
+}
+
+impl<T: Triage + ?Sized> Triage for Box<T> {
+    fn ward(&self) -> Ward {
+        (**self).ward()
+    }
+}
+
+impl Triage for io::ErrorKind {
+    fn ward(&self) -> Ward {
+        use io::ErrorKind::*;
+        match self {
+            Interrupted | WouldBlock | TimedOut | ConnectionRefused | ConnectionReset
+            | ConnectionAborted | NotConnected | BrokenPipe | AddrInUse | AddrNotAvailable
+            | OutOfMemory => Ward::Transient,
+            NotFound | PermissionDenied | AlreadyExists | InvalidInput | InvalidData
+            | Unsupported | UnexpectedEof => Ward::Permanent,
+            _ => Ward::ArtifactAccident,
+        }
+    }
+}
+
+impl Triage for io::Error {
+    fn ward(&self) -> Ward {
+        self.kind().ward()
+    }
+}
+
+impl Triage for RecvTimeoutError {
+    fn ward(&self) -> Ward {
+        match self {
+            Self::Timeout => Ward::Transient,
+            Self::Disconnected => Ward::Permanent,
+        }
+    }
+}
+
+impl Triage for TryRecvError {
+    fn ward(&self) -> Ward {
+        match self {
+            Self::Empty => Ward::Transient,
+            Self::Disconnected => Ward::Permanent,
+        }
+    }
+}
+
+/// Implements [`Triage`] for errors that always end up in the same ward.
+macro_rules! admit {
+    ($ward:ident: $($error:ty),+ $(,)?) => {
+        $(
+            impl Triage for $error {
+                fn ward(&self) -> Ward {
+                    Ward::$ward
+                }
+            }
+        )+
+    };
+}
+
+admit!(
+    Permanent: ParseIntError,
+    ParseFloatError,
+    ParseBoolError,
+    TryFromIntError,
+    Utf8Error,
+    FromUtf8Error,
+    RecvError,
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::st_mungos;
+
+    #[test]
+    fn practice_st_mungos() {
+        let timeout = io::Error::from(io::ErrorKind::TimedOut);
+        assert_eq!(st_mungos!(timeout), Ward::Transient);
+        assert_eq!(st_mungos!("x".parse::<u8>().unwrap_err()), Ward::Permanent);
+        assert_eq!(st_mungos!(RecvTimeoutError::Timeout), Ward::Transient);
+        let boxed: Box<dyn Triage> = Box::new(Ward::SpellDamage);
+        assert_eq!(st_mungos!(boxed), Ward::SpellDamage);
+        assert_eq!(Ward::SpellDamage.to_string(), "the Spell Damage ward");
+    }
+
+    #[test]
+    fn only_some_wards_are_worth_retrying() {
+        assert!(Ward::Transient.is_worth_retrying());
+        assert!(Ward::ArtifactAccident.is_worth_retrying());
+        assert!(!Ward::Permanent.is_worth_retrying());
+        assert!(!Ward::SpellDamage.is_worth_retrying());
+        assert_eq!(io::ErrorKind::NotFound.ward(), Ward::Permanent);
+        assert_eq!(io::ErrorKind::Other.ward(), Ward::ArtifactAccident);
+    }
+
+    #[cfg(feature = "macros")]
+    #[test]
+    fn triage_can_be_derived() {
+        #[derive(Debug, Triage)]
+        #[ward(inherit)]
+        struct Relapse(io::Error);
+
+        #[derive(Debug, Triage)]
+        #[ward(SpellDamage)]
+        struct Splinched {
+            _limb: &'static str,
+        }
+
+        #[derive(Debug, Triage)]
+        #[repr(u8)]
+        enum Admission {
+            #[ward(Transient)]
+            Queue,
+            #[ward(inherit)]
+            Relapse { relapse: Relapse, _since: u8 },
+            #[ward(inherit)]
+            Splinched(Splinched),
+            #[ward(Permanent)]
+            Lycanthropy = 4,
+        }
+
+        let relapse = Relapse(io::Error::from(io::ErrorKind::BrokenPipe));
+        assert_eq!(relapse.ward(), Ward::Transient);
+        assert_eq!(
+            Admission::Relapse { relapse, _since: 1 }.ward(),
+            Ward::Transient
+        );
+        assert_eq!(Admission::Queue.ward(), Ward::Transient);
+        let splinched = Admission::Splinched(Splinched { _limb: "eyebrow" });
+        assert_eq!(splinched.ward(), Ward::SpellDamage);
+        assert_eq!(Admission::Lycanthropy.ward(), Ward::Permanent);
+    }
+}
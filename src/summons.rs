@@ -0,0 +1,183 @@
+//! A registry of trait object implementations, summoned with [`accio_trait!`](crate::accio_trait).
+//!
+//! Implementations answer the summons of a trait by being [`register`]ed under it,
+//! either by hand or with the `#[answers_summons(Trait)]` attribute from the `macros` feature.
+//! ```
+//! # use code_spells::{accio_trait, summons};
+//! trait Greeter {
+//!     fn greet(&self) -> String;
+//! }
+//!
+//! struct Hagrid;
+//! impl Greeter for Hagrid {
+//!     fn greet(&self) -> String {
+//!         String::from("Yer a wizard, Harry.")
+//!     }
+//! }
+//!
+//! summons::register::<dyn Greeter>("hagrid", || Box::new(Hagrid));
+//!
+//! let greeters = accio_trait!(dyn Greeter);
+//! assert_eq!(greeters.len(), 1);
+//! let hagrid = accio_trait!(dyn Greeter, "hagrid").unwrap();
+//! assert_eq!(hagrid.greet(), "Yer a wizard, Harry.");
+//! ```
+
+use core::any::{Any, TypeId};
+use std::sync::{Mutex, MutexGuard};
+
+struct Answer {
+    summoned: TypeId,
+    name: &'static str,
+    // Always a `fn() -> Box<T>` where `T` is the type with the id `summoned`.
+    summon: Box<dyn Any + Send>,
+}
+
+static REGISTRY: Mutex<Vec<Answer>> = Mutex::new(Vec::new());
+
+fn registry() -> MutexGuard<'static, Vec<Answer>> {
+    // The registry is never left in an inconsistent state, so a poisoned lock is fine to use.
+    REGISTRY
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+/// Registers a constructor for an implementation of `T` under the given name,
+/// so that it can be summoned with [`accio_trait!`](crate::accio_trait).
+/// `T` is usually a trait object type such as `dyn Greeter`.
+///
+/// Registering a second implementation with the same name for the same `T` replaces the first.
+pub fn register<T: ?Sized + 'static>(name: &'static str, summon: fn() -> Box<T>) {
+    let summoned = TypeId::of::<T>();
+    let mut registry = registry();
+    registry.retain(|answer| answer.summoned != summoned || answer.name != name);
+    registry.push(Answer {
+        summoned,
+        name,
+        summon: Box::new(summon),
+    });
+}
+
+/// Summons every registered implementation of `T`, in the order they were registered.
+pub fn summon_all<T: ?Sized + 'static>() -> Vec<Box<T>> {
+    summoners::<T>()
+        .into_iter()
+        .map(|(_, summon)| summon())
+        .collect()
+}
+
+/// Summons the implementation of `T` that was registered under the given name, if there is one.
+pub fn summon<T: ?Sized + 'static>(name: &str) -> Option<Box<T>> {
+    summoners::<T>()
+        .into_iter()
+        .find(|(registered, _)| *registered == name)
+        .map(|(_, summon)| summon())
+}
+
+/// Returns the names of every registered implementation of `T`, in the order they were registered.
+pub fn names<T: ?Sized + 'static>() -> Vec<&'static str> {
+    summoners::<T>().into_iter().map(|(name, _)| name).collect()
+}
+
+/// The name and constructor of a registered implementation of `T`.
+type Summoner<T> = (&'static str, fn() -> Box<T>);
+
+// The constructors are copied out of the registry so that they can register things themselves.
+fn summoners<T: ?Sized + 'static>() -> Vec<Summoner<T>> {
+    let summoned = TypeId::of::<T>();
+    registry()
+        .iter()
+        .filter(|answer| answer.summoned == summoned)
+        .filter_map(|answer| {
+            answer
+                .summon
+                .downcast_ref::<fn() -> Box<T>>()
+                .map(|summon| (answer.name, *summon))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::accio_trait;
+
+    trait Creature {
+        fn legs(&self) -> u8;
+    }
+
+    struct Owl;
+    impl Creature for Owl {
+        fn legs(&self) -> u8 {
+            2
+        }
+    }
+
+    struct Acromantula;
+    impl Creature for Acromantula {
+        fn legs(&self) -> u8 {
+            8
+        }
+    }
+
+    trait Unregistered {}
+
+    #[test]
+    fn practice_accio_trait() {
+        register::<dyn Creature>("owl", || Box::new(Owl));
+        register::<dyn Creature>("acromantula", || Box::new(Acromantula));
+        register::<dyn Creature>("owl", || Box::new(Owl));
+        assert_eq!(names::<dyn Creature>(), ["acromantula", "owl"]);
+        let legs: Vec<u8> = accio_trait!(dyn Creature)
+            .iter()
+            .map(|c| c.legs())
+            .collect();
+        assert_eq!(legs, [8, 2]);
+        assert_eq!(accio_trait!(dyn Creature, "owl").unwrap().legs(), 2);
+        assert!(accio_trait!(dyn Creature, "hippogriff").is_none());
+        assert!(accio_trait!(dyn Unregistered).is_empty());
+    }
+
+    #[cfg(feature = "macros")]
+    #[test]
+    fn practice_answers_summons() {
+        trait Patronus {
+            fn form(&self) -> &'static str;
+        }
+
+        #[derive(Default)]
+        struct Stag;
+        #[crate::answers_summons(Patronus)]
+        impl Patronus for Stag {
+            fn form(&self) -> &'static str {
+                "stag"
+            }
+        }
+
+        #[derive(Default)]
+        struct Otter;
+        #[crate::answers_summons(Patronus, name = "hermione")]
+        impl Patronus for Otter {
+            fn form(&self) -> &'static str {
+                "otter"
+            }
+        }
+
+        #[derive(Default)]
+        struct Hare;
+        #[crate::answers_summons(Patronus, crate = crate, name = "luna")]
+        impl Patronus for Hare {
+            fn form(&self) -> &'static str {
+                "hare"
+            }
+        }
+
+        let mut names = names::<dyn Patronus>();
+        names.sort_unstable();
+        assert_eq!(names, ["Stag", "hermione", "luna"]);
+        assert_eq!(
+            accio_trait!(dyn Patronus, "hermione").unwrap().form(),
+            "otter"
+        );
+    }
+}
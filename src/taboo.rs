@@ -0,0 +1,105 @@
+//! Strings that are hashed at compile time, cast with [`taboo_hash!`](crate::taboo_hash!)
+//! and [`taboo_match!`](crate::taboo_match!).
+//!
+//! Some names must not be spoken. [`taboo_hash!`](crate::taboo_hash!) hashes a string literal while the program is compiled,
+//! and [`taboo_hash`] computes the same hash at runtime, so a string that is received at runtime can be
+//! dispatched on by its hash without the strings it is compared to ever being stored in the program.
+//! [`taboo_match!`](crate::taboo_match!) builds such a dispatch table, and fails to compile if two of its names have the same hash.
+//!
+//! The hash is the 64-bit FNV-1a hash of the UTF-8 bytes of the string. It is fast and stable across platforms
+//! and versions, but it is not cryptographic: it hides the names from a casual reader of the binary,
+//! not from someone who guesses them and hashes the guesses.
+//! ```
+//! # use code_spells::{taboo_hash, taboo_match};
+//! use code_spells::taboo::taboo_hash;
+//!
+//! const TABOO: u64 = taboo_hash!("Voldemort");
+//! assert_eq!(taboo_hash("Voldemort"), TABOO);
+//!
+//! let spoken = String::from("Voldemort");
+//! let snatchers = taboo_match!(&spoken, {
+//!     "Voldemort" => 6,
+//!     "Tom Riddle" => 1,
+//!     _ => 0,
+//! });
+//! assert_eq!(snatchers, 6);
+//! ```
+
+/// The 64-bit FNV-1a offset basis.
+pub(crate) const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+/// The 64-bit FNV-1a prime.
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// Continues the 64-bit FNV-1a hash `hash` with `bytes`.
+pub(crate) const fn fnv1a(mut hash: u64, bytes: &[u8]) -> u64 {
+    let mut i = 0;
+    while i < bytes.len() {
+        hash ^= bytes[i] as u64;
+        hash = hash.wrapping_mul(FNV_PRIME);
+        i += 1;
+    }
+    hash
+}
+
+/// Returns the 64-bit FNV-1a hash of `name`, which is the hash that [`taboo_hash!`](crate::taboo_hash!) computes at compile time.
+pub const fn taboo_hash(name: &str) -> u64 {
+    fnv1a(FNV_OFFSET_BASIS, name.as_bytes())
+}
+
+/// Panics if two of the hashes are equal. [`taboo_match!`](crate::taboo_match!) calls this at compile time.
+/// # Panics
+/// Panics if two of the hashes are equal.
+pub const fn forbid_collisions(hashes: &[u64]) {
+    let mut i = 0;
+    while i < hashes.len() {
+        let mut j = i + 1;
+        while j < hashes.len() {
+            if hashes[i] == hashes[j] {
+                panic!("two names of a taboo_match! have the same hash, or the same name is listed twice");
+            }
+            j += 1;
+        }
+        i += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{taboo_hash, taboo_match};
+
+    fn snatch(spoken: &str) -> &'static str {
+        taboo_match!(spoken, {
+            "Voldemort" => "snatched",
+            "You-Know-Who" => "frowned upon",
+            "He-Who-Must-Not-Be-Named" => "frowned upon",
+            _ => "safe",
+        })
+    }
+
+    #[test]
+    fn practice_taboo() {
+        const VOLDEMORT: u64 = taboo_hash!("Voldemort");
+        assert_eq!(VOLDEMORT, taboo_hash("Voldemort"));
+        assert_ne!(VOLDEMORT, taboo_hash("voldemort"));
+        assert_eq!(snatch("Voldemort"), "snatched");
+        assert_eq!(snatch("He-Who-Must-Not-Be-Named"), "frowned upon");
+        assert_eq!(snatch("Harry"), "safe");
+        assert_eq!(taboo_match!("Dumbledore", { _ => 1 }), 1);
+    }
+
+    #[test]
+    fn the_hash_is_fnv1a() {
+        assert_eq!(fnv1a(FNV_OFFSET_BASIS, b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a(FNV_OFFSET_BASIS, b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(fnv1a(FNV_OFFSET_BASIS, b"foobar"), 0x8594_4171_f739_67e8);
+        assert_eq!(taboo_hash!("foobar"), 0x8594_4171_f739_67e8);
+        assert_eq!(taboo_hash("🐍"), fnv1a(FNV_OFFSET_BASIS, "🐍".as_bytes()));
+    }
+
+    #[test]
+    #[should_panic(expected = "have the same hash")]
+    fn collisions_are_forbidden() {
+        forbid_collisions(&[taboo_hash("Tom"), taboo_hash("Riddle"), taboo_hash("Tom")]);
+    }
+}
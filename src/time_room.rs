@@ -0,0 +1,345 @@
+//! Virtual time for tests, entered with [`time_room!`](crate::time_room!).
+//!
+//! Time does not pass in the room where time is kept unless someone moves it along.
+//! Every spell that waits or reads the time goes through the [`Clock`] of the current thread:
+//! [`petrificus_totalus!`](crate::petrificus_totalus!), the [`TimeTurner`](crate::time_turner::TimeTurner),
+//! [`Impedimenta`](crate::impedimenta::Impedimenta) and [`Bogies`](crate::bogies::Bogies).
+//! Outside of a time room that is the [`RealClock`]. Inside one it is a [`VirtualClock`],
+//! where [sleeping](sleep) returns at once after moving the clock forward, and the clock can be [advanced](advance)
+//! by hand, so that tests of timing behaviour are fast and deterministic.
+//! A room can also be [entered](enter_with) with any other [`Clock`].
+//!
+//! A time room only holds the thread that entered it. Spells that wait on a thread of their own,
+//! like [`Portkey`](crate::portkey::Portkey) and [`arrest`](crate::arresto_momentum::arrest), keep real time.
+//! Other threads can [join](enter_with) the same virtual clock.
+//! ```
+//! use code_spells::{petrificus_totalus, time_room};
+//! use std::time::Duration;
+//!
+//! let waited = time_room!({
+//!     let start = time_room::now();
+//!     petrificus_totalus!(Duration::from_secs(60 * 60));
+//!     time_room::advance(Duration::from_secs(5));
+//!     time_room::now() - start
+//! });
+//! assert_eq!(waited, Duration::from_secs(3605));
+//! ```
+
+use core::cell::RefCell;
+use core::fmt;
+use core::marker::PhantomData;
+use core::time::Duration;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::thread;
+use std::time::Instant;
+
+/// A source of time that spells can read and wait on.
+///
+/// A clock can be shared by the time rooms of several threads, so it must be `Send` and `Sync`.
+/// # Example
+/// ```
+/// use code_spells::{petrificus_totalus, time_room};
+/// use code_spells::time_room::{Clock, RealClock};
+/// use std::sync::{Arc, Mutex};
+/// use std::time::{Duration, Instant};
+///
+/// /// A clock that keeps real time, but never waits.
+/// #[derive(Default)]
+/// struct Insomniac {
+///     naps: Mutex<Vec<Duration>>,
+/// }
+///
+/// impl Clock for Insomniac {
+///     fn now(&self) -> Instant {
+///         RealClock.now()
+///     }
+///
+///     fn sleep(&self, duration: Duration) {
+///         self.naps.lock().unwrap().push(duration);
+///     }
+/// }
+///
+/// let insomniac = Arc::new(Insomniac::default());
+/// time_room!(insomniac, { petrificus_totalus!(Duration::from_secs(8 * 60 * 60)) });
+/// assert_eq!(*insomniac.naps.lock().unwrap(), [Duration::from_secs(8 * 60 * 60)]);
+/// ```
+pub trait Clock: Send + Sync {
+    /// Returns the current instant of the clock.
+    fn now(&self) -> Instant;
+
+    /// Waits until `duration` has passed on the clock.
+    fn sleep(&self, duration: Duration);
+
+    /// Moves the clock forward by `duration`.
+    /// # Panics
+    /// Unless the clock implements this, it can not be moved by hand and panics.
+    fn advance(&self, duration: Duration) {
+        let _ = duration;
+        panic!("this clock can not be moved by hand");
+    }
+}
+
+/// The clock of the real world, which reads [`Instant::now`] and waits with [`thread::sleep`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct RealClock;
+
+impl Clock for RealClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        thread::sleep(duration);
+    }
+}
+
+/// A clock that only moves when it is advanced or slept on. Sleeping on it returns at once.
+pub struct VirtualClock {
+    origin: Instant,
+    elapsed: Mutex<Duration>,
+}
+
+impl VirtualClock {
+    /// Creates a virtual clock that starts at the current real instant.
+    pub fn new() -> Self {
+        Self {
+            origin: Instant::now(),
+            elapsed: Mutex::new(Duration::ZERO),
+        }
+    }
+
+    /// Moves the clock forward by `duration`.
+    pub fn advance(&self, duration: Duration) {
+        *self.lock() += duration;
+    }
+
+    /// Returns how far the clock has moved since it was created.
+    pub fn elapsed(&self) -> Duration {
+        *self.lock()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Duration> {
+        self.elapsed.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Clock for VirtualClock {
+    fn now(&self) -> Instant {
+        self.origin + self.elapsed()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        self.advance(duration);
+    }
+
+    fn advance(&self, duration: Duration) {
+        VirtualClock::advance(self, duration);
+    }
+}
+
+impl Default for VirtualClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for VirtualClock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("VirtualClock")
+            .field("elapsed", &self.elapsed())
+            .finish_non_exhaustive()
+    }
+}
+
+thread_local! {
+    // The clocks of the time rooms the current thread is in, innermost last.
+    static ROOMS: RefCell<Vec<Arc<dyn Clock>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// A guard that keeps the current thread in a time room. Dropping it leaves the room,
+/// back to the room the thread was in before, if any.
+///
+/// Rooms must be left in the opposite order they were entered.
+pub struct TimeRoom<C: ?Sized = VirtualClock> {
+    clock: Arc<C>,
+    // The number of rooms the thread is in while it is in this one.
+    depth: usize,
+    // The room belongs to the thread that entered it.
+    _thread: PhantomData<*const ()>,
+}
+
+impl<C: ?Sized> TimeRoom<C> {
+    /// Returns the clock of the room, which other threads can [join](enter_with).
+    pub fn clock(&self) -> &Arc<C> {
+        &self.clock
+    }
+}
+
+impl<C: ?Sized> Drop for TimeRoom<C> {
+    /// # Panics
+    /// Panics if the thread has entered another room since this one, which it has not left yet.
+    fn drop(&mut self) {
+        let innermost = ROOMS.with(|rooms| {
+            let mut rooms = rooms.borrow_mut();
+            let innermost = rooms.len() == self.depth;
+            if innermost {
+                rooms.pop();
+            }
+            innermost
+        });
+        if !innermost && !thread::panicking() {
+            panic!("time rooms must be left in the opposite order they were entered");
+        }
+    }
+}
+
+impl<C: ?Sized + fmt::Debug> fmt::Debug for TimeRoom<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TimeRoom")
+            .field("clock", &self.clock)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Enters a time room with a new [`VirtualClock`] on the current thread.
+pub fn enter() -> TimeRoom {
+    enter_with(Arc::new(VirtualClock::new()))
+}
+
+/// Enters a time room with an existing clock on the current thread, e.g. the clock of a room on another thread.
+pub fn enter_with<C: Clock + 'static>(clock: Arc<C>) -> TimeRoom<C> {
+    let depth = ROOMS.with(|rooms| {
+        let mut rooms = rooms.borrow_mut();
+        rooms.push(Arc::clone(&clock) as Arc<dyn Clock>);
+        rooms.len()
+    });
+    TimeRoom {
+        clock,
+        depth,
+        _thread: PhantomData,
+    }
+}
+
+/// Returns the clock of the time room that the current thread is in, if it is in one.
+pub fn current() -> Option<Arc<dyn Clock>> {
+    ROOMS.with(|rooms| rooms.borrow().last().cloned())
+}
+
+/// Returns the current instant of the clock of the current thread.
+pub fn now() -> Instant {
+    current().map_or_else(|| RealClock.now(), |clock| clock.now())
+}
+
+/// Waits until `duration` has passed on the clock of the current thread.
+pub fn sleep(duration: Duration) {
+    match current() {
+        Some(clock) => clock.sleep(duration),
+        None => RealClock.sleep(duration),
+    }
+}
+
+/// Moves the clock of the current thread forward by `duration`.
+/// # Panics
+/// Panics if the current thread is not in a time room, since real time can not be advanced,
+/// or if the clock of the room can not be [advanced](Clock::advance).
+pub fn advance(duration: Duration) {
+    current()
+        .expect("time_room::advance can only move time inside a time room")
+        .advance(duration);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::impedimenta::Impedimenta;
+    use crate::time_turner::TimeTurner;
+    use crate::{petrificus_totalus, time_room};
+
+    #[test]
+    fn practice_time_room() {
+        let real_start = Instant::now();
+        let (virtual_start, slept) = time_room!({
+            let start = now();
+            petrificus_totalus!(Duration::from_secs(24 * 60 * 60));
+            (start, now() - start)
+        });
+        assert_eq!(slept, Duration::from_secs(24 * 60 * 60));
+        assert!(virtual_start >= real_start);
+        assert!(current().is_none());
+        assert!(real_start.elapsed() < Duration::from_secs(60 * 60));
+    }
+
+    #[test]
+    fn spells_wait_in_virtual_time() {
+        let _room = enter();
+        let jinx = Impedimenta::new(2, Duration::from_secs(10));
+        assert!(jinx.try_pass());
+        assert!(jinx.try_pass());
+        assert!(!jinx.try_pass());
+        advance(Duration::from_secs(5));
+        assert!(jinx.try_pass());
+        assert!(!jinx.try_pass());
+
+        let start = now();
+        let mut attempts = 0;
+        let result: Result<(), u32> = TimeTurner::new()
+            .attempts(4)
+            .backoff_exponential(Duration::from_secs(1), 10)
+            .turn(|| {
+                attempts += 1;
+                Err(attempts)
+            });
+        assert_eq!(result, Err(4));
+        assert_eq!(now() - start, Duration::from_secs(1 + 10 + 100));
+    }
+
+    #[test]
+    fn rooms_nest_and_can_be_shared() {
+        let outer = enter();
+        advance(Duration::from_secs(1));
+        {
+            let inner = enter();
+            advance(Duration::from_secs(2));
+            assert_eq!(inner.clock().elapsed(), Duration::from_secs(2));
+        }
+        assert_eq!(outer.clock().elapsed(), Duration::from_secs(1));
+
+        let clock = Arc::clone(outer.clock());
+        thread::spawn(move || {
+            let _room = enter_with(clock);
+            sleep(Duration::from_secs(3));
+        })
+        .join()
+        .unwrap();
+        assert_eq!(outer.clock().elapsed(), Duration::from_secs(4));
+        assert_eq!(
+            format!("{:?}", outer),
+            "TimeRoom { clock: VirtualClock { elapsed: 4s, .. }, .. }"
+        );
+        drop(outer);
+        assert!(current().is_none());
+    }
+
+    #[test]
+    fn rooms_keep_any_clock() {
+        let _room = enter_with(Arc::new(RealClock));
+        let start = Instant::now();
+        assert!(now() >= start);
+        let advanced = std::panic::catch_unwind(|| advance(Duration::from_secs(1)));
+        assert!(advanced.is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "opposite order they were entered")]
+    fn rooms_are_left_in_order() {
+        let outer = enter();
+        let _inner = enter();
+        drop(outer);
+    }
+
+    #[test]
+    #[should_panic(expected = "only move time inside a time room")]
+    fn real_time_can_not_be_advanced() {
+        advance(Duration::from_secs(1));
+    }
+}
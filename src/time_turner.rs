@@ -0,0 +1,271 @@
+//! Retrying fallible work, cast with [`time_turner!`](crate::time_turner!).
+//!
+//! Every turn of a [`TimeTurner`] goes back in time to try the work again, until it succeeds
+//! or the time turner runs out of turns. Between turns it can wait a fixed or exponentially growing delay,
+//! and with the `rand` feature the delays can be jittered so that many retrying threads do not all
+//! come back at the same time.
+//! ```
+//! # use code_spells::time_turner::TimeTurner;
+//! use std::time::Duration;
+//!
+//! let mut hippogriffs_saved = 0;
+//! let rescue = TimeTurner::new()
+//!     .attempts(5)
+//!     .backoff_exponential(Duration::from_millis(1), 2)
+//!     .turn(|| {
+//!         hippogriffs_saved += 1;
+//!         if hippogriffs_saved < 3 {
+//!             Err("Buckbeak was executed")
+//!         } else {
+//!             Ok("Buckbeak escaped")
+//!         }
+//!     });
+//! assert_eq!(rescue, Ok("Buckbeak escaped"));
+//! assert_eq!(hippogriffs_saved, 3);
+//! ```
+
+use core::time::Duration;
+
+use crate::st_mungos::Triage;
+
+#[cfg(feature = "rand")]
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+/// A configurable retry strategy.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimeTurner {
+    attempts: u32,
+    delay: Duration,
+    factor: u32,
+    max_delay: Duration,
+    #[cfg(feature = "rand")]
+    jitter: f64,
+    #[cfg(feature = "rand")]
+    seed: Option<u64>,
+}
+
+impl TimeTurner {
+    /// The default number of attempts.
+    pub const DEFAULT_ATTEMPTS: u32 = 3;
+
+    /// Creates a time turner that makes the default number of attempts without waiting between them.
+    pub const fn new() -> Self {
+        Self {
+            attempts: Self::DEFAULT_ATTEMPTS,
+            delay: Duration::ZERO,
+            factor: 1,
+            max_delay: Duration::MAX,
+            #[cfg(feature = "rand")]
+            jitter: 0.0,
+            #[cfg(feature = "rand")]
+            seed: None,
+        }
+    }
+
+    /// Sets the number of attempts, including the first one. A time turner always makes at least one attempt.
+    pub const fn attempts(mut self, attempts: u32) -> Self {
+        self.attempts = if attempts == 0 { 1 } else { attempts };
+        self
+    }
+
+    /// Waits the same `delay` before every retry.
+    pub const fn delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self.factor = 1;
+        self
+    }
+
+    /// Waits `initial` before the first retry, and `factor` times longer before every retry after that.
+    pub const fn backoff_exponential(mut self, initial: Duration, factor: u32) -> Self {
+        self.delay = initial;
+        self.factor = factor;
+        self
+    }
+
+    /// Caps the delay between two attempts.
+    pub const fn max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// Shortens every delay by a random fraction of at most `jitter`, so that a jitter of 0.5 waits between
+    /// half and all of each delay. The jitter is clamped to `0.0..=1.0`.
+    #[cfg(feature = "rand")]
+    pub fn jitter(mut self, jitter: f64) -> Self {
+        self.jitter = if jitter.is_nan() {
+            0.0
+        } else {
+            jitter.clamp(0.0, 1.0)
+        };
+        self
+    }
+
+    /// Seeds the random number generator used for the jitter,
+    /// so that the same delays are drawn every time the time turner is turned.
+    #[cfg(feature = "rand")]
+    pub const fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Returns the delays before each retry, before any jitter.
+    pub fn delays(&self) -> impl Iterator<Item = Duration> {
+        let (factor, max_delay) = (self.factor, self.max_delay);
+        let mut delay = self.delay;
+        (1..self.attempts).map(move |_| {
+            let current = delay.min(max_delay);
+            delay = delay.saturating_mul(factor);
+            current
+        })
+    }
+
+    /// Runs `work` until it succeeds or the time turner runs out of attempts, waiting between the attempts.
+    /// # Errors
+    /// Returns the error of the last attempt if every attempt failed.
+    pub fn turn<T, E>(&self, work: impl FnMut() -> Result<T, E>) -> Result<T, E> {
+        self.turn_while(work, |_| true)
+    }
+
+    /// Runs `work` like [`turn`](Self::turn), but stops as soon as an attempt fails with an error
+    /// that is not [worth retrying](crate::st_mungos::Ward::is_worth_retrying) according to its [`Triage`].
+    /// # Errors
+    /// Returns the error of the last attempt if every attempt failed, or the first error that is not worth retrying.
+    pub fn turn_triaged<T, E: Triage>(&self, work: impl FnMut() -> Result<T, E>) -> Result<T, E> {
+        self.turn_while(work, |error| error.ward().is_worth_retrying())
+    }
+
+    fn turn_while<T, E>(
+        &self,
+        mut work: impl FnMut() -> Result<T, E>,
+        worth_retrying: impl Fn(&E) -> bool,
+    ) -> Result<T, E> {
+        #[cfg(feature = "rand")]
+        let mut rng = (self.jitter > 0.0).then(|| {
+            self.seed
+                .map_or_else(StdRng::from_os_rng, StdRng::seed_from_u64)
+        });
+        let mut delays = self.delays();
+        loop {
+            let error = match work() {
+                Ok(value) => return Ok(value),
+                Err(error) => error,
+            };
+            let delay = match delays.next() {
+                Some(delay) if worth_retrying(&error) => delay,
+                _ => return Err(error),
+            };
+            #[cfg(feature = "rand")]
+            let delay = match &mut rng {
+                Some(rng) => delay.mul_f64(1.0 - self.jitter * rng.random::<f64>()),
+                None => delay,
+            };
+            if !delay.is_zero() {
+                crate::time_room::sleep(delay);
+            }
+        }
+    }
+}
+
+impl Default for TimeTurner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::st_mungos::Ward;
+    use crate::time_turner;
+
+    #[test]
+    fn practice_time_turner() {
+        let mut turns = 0;
+        let result: Result<(), u32> = time_turner!(3, || {
+            turns += 1;
+            Err(turns)
+        });
+        assert_eq!(result, Err(3));
+        assert_eq!(turns, 3);
+
+        let mut turns = 0;
+        let result: Result<u32, ()> = time_turner!(5, || {
+            turns += 1;
+            if turns == 2 {
+                Ok(turns)
+            } else {
+                Err(())
+            }
+        });
+        assert_eq!(result, Ok(2));
+        assert_eq!(turns, 2);
+
+        let mut turns = 0;
+        let _ = TimeTurner::new().attempts(0).turn(|| {
+            turns += 1;
+            Err::<(), ()>(())
+        });
+        assert_eq!(turns, 1);
+    }
+
+    #[test]
+    fn a_time_turner_backs_off() {
+        let ms = Duration::from_millis;
+        let turner = TimeTurner::new()
+            .attempts(6)
+            .backoff_exponential(ms(10), 3)
+            .max_delay(ms(200));
+        assert_eq!(
+            turner.delays().collect::<Vec<_>>(),
+            [ms(10), ms(30), ms(90), ms(200), ms(200)]
+        );
+        let fixed = TimeTurner::new().delay(ms(5));
+        assert_eq!(fixed.delays().collect::<Vec<_>>(), [ms(5), ms(5)]);
+        let huge = TimeTurner::new()
+            .attempts(100)
+            .backoff_exponential(Duration::from_secs(1), 10);
+        assert_eq!(huge.delays().last(), Some(Duration::MAX));
+
+        let start = std::time::Instant::now();
+        let _ = TimeTurner::new().delay(ms(10)).turn(|| Err::<(), ()>(()));
+        assert!(start.elapsed() >= ms(20));
+    }
+
+    #[test]
+    fn a_time_turner_only_retries_what_can_be_cured() {
+        let mut turns = 0;
+        let result: Result<(), Ward> = time_turner!(triage 5, || {
+            turns += 1;
+            Err(if turns < 3 {
+                Ward::Transient
+            } else {
+                Ward::SpellDamage
+            })
+        });
+        assert_eq!(result, Err(Ward::SpellDamage));
+        assert_eq!(turns, 3);
+
+        let mut turns = 0;
+        let result = TimeTurner::new().attempts(4).turn_triaged(|| {
+            turns += 1;
+            Err::<(), _>(std::io::Error::from(std::io::ErrorKind::Interrupted))
+        });
+        assert_eq!(result.unwrap_err().kind(), std::io::ErrorKind::Interrupted);
+        assert_eq!(turns, 4);
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn a_time_turner_jitters() {
+        let ms = Duration::from_millis;
+        let turner = TimeTurner::new()
+            .attempts(4)
+            .delay(ms(20))
+            .jitter(0.5)
+            .seed(1994);
+        assert_eq!(turner, turner.jitter(f64::NAN).jitter(0.5));
+        let start = std::time::Instant::now();
+        assert_eq!(turner.turn(|| Err::<(), u8>(7)), Err(7));
+        assert!(start.elapsed() >= ms(30));
+    }
+}
@@ -0,0 +1,265 @@
+//! Values that tell whoever is interested when they are dropped, cast with [`veil_of_death!`](crate::veil_of_death!).
+//!
+//! Whatever falls through the veil in the Department of Mysteries is gone for good, but the ones left behind
+//! can at least be told about it. A [`Veil`] wraps a value and counts how often it is accessed.
+//! Interested parties register with [`on_passing`](Veil::on_passing) to have a callback run,
+//! or with [`watch`](Veil::watch) to receive a message on a channel, when the value is dropped.
+//! Both are handed a [`Passing`] that sums up the life of the value.
+//!
+//! The age of the value is measured with [`time_room`](mod@crate::time_room), so it is deterministic inside a time room.
+//! ```
+//! # use code_spells::veil_of_death;
+//! let sirius = veil_of_death!(String::from("Sirius Black"));
+//! let news = sirius.watch();
+//! sirius.on_passing(|passing| println!("{}", passing));
+//! assert_eq!(sirius.len(), 12);
+//! drop(sirius);
+//!
+//! let passing = news.recv().unwrap();
+//! assert_eq!(passing.accesses(), 1);
+//! assert!(passing.type_name().ends_with("String"));
+//! ```
+
+use core::fmt;
+use core::ops::{Deref, DerefMut};
+use core::sync::atomic::{AtomicU64, Ordering};
+use core::time::Duration;
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::Instant;
+
+use crate::time_room;
+
+/// Is told about the passing of a value.
+type Observer = Box<dyn FnOnce(&Passing) + Send>;
+
+/// A summary of the life of a value that passed through a [`Veil`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Passing {
+    type_name: &'static str,
+    age: Duration,
+    accesses: u64,
+}
+
+impl Passing {
+    /// Returns the name of the type of the value, as given by [`core::any::type_name`].
+    pub const fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    /// Returns how long the value lived behind the veil.
+    pub const fn age(&self) -> Duration {
+        self.age
+    }
+
+    /// Returns how many times the value was accessed through the veil.
+    pub const fn accesses(&self) -> u64 {
+        self.accesses
+    }
+}
+
+impl fmt::Display for Passing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "a {} passed through the veil after {:?}, having been accessed {} times",
+            self.type_name, self.age, self.accesses
+        )
+    }
+}
+
+/// A wrapper that tells its observers when its value is dropped.
+///
+/// Every [`deref`](Deref::deref) and [`deref_mut`](DerefMut::deref_mut) counts as an access.
+pub struct Veil<T> {
+    value: Option<T>,
+    born: Instant,
+    accesses: AtomicU64,
+    observers: Mutex<Vec<Observer>>,
+}
+
+impl<T> Veil<T> {
+    /// Puts a value behind a veil that nobody observes yet.
+    pub fn new(value: T) -> Self {
+        Self {
+            value: Some(value),
+            born: time_room::now(),
+            accesses: AtomicU64::new(0),
+            observers: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Registers a callback that is run with the [`Passing`] of the value, right after it is dropped.
+    ///
+    /// The callbacks run on the thread that drops the value, in the order they were registered.
+    pub fn on_passing(&self, observer: impl FnOnce(&Passing) + Send + 'static) {
+        self.lock().push(Box::new(observer));
+    }
+
+    /// Returns a channel that receives the [`Passing`] of the value, right after it is dropped.
+    ///
+    /// The channel disconnects without a message if the value is taken back with [`into_inner`](Veil::into_inner).
+    pub fn watch(&self) -> Receiver<Passing> {
+        let (sender, receiver) = mpsc::channel();
+        self.on_passing(move |passing| {
+            // Nobody is told if the watcher stopped listening.
+            let _ = sender.send(*passing);
+        });
+        receiver
+    }
+
+    /// Returns how many times the value has been accessed through the veil.
+    pub fn accesses(&self) -> u64 {
+        self.accesses.load(Ordering::Relaxed)
+    }
+
+    /// Returns how long the value has been behind the veil.
+    pub fn age(&self) -> Duration {
+        time_room::now().saturating_duration_since(self.born)
+    }
+
+    /// Takes the value back out of the veil, without telling the observers, who are dropped.
+    pub fn into_inner(mut self) -> T {
+        self.value
+            .take()
+            .expect("the value is behind the veil until it is dropped")
+    }
+
+    /// Returns the value without counting an access.
+    fn value(&self) -> &T {
+        self.value
+            .as_ref()
+            .expect("the value is behind the veil until it is dropped")
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Vec<Observer>> {
+        self.observers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<T> Deref for Veil<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.accesses.fetch_add(1, Ordering::Relaxed);
+        self.value()
+    }
+}
+
+impl<T> DerefMut for Veil<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        *self.accesses.get_mut() += 1;
+        self.value
+            .as_mut()
+            .expect("the value is behind the veil until it is dropped")
+    }
+}
+
+impl<T> Drop for Veil<T> {
+    fn drop(&mut self) {
+        if self.value.take().is_none() {
+            return;
+        }
+        let passing = Passing {
+            type_name: core::any::type_name::<T>(),
+            age: self.age(),
+            accesses: *self.accesses.get_mut(),
+        };
+        let observers = core::mem::take(
+            self.observers
+                .get_mut()
+                .unwrap_or_else(PoisonError::into_inner),
+        );
+        for observer in observers {
+            observer(&passing);
+        }
+    }
+}
+
+impl<T> From<T> for Veil<T> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T: Default> Default for Veil<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Veil<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Veil")
+            .field("value", self.value())
+            .field("accesses", &self.accesses())
+            .field("observers", &self.lock().len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::veil_of_death;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn practice_veil_of_death() {
+        let told = Arc::new(Mutex::new(Vec::new()));
+        let mut cedric = veil_of_death!(vec![1_u8]);
+        for order in ["first", "second"] {
+            let told = Arc::clone(&told);
+            cedric.on_passing(move |passing| told.lock().unwrap().push((order, *passing)));
+        }
+        cedric.push(2);
+        assert_eq!(cedric.len(), 2);
+        assert_eq!(
+            format!("{:?}", cedric),
+            "Veil { value: [1, 2], accesses: 2, observers: 2 }"
+        );
+        drop(cedric);
+        let told = told.lock().unwrap();
+        assert_eq!(told.len(), 2);
+        assert_eq!((told[0].0, told[1].0), ("first", "second"));
+        assert_eq!(told[0].1, told[1].1);
+        assert_eq!(told[0].1.accesses(), 2);
+        assert_eq!(told[0].1.type_name(), "alloc::vec::Vec<u8>");
+    }
+
+    #[test]
+    fn the_age_in_virtual_time() {
+        let _room = time_room::enter();
+        let dobby = Veil::new("a free elf");
+        let news = dobby.watch();
+        time_room::advance(Duration::from_secs(97));
+        assert_eq!(dobby.age(), Duration::from_secs(97));
+        drop(dobby);
+        assert_eq!(
+            news.recv().unwrap().to_string(),
+            "a &str passed through the veil after 97s, having been accessed 0 times"
+        );
+    }
+
+    #[test]
+    fn watching_from_another_thread() {
+        let fred = veil_of_death!(String::from("Fred Weasley"));
+        let news = fred.watch();
+        let mourner = thread::spawn(move || news.recv().unwrap().accesses());
+        assert!(fred.starts_with("Fred"));
+        drop(fred);
+        assert_eq!(mourner.join().unwrap(), 1);
+    }
+
+    #[test]
+    fn a_value_taken_back_does_not_pass() {
+        let stone = Veil::from(3_u32);
+        let news = stone.watch();
+        stone.on_passing(|_| panic!("the resurrection stone brought the value back"));
+        assert_eq!(stone.into_inner(), 3);
+        assert!(news.recv().is_err());
+    }
+}
@@ -0,0 +1,257 @@
+//! Pairs of values that should be identical, cast with [`weasley_twins!`](crate::weasley_twins!).
+//!
+//! Fred and George look the same, until they do not. [`Twins`] holds two values that are supposed to be
+//! equal, like the outputs of an old and a new implementation of the same thing during a migration.
+//! Every access compares the twins, and reports a [`FredAndGeorgeDiverged`] with a line-by-line [`Diff`]
+//! of their [`Debug`](fmt::Debug) output when they differ. The [`Policy`] decides which twin is handed out then,
+//! or whether to panic.
+//!
+//! The reports are written to stderr unless another reporter is given with [`on_divergence`](Twins::on_divergence).
+//! ```
+//! # use code_spells::weasley_twins;
+//! use code_spells::weasley_twins::Policy;
+//!
+//! fn old_joke_count(shop: &str) -> usize { shop.len() }
+//! fn new_joke_count(shop: &str) -> usize { shop.chars().count() }
+//!
+//! let jokes = weasley_twins!(old_joke_count("Weasleys' Wizard Wheezes"), new_joke_count("Weasleys' Wizard Wheezes"));
+//! assert_eq!(*jokes, 24);
+//! assert_eq!(jokes.divergences(), 0);
+//!
+//! let ears = weasley_twins!(2, 1, policy: Policy::George, on_divergence: |report| println!("{}", report));
+//! assert_eq!(*ears.get(), 1);
+//! assert_eq!(ears.divergences(), 1);
+//! ```
+
+use core::fmt;
+use core::ops::Deref;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::examples::Diff;
+
+/// Reports a divergence of the twins.
+type Reporter = Box<dyn Fn(&FredAndGeorgeDiverged) + Send + Sync>;
+
+/// Which twin is handed out when the twins differ.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Policy {
+    /// Hand out Fred, the first twin. Use this while the first twin is the trusted implementation.
+    #[default]
+    Fred,
+    /// Hand out George, the second twin.
+    George,
+    /// Panic with the report.
+    Panic,
+}
+
+/// A report of twins that differ.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FredAndGeorgeDiverged {
+    fred: String,
+    george: String,
+    diff: Diff,
+}
+
+impl FredAndGeorgeDiverged {
+    /// Returns the [`Debug`](fmt::Debug) output of Fred.
+    pub fn fred(&self) -> &str {
+        &self.fred
+    }
+
+    /// Returns the [`Debug`](fmt::Debug) output of George.
+    pub fn george(&self) -> &str {
+        &self.george
+    }
+
+    /// Returns the difference between the pretty-printed twins.
+    pub const fn diff(&self) -> &Diff {
+        &self.diff
+    }
+}
+
+impl fmt::Display for FredAndGeorgeDiverged {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Fred and George diverged: {} and {} differ\n(- Fred, + George)\n{}",
+            self.fred, self.george, self.diff
+        )
+    }
+}
+
+impl std::error::Error for FredAndGeorgeDiverged {}
+
+/// Two values that are supposed to be equal, which are compared every time they are accessed.
+pub struct Twins<T> {
+    fred: T,
+    george: T,
+    policy: Policy,
+    reporter: Option<Reporter>,
+    divergences: AtomicUsize,
+}
+
+impl<T: PartialEq + fmt::Debug> Twins<T> {
+    /// Pairs up two values, with the [`Policy::Fred`] policy.
+    pub fn new(fred: T, george: T) -> Self {
+        Self {
+            fred,
+            george,
+            policy: Policy::Fred,
+            reporter: None,
+            divergences: AtomicUsize::new(0),
+        }
+    }
+
+    /// Sets which twin is handed out when they differ.
+    #[must_use]
+    pub fn policy(mut self, policy: Policy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Reports divergences to `reporter` instead of writing them to stderr.
+    #[must_use]
+    pub fn on_divergence(
+        mut self,
+        reporter: impl Fn(&FredAndGeorgeDiverged) + Send + Sync + 'static,
+    ) -> Self {
+        self.reporter = Some(Box::new(reporter));
+        self
+    }
+
+    /// Compares the twins, reports them if they differ, and returns the twin that the policy picks.
+    /// # Panics
+    /// Panics with the report if the twins differ and the policy is [`Policy::Panic`].
+    #[track_caller]
+    pub fn get(&self) -> &T {
+        if self.fred == self.george {
+            return &self.fred;
+        }
+        self.divergences.fetch_add(1, Ordering::Relaxed);
+        let report = self.compare();
+        match &self.reporter {
+            Some(reporter) => reporter(&report),
+            None => eprintln!("{}", report),
+        }
+        match self.policy {
+            Policy::Fred => &self.fred,
+            Policy::George => &self.george,
+            Policy::Panic => panic!("{}", report),
+        }
+    }
+
+    /// Compares the twins like [`get`](Twins::get), and returns the twin that the policy picks.
+    /// # Panics
+    /// Panics with the report if the twins differ and the policy is [`Policy::Panic`].
+    #[track_caller]
+    pub fn into_inner(self) -> T {
+        let george = core::ptr::eq(self.get(), &self.george);
+        if george {
+            self.george
+        } else {
+            self.fred
+        }
+    }
+
+    /// Returns a report of how the twins differ, or `None` if they are equal. Does not count as an access.
+    pub fn diverged(&self) -> Option<FredAndGeorgeDiverged> {
+        (self.fred != self.george).then(|| self.compare())
+    }
+
+    /// Returns how many accesses found the twins to differ.
+    pub fn divergences(&self) -> usize {
+        self.divergences.load(Ordering::Relaxed)
+    }
+
+    /// Returns Fred, without comparing the twins.
+    pub const fn fred(&self) -> &T {
+        &self.fred
+    }
+
+    /// Returns George, without comparing the twins.
+    pub const fn george(&self) -> &T {
+        &self.george
+    }
+
+    fn compare(&self) -> FredAndGeorgeDiverged {
+        FredAndGeorgeDiverged {
+            fred: format!("{:?}", self.fred),
+            george: format!("{:?}", self.george),
+            diff: Diff::new(&format!("{:#?}", self.fred), &format!("{:#?}", self.george)),
+        }
+    }
+}
+
+impl<T: PartialEq + fmt::Debug> Deref for Twins<T> {
+    type Target = T;
+
+    /// Compares the twins like [`get`](Twins::get).
+    #[track_caller]
+    fn deref(&self) -> &T {
+        self.get()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Twins<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Twins")
+            .field("fred", &self.fred)
+            .field("george", &self.george)
+            .field("policy", &self.policy)
+            .field("divergences", &self.divergences.load(Ordering::Relaxed))
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::weasley_twins;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn practice_weasley_twins() {
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let heard = Arc::clone(&reports);
+        let wheezes = weasley_twins!(
+            vec!["Skiving Snackbox", "Puking Pastille"],
+            vec!["Skiving Snackbox", "Nosebleed Nougat"],
+            on_divergence: move |report: &FredAndGeorgeDiverged| heard.lock().unwrap().push(report.clone()),
+        );
+        assert_eq!(wheezes.len(), 2);
+        assert_eq!(wheezes[1], "Puking Pastille");
+        assert_eq!(wheezes.divergences(), 2);
+        let reports = reports.lock().unwrap();
+        assert_eq!(reports.len(), 2);
+        assert_eq!(
+            reports[0].to_string(),
+            "Fred and George diverged: [\"Skiving Snackbox\", \"Puking Pastille\"] and \
+             [\"Skiving Snackbox\", \"Nosebleed Nougat\"] differ\n\
+             (- Fred, + George)\n \
+             [\n     \"Skiving Snackbox\",\n-    \"Puking Pastille\",\n+    \"Nosebleed Nougat\",\n ]\n"
+        );
+        assert!(format!("{:?}", wheezes).ends_with("policy: Fred, divergences: 2, .. }"));
+    }
+
+    #[test]
+    fn the_policy_picks_a_twin() {
+        let twins = Twins::new(1, 2)
+            .policy(Policy::George)
+            .on_divergence(|_| {});
+        assert_eq!(*twins, 2);
+        assert_eq!(twins.diverged().unwrap().george(), "2");
+        assert_eq!(twins.divergences(), 1);
+        assert_eq!(twins.into_inner(), 2);
+
+        let identical = weasley_twins!(String::from("Gred"), String::from("Gred"));
+        assert!(identical.diverged().is_none());
+        assert_eq!(identical.into_inner(), "Gred");
+    }
+
+    #[test]
+    #[should_panic(expected = "Fred and George diverged: 'F' and 'G' differ")]
+    fn a_panicking_policy() {
+        let twins = weasley_twins!('F', 'G', policy: Policy::Panic, on_divergence: |_| {});
+        let _ = twins.get();
+    }
+}